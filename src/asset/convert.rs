@@ -1,4 +1,4 @@
-use std::{io::Cursor, path::Path};
+use std::io::Cursor;
 
 use anyhow::Context;
 use image::{DynamicImage, ImageBuffer, ImageFormat};
@@ -10,74 +10,121 @@ use crate::data;
 use super::{
 	error::{Error, Result},
 	format::Format,
+	path::AssetPath,
+	texture::{self, Slice, Surface, TextureInfo},
 };
 
+/// Output of a conversion.
+pub struct Conversion {
+	pub bytes: Vec<u8>,
+	/// Layout of the source texture, if the source was a texture.
+	pub texture: Option<TextureInfo>,
+}
+
 pub trait Converter {
 	// TODO: Consider using a stream for this - the only converter I actually have right now doesn't operate with streams, but it may be relevant for other converters - or possibly would tie in with caching. Ref. https://github.com/tokio-rs/axum/discussions/608 re: responding to requests with streams.
-	fn convert(&self, data: &data::Version, path: &str, format: Format) -> Result<Vec<u8>>;
+	fn convert(
+		&self,
+		data: &data::Version,
+		path: &AssetPath,
+		format: Format,
+		slice: Slice,
+	) -> Result<Conversion>;
 }
 
 pub struct Image;
 
 impl Converter for Image {
-	fn convert(&self, data: &data::Version, path: &str, format: Format) -> Result<Vec<u8>> {
-		let extension = Path::new(path)
-			.extension()
-			.and_then(|extension| extension.to_str());
-
-		// TODO: add error handling case on this once more than one format exists.
+	fn convert(
+		&self,
+		data: &data::Version,
+		path: &AssetPath,
+		format: Format,
+		slice: Slice,
+	) -> Result<Conversion> {
 		let output_format = match format {
 			Format::Png => ImageFormat::Png,
+			Format::Jpg => ImageFormat::Jpeg,
 		};
 
 		// TODO: should i just pass IW to convert? is there any realistic expectation that a converter will need excel?
 		let ironworks = data.ironworks();
 
-		let buffer = match extension {
-			Some("tex") | Some("atex") => read_texture(&ironworks, path),
+		let (buffer, texture) = match path.extension() {
+			"tex" | "atex" => read_texture(&ironworks, path.as_str(), slice),
 
 			other => {
-				return Err(Error::InvalidConversion(
-					other.unwrap_or("(none)").into(),
-					format,
-				));
+				return Err(Error::InvalidConversion(other.into(), format));
 			}
 		}?;
 
+		let buffer = match output_format {
+			ImageFormat::Jpeg => flatten(buffer),
+			_ => buffer,
+		};
+
 		// TODO: are there any non-failure cases here?
 		let mut bytes = Cursor::new(vec![]);
 		buffer
 			.write_to(&mut bytes, output_format)
 			.context("failed to write output buffer")?;
 
-		Ok(bytes.into_inner())
+		Ok(Conversion {
+			bytes: bytes.into_inner(),
+			texture: Some(texture),
+		})
 	}
 }
 
-fn read_texture(ironworks: &Ironworks, path: &str) -> Result<DynamicImage> {
+/// Remove the alpha channel from an image, for formats that do not support
+/// transparency. Colour is scaled by alpha, flattening the image onto black.
+fn flatten(buffer: DynamicImage) -> DynamicImage {
+	if !buffer.color().has_alpha() {
+		return buffer;
+	}
+
+	let mut rgba = buffer.into_rgba8();
+	for pixel in rgba.pixels_mut() {
+		let [r, g, b, a] = pixel.0;
+		let scale = |channel: u8| (u16::from(channel) * u16::from(a) / 0xFF) as u8;
+		pixel.0 = [scale(r), scale(g), scale(b), 0xFF];
+	}
+
+	DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(rgba).into_rgb8())
+}
+
+fn read_texture(
+	ironworks: &Ironworks,
+	path: &str,
+	slice: Slice,
+) -> Result<(DynamicImage, TextureInfo)> {
 	let texture = match ironworks.file::<tex::Texture>(path) {
 		Ok(value) => value,
 		Err(ironworks::Error::NotFound(_)) => return Err(Error::NotFound(path.into())),
 		other => other.context("read file")?,
 	};
 
-	if !matches!(texture.kind(), tex::TextureKind::D2) {
+	// Cubemaps and volume textures are converted one 2D slice at a time.
+	if matches!(texture.kind(), tex::TextureKind::D1) {
 		return Err(Error::UnsupportedSource(
 			path.into(),
 			format!("unhandled texture dimension {:?}", texture.kind()),
 		));
 	}
 
+	let info = TextureInfo::new(&texture);
+	let surface = texture::surface(&texture, &info, slice, path)?;
+
 	let buffer = match texture.format() {
-		tex::Format::A8 => read_texture_a8(texture)?,
+		tex::Format::A8 => read_texture_a8(surface)?,
 
-		tex::Format::Rgba4 => read_texture_rgba4(texture)?,
-		tex::Format::Rgb5a1 => read_texture_rgb5a1(texture)?,
-		tex::Format::Argb8 => read_texture_argb8(texture)?,
+		tex::Format::Rgba4 => read_texture_rgba4(surface)?,
+		tex::Format::Rgb5a1 => read_texture_rgb5a1(surface)?,
+		tex::Format::Argb8 => read_texture_argb8(surface)?,
 
-		tex::Format::Dxt1 => read_texture_dxt(texture, texpresso::Format::Bc1)?,
-		tex::Format::Dxt3 => read_texture_dxt(texture, texpresso::Format::Bc2)?,
-		tex::Format::Dxt5 => read_texture_dxt(texture, texpresso::Format::Bc3)?,
+		tex::Format::Dxt1 => read_texture_dxt(surface, texpresso::Format::Bc1)?,
+		tex::Format::Dxt3 => read_texture_dxt(surface, texpresso::Format::Bc2)?,
+		tex::Format::Dxt5 => read_texture_dxt(surface, texpresso::Format::Bc3)?,
 
 		other => {
 			return Err(Error::UnsupportedSource(
@@ -87,22 +134,18 @@ fn read_texture(ironworks: &Ironworks, path: &str) -> Result<DynamicImage> {
 		}
 	};
 
-	Ok(buffer)
+	Ok((buffer, info))
 }
 
-fn read_texture_a8(texture: tex::Texture) -> Result<DynamicImage> {
-	let buffer = ImageBuffer::from_raw(
-		texture.width().into(),
-		texture.height().into(),
-		texture.data().to_owned(),
-	)
-	.context("failed to build image buffer")?;
+fn read_texture_a8(surface: Surface) -> Result<DynamicImage> {
+	let buffer = ImageBuffer::from_raw(surface.width, surface.height, surface.data.to_owned())
+		.context("failed to build image buffer")?;
 	Ok(DynamicImage::ImageLuma8(buffer))
 }
 
-fn read_texture_rgba4(texture: tex::Texture) -> Result<DynamicImage> {
-	let data = texture
-		.data()
+fn read_texture_rgba4(surface: Surface) -> Result<DynamicImage> {
+	let data = surface
+		.data
 		.iter()
 		.tuples()
 		.flat_map(|(gr, ab)| {
@@ -114,14 +157,14 @@ fn read_texture_rgba4(texture: tex::Texture) -> Result<DynamicImage> {
 		})
 		.collect::<Vec<_>>();
 
-	let buffer = ImageBuffer::from_raw(texture.width().into(), texture.height().into(), data)
+	let buffer = ImageBuffer::from_raw(surface.width, surface.height, data)
 		.context("failed to build image buffer")?;
 	Ok(DynamicImage::ImageRgba8(buffer))
 }
 
-fn read_texture_rgb5a1(texture: tex::Texture) -> Result<DynamicImage> {
-	let data = texture
-		.data()
+fn read_texture_rgb5a1(surface: Surface) -> Result<DynamicImage> {
+	let data = surface
+		.data
 		.iter()
 		.tuples()
 		.flat_map(|(b, a)| {
@@ -135,39 +178,57 @@ fn read_texture_rgb5a1(texture: tex::Texture) -> Result<DynamicImage> {
 		.map(|value| u8::try_from(value).unwrap())
 		.collect::<Vec<_>>();
 
-	let buffer = ImageBuffer::from_raw(texture.width().into(), texture.height().into(), data)
+	let buffer = ImageBuffer::from_raw(surface.width, surface.height, data)
 		.context("failed to build image buffer")?;
 	Ok(DynamicImage::ImageRgba8(buffer))
 }
 
-fn read_texture_argb8(texture: tex::Texture) -> Result<DynamicImage> {
+fn read_texture_argb8(surface: Surface) -> Result<DynamicImage> {
 	// TODO: seems really wasteful to copy the entire image in memory just to reassign the channels. think of a better way to do this.
 	// TODO: use array_chunks once it hits stable
-	let data = texture
-		.data()
+	let data = surface
+		.data
 		.iter()
 		.tuples()
 		.flat_map(|(b, g, r, a)| [r, g, b, a])
 		.copied()
 		.collect::<Vec<_>>();
 
-	let buffer = ImageBuffer::from_raw(texture.width().into(), texture.height().into(), data)
+	let buffer = ImageBuffer::from_raw(surface.width, surface.height, data)
 		.context("failed to build image buffer")?;
 	Ok(DynamicImage::ImageRgba8(buffer))
 }
 
-fn read_texture_dxt(texture: tex::Texture, dxt_format: texpresso::Format) -> Result<DynamicImage> {
-	let width = usize::from(texture.width());
-	let height = usize::from(texture.height());
+fn read_texture_dxt(surface: Surface, dxt_format: texpresso::Format) -> Result<DynamicImage> {
+	let width = surface.width as usize;
+	let height = surface.height as usize;
 
 	let mut dxt_buffer = vec![0; width * height * 4];
-	dxt_format.decompress(texture.data(), width, height, &mut dxt_buffer);
-
-	let image_buffer = ImageBuffer::from_raw(
-		width.try_into().unwrap(),
-		height.try_into().unwrap(),
-		dxt_buffer,
-	)
-	.context("failed to build image buffer")?;
+	dxt_format.decompress(surface.data, width, height, &mut dxt_buffer);
+
+	let image_buffer = ImageBuffer::from_raw(surface.width, surface.height, dxt_buffer)
+		.context("failed to build image buffer")?;
 	Ok(DynamicImage::ImageRgba8(image_buffer))
 }
+
+#[cfg(test)]
+mod test {
+	use image::{Rgb, Rgba, RgbaImage};
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn flatten_alpha() {
+		let mut image = RgbaImage::new(2, 1);
+		image.put_pixel(0, 0, Rgba([200, 100, 50, 0xFF]));
+		image.put_pixel(1, 0, Rgba([200, 100, 50, 0x80]));
+
+		let flat = flatten(DynamicImage::ImageRgba8(image))
+			.as_rgb8()
+			.cloned()
+			.unwrap();
+		assert_eq!(flat.get_pixel(0, 0), &Rgb([200, 100, 50]));
+		assert_eq!(flat.get_pixel(1, 0), &Rgb([100, 50, 25]));
+	}
+}
@@ -2,6 +2,9 @@ use super::format::Format;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+	#[error("invalid path \"{0}\": {1}")]
+	InvalidPath(String, String),
+
 	#[error("source \"{0}\" does not exist")]
 	NotFound(String),
 
@@ -14,6 +17,9 @@ pub enum Error {
 	#[error("{0} cannot be converted to {1:?}")]
 	InvalidConversion(String, Format),
 
+	#[error("conversion queue is full")]
+	QueueFull,
+
 	#[error(transparent)]
 	Failure(#[from] anyhow::Error),
 }
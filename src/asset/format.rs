@@ -11,21 +11,25 @@ use crate::utility::jsonschema::impl_jsonschema;
 
 use super::{convert, error::Error};
 
-#[derive(Debug, Clone, Copy, EnumIter)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EnumIter)]
 pub enum Format {
 	Png,
+	/// JPEG does not support transparency - transparent pixels are flattened
+	/// onto black.
+	Jpg,
 }
 
 impl Format {
 	pub fn extension(&self) -> &str {
 		match self {
 			Self::Png => "png",
+			Self::Jpg => "jpg",
 		}
 	}
 
 	pub(super) fn converter(&self) -> &dyn convert::Converter {
 		match self {
-			Self::Png => &convert::Image,
+			Self::Png | Self::Jpg => &convert::Image,
 		}
 	}
 }
@@ -46,6 +50,7 @@ impl FromStr for Format {
 	fn from_str(input: &str) -> Result<Self, Self::Err> {
 		Ok(match input {
 			"png" => Self::Png,
+			"jpg" | "jpeg" => Self::Jpg,
 			other => return Err(Error::UnknownFormat(other.into())),
 		})
 	}
@@ -0,0 +1,51 @@
+use ironworks::{file::tex, Ironworks};
+
+/// Details of an icon present in the game data.
+#[derive(Debug, Clone)]
+pub struct IconInfo {
+	pub id: u32,
+	pub width: u32,
+	pub height: u32,
+	/// Whether a high resolution (`_hr1`) variant of the icon is available.
+	pub hires: bool,
+}
+
+/// Paths to the standard and high resolution variants of an icon.
+pub fn icon_paths(id: u32) -> (String, String) {
+	let group = (id / 1000) * 1000;
+	let icon_path = format!("ui/icon/{group:0>6}/{id:0>6}");
+	(format!("{icon_path}.tex"), format!("{icon_path}_hr1.tex"))
+}
+
+/// Build a manifest of the provided icons that are present in the game data,
+/// ordered by ID. Icons without a standard resolution texture are omitted.
+/// Every texture is read in full, so this is expensive for large sets.
+pub fn icon_manifest(ironworks: &Ironworks, ids: impl IntoIterator<Item = u32>) -> Vec<IconInfo> {
+	let mut icons = ids
+		.into_iter()
+		.filter_map(|id| {
+			let (path, path_hr1) = icon_paths(id);
+
+			let texture = match ironworks.file::<tex::Texture>(&path) {
+				Ok(texture) => texture,
+				Err(ironworks::Error::NotFound(_)) => return None,
+				Err(error) => {
+					tracing::warn!(%path, ?error, "could not read icon");
+					return None;
+				}
+			};
+
+			Some(IconInfo {
+				id,
+				width: texture.width().into(),
+				height: texture.height().into(),
+				hires: ironworks.file::<tex::Texture>(&path_hr1).is_ok(),
+			})
+		})
+		.collect::<Vec<_>>();
+
+	icons.sort_unstable_by_key(|icon| icon.id);
+	icons.dedup_by_key(|icon| icon.id);
+
+	icons
+}
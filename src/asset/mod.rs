@@ -1,6 +1,20 @@
 mod convert;
 mod error;
 mod format;
+mod icon;
+mod queue;
 mod service;
+mod texture;
 
-pub use {error::Error, format::Format, service::Service};
+pub mod path;
+
+pub use {
+	convert::Conversion,
+	error::Error,
+	format::Format,
+	icon::{icon_paths, IconInfo},
+	path::AssetPath,
+	queue::Queued,
+	service::{Config, Service},
+	texture::{Slice, TextureInfo},
+};
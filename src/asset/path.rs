@@ -0,0 +1,194 @@
+use std::fmt;
+
+use super::error::{Error, Result};
+
+/// Name of the repository containing files for the base game.
+const BASE_REPOSITORY: &str = "ffxiv";
+
+/// Top-level categories of the game's sqpack file tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+	Common,
+	BgCommon,
+	Bg,
+	Cut,
+	Chara,
+	Shader,
+	Ui,
+	Sound,
+	Vfx,
+	UiScript,
+	Exd,
+	GameScript,
+	Music,
+	SqpackTest,
+	Debug,
+}
+
+impl Category {
+	fn from_name(name: &str) -> Option<Self> {
+		Some(match name {
+			"common" => Self::Common,
+			"bgcommon" => Self::BgCommon,
+			"bg" => Self::Bg,
+			"cut" => Self::Cut,
+			"chara" => Self::Chara,
+			"shader" => Self::Shader,
+			"ui" => Self::Ui,
+			"sound" => Self::Sound,
+			"vfx" => Self::Vfx,
+			"ui_script" => Self::UiScript,
+			"exd" => Self::Exd,
+			"game_script" => Self::GameScript,
+			"music" => Self::Music,
+			"sqpack_test" => Self::SqpackTest,
+			"debug" => Self::Debug,
+			_ => return None,
+		})
+	}
+
+	/// Whether files in this category may be split across expansion
+	/// repositories, selected by the second segment of their path.
+	fn has_expansions(&self) -> bool {
+		matches!(self, Self::Bg | Self::Cut | Self::Music)
+	}
+}
+
+/// A validated path to a file within the game's sqpack data. Paths are
+/// normalized to the form used for lookups - lowercase, forward-slash
+/// separated, and without a leading slash.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetPath {
+	path: String,
+	category: Category,
+	repository: String,
+}
+
+impl AssetPath {
+	/// Validate and normalize a requested path. Paths that could never refer to
+	/// a game file are rejected, such that callers can differentiate malformed
+	/// requests from files that are simply missing.
+	pub fn parse(input: &str) -> Result<Self> {
+		let invalid = |reason: &str| Error::InvalidPath(input.into(), reason.into());
+
+		let path = input
+			.trim()
+			.trim_start_matches(['/', '\\'])
+			.replace('\\', "/")
+			.to_lowercase();
+
+		if path.chars().any(char::is_control) {
+			return Err(invalid("contains control characters"));
+		}
+
+		let segments = path.split('/').collect::<Vec<_>>();
+		if segments
+			.iter()
+			.any(|segment| matches!(*segment, "" | "." | ".."))
+		{
+			return Err(invalid("contains empty or relative segments"));
+		}
+
+		let [category, .., file] = segments[..] else {
+			return Err(invalid("must contain a category and file name"));
+		};
+
+		let category = Category::from_name(category).ok_or_else(|| invalid("unknown category"))?;
+
+		if !file.contains('.') {
+			return Err(invalid("file name must have an extension"));
+		}
+
+		let repository = match (category.has_expansions(), segments.get(1)) {
+			(true, Some(segment)) if is_expansion(segment) && segments.len() > 2 => {
+				segment.to_string()
+			}
+			_ => BASE_REPOSITORY.to_string(),
+		};
+
+		Ok(Self {
+			path,
+			category,
+			repository,
+		})
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.path
+	}
+
+	pub fn category(&self) -> Category {
+		self.category
+	}
+
+	/// Name of the repository the file is stored in, i.e. `ffxiv` or `ex1`.
+	pub fn repository(&self) -> &str {
+		&self.repository
+	}
+
+	/// Extension of the file, i.e. `tex`.
+	pub fn extension(&self) -> &str {
+		self.path
+			.rsplit_once('.')
+			.map(|(_, extension)| extension)
+			.unwrap_or_default()
+	}
+}
+
+impl fmt::Display for AssetPath {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str(&self.path)
+	}
+}
+
+fn is_expansion(segment: &str) -> bool {
+	segment.strip_prefix("ex").map_or(false, |number| {
+		!number.is_empty() && number.chars().all(|char| char.is_ascii_digit())
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn normalizes() {
+		let path = AssetPath::parse("/UI\\Icon/051000/051474_HR1.tex").unwrap();
+		assert_eq!(path.as_str(), "ui/icon/051000/051474_hr1.tex");
+		assert_eq!(path.category(), Category::Ui);
+		assert_eq!(path.repository(), "ffxiv");
+		assert_eq!(path.extension(), "tex");
+	}
+
+	#[test]
+	fn expansion_repository() {
+		let path =
+			AssetPath::parse("bg/ex2/01_gyr_g5/fld/g5f1/texture/g5f1_a0_roc1_d.tex").unwrap();
+		assert_eq!(path.category(), Category::Bg);
+		assert_eq!(path.repository(), "ex2");
+
+		// Only categories split across expansions take a repository from the path.
+		let path = AssetPath::parse("ui/ex2/file.tex").unwrap();
+		assert_eq!(path.repository(), "ffxiv");
+	}
+
+	#[test]
+	fn invalid() {
+		for input in [
+			"",
+			"ui",
+			"unknown/file.tex",
+			"ui/icon/../file.tex",
+			"ui//file.tex",
+			"ui/icon/file",
+			"ui/icon/file\0.tex",
+		] {
+			assert!(
+				matches!(AssetPath::parse(input), Err(Error::InvalidPath(..))),
+				"{input:?} should be invalid"
+			);
+		}
+	}
+}
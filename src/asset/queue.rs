@@ -0,0 +1,171 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex},
+};
+
+use serde::Deserialize;
+use tokio::sync::Semaphore;
+
+use crate::{job::JobId, version::VersionKey};
+
+use super::{
+	convert::Conversion,
+	error::{Error, Result},
+	format::Format,
+	path::AssetPath,
+	texture::Slice,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Path prefixes of assets expensive enough to convert in the background,
+	/// rather than while the request waits, i.e. `ui/map/`.
+	paths: Vec<String>,
+
+	/// Maximum number of background conversions to run at once.
+	concurrency: usize,
+
+	/// Maximum number of background conversions to hold, including those
+	/// running. Further conversions are rejected until space frees up.
+	capacity: usize,
+
+	/// Number of converted assets to retain for subsequent requests.
+	retain: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			paths: vec![],
+			concurrency: 2,
+			capacity: 32,
+			retain: 64,
+		}
+	}
+}
+
+/// Key uniquely identifying the output of a conversion.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ConversionKey {
+	pub version: VersionKey,
+	pub path: String,
+	pub format: Format,
+	pub slice: Slice,
+}
+
+/// State of a background conversion.
+pub enum Queued {
+	/// The conversion has completed, and the output is available.
+	Ready(Arc<Conversion>),
+	/// The conversion is being performed by the specified job.
+	Pending {
+		job: JobId,
+		/// Whether the conversion was enqueued by this request.
+		enqueued: bool,
+	},
+}
+
+/// Bounded queue of background conversions, alongside the outputs of recently
+/// completed conversions.
+pub struct Queue {
+	paths: Vec<String>,
+	capacity: usize,
+	retain: usize,
+
+	permits: Semaphore,
+	state: Mutex<QueueState>,
+}
+
+#[derive(Default)]
+struct QueueState {
+	pending: HashMap<ConversionKey, JobId>,
+	completed: HashMap<ConversionKey, Arc<Conversion>>,
+	// Completion order of retained outputs, oldest first.
+	order: VecDeque<ConversionKey>,
+}
+
+impl Queue {
+	pub fn new(config: Config) -> Self {
+		Self {
+			paths: config.paths,
+			capacity: config.capacity,
+			retain: config.retain,
+
+			permits: Semaphore::new(config.concurrency.max(1)),
+			state: Default::default(),
+		}
+	}
+
+	/// Whether the asset at the path should be converted in the background.
+	pub fn handles(&self, path: &AssetPath) -> bool {
+		self.paths
+			.iter()
+			.any(|prefix| path.as_str().starts_with(prefix.as_str()))
+	}
+
+	/// Get the state of a conversion. If the conversion is neither complete nor
+	/// pending, a job is created for it with the provided function. Failures to
+	/// create the job leave the queue untouched.
+	pub fn get_or_enqueue<E>(
+		&self,
+		key: &ConversionKey,
+		create: impl FnOnce() -> Result<JobId, E>,
+	) -> Result<Queued, E>
+	where
+		E: From<Error>,
+	{
+		let mut state = self.state.lock().expect("poisoned");
+
+		if let Some(conversion) = state.completed.get(key) {
+			return Ok(Queued::Ready(conversion.clone()));
+		}
+
+		if let Some(id) = state.pending.get(key) {
+			return Ok(Queued::Pending {
+				job: *id,
+				enqueued: false,
+			});
+		}
+
+		if state.pending.len() >= self.capacity {
+			return Err(Error::QueueFull.into());
+		}
+
+		let id = create()?;
+		state.pending.insert(key.clone(), id);
+		Ok(Queued::Pending {
+			job: id,
+			enqueued: true,
+		})
+	}
+
+	/// Wait for a slot to perform a conversion in.
+	pub async fn permit(&self) -> tokio::sync::SemaphorePermit<'_> {
+		self.permits.acquire().await.expect("semaphore closed")
+	}
+
+	/// Record the outcome of a conversion. Failed conversions are not retained,
+	/// such that they are retried by the next request.
+	pub fn complete(&self, key: ConversionKey, conversion: Option<Conversion>) {
+		let mut state = self.state.lock().expect("poisoned");
+		state.pending.remove(&key);
+
+		let Some(conversion) = conversion else {
+			return;
+		};
+
+		if self.retain == 0 {
+			return;
+		}
+
+		while state.order.len() >= self.retain {
+			if let Some(oldest) = state.order.pop_front() {
+				state.completed.remove(&oldest);
+			}
+		}
+
+		state.order.push_back(key.clone());
+		state.completed.insert(key, Arc::new(conversion));
+	}
+}
@@ -2,17 +2,39 @@ use std::sync::Arc;
 
 use anyhow::Context;
 
-use crate::{data, version::VersionKey};
+use serde::Deserialize;
 
-use super::{error::Result, format::Format};
+use crate::{data, job, version::VersionKey};
+
+use super::{
+	convert::Conversion,
+	error::{Error, Result},
+	format::Format,
+	icon::{self, IconInfo},
+	path::AssetPath,
+	queue::{self, ConversionKey, Queue, Queued},
+	texture::Slice,
+};
+
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+	#[serde(default)]
+	queue: queue::Config,
+}
 
 pub struct Service {
 	data: Arc<data::Data>,
+	jobs: Arc<job::Manager>,
+	queue: Queue,
 }
 
 impl Service {
-	pub fn new(data: Arc<data::Data>) -> Self {
-		Self { data }
+	pub fn new(config: Config, data: Arc<data::Data>, jobs: Arc<job::Manager>) -> Self {
+		Self {
+			data,
+			jobs,
+			queue: Queue::new(config.queue),
+		}
 	}
 
 	pub fn ready(&self) -> bool {
@@ -20,7 +42,13 @@ impl Service {
 		true
 	}
 
-	pub fn convert(&self, version: VersionKey, path: &str, format: Format) -> Result<Vec<u8>> {
+	pub fn convert(
+		&self,
+		version: VersionKey,
+		path: &str,
+		format: Format,
+		slice: Slice,
+	) -> Result<Conversion> {
 		// TODO: presumably this is where caching would be resolved
 
 		let data_version = self
@@ -28,7 +56,79 @@ impl Service {
 			.version(version)
 			.with_context(|| format!("data for {version} not ready"))?;
 
+		let path = AssetPath::parse(path)?;
+
 		let converter = format.converter();
-		converter.convert(&data_version, path, format)
+		converter.convert(&data_version, &path, format, slice)
+	}
+
+	/// Convert an asset in the background, if it is configured as too expensive
+	/// to convert while a request waits. Returns `None` for any other asset,
+	/// which should be converted directly with `convert`.
+	pub fn convert_queued<E>(
+		self: &Arc<Self>,
+		version: VersionKey,
+		path: &str,
+		format: Format,
+		slice: Slice,
+		admit: impl FnOnce() -> Result<(), E>,
+	) -> Result<Option<Queued>, E>
+	where
+		E: From<Error>,
+	{
+		let path = AssetPath::parse(path)?;
+		if !self.queue.handles(&path) {
+			return Ok(None);
+		}
+
+		let key = ConversionKey {
+			version,
+			path: path.to_string(),
+			format,
+			slice,
+		};
+
+		let queued = self.queue.get_or_enqueue(&key, || {
+			admit()?;
+			let job = self.jobs.create(
+				job::Kind::AssetConversion,
+				format!("{path} to {}", format.extension()),
+			);
+			let id = job.id();
+			tokio::spawn(Arc::clone(self).run_conversion(job, key.clone()));
+			Ok(id)
+		})?;
+
+		Ok(Some(queued))
+	}
+
+	async fn run_conversion(self: Arc<Self>, job: job::Handle, key: ConversionKey) {
+		let _permit = self.queue.permit().await;
+		job.start();
+
+		let result = self
+			.data
+			.blocking()
+			.permit()
+			.await
+			.run(|| self.convert(key.version, &key.path, key.format, key.slice));
+
+		job.finish(&result);
+		self.queue.complete(key, result.ok());
+	}
+
+	/// Build a manifest of the provided icon IDs that are present in the
+	/// specified version.
+	pub fn icon_manifest(
+		&self,
+		version: VersionKey,
+		ids: impl IntoIterator<Item = u32>,
+	) -> Result<Vec<IconInfo>> {
+		let data_version = self
+			.data
+			.version(version)
+			.with_context(|| format!("data for {version} not ready"))?;
+
+		Ok(icon::icon_manifest(&data_version.ironworks(), ids))
 	}
 }
@@ -0,0 +1,167 @@
+use ironworks::file::tex;
+
+use super::error::{Error, Result};
+
+/// Surface of a texture to convert. Textures may contain multiple mip levels,
+/// each of which may in turn contain multiple slices - array layers, cubemap
+/// faces, or depth slices of a volume texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Slice {
+	/// Mip level to read, where 0 is the full-size surface.
+	pub mip: u32,
+	/// Index of the slice within the mip level.
+	pub index: u32,
+}
+
+/// Layout of the surfaces available within a texture.
+#[derive(Debug, Clone)]
+pub struct TextureInfo {
+	pub kind: tex::TextureKind,
+	pub width: u32,
+	pub height: u32,
+	pub mip_levels: u32,
+	/// Number of slices in each mip level, starting from the full-size surface.
+	pub slices: Vec<u32>,
+}
+
+/// A single two-dimensional surface read from a texture.
+pub struct Surface<'a> {
+	pub width: u32,
+	pub height: u32,
+	pub data: &'a [u8],
+}
+
+impl TextureInfo {
+	pub fn new(texture: &tex::Texture) -> Self {
+		let kind = texture.kind();
+		let width = u32::from(texture.width());
+		let height = u32::from(texture.height());
+		let depth = u32::from(texture.depth()).max(1);
+		let array_size = u32::from(texture.array_size()).max(1);
+		let mip_levels = u32::from(texture.mip_levels()).max(1);
+
+		let slices = (0..mip_levels)
+			.map(|mip| match kind {
+				tex::TextureKind::Cube => 6 * array_size,
+				// Volume textures halve their depth alongside width and height.
+				tex::TextureKind::D3 => (depth >> mip).max(1),
+				_ => array_size,
+			})
+			.collect();
+
+		Self {
+			kind,
+			width,
+			height,
+			mip_levels,
+			slices,
+		}
+	}
+}
+
+/// Read the requested surface from a texture. Surfaces are stored mip-major,
+/// with every slice of a mip level stored contiguously before the next level.
+pub fn surface<'a>(
+	texture: &'a tex::Texture,
+	info: &TextureInfo,
+	slice: Slice,
+	path: &str,
+) -> Result<Surface<'a>> {
+	let unavailable = |reason: String| Error::UnsupportedSource(path.into(), reason);
+
+	if slice.mip >= info.mip_levels {
+		return Err(unavailable(format!(
+			"mip level {} requested, texture has {}",
+			slice.mip, info.mip_levels
+		)));
+	}
+
+	let slice_count = info.slices[usize::try_from(slice.mip).unwrap()];
+	if slice.index >= slice_count {
+		return Err(unavailable(format!(
+			"slice {} requested, mip level {} has {slice_count}",
+			slice.index, slice.mip
+		)));
+	}
+
+	let format = texture.format();
+	let mip_size = |mip: u32| {
+		let (width, height) = mip_dimensions(info, mip);
+		surface_size(format, width, height)
+			.ok_or_else(|| unavailable(format!("unhandled texture format {format:?}")))
+	};
+
+	let mut offset = 0;
+	for mip in 0..slice.mip {
+		offset += mip_size(mip)? * info.slices[usize::try_from(mip).unwrap()] as usize;
+	}
+	let size = mip_size(slice.mip)?;
+	offset += size * slice.index as usize;
+
+	let data = texture
+		.data()
+		.get(offset..offset + size)
+		.ok_or_else(|| unavailable("texture data is truncated".into()))?;
+
+	let (width, height) = mip_dimensions(info, slice.mip);
+	Ok(Surface {
+		width,
+		height,
+		data,
+	})
+}
+
+fn mip_dimensions(info: &TextureInfo, mip: u32) -> (u32, u32) {
+	((info.width >> mip).max(1), (info.height >> mip).max(1))
+}
+
+/// Size in bytes of a single surface of the given dimensions. Block compressed
+/// formats are stored in 4x4 pixel blocks.
+fn surface_size(format: tex::Format, width: u32, height: u32) -> Option<usize> {
+	let (width, height) = (width as usize, height as usize);
+	let blocks = || width.div_ceil(4) * height.div_ceil(4);
+
+	let size = match format {
+		tex::Format::A8 => width * height,
+		tex::Format::Rgba4 | tex::Format::Rgb5a1 => width * height * 2,
+		tex::Format::Argb8 => width * height * 4,
+		tex::Format::Dxt1 => blocks() * 8,
+		tex::Format::Dxt3 | tex::Format::Dxt5 => blocks() * 16,
+		_ => return None,
+	};
+
+	Some(size)
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn uncompressed_size() {
+		assert_eq!(surface_size(tex::Format::Argb8, 4, 2), Some(32));
+		assert_eq!(surface_size(tex::Format::A8, 3, 3), Some(9));
+	}
+
+	#[test]
+	fn block_compressed_size() {
+		assert_eq!(surface_size(tex::Format::Dxt1, 8, 8), Some(32));
+		// Partial blocks occupy an entire block.
+		assert_eq!(surface_size(tex::Format::Dxt5, 2, 1), Some(16));
+	}
+
+	#[test]
+	fn mip_dimensions_clamp() {
+		let info = TextureInfo {
+			kind: tex::TextureKind::D2,
+			width: 8,
+			height: 2,
+			mip_levels: 4,
+			slices: vec![1; 4],
+		};
+		assert_eq!(mip_dimensions(&info, 1), (4, 1));
+		assert_eq!(mip_dimensions(&info, 3), (1, 1));
+	}
+}
@@ -0,0 +1,133 @@
+use std::{
+	fs,
+	io::{self, BufRead, Write},
+	path::PathBuf,
+	sync::Mutex,
+	time::SystemTime,
+};
+
+use anyhow::Result;
+use figment::value::magic::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	directory: RelativePathBuf,
+}
+
+/// A single recorded administrative action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+	pub timestamp: SystemTime,
+	/// Key identifying who performed the action, i.e. an admin username.
+	pub actor: String,
+	pub action: String,
+	pub parameters: serde_json::Value,
+}
+
+/// Criteria to select audit entries by. Unset fields match all entries.
+#[derive(Debug, Default)]
+pub struct Filter {
+	pub actor: Option<String>,
+	pub action: Option<String>,
+	pub limit: Option<usize>,
+}
+
+/// Append-only, persistent record of administrative actions.
+pub struct Log {
+	path: PathBuf,
+	// Serialises writes, so concurrent entries don't interleave.
+	file: Mutex<fs::File>,
+}
+
+impl Log {
+	pub fn new(config: Config) -> Result<Self> {
+		let directory = config.directory.relative();
+		fs::create_dir_all(&directory)?;
+
+		let path = directory.join("audit.jsonl");
+		let file = fs::File::options().create(true).append(true).open(&path)?;
+
+		Ok(Self {
+			path,
+			file: Mutex::new(file),
+		})
+	}
+
+	/// Record an action. Failing to record is logged rather than returned - the
+	/// action itself has already taken place by the time it is audited.
+	pub fn record(&self, actor: &str, action: &str, parameters: serde_json::Value) {
+		let entry = Entry {
+			timestamp: SystemTime::now(),
+			actor: actor.to_string(),
+			action: action.to_string(),
+			parameters,
+		};
+
+		tracing::info!(actor, action, parameters = %entry.parameters, "audit");
+
+		if let Err(error) = self.append(&entry) {
+			tracing::error!(?error, ?entry, "failed to record audit entry");
+		}
+	}
+
+	fn append(&self, entry: &Entry) -> Result<()> {
+		let mut line = serde_json::to_vec(entry)?;
+		line.push(b'\n');
+
+		let mut file = self.file.lock().expect("poisoned");
+		file.write_all(&line)?;
+		file.flush()?;
+
+		Ok(())
+	}
+
+	/// Read entries matching the filter, newest first.
+	pub fn entries(&self, filter: &Filter) -> Result<Vec<Entry>> {
+		let file = match fs::File::open(&self.path) {
+			Ok(file) => file,
+			Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+			Err(error) => return Err(error.into()),
+		};
+
+		let mut entries = vec![];
+		for line in io::BufReader::new(file).lines() {
+			let line = line?;
+			if line.is_empty() {
+				continue;
+			}
+
+			// A partially written trailing line shouldn't hide the rest of the log.
+			let entry = match serde_json::from_str::<Entry>(&line) {
+				Ok(entry) => entry,
+				Err(error) => {
+					tracing::warn!(?error, "skipping malformed audit entry");
+					continue;
+				}
+			};
+
+			if filter.matches(&entry) {
+				entries.push(entry);
+			}
+		}
+
+		entries.reverse();
+		if let Some(limit) = filter.limit {
+			entries.truncate(limit);
+		}
+
+		Ok(entries)
+	}
+}
+
+impl Filter {
+	fn matches(&self, entry: &Entry) -> bool {
+		let matches = |expected: &Option<String>, value: &str| {
+			expected
+				.as_deref()
+				.map_or(true, |expected| expected == value)
+		};
+
+		matches(&self.actor, &entry.actor) && matches(&self.action, &entry.action)
+	}
+}
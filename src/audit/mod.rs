@@ -0,0 +1,3 @@
+mod log;
+
+pub use log::{Config, Entry, Filter, Log};
@@ -0,0 +1,150 @@
+use std::{
+	sync::atomic::{AtomicU64, AtomicUsize, Ordering},
+	time::{Duration, Instant},
+};
+
+use tokio::{
+	sync::{Semaphore, SemaphorePermit},
+	task,
+};
+
+/// Gatekeeper for synchronous game data access, such as excel and sqpack reads.
+/// Work is run on the runtime's blocking pool rather than stalling async
+/// workers, and is timed to help tune deployments backed by slow disks.
+pub struct Blocking {
+	limit: Option<usize>,
+	permits: Option<Semaphore>,
+
+	active: AtomicUsize,
+	calls: AtomicU64,
+	queued: AtomicU64,
+	running: AtomicU64,
+	cpu: AtomicU64,
+}
+
+/// Summary of time spent on blocking data access since startup.
+#[derive(Debug, Clone)]
+pub struct BlockingStatistics {
+	/// Maximum number of concurrent data accesses, if limited.
+	pub limit: Option<usize>,
+	/// Number of data accesses currently running.
+	pub active: usize,
+	/// Total number of data accesses performed.
+	pub calls: u64,
+	/// Time spent waiting for a slot in the pool.
+	pub queued: Duration,
+	/// Wall time spent performing data access.
+	pub running: Duration,
+	/// CPU time spent performing data access. Not available on all platforms.
+	pub cpu: Option<Duration>,
+}
+
+impl BlockingStatistics {
+	/// Time spent performing data access while not on the CPU - typically
+	/// blocked on disk IO.
+	pub fn io(&self) -> Option<Duration> {
+		self.cpu.map(|cpu| self.running.saturating_sub(cpu))
+	}
+}
+
+impl Blocking {
+	pub fn new(limit: Option<usize>) -> Self {
+		Self {
+			limit,
+			permits: limit.map(Semaphore::new),
+			active: Default::default(),
+			calls: Default::default(),
+			queued: Default::default(),
+			running: Default::default(),
+			cpu: Default::default(),
+		}
+	}
+
+	/// Wait for a slot in the pool. Acquire this before building any state that
+	/// would prevent the calling future from being sent across threads.
+	pub async fn permit(&self) -> Permit<'_> {
+		let start = Instant::now();
+		let permit = match &self.permits {
+			Some(semaphore) => Some(semaphore.acquire().await.expect("semaphore closed")),
+			None => None,
+		};
+		add_duration(&self.queued, start.elapsed());
+
+		Permit {
+			blocking: self,
+			_permit: permit,
+		}
+	}
+
+	pub fn statistics(&self) -> BlockingStatistics {
+		let duration = |value: &AtomicU64| Duration::from_nanos(value.load(Ordering::Relaxed));
+
+		BlockingStatistics {
+			limit: self.limit,
+			active: self.active.load(Ordering::Relaxed),
+			calls: self.calls.load(Ordering::Relaxed),
+			queued: duration(&self.queued),
+			running: duration(&self.running),
+			cpu: thread_cpu_time().map(|_| duration(&self.cpu)),
+		}
+	}
+}
+
+/// A held slot in the blocking pool. The slot is released on drop.
+pub struct Permit<'a> {
+	blocking: &'a Blocking,
+	_permit: Option<SemaphorePermit<'a>>,
+}
+
+impl Permit<'_> {
+	/// Run synchronous data access. The current worker thread is handed over to
+	/// the blocking pool for the duration, so other tasks continue to progress.
+	pub fn run<T>(&self, function: impl FnOnce() -> T) -> T {
+		let blocking = self.blocking;
+		blocking.active.fetch_add(1, Ordering::Relaxed);
+
+		let (value, running, cpu) = task::block_in_place(|| {
+			let cpu_start = thread_cpu_time();
+			let start = Instant::now();
+			let value = function();
+			let running = start.elapsed();
+			let cpu = cpu_start
+				.zip(thread_cpu_time())
+				.map(|(start, end)| end.saturating_sub(start));
+			(value, running, cpu)
+		});
+
+		blocking.active.fetch_sub(1, Ordering::Relaxed);
+		blocking.calls.fetch_add(1, Ordering::Relaxed);
+		add_duration(&blocking.running, running);
+		if let Some(cpu) = cpu {
+			add_duration(&blocking.cpu, cpu);
+		}
+
+		value
+	}
+}
+
+fn add_duration(total: &AtomicU64, duration: Duration) {
+	let nanos = u64::try_from(duration.as_nanos()).unwrap_or(u64::MAX);
+	total.fetch_add(nanos, Ordering::Relaxed);
+}
+
+#[cfg(unix)]
+fn thread_cpu_time() -> Option<Duration> {
+	let mut time = libc::timespec {
+		tv_sec: 0,
+		tv_nsec: 0,
+	};
+	// SAFETY: clock_gettime only writes to the provided timespec.
+	let result = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut time) };
+	match result {
+		0 => Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32)),
+		_ => None,
+	}
+}
+
+#[cfg(not(unix))]
+fn thread_cpu_time() -> Option<Duration> {
+	None
+}
@@ -0,0 +1,178 @@
+use std::{collections::HashMap, time::SystemTime};
+
+use strum::Display;
+
+use crate::version::VersionKey;
+
+use super::data::Version;
+
+/// Changes made to the sheets of a version, relative to the version released
+/// before it.
+#[derive(Debug)]
+pub struct Changelog {
+	pub version: VersionKey,
+	/// Version the changes are relative to. Absent for the earliest known
+	/// version, for which every sheet is considered added.
+	pub previous: Option<VersionKey>,
+	pub generated: SystemTime,
+	/// Changed sheets, ordered by name.
+	pub sheets: Vec<SheetChange>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+#[strum(serialize_all = "snake_case")]
+pub enum ChangeKind {
+	Added,
+	Removed,
+	Modified,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SheetChange {
+	pub sheet: String,
+	pub kind: ChangeKind,
+	/// Number of rows in the previous version, if the sheet was present.
+	pub rows_before: Option<usize>,
+	/// Number of rows in this version, if the sheet is present.
+	pub rows_after: Option<usize>,
+}
+
+impl SheetChange {
+	/// Change in the number of rows between the versions.
+	pub fn rows_delta(&self) -> i64 {
+		let count = |rows: Option<usize>| rows.unwrap_or(0) as i64;
+		count(self.rows_after) - count(self.rows_before)
+	}
+}
+
+/// State of a sheet within a version, as relevant to detecting changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SheetState {
+	hash: Option<u64>,
+	rows: usize,
+}
+
+impl Changelog {
+	pub fn build(version: &Version, previous: Option<&Version>) -> Self {
+		Self {
+			version: version.key(),
+			previous: previous.map(Version::key),
+			generated: SystemTime::now(),
+			sheets: diff(
+				&sheet_states(version),
+				&previous.map(sheet_states).unwrap_or_default(),
+			),
+		}
+	}
+}
+
+fn sheet_states(version: &Version) -> HashMap<&str, SheetState> {
+	version
+		.sheets()
+		.map(|sheet| {
+			let state = SheetState {
+				hash: version.sheet_hash(sheet),
+				rows: version.rows(sheet).map_or(0, |rows| rows.len()),
+			};
+			(sheet, state)
+		})
+		.collect()
+}
+
+fn diff(
+	current: &HashMap<&str, SheetState>,
+	previous: &HashMap<&str, SheetState>,
+) -> Vec<SheetChange> {
+	let added_modified = current.iter().filter_map(|(&sheet, after)| {
+		let kind = match previous.get(sheet) {
+			None => ChangeKind::Added,
+			// Sheets that could not be hashed fall back to comparing row counts.
+			Some(before) => match (before.hash, after.hash) {
+				(Some(a), Some(b)) if a == b => return None,
+				(Some(_), Some(_)) => ChangeKind::Modified,
+				_ if before.rows == after.rows => return None,
+				_ => ChangeKind::Modified,
+			},
+		};
+
+		Some(SheetChange {
+			sheet: sheet.to_string(),
+			kind,
+			rows_before: previous.get(sheet).map(|state| state.rows),
+			rows_after: Some(after.rows),
+		})
+	});
+
+	let removed = previous
+		.iter()
+		.filter(|(sheet, _)| !current.contains_key(*sheet))
+		.map(|(&sheet, before)| SheetChange {
+			sheet: sheet.to_string(),
+			kind: ChangeKind::Removed,
+			rows_before: Some(before.rows),
+			rows_after: None,
+		});
+
+	let mut changes = added_modified.chain(removed).collect::<Vec<_>>();
+	changes.sort_by(|a, b| a.sheet.cmp(&b.sheet));
+	changes
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn state(hash: Option<u64>, rows: usize) -> SheetState {
+		SheetState { hash, rows }
+	}
+
+	#[test]
+	fn diff_sheets() {
+		let previous = HashMap::from([
+			("Same", state(Some(1), 10)),
+			("Changed", state(Some(2), 10)),
+			("Removed", state(Some(3), 5)),
+			("Unhashed", state(None, 4)),
+		]);
+		let current = HashMap::from([
+			("Same", state(Some(1), 10)),
+			("Changed", state(Some(4), 12)),
+			("Added", state(Some(5), 3)),
+			("Unhashed", state(None, 4)),
+		]);
+
+		let changes = diff(&current, &previous);
+		assert_eq!(
+			changes,
+			vec![
+				SheetChange {
+					sheet: "Added".into(),
+					kind: ChangeKind::Added,
+					rows_before: None,
+					rows_after: Some(3),
+				},
+				SheetChange {
+					sheet: "Changed".into(),
+					kind: ChangeKind::Modified,
+					rows_before: Some(10),
+					rows_after: Some(12),
+				},
+				SheetChange {
+					sheet: "Removed".into(),
+					kind: ChangeKind::Removed,
+					rows_before: Some(5),
+					rows_after: None,
+				},
+			]
+		);
+		assert_eq!(
+			changes
+				.iter()
+				.map(SheetChange::rows_delta)
+				.collect::<Vec<_>>(),
+			vec![3, 2, -5]
+		);
+	}
+}
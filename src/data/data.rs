@@ -1,28 +1,49 @@
 use std::{
 	collections::{HashMap, HashSet},
-	sync::{Arc, RwLock},
+	ops::Deref,
+	path::{Path, PathBuf},
+	sync::{
+		atomic::{AtomicUsize, Ordering},
+		Arc, RwLock,
+	},
 };
 
 use anyhow::Context;
+use figment::value::magic::RelativePathBuf;
 use ironworks::{
 	excel::{Excel, Language},
 	sqpack::SqPack,
 	zipatch, Ironworks,
 };
 use serde::Deserialize;
-use tokio::{select, sync::watch};
+use tokio::{select, sync::watch, task};
 use tokio_util::sync::CancellationToken;
 
-use crate::version::{self, VersionKey};
+use crate::{
+	job,
+	version::{self, VersionKey},
+};
 
 use super::{
+	blocking::Blocking,
+	changelog::Changelog,
 	error::{Error, Result},
+	hash,
 	language::LanguageString,
+	rows::RowSet,
 };
 
 #[derive(Debug, Deserialize)]
 pub struct Config {
 	language: LanguageString,
+
+	/// Directory that data derived from game versions, such as content hashes,
+	/// is persisted to.
+	directory: RelativePathBuf,
+
+	/// Maximum number of requests that may access game data concurrently. Each
+	/// occupies a thread in the blocking pool while reading. Omit for no limit.
+	blocking_threads: Option<usize>,
 }
 
 pub struct Data {
@@ -34,10 +55,19 @@ pub struct Data {
 	zipatch: zipatch::ZiPatch,
 
 	versions: RwLock<HashMap<VersionKey, Arc<Version>>>,
+
+	/// Changes made by each version relative to the version released before it.
+	changelogs: RwLock<HashMap<VersionKey, Arc<Changelog>>>,
+
+	directory: PathBuf,
+
+	jobs: Arc<job::Manager>,
+
+	blocking: Blocking,
 }
 
 impl Data {
-	pub fn new(config: Config) -> Self {
+	pub fn new(config: Config, jobs: Arc<job::Manager>) -> Self {
 		let (sender, _receiver) = watch::channel(vec![]);
 
 		Data {
@@ -45,6 +75,10 @@ impl Data {
 			channel: sender,
 			zipatch: zipatch::ZiPatch::new().with_persisted_lookups(),
 			versions: Default::default(),
+			changelogs: Default::default(),
+			directory: config.directory.relative(),
+			jobs,
+			blocking: Blocking::new(config.blocking_threads),
 		}
 	}
 
@@ -58,12 +92,18 @@ impl Data {
 		self.default_language
 	}
 
+	/// Pool used to run synchronous reads of game data.
+	pub fn blocking(&self) -> &Blocking {
+		&self.blocking
+	}
+
 	pub fn subscribe(&self) -> watch::Receiver<Vec<VersionKey>> {
 		self.channel.subscribe()
 	}
 
 	pub async fn start(&self, cancel: CancellationToken, version: &version::Manager) -> Result<()> {
 		let execute_prepare = |versions: Vec<VersionKey>| async {
+			self.retire_old_versions(&versions);
 			select! {
 				result = self.prepare_new_versions(version, versions) => result,
 				_ = cancel.cancelled() => Ok(()),
@@ -84,6 +124,40 @@ impl Data {
 		Ok(())
 	}
 
+	fn retire_old_versions(&self, versions: &[VersionKey]) {
+		let current = versions.iter().collect::<HashSet<_>>();
+
+		let retired = {
+			let mut known = self.versions.write().expect("poisoned");
+			let retired_keys = known
+				.keys()
+				.filter(|key| !current.contains(key))
+				.copied()
+				.collect::<Vec<_>>();
+			retired_keys
+				.into_iter()
+				.filter_map(|key| known.remove(&key))
+				.collect::<Vec<_>>()
+		};
+
+		if retired.is_empty() {
+			return;
+		}
+
+		// Removing the version from the map prevents new requests from acquiring
+		// it - any requests already holding a guard will keep its resources alive
+		// until they complete.
+		for version in &retired {
+			tracing::info!(
+				key = %version.key,
+				in_flight = version.in_flight(),
+				"version retired"
+			);
+		}
+
+		self.broadcast_version_list();
+	}
+
 	async fn prepare_new_versions(
 		&self,
 		version: &version::Manager,
@@ -98,24 +172,78 @@ impl Data {
 			.cloned()
 			.collect::<HashSet<_>>();
 
-		let results = versions
-			.into_iter()
-			.filter(|key| !known_keys.contains(key))
-			.map(|key| {
-				self.prepare_version(version, key)
-					.map_err(|error| (key, error))
-			});
-
 		// Run all the version preparation. We aren't failing fast on this, as an
 		// erroneous version should not prevent other versions from being prepared.
-		for (key, error) in results.filter_map(Result::err) {
-			tracing::warn!(%key, reason = %error, "did not prepare version")
+		for key in versions.into_iter().filter(|key| !known_keys.contains(key)) {
+			let job = self
+				.jobs
+				.create(job::Kind::Ingestion, format!("version {key}"));
+			job.start();
+			let result = self.prepare_version(version, key).await;
+			job.finish(&result);
+			if let Err(error) = result {
+				tracing::warn!(%key, reason = %error, "did not prepare version")
+			}
 		}
 
+		self.update_changelogs(version);
+
 		Ok(())
 	}
 
-	fn prepare_version(&self, manager: &version::Manager, version_key: VersionKey) -> Result<()> {
+	/// Build changelogs for any versions that lack one, or whose preceding
+	/// version has changed as versions are prepared and retired. Versions are
+	/// ordered by their release time - versions without one have no predecessor.
+	fn update_changelogs(&self, manager: &version::Manager) {
+		let versions = self.versions.read().expect("poisoned").clone();
+
+		let mut released = versions
+			.keys()
+			.filter_map(|&key| manager.released(key).map(|time| (time, key)))
+			.collect::<Vec<_>>();
+		released.sort();
+		let previous = released
+			.windows(2)
+			.map(|pair| (pair[1].1, pair[0].1))
+			.collect::<HashMap<_, _>>();
+
+		let mut changelogs = self.changelogs.write().expect("poisoned");
+		changelogs.retain(|key, _| versions.contains_key(key));
+
+		for (key, version) in &versions {
+			let previous = previous.get(key).copied();
+			if changelogs
+				.get(key)
+				.is_some_and(|changelog| changelog.previous == previous)
+			{
+				continue;
+			}
+
+			let changelog = Changelog::build(
+				version,
+				previous
+					.and_then(|key| versions.get(&key))
+					.map(|version| version.as_ref()),
+			);
+			tracing::debug!(%key, ?previous, sheets = changelog.sheets.len(), "changelog built");
+			changelogs.insert(*key, Arc::new(changelog));
+		}
+	}
+
+	/// Changelog of the specified version, if it has been built.
+	pub fn changelog(&self, version: VersionKey) -> Option<Arc<Changelog>> {
+		self.changelogs
+			.read()
+			.expect("poisoned")
+			.get(&version)
+			.cloned()
+	}
+
+	async fn prepare_version(
+		&self,
+		manager: &version::Manager,
+		version_key: VersionKey,
+	) -> Result<()> {
 		// Preparation only happens when we're told that a version exists, so anything going wrong _here_ is a hefty failure.
 		let version = manager
 			.version(version_key)
@@ -140,8 +268,13 @@ impl Data {
 			})
 			.build();
 
-		// Build a version and save it out to the struct.
-		let version = Version::new(view);
+		// Build a version and save it out to the struct. Indexing and hashing the
+		// version's sheets reads every page of game data - keep it off the runtime.
+		let hash_directory = self.directory.join("hashes");
+		let version =
+			task::spawn_blocking(move || Version::new(version_key, view, &hash_directory))
+				.await
+				.context("version preparation panicked")?;
 		self.versions
 			.write()
 			.expect("poisoned")
@@ -158,13 +291,15 @@ impl Data {
 		Ok(())
 	}
 
-	pub fn version(&self, version: VersionKey) -> Result<Arc<Version>> {
+	/// Acquire a guard for the specified version. The version's resources will
+	/// not be torn down while the guard is held, even if it is retired.
+	pub fn version(&self, version: VersionKey) -> Result<VersionGuard> {
 		let versions = self.versions.read().expect("poisoned");
 
 		versions
 			.get(&version)
 			.ok_or_else(|| Error::UnknownVersion(version))
-			.cloned()
+			.map(|version| VersionGuard::new(version.clone()))
 	}
 
 	fn broadcast_version_list(&self) {
@@ -183,15 +318,46 @@ impl Data {
 }
 
 pub struct Version {
+	key: VersionKey,
+	in_flight: AtomicUsize,
+
 	ironworks: Arc<Ironworks>,
 	excel: Arc<Excel<'static>>,
+
+	rows: HashMap<String, RowSet>,
+	languages: HashMap<String, Vec<Language>>,
+	hashes: HashMap<String, u64>,
 }
 
 impl Version {
-	fn new(view: zipatch::View) -> Self {
+	fn new(key: VersionKey, view: zipatch::View, hash_directory: &Path) -> Self {
 		let ironworks = Arc::new(Ironworks::new().with_resource(SqPack::new(view)));
 		let excel = Arc::new(Excel::new(ironworks.clone()));
-		Self { ironworks, excel }
+		let (rows, languages) = index_sheets(key, &excel);
+		let hashes = hash::sheet_hashes(hash_directory, key, &ironworks, &languages);
+		Self {
+			key,
+			in_flight: AtomicUsize::new(0),
+			ironworks,
+			excel,
+			rows,
+			languages,
+			hashes,
+		}
+	}
+
+	pub fn key(&self) -> VersionKey {
+		self.key
+	}
+
+	/// Names of the sheets in this version.
+	pub fn sheets(&self) -> impl Iterator<Item = &str> {
+		self.rows.keys().map(String::as_str)
+	}
+
+	/// Number of guards currently held for this version.
+	pub fn in_flight(&self) -> usize {
+		self.in_flight.load(Ordering::Relaxed)
 	}
 
 	pub fn ironworks(&self) -> Arc<Ironworks> {
@@ -201,4 +367,107 @@ impl Version {
 	pub fn excel(&self) -> Arc<Excel<'static>> {
 		self.excel.clone()
 	}
+
+	/// Row IDs present in the specified sheet. `None` if the sheet does not
+	/// exist, or its rows could not be listed when the version was prepared.
+	pub fn rows(&self, sheet: &str) -> Option<&RowSet> {
+		self.rows.get(sheet)
+	}
+
+	/// Languages declared by the specified sheet's header. `None` if the sheet
+	/// does not exist, or its header could not be read when the version was
+	/// prepared.
+	pub fn languages(&self, sheet: &str) -> Option<&[Language]> {
+		self.languages.get(sheet).map(Vec::as_slice)
+	}
+
+	/// Hash of the content of the specified sheet, across all languages. Stable
+	/// between versions for as long as the sheet's data is unchanged.
+	pub fn sheet_hash(&self, sheet: &str) -> Option<u64> {
+		self.hashes.get(sheet).copied()
+	}
+
+	/// Hash of the content of a single row, across all languages.
+	pub fn row_hash(&self, sheet: &str, row_id: u32, subrow_id: Option<u16>) -> Result<u64> {
+		let languages = self
+			.languages(sheet)
+			.with_context(|| format!("unknown sheet {sheet}"))?;
+		let hash = hash::row_hash(&self.ironworks, sheet, languages, row_id, subrow_id)?;
+		Ok(hash)
+	}
+}
+
+type SheetIndex = (HashMap<String, RowSet>, HashMap<String, Vec<Language>>);
+
+fn index_sheets(key: VersionKey, excel: &Excel) -> SheetIndex {
+	let list = match excel.list() {
+		Ok(list) => list,
+		Err(error) => {
+			tracing::warn!(%key, ?error, "could not list sheets, skipping sheet index");
+			return Default::default();
+		}
+	};
+
+	let mut row_sets = HashMap::new();
+	let mut languages = HashMap::new();
+
+	for name in list.iter() {
+		let sheet = match excel.sheet(name.as_ref()) {
+			Ok(sheet) => sheet,
+			Err(error) => {
+				tracing::debug!(%key, %name, ?error, "could not read sheet, skipping index");
+				continue;
+			}
+		};
+
+		match sheet.languages() {
+			Ok(mut sheet_languages) => {
+				sheet_languages.sort_by_key(|language| u8::from(*language));
+				languages.insert(name.to_string(), sheet_languages);
+			}
+			Err(error) => {
+				tracing::debug!(%key, %name, ?error, "could not read sheet languages");
+			}
+		}
+
+		let rows = sheet.with().iter().map(|row| row.row_id()).collect();
+		row_sets.insert(name.into_owned(), rows);
+	}
+
+	tracing::debug!(%key, sheets = row_sets.len(), "sheet index built");
+
+	(row_sets, languages)
+}
+
+impl Drop for Version {
+	fn drop(&mut self) {
+		tracing::debug!(key = %self.key, "version resources released");
+	}
+}
+
+/// Reference to a version held for the duration of a request. Retiring a
+/// version defers teardown of its resources until all guards are dropped.
+pub struct VersionGuard {
+	version: Arc<Version>,
+}
+
+impl VersionGuard {
+	fn new(version: Arc<Version>) -> Self {
+		version.in_flight.fetch_add(1, Ordering::Relaxed);
+		Self { version }
+	}
+}
+
+impl Deref for VersionGuard {
+	type Target = Version;
+
+	fn deref(&self) -> &Self::Target {
+		&self.version
+	}
+}
+
+impl Drop for VersionGuard {
+	fn drop(&mut self) {
+		self.version.in_flight.fetch_sub(1, Ordering::Relaxed);
+	}
 }
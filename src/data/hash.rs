@@ -0,0 +1,118 @@
+use std::{collections::HashMap, fs, hash::Hasher, path::Path};
+
+use anyhow::{Context, Result};
+use ironworks::{
+	excel::Language,
+	file::{exd, exh},
+	Ironworks,
+};
+use seahash::SeaHasher;
+
+use crate::{utility::persist, version::VersionKey};
+
+/// Path to the data file of a sheet's page, in the specified language.
+pub fn page_path(sheet: &str, start_id: u32, language: Language) -> String {
+	let suffix = match language {
+		Language::None => "",
+		Language::Japanese => "_ja",
+		Language::English => "_en",
+		Language::German => "_de",
+		Language::French => "_fr",
+		Language::ChineseSimplified => "_chs",
+		Language::ChineseTraditional => "_cht",
+		Language::Korean => "_ko",
+	};
+	format!("exd/{sheet}_{start_id}{suffix}.exd")
+}
+
+/// Load the content hashes of each sheet in a version, building and persisting
+/// them if they have not been built before. Versions are immutable, so hashes
+/// built once remain valid for the lifetime of the version.
+pub fn sheet_hashes(
+	directory: &Path,
+	key: VersionKey,
+	ironworks: &Ironworks,
+	languages: &HashMap<String, Vec<Language>>,
+) -> HashMap<String, u64> {
+	let path = directory.join(format!("{key}.json"));
+
+	match persist::read(&path, |bytes| Ok(serde_json::from_slice(bytes)?)) {
+		Ok(Some(hashes)) => return hashes,
+		Ok(None) => {}
+		Err(error) => tracing::warn!(%key, ?error, "could not read sheet hashes, rebuilding"),
+	}
+
+	let hashes = languages
+		.iter()
+		.filter_map(
+			|(sheet, languages)| match sheet_hash(ironworks, sheet, languages) {
+				Ok(hash) => Some((sheet.clone(), hash)),
+				Err(error) => {
+					tracing::debug!(%key, %sheet, ?error, "could not hash sheet");
+					None
+				}
+			},
+		)
+		.collect::<HashMap<_, _>>();
+
+	tracing::debug!(%key, sheets = hashes.len(), "sheet hashes built");
+
+	let result = fs::create_dir_all(directory)
+		.map_err(anyhow::Error::from)
+		.and_then(|_| serde_json::to_vec(&hashes).map_err(anyhow::Error::from))
+		.and_then(|bytes| persist::write(&path, &bytes));
+	if let Err(error) = result {
+		tracing::warn!(%key, ?error, "could not persist sheet hashes");
+	}
+
+	hashes
+}
+
+/// Hash of a sheet's header and the raw data of every page, in every language.
+fn sheet_hash(ironworks: &Ironworks, sheet: &str, languages: &[Language]) -> Result<u64> {
+	let header_path = format!("exd/{sheet}.exh");
+	let header = ironworks.file::<exh::ExcelHeader>(&header_path)?;
+
+	let mut hasher = SeaHasher::new();
+	hasher.write(&ironworks.file::<Vec<u8>>(&header_path)?);
+
+	for page in header.pages() {
+		for language in languages {
+			let bytes = ironworks.file::<Vec<u8>>(&page_path(sheet, page.start_id(), *language))?;
+			hasher.write_u8(u8::from(*language));
+			hasher.write(&bytes);
+		}
+	}
+
+	Ok(hasher.finish())
+}
+
+/// Hash of the raw data of a single row, in every language.
+pub fn row_hash(
+	ironworks: &Ironworks,
+	sheet: &str,
+	languages: &[Language],
+	row_id: u32,
+	subrow_id: Option<u16>,
+) -> Result<u64> {
+	let header = ironworks.file::<exh::ExcelHeader>(&format!("exd/{sheet}.exh"))?;
+	let page = header
+		.pages()
+		.iter()
+		.find(|page| (page.start_id()..page.start_id() + page.row_count()).contains(&row_id))
+		.with_context(|| format!("row {row_id} is not within any page of {sheet}"))?;
+
+	let mut hasher = SeaHasher::new();
+	for language in languages {
+		let data =
+			ironworks.file::<exd::ExcelData>(&page_path(sheet, page.start_id(), *language))?;
+		let bytes = match subrow_id {
+			Some(subrow_id) => data.subrow_data(row_id, subrow_id)?,
+			None => data.row_data(row_id)?,
+		};
+		hasher.write_u8(u8::from(*language));
+		hasher.write(bytes);
+	}
+
+	Ok(hasher.finish())
+}
@@ -1,9 +1,17 @@
+mod blocking;
+mod changelog;
 mod data;
 mod error;
+mod hash;
 mod language;
+mod rows;
 
 pub use {
-	data::{Config, Data, Version},
+	blocking::{Blocking, BlockingStatistics, Permit},
+	changelog::{ChangeKind, Changelog, SheetChange},
+	data::{Config, Data, Version, VersionGuard},
 	error::Error,
+	hash::page_path,
 	language::LanguageString,
+	rows::RowSet,
 };
@@ -0,0 +1,189 @@
+use std::iter;
+
+use either::Either;
+
+const WORD_BITS: u32 = u64::BITS;
+
+/// Maximum span of row IDs a bitmap may cover per row present. A bitmap costs
+/// a bit per ID in its span, and a sorted list 32 bits per row - beyond this,
+/// sheets are sparse enough that the list is smaller.
+const MAX_SPAN_PER_ROW: u64 = 32;
+
+/// Compact index of the row IDs present in a sheet. Dense sheets are stored as
+/// a bitmap sized by the range between the lowest and highest row ID, at one
+/// bit per ID; sparse sheets fall back to a sorted list.
+#[derive(Debug, Default)]
+pub struct RowSet {
+	storage: Storage,
+	len: usize,
+}
+
+#[derive(Debug)]
+enum Storage {
+	Bitmap { offset: u32, words: Vec<u64> },
+	Sorted(Vec<u32>),
+}
+
+impl Default for Storage {
+	fn default() -> Self {
+		Self::Sorted(vec![])
+	}
+}
+
+impl RowSet {
+	pub fn contains(&self, row_id: u32) -> bool {
+		match &self.storage {
+			Storage::Bitmap { offset, words } => {
+				let Some(index) = row_id.checked_sub(*offset) else {
+					return false;
+				};
+
+				words
+					.get((index / WORD_BITS) as usize)
+					.map_or(false, |word| word & (1 << (index % WORD_BITS)) != 0)
+			}
+			Storage::Sorted(row_ids) => row_ids.binary_search(&row_id).is_ok(),
+		}
+	}
+
+	/// Number of distinct row IDs in the set.
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	/// Iterate over row IDs in ascending order, starting at the provided ID
+	/// (inclusive). Gaps in the sheet are skipped a word at a time.
+	pub fn iter_from(&self, start: u32) -> impl Iterator<Item = u32> + '_ {
+		let (offset, words) = match &self.storage {
+			Storage::Bitmap { offset, words } => (*offset, words),
+			Storage::Sorted(row_ids) => {
+				let first = row_ids.partition_point(|&row_id| row_id < start);
+				return Either::Right(row_ids[first..].iter().copied());
+			}
+		};
+
+		let start = start.saturating_sub(offset);
+		let first_word = (start / WORD_BITS) as usize;
+		let first_mask = u64::MAX << (start % WORD_BITS);
+
+		Either::Left(
+			words
+				.iter()
+				.enumerate()
+				.skip(first_word)
+				.flat_map(move |(index, &word)| {
+					let mut word = match index == first_word {
+						true => word & first_mask,
+						false => word,
+					};
+					let base = offset + index as u32 * WORD_BITS;
+
+					iter::from_fn(move || match word {
+						0 => None,
+						_ => {
+							let bit = word.trailing_zeros();
+							word &= word - 1;
+							Some(base + bit)
+						}
+					})
+				}),
+		)
+	}
+}
+
+impl FromIterator<u32> for RowSet {
+	fn from_iter<T: IntoIterator<Item = u32>>(iter: T) -> Self {
+		let mut row_ids = iter.into_iter().collect::<Vec<_>>();
+		row_ids.sort_unstable();
+		row_ids.dedup();
+
+		let (Some(&min), Some(&max)) = (row_ids.first(), row_ids.last()) else {
+			return Self::default();
+		};
+
+		let len = row_ids.len();
+		let span = u64::from(max - min) + 1;
+		if span > len as u64 * MAX_SPAN_PER_ROW {
+			return Self {
+				storage: Storage::Sorted(row_ids),
+				len,
+			};
+		}
+
+		let mut words = vec![0u64; ((max - min) / WORD_BITS) as usize + 1];
+		for row_id in row_ids {
+			let index = row_id - min;
+			words[(index / WORD_BITS) as usize] |= 1 << (index % WORD_BITS);
+		}
+
+		Self {
+			storage: Storage::Bitmap { offset: min, words },
+			len,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn contains() {
+		let rows = [5, 6, 200, 6].into_iter().collect::<RowSet>();
+		assert_eq!(rows.len(), 3);
+		assert!(rows.contains(5));
+		assert!(rows.contains(200));
+		assert!(!rows.contains(0));
+		assert!(!rows.contains(7));
+		assert!(!rows.contains(201));
+	}
+
+	#[test]
+	fn iter_from_skips_gaps() {
+		let rows = [3, 10, 70, 1000].into_iter().collect::<RowSet>();
+		assert_eq!(rows.iter_from(0).collect::<Vec<_>>(), vec![3, 10, 70, 1000]);
+		assert_eq!(rows.iter_from(10).collect::<Vec<_>>(), vec![10, 70, 1000]);
+		assert_eq!(rows.iter_from(71).collect::<Vec<_>>(), vec![1000]);
+		assert_eq!(rows.iter_from(1001).count(), 0);
+	}
+
+	#[test]
+	fn dense() {
+		let rows = (0..100).step_by(3).chain(200..=300).collect::<RowSet>();
+		assert!(matches!(rows.storage, Storage::Bitmap { .. }));
+		assert_eq!(rows.len(), 135);
+		assert!(rows.contains(99));
+		assert!(!rows.contains(100));
+		assert_eq!(
+			rows.iter_from(98).take(3).collect::<Vec<_>>(),
+			vec![99, 200, 201]
+		);
+	}
+
+	#[test]
+	fn sparse() {
+		let rows = [u32::MAX, 0, 1_000_000, 0].into_iter().collect::<RowSet>();
+		assert!(matches!(rows.storage, Storage::Sorted(..)));
+		assert_eq!(rows.len(), 3);
+		assert!(rows.contains(1_000_000));
+		assert!(!rows.contains(1));
+		assert_eq!(
+			rows.iter_from(1).collect::<Vec<_>>(),
+			vec![1_000_000, u32::MAX]
+		);
+	}
+
+	#[test]
+	fn empty() {
+		let rows = RowSet::from_iter([]);
+		assert!(rows.is_empty());
+		assert!(!rows.contains(0));
+		assert_eq!(rows.iter_from(0).count(), 0);
+	}
+}
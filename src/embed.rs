@@ -0,0 +1,219 @@
+use std::sync::Arc;
+
+use anyhow::{bail, Context, Result};
+use futures::TryFutureExt;
+use ironworks::excel::Language;
+use serde::Deserialize;
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio_util::sync::CancellationToken;
+
+use crate::{data, job, read, schema, storage, version, version::VersionKey};
+
+pub use crate::utility::warnings::Warnings;
+
+/// Configuration for an embedded instance. Mirrors the equivalent sections of
+/// `boilmaster.toml`, such that an existing configuration file may be reused.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	pub job: job::Config,
+	pub storage: Option<storage::Config>,
+	pub version: version::Config,
+	pub data: data::Config,
+	pub schema: schema::Config,
+}
+
+/// Row to read from a sheet.
+#[derive(Debug, Clone)]
+pub struct RowRequest {
+	pub sheet: String,
+	pub row_id: u32,
+	pub subrow_id: u16,
+
+	/// Schema to read the row with. Defaults to the configured default schema.
+	pub schema: Option<schema::Specifier>,
+
+	/// Language to read string fields in, where the filter does not specify
+	/// one. Defaults to the configured default language.
+	pub language: Option<Language>,
+
+	/// Fields to read. Defaults to all fields.
+	pub filter: Option<read::Filter>,
+
+	/// Depth to which references are resolved. Defaults to 2.
+	pub depth: u8,
+}
+
+impl RowRequest {
+	pub fn new(sheet: impl Into<String>, row_id: u32) -> Self {
+		Self {
+			sheet: sheet.into(),
+			row_id,
+			subrow_id: 0,
+			schema: None,
+			language: None,
+			filter: None,
+			depth: 2,
+		}
+	}
+}
+
+/// Entry point for using boilmaster as a library, without its HTTP layer.
+///
+/// Owns the job, version, data, and schema services, and provides access to
+/// each for use cases not covered by the helpers here. Search is not available,
+/// as the search module is not currently built.
+///
+/// Data access hands worker threads over to the blocking pool, so a
+/// multi-threaded tokio runtime is required - `start` and `read` fail on a
+/// `current_thread` runtime.
+///
+/// ```no_run
+/// # async fn example(config: boilmaster::embed::Config) -> anyhow::Result<()> {
+/// use boilmaster::embed::{Boilmaster, RowRequest};
+/// use tokio_util::sync::CancellationToken;
+///
+/// let boilmaster = std::sync::Arc::new(Boilmaster::new(config)?);
+/// tokio::spawn({
+/// 	let boilmaster = boilmaster.clone();
+/// 	async move { boilmaster.start(CancellationToken::new()).await }
+/// });
+///
+/// // ... wait for `ready` ...
+///
+/// let version = boilmaster.resolve_version(None).expect("latest version");
+/// let (row, warnings) = boilmaster
+/// 	.read(version, RowRequest::new("Item", 1))
+/// 	.await?
+/// 	.decompose();
+/// # Ok(())
+/// # }
+/// ```
+pub struct Boilmaster {
+	job: Arc<job::Manager>,
+	version: Arc<version::Manager>,
+	data: Arc<data::Data>,
+	schema: Arc<schema::Provider>,
+}
+
+impl Boilmaster {
+	pub fn new(config: Config) -> Result<Self> {
+		let job = Arc::new(job::Manager::new(config.job).context("failed to create job manager")?);
+		let storage = config
+			.storage
+			.map(storage::Storage::new)
+			.transpose()
+			.context("failed to create storage backend")?
+			.map(Arc::new);
+		let version = Arc::new(
+			version::Manager::new(config.version, job.clone(), storage)
+				.context("failed to create version manager")?,
+		);
+		let data = Arc::new(data::Data::new(config.data, job.clone()));
+		let schema = Arc::new(
+			schema::Provider::new(config.schema, data.clone())
+				.context("failed to create schema provider")?,
+		);
+
+		Ok(Self {
+			job,
+			version,
+			data,
+			schema,
+		})
+	}
+
+	/// Run the services until the cancellation token fires. Versions are
+	/// discovered, prepared, and kept up to date while this is running.
+	pub async fn start(&self, cancel: CancellationToken) -> Result<()> {
+		ensure_multi_thread()?;
+
+		tokio::try_join!(
+			self.job.start(cancel.clone()),
+			self.version.start(cancel.clone()),
+			self.data
+				.start(cancel.clone(), &self.version)
+				.map_err(anyhow::Error::from),
+			self.schema.start(cancel).map_err(anyhow::Error::from),
+		)?;
+
+		Ok(())
+	}
+
+	/// Whether each service has completed its initial setup, and data may be
+	/// read.
+	pub fn ready(&self) -> bool {
+		self.version.ready() && self.data.ready() && self.schema.ready()
+	}
+
+	pub fn job(&self) -> &Arc<job::Manager> {
+		&self.job
+	}
+
+	pub fn version(&self) -> &Arc<version::Manager> {
+		&self.version
+	}
+
+	pub fn data(&self) -> &Arc<data::Data> {
+		&self.data
+	}
+
+	pub fn schema(&self) -> &Arc<schema::Provider> {
+		&self.schema
+	}
+
+	/// Resolve a version name to its key. `None` resolves the latest version.
+	pub fn resolve_version(&self, name: Option<&str>) -> Option<VersionKey> {
+		self.version.resolve(name)
+	}
+
+	/// Read a single row from a prepared version. Reads share the data
+	/// service's blocking limit with any other readers.
+	pub async fn read(
+		&self,
+		version: VersionKey,
+		request: RowRequest,
+	) -> Result<Warnings<read::Value>> {
+		ensure_multi_thread()?;
+
+		let reader = self.data.blocking().permit().await;
+
+		let version_data = self.data.version(version)?;
+		let excel = version_data.excel();
+
+		let specifier = self.schema.canonicalize(request.schema, version)?;
+		let schema = self.schema.schema(specifier)?;
+
+		let language = request
+			.language
+			.unwrap_or_else(|| self.data.default_language());
+		let filter = request.filter.unwrap_or(read::Filter::All);
+
+		let value = reader.run(|| {
+			read::read(
+				&excel,
+				schema.as_ref(),
+				&request.sheet,
+				request.row_id,
+				request.subrow_id,
+				language,
+				&filter,
+				&read::Sentinels::new(),
+				request.depth,
+				read::ReferenceMode::Full,
+			)
+		})?;
+
+		Ok(value)
+	}
+}
+
+// `Permit::run` uses `block_in_place`, which panics outside a multi-threaded
+// runtime. Surface that as an error rather than taking down the host.
+fn ensure_multi_thread() -> Result<()> {
+	match Handle::current().runtime_flavor() {
+		RuntimeFlavor::CurrentThread => {
+			bail!("boilmaster requires a multi-threaded tokio runtime")
+		}
+		_ => Ok(()),
+	}
+}
@@ -0,0 +1,382 @@
+use std::{
+	fs,
+	hash::Hasher,
+	io::{self, Write},
+	net::{IpAddr, SocketAddr},
+	path::{Path, PathBuf},
+	sync::{mpsc, Arc, Mutex},
+	thread,
+	time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use axum::{
+	extract::{ConnectInfo, Request, State},
+	http::Extensions,
+	middleware::Next,
+	response::Response,
+};
+use figment::value::magic::RelativePathBuf;
+use seahash::SeaHasher;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema, version::VersionKey};
+
+use super::quota;
+
+const FILE_NAME: &str = "access.jsonl";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Directory to write rotating access log files to. When omitted, entries
+	/// are written to stdout as JSON lines, separate from application logs.
+	directory: Option<RelativePathBuf>,
+
+	/// Size, in bytes, at which the current log file is rotated.
+	max_size: u64,
+
+	/// Number of rotated log files to keep, in addition to the current file.
+	max_files: usize,
+
+	/// How client IP addresses are recorded.
+	ip: IpRedaction,
+
+	/// Maximum length of the recorded query string, in bytes. Longer query
+	/// strings are truncated.
+	query_length: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			directory: None,
+			max_size: 100 * 1024 * 1024,
+			max_files: 5,
+			ip: IpRedaction::Truncate,
+			query_length: 256,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum IpRedaction {
+	/// Record addresses in full.
+	Full,
+	/// Record the network of addresses only - the last octet of IPv4 addresses,
+	/// and all but the first 48 bits of IPv6 addresses, are zeroed.
+	Truncate,
+	/// Do not record addresses.
+	Omit,
+}
+
+/// A single request, as recorded in the access log.
+#[derive(Debug, Serialize)]
+struct Entry {
+	/// Time the request was received, in milliseconds since the Unix epoch.
+	timestamp: u128,
+	method: String,
+	path: String,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	query: Option<String>,
+	status: u16,
+	/// Time taken to produce the response, in milliseconds.
+	latency: f64,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	version: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	schema: Option<String>,
+	/// Identifier derived from the request's API key. The key itself is never
+	/// recorded.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	api_key: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	ip: Option<String>,
+}
+
+/// Details resolved while handling a request, recorded by extractors for
+/// inclusion in the access log.
+#[derive(Clone, Default)]
+pub struct Resolved(Arc<Mutex<ResolvedDetails>>);
+
+#[derive(Default)]
+struct ResolvedDetails {
+	version: Option<VersionKey>,
+	schema: Option<schema::CanonicalSpecifier>,
+}
+
+/// Record the version a request resolved, if the request is being logged.
+pub fn record_version(extensions: &Extensions, version: VersionKey) {
+	if let Some(Resolved(details)) = extensions.get::<Resolved>() {
+		details.lock().expect("poisoned").version = Some(version);
+	}
+}
+
+/// Record the schema a request resolved, if the request is being logged.
+pub fn record_schema(extensions: &Extensions, schema: &schema::CanonicalSpecifier) {
+	if let Some(Resolved(details)) = extensions.get::<Resolved>() {
+		details.lock().expect("poisoned").schema = Some(schema.clone());
+	}
+}
+
+/// Structured log of requests served, written in the background such that
+/// requests are not held up by log output.
+pub struct AccessLog {
+	ip: IpRedaction,
+	query_length: usize,
+	sender: Mutex<mpsc::Sender<Entry>>,
+}
+
+impl AccessLog {
+	pub fn new(config: Config) -> Result<Self> {
+		let mut sink = match &config.directory {
+			None => Sink::Stdout,
+			Some(directory) => Sink::File(RotatingFile::open(
+				directory.relative(),
+				config.max_size,
+				config.max_files,
+			)?),
+		};
+
+		let (sender, receiver) = mpsc::channel::<Entry>();
+		thread::Builder::new()
+			.name("access-log".into())
+			.spawn(move || {
+				for entry in receiver {
+					if let Err(error) = sink.write(&entry) {
+						tracing::error!(?error, "failed to write access log entry");
+					}
+				}
+			})?;
+
+		Ok(Self {
+			ip: config.ip,
+			query_length: config.query_length,
+			sender: Mutex::new(sender),
+		})
+	}
+
+	fn record(&self, entry: Entry) {
+		// The writer only stops if it panics - there's nothing to be done about
+		// entries sent after that.
+		let _ = self.sender.lock().expect("poisoned").send(entry);
+	}
+
+	fn redact_ip(&self, ip: IpAddr) -> Option<String> {
+		match self.ip {
+			IpRedaction::Full => Some(ip.to_string()),
+			IpRedaction::Truncate => Some(truncate_ip(ip).to_string()),
+			IpRedaction::Omit => None,
+		}
+	}
+}
+
+/// Record each request passing through to the access log.
+pub async fn access_log(
+	State(log): State<Arc<AccessLog>>,
+	mut request: Request,
+	next: Next,
+) -> Response {
+	let received = SystemTime::now();
+	let start = Instant::now();
+
+	let resolved = Resolved::default();
+	request.extensions_mut().insert(resolved.clone());
+
+	let method = request.method().to_string();
+	let path = request.uri().path().to_string();
+	let query = request
+		.uri()
+		.query()
+		.map(|query| truncate(query, log.query_length).to_string());
+	let api_key = quota::api_key(request.headers()).map(api_key_id);
+	let ip = request
+		.extensions()
+		.get::<ConnectInfo<SocketAddr>>()
+		.and_then(|ConnectInfo(address)| log.redact_ip(address.ip()));
+
+	let response = next.run(request).await;
+
+	let details = resolved.0.lock().expect("poisoned");
+	log.record(Entry {
+		timestamp: received
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_millis(),
+		method,
+		path,
+		query,
+		status: response.status().as_u16(),
+		latency: start.elapsed().as_secs_f64() * 1000.0,
+		version: details.version.map(|version| version.to_string()),
+		schema: details.schema.as_ref().map(|schema| schema.to_string()),
+		api_key,
+		ip,
+	});
+	drop(details);
+
+	response
+}
+
+/// Identify an API key without recording it, such that usage by a single key
+/// can be correlated.
+fn api_key_id(key: &str) -> String {
+	let mut hasher = SeaHasher::new();
+	hasher.write(key.as_bytes());
+	format!("{:016x}", hasher.finish())
+}
+
+fn truncate_ip(ip: IpAddr) -> IpAddr {
+	match ip {
+		IpAddr::V4(ip) => {
+			let [a, b, c, _] = ip.octets();
+			IpAddr::from([a, b, c, 0])
+		}
+		IpAddr::V6(ip) => {
+			let [a, b, c, ..] = ip.segments();
+			IpAddr::from([a, b, c, 0, 0, 0, 0, 0])
+		}
+	}
+}
+
+/// Truncate a string to at most the given length in bytes, without splitting
+/// a character.
+fn truncate(value: &str, length: usize) -> &str {
+	if value.len() <= length {
+		return value;
+	}
+
+	let mut end = length;
+	while !value.is_char_boundary(end) {
+		end -= 1;
+	}
+	&value[..end]
+}
+
+enum Sink {
+	Stdout,
+	File(RotatingFile),
+}
+
+impl Sink {
+	fn write(&mut self, entry: &Entry) -> Result<()> {
+		let mut line = serde_json::to_vec(entry)?;
+		line.push(b'\n');
+
+		match self {
+			Self::Stdout => {
+				let mut stdout = io::stdout().lock();
+				stdout.write_all(&line)?;
+				stdout.flush()?;
+			}
+			Self::File(file) => file.write(&line)?,
+		}
+
+		Ok(())
+	}
+}
+
+/// Log file that is rotated once it exceeds a maximum size, keeping a bounded
+/// number of previous files alongside it, i.e. `access.jsonl.1`.
+struct RotatingFile {
+	directory: PathBuf,
+	max_size: u64,
+	max_files: usize,
+	file: fs::File,
+	size: u64,
+}
+
+impl RotatingFile {
+	fn open(directory: PathBuf, max_size: u64, max_files: usize) -> Result<Self> {
+		fs::create_dir_all(&directory)?;
+		let (file, size) = open_append(&directory.join(FILE_NAME))?;
+		Ok(Self {
+			directory,
+			max_size,
+			max_files,
+			file,
+			size,
+		})
+	}
+
+	fn write(&mut self, line: &[u8]) -> Result<()> {
+		if self.size > 0 && self.size + line.len() as u64 > self.max_size {
+			self.rotate()?;
+		}
+
+		self.file.write_all(line)?;
+		self.file.flush()?;
+		self.size += line.len() as u64;
+
+		Ok(())
+	}
+
+	fn rotate(&mut self) -> Result<()> {
+		let path = |index: usize| match index {
+			0 => self.directory.join(FILE_NAME),
+			index => self.directory.join(format!("{FILE_NAME}.{index}")),
+		};
+
+		// Shift each file along by one, dropping the oldest.
+		for index in (0..self.max_files).rev() {
+			let from = path(index);
+			if from.exists() {
+				fs::rename(from, path(index + 1))?;
+			}
+		}
+		if self.max_files == 0 {
+			fs::remove_file(path(0))?;
+		}
+
+		(self.file, self.size) = open_append(&path(0))?;
+
+		Ok(())
+	}
+}
+
+fn open_append(path: &Path) -> Result<(fs::File, u64)> {
+	let file = fs::File::options().create(true).append(true).open(path)?;
+	let size = file.metadata()?.len();
+	Ok((file, size))
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn truncate_addresses() {
+		let v4 = "192.0.2.123".parse().unwrap();
+		assert_eq!(truncate_ip(v4).to_string(), "192.0.2.0");
+
+		let v6 = "2001:db8:1234:5678::1".parse().unwrap();
+		assert_eq!(truncate_ip(v6).to_string(), "2001:db8:1234::");
+	}
+
+	#[test]
+	fn truncate_query_at_char_boundary() {
+		assert_eq!(truncate("abc", 5), "abc");
+		assert_eq!(truncate("abcdef", 3), "abc");
+		assert_eq!(truncate("aé", 2), "a");
+	}
+
+	#[test]
+	fn rotate_files() {
+		let directory = std::env::temp_dir().join(format!("access-{}", uuid::Uuid::new_v4()));
+		let mut file = RotatingFile::open(directory.clone(), 10, 1).unwrap();
+
+		file.write(b"12345678\n").unwrap();
+		file.write(b"abcdefgh\n").unwrap();
+		file.write(b"ABCDEFGH\n").unwrap();
+
+		let read = |name: &str| fs::read_to_string(directory.join(name)).unwrap();
+		assert_eq!(read(FILE_NAME), "ABCDEFGH\n");
+		assert_eq!(read(&format!("{FILE_NAME}.1")), "abcdefgh\n");
+		assert!(!directory.join(format!("{FILE_NAME}.2")).exists());
+
+		fs::remove_dir_all(directory).unwrap();
+	}
+}
@@ -4,11 +4,12 @@ use serde::Deserialize;
 use crate::http::service;
 
 use super::{
+	audit,
 	auth::{basic_auth, BasicAuth},
-	version, versions,
+	data, jobs, maintenance, patches, prime, sheet, snapshot, version, versions,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Config {
 	auth: BasicAuth,
 }
@@ -16,6 +17,14 @@ pub struct Config {
 pub fn router(config: Config) -> Router<service::State> {
 	Router::new()
 		.merge(versions::router())
+		.merge(audit::router())
+		.merge(jobs::router())
+		.merge(maintenance::router())
+		.merge(prime::router())
+		.merge(data::router())
 		.merge(version::router())
+		.merge(sheet::router())
+		.merge(snapshot::router())
+		.merge(patches::router())
 		.layer(middleware::from_fn_with_state(config.auth, basic_auth))
 }
@@ -0,0 +1,79 @@
+use std::time::UNIX_EPOCH;
+
+use axum::{
+	debug_handler,
+	extract::{Query, State},
+	response::IntoResponse,
+	routing::get,
+	Router,
+};
+use maud::{html, Render};
+use serde::Deserialize;
+
+use crate::{audit, http::service};
+
+use super::{base::BaseTemplate, error::Result};
+
+const DEFAULT_LIMIT: usize = 100;
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/audit", get(audit))
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditQuery {
+	actor: Option<String>,
+	action: Option<String>,
+	limit: Option<usize>,
+}
+
+#[debug_handler]
+async fn audit(
+	State(audit): State<service::Audit>,
+	Query(query): Query<AuditQuery>,
+) -> Result<impl IntoResponse> {
+	// Empty form fields are submitted as empty strings - treat them as unset.
+	let non_empty = |value: Option<String>| value.filter(|value| !value.is_empty());
+	let filter = audit::Filter {
+		actor: non_empty(query.actor),
+		action: non_empty(query.action),
+		limit: Some(query.limit.unwrap_or(DEFAULT_LIMIT)),
+	};
+
+	let entries = audit.entries(&filter)?;
+
+	Ok(BaseTemplate {
+		title: "audit".to_string(),
+		content: html! {
+			form method="get" {
+				input type="text" name="actor" placeholder="actor" value=[&filter.actor];
+				input type="text" name="action" placeholder="action" value=[&filter.action];
+				input type="number" name="limit" value=[filter.limit];
+				button type="submit" { "filter" }
+			}
+			table {
+				thead {
+					tr {
+						th { "timestamp" }
+						th { "actor" }
+						th { "action" }
+						th { "parameters" }
+					}
+				}
+				tbody {
+					@for entry in entries {
+						tr {
+							td {
+								(entry.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs())
+							}
+							td { (entry.actor) }
+							td { (entry.action) }
+							td { code { (entry.parameters) } }
+						}
+					}
+				}
+			}
+		},
+	}
+	.render())
+}
@@ -1,19 +1,24 @@
+use std::convert::Infallible;
+
 use axum::{
-	extract::{Request, State},
-	http::{header, StatusCode},
+	async_trait,
+	extract::{FromRequestParts, Request, State},
+	http::{header, request::Parts, StatusCode},
 	middleware::Next,
 	response::{IntoResponse, Response},
 };
 use axum_extra::{
-	headers::{authorization::Basic, Authorization},
+	headers::{authorization::Basic, Authorization, HeaderMapExt},
 	TypedHeader,
 };
 use serde::Deserialize;
 
+use crate::utility::secret::Secret;
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct BasicAuth {
 	username: String,
-	password: String,
+	password: Secret<String>,
 }
 
 pub async fn basic_auth(
@@ -23,7 +28,7 @@ pub async fn basic_auth(
 	next: Next,
 ) -> Response {
 	let authenticated = authorization.map_or(false, |TypedHeader(auth)| {
-		auth.username() == expected.username && auth.password() == expected.password
+		auth.username() == expected.username && auth.password() == expected.password.expose()
 	});
 
 	match authenticated {
@@ -41,3 +46,25 @@ pub async fn basic_auth(
 		}
 	}
 }
+
+/// Key identifying the operator performing a request, for auditing.
+pub struct Actor(pub String);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Actor
+where
+	S: Send + Sync,
+{
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		// Admin routes are behind basic auth, so there should always be a username.
+		let username = parts
+			.headers
+			.typed_get::<Authorization<Basic>>()
+			.map(|auth| auth.username().to_string())
+			.unwrap_or_else(|| "unknown".to_string());
+
+		Ok(Self(username))
+	}
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use axum::{debug_handler, extract::State, response::IntoResponse, routing::get, Router};
+use maud::{html, Render};
+
+use crate::http::service;
+
+use super::base::BaseTemplate;
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/data", get(data))
+}
+
+#[debug_handler]
+async fn data(State(data): State<service::Data>) -> impl IntoResponse {
+	let statistics = data.blocking().statistics();
+
+	let seconds = |duration: Duration| format!("{:.3}s", duration.as_secs_f64());
+	let unavailable = || "unavailable".to_string();
+
+	BaseTemplate {
+		title: "data".to_string(),
+		content: html! {
+			h2 { "blocking reads" }
+			p {
+				"time spent reading game data. cpu time excludes time blocked on disk io - "
+				"a large io share suggests slow storage, and may benefit from a larger pool"
+			}
+			table {
+				tbody {
+					tr {
+						th { "pool size" }
+						td {
+							@match statistics.limit {
+								Some(limit) => (limit),
+								None => "unlimited",
+							}
+						}
+					}
+					tr { th { "active" } td { (statistics.active) } }
+					tr { th { "reads" } td { (statistics.calls) } }
+					tr { th { "queued" } td { (seconds(statistics.queued)) } }
+					tr { th { "running" } td { (seconds(statistics.running)) } }
+					tr { th { "cpu" } td { (statistics.cpu.map(seconds).unwrap_or_else(unavailable)) } }
+					tr { th { "io" } td { (statistics.io().map(seconds).unwrap_or_else(unavailable)) } }
+				}
+			}
+		},
+	}
+	.render()
+}
@@ -3,6 +3,8 @@ use axum::{
 	response::{IntoResponse, Response},
 };
 
+use crate::version;
+
 #[derive(Debug)]
 pub struct Error(anyhow::Error);
 
@@ -17,11 +19,13 @@ where
 
 impl IntoResponse for Error {
 	fn into_response(self) -> Response {
-		(
-			StatusCode::INTERNAL_SERVER_ERROR,
-			format!("error: {}", self.0),
-		)
-			.into_response()
+		// Errors caused by the submitted values are the operator's to fix.
+		let status = match self.0.downcast_ref::<version::NameError>() {
+			Some(..) => StatusCode::BAD_REQUEST,
+			None => StatusCode::INTERNAL_SERVER_ERROR,
+		};
+
+		(status, format!("error: {}", self.0)).into_response()
 	}
 }
 
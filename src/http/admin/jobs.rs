@@ -0,0 +1,96 @@
+use std::time::SystemTime;
+
+use axum::{
+	debug_handler,
+	extract::{Path, State},
+	response::{IntoResponse, Redirect},
+	routing::{get, post},
+	Router,
+};
+use maud::{html, Render};
+use serde_json::json;
+
+use crate::{http::service, job::JobId};
+
+use super::{auth::Actor, base::BaseTemplate};
+
+pub fn router() -> Router<service::State> {
+	Router::new()
+		.route("/jobs", get(jobs))
+		.route("/jobs/:job_id/cancel", post(cancel_job))
+}
+
+#[debug_handler]
+async fn jobs(State(job): State<service::Job>) -> impl IntoResponse {
+	let jobs = job.jobs();
+
+	let age = |time: SystemTime| {
+		let seconds = time.elapsed().unwrap_or_default().as_secs();
+		format!("{seconds}s ago")
+	};
+
+	BaseTemplate {
+		title: "jobs".to_string(),
+		content: html! {
+			table {
+				thead {
+					tr {
+						th { "id" }
+						th { "kind" }
+						th { "description" }
+						th { "state" }
+						th { "progress" }
+						th { "updated" }
+						th {}
+					}
+				}
+				tbody {
+					@for job in jobs {
+						tr {
+							td { (job.id) }
+							td { (job.kind) }
+							td { (job.description) }
+							td {
+								(job.state)
+								@if let Some(error) = &job.error {
+									": " (error)
+								}
+							}
+							td {
+								@if let Some(progress) = job.progress {
+									(progress)
+								}
+							}
+							td { (age(job.updated)) }
+							td {
+								@if !job.state.finished() {
+									form action={ "jobs/" (job.id) "/cancel" } method="post" {
+										button type="submit" { "cancel" }
+									}
+								}
+							}
+						}
+					}
+				}
+			}
+		},
+	}
+	.render()
+}
+
+#[debug_handler]
+async fn cancel_job(
+	Path(job_id): Path<JobId>,
+	State(job): State<service::Job>,
+	State(audit): State<service::Audit>,
+	Actor(actor): Actor,
+) -> impl IntoResponse {
+	match job.cancel(job_id) {
+		true => audit.record(&actor, "job_cancel", json!({ "job": job_id })),
+		false => {
+			tracing::debug!(%job_id, "cancellation requested for job that is not running")
+		}
+	}
+
+	Redirect::to("../../jobs")
+}
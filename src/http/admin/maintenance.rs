@@ -0,0 +1,75 @@
+use axum::{
+	debug_handler,
+	extract::State,
+	response::{IntoResponse, Redirect},
+	routing::get,
+	Form, Router,
+};
+use maud::{html, Render};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::http::service;
+
+use super::{auth::Actor, base::BaseTemplate};
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/maintenance", get(get_maintenance).post(post_maintenance))
+}
+
+#[debug_handler]
+async fn get_maintenance(State(maintenance): State<service::Maintenance>) -> impl IntoResponse {
+	let message = maintenance.message();
+
+	BaseTemplate {
+		title: "maintenance".to_string(),
+		content: html! {
+			@match &message {
+				Some(message) => {
+					p { "maintenance mode is enabled, public endpoints are returning: " (message) }
+					form action="maintenance" method="post" {
+						input type="hidden" name="enabled" value="false";
+						button type="submit" { "disable" }
+					}
+				}
+				None => {
+					p { "maintenance mode is disabled" }
+					form action="maintenance" method="post" {
+						input type="hidden" name="enabled" value="true";
+						input type="text" name="message" placeholder="message (optional)";
+						button type="submit" { "enable" }
+					}
+				}
+			}
+		},
+	}
+	.render()
+}
+
+#[derive(Debug, Deserialize)]
+struct MaintenanceRequest {
+	enabled: bool,
+	// Empty to use the configured message.
+	message: Option<String>,
+}
+
+#[debug_handler]
+async fn post_maintenance(
+	State(maintenance): State<service::Maintenance>,
+	State(audit): State<service::Audit>,
+	Actor(actor): Actor,
+	Form(request): Form<MaintenanceRequest>,
+) -> impl IntoResponse {
+	match request.enabled {
+		true => maintenance.enable(request.message.filter(|message| !message.is_empty())),
+		false => maintenance.disable(),
+	}
+
+	audit.record(
+		&actor,
+		"maintenance",
+		json!({ "enabled": request.enabled, "message": maintenance.message() }),
+	);
+
+	Redirect::to("maintenance")
+}
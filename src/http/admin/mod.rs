@@ -1,7 +1,15 @@
 mod admin;
+mod audit;
 mod auth;
 mod base;
+mod data;
 mod error;
+mod jobs;
+mod maintenance;
+mod patches;
+mod prime;
+mod sheet;
+mod snapshot;
 mod version;
 mod versions;
 
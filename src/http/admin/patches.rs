@@ -0,0 +1,53 @@
+use std::io;
+
+use axum::{
+	body::Body,
+	debug_handler,
+	extract::{Path, State},
+	http::{header, StatusCode},
+	response::IntoResponse,
+	routing::get,
+	Router,
+};
+use futures::stream;
+use tokio::{fs, io::AsyncReadExt};
+
+use crate::http::service;
+
+use super::error::Result;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/patches/:repository/:patch", get(patch))
+}
+
+/// Serve a patch file, allowing other instances to bootstrap from this one.
+#[debug_handler]
+async fn patch(
+	Path((repository, patch)): Path<(String, String)>,
+	State(version): State<service::Version>,
+) -> Result<impl IntoResponse> {
+	// Only patches referenced by a known version are served, which keeps requests
+	// from reaching arbitrary paths on disk.
+	let Some(path) = version.patch_path(&repository, &patch) else {
+		return Ok((StatusCode::NOT_FOUND, "unknown patch").into_response());
+	};
+
+	let file = fs::File::open(&path).await?;
+	let length = file.metadata().await?.len();
+
+	let chunks = stream::try_unfold(file, |mut file| async move {
+		let mut buffer = Vec::with_capacity(CHUNK_SIZE);
+		match file.read_buf(&mut buffer).await? {
+			0 => Ok::<_, io::Error>(None),
+			_ => Ok(Some((buffer, file))),
+		}
+	});
+
+	Ok((
+		[(header::CONTENT_LENGTH, length.to_string())],
+		Body::from_stream(chunks),
+	)
+		.into_response())
+}
@@ -0,0 +1,91 @@
+use std::time::UNIX_EPOCH;
+
+use axum::{
+	debug_handler,
+	extract::State,
+	response::{IntoResponse, Redirect},
+	routing::get,
+	Form, Router,
+};
+use maud::{html, Render};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::http::service;
+
+use super::{auth::Actor, base::BaseTemplate, error::Result};
+
+/// Number of requests listed on the prime page.
+const LISTED_REQUESTS: usize = 50;
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/prime", get(get_prime).post(post_prime))
+}
+
+#[debug_handler]
+async fn get_prime(State(primer): State<service::Primer>) -> impl IntoResponse {
+	let requests = primer.requests();
+	let last_run = primer.last_run();
+
+	BaseTemplate {
+		title: "cache priming".to_string(),
+		content: html! {
+			p {
+				"requests are replayed against each version as it becomes ready, priming caches before traffic reaches it."
+			}
+
+			@match &last_run {
+				Some(run) => {
+					p {
+						"last primed version " (run.version) " at "
+						(run.started.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs())
+						": " (run.succeeded) " succeeded, " (run.failed) " failed, in "
+						(format!("{:?}", run.duration))
+					}
+				}
+				None => {
+					p { "no version has been primed" }
+				}
+			}
+
+			h2 { "requests (" (requests.len()) ")" }
+			ol {
+				@for request in requests.iter().take(LISTED_REQUESTS) {
+					li { code { (request) } }
+				}
+			}
+			@if requests.len() > LISTED_REQUESTS {
+				p { "..." }
+			}
+
+			h2 { "replace requests" }
+			p {
+				"one request per line, either as an access log entry, or as a path and query. requests are ranked by frequency - version parameters are ignored."
+			}
+			form action="prime" method="post" {
+				textarea name="requests" rows="20" cols="100" {}
+				button type="submit" { "replace" }
+			}
+		},
+	}
+	.render()
+}
+
+#[derive(Debug, Deserialize)]
+struct PrimeRequest {
+	requests: String,
+}
+
+#[debug_handler]
+async fn post_prime(
+	State(primer): State<service::Primer>,
+	State(audit): State<service::Audit>,
+	Actor(actor): Actor,
+	Form(request): Form<PrimeRequest>,
+) -> Result<impl IntoResponse> {
+	let count = primer.set_requests(&request.requests)?;
+
+	audit.record(&actor, "prime", json!({ "requests": count }));
+
+	Ok(Redirect::to("prime"))
+}
@@ -0,0 +1,190 @@
+use anyhow::{anyhow, Context};
+use axum::{
+	debug_handler,
+	extract::{Path, Query, State},
+	response::IntoResponse,
+	routing::get,
+	Router,
+};
+use ironworks::{
+	excel,
+	file::{exd, exh},
+};
+use maud::{html, Render};
+use serde::Deserialize;
+
+use crate::{
+	data::{page_path, LanguageString},
+	http::service,
+	version::VersionKey,
+};
+
+use super::{base::BaseTemplate, error::Result};
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/:version_key/sheet/:sheet/:row", get(get_row))
+}
+
+#[derive(Debug, Deserialize)]
+struct RowPath {
+	version_key: VersionKey,
+	sheet: String,
+	row: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RowQuery {
+	language: Option<LanguageString>,
+}
+
+/// Annotated view of a single column within a row's raw data.
+struct ColumnBytes {
+	index: usize,
+	offset: u16,
+	kind: exh::ColumnKind,
+	bytes: String,
+	value: String,
+}
+
+#[debug_handler]
+async fn get_row(
+	Path(path): Path<RowPath>,
+	Query(query): Query<RowQuery>,
+	State(data): State<service::Data>,
+) -> Result<impl IntoResponse> {
+	let (row_id, subrow_id) = match path.row.split_once(':') {
+		Some((row_id, subrow_id)) => (row_id.parse()?, Some(subrow_id.parse::<u16>()?)),
+		None => (path.row.parse::<u32>()?, None),
+	};
+
+	let version = data.version(path.version_key)?;
+	let ironworks = version.ironworks();
+	let excel = version.excel();
+
+	let sheet = excel.sheet(path.sheet.as_str())?;
+
+	// Sheets without language variants are stored under the `None` language,
+	// fall back to it if the requested language isn't available.
+	let languages = sheet.languages()?;
+	let requested_language = query
+		.language
+		.map(excel::Language::from)
+		.unwrap_or_else(|| data.default_language());
+	let language = match languages.contains(&requested_language) {
+		true => requested_language,
+		false => excel::Language::None,
+	};
+
+	// Find the page containing the row, and read its raw data.
+	let header = ironworks.file::<exh::ExcelHeader>(&format!("exd/{}.exh", path.sheet))?;
+	let page = header
+		.pages()
+		.iter()
+		.find(|page| (page.start_id()..page.start_id() + page.row_count()).contains(&row_id))
+		.with_context(|| format!("row {row_id} is not within any page of {}", path.sheet))?;
+	let page_path = page_path(&path.sheet, page.start_id(), language);
+	let page_data = ironworks.file::<exd::ExcelData>(&page_path)?;
+	let row_bytes = match subrow_id {
+		Some(subrow_id) => page_data.subrow_data(row_id, subrow_id)?,
+		None => page_data.row_data(row_id)?,
+	};
+
+	// Annotate the fixed-size portion of the row with the column definitions.
+	let mut row_builder = sheet.with();
+	row_builder.language(language);
+	let row = match subrow_id {
+		Some(subrow_id) => row_builder.subrow(row_id, subrow_id)?,
+		None => row_builder.row(row_id)?,
+	};
+
+	let mut columns = sheet.columns()?;
+	columns.sort_by_key(|column| column.offset());
+	let columns = columns
+		.iter()
+		.enumerate()
+		.map(|(index, column)| {
+			let offset = column.offset();
+			let start = usize::from(offset);
+			let bytes = row_bytes
+				.get(start..start + column_size(column.kind()))
+				.ok_or_else(|| anyhow!("column at offset {offset} exceeds row data"))?;
+
+			Ok(ColumnBytes {
+				index,
+				offset,
+				kind: column.kind(),
+				bytes: hex(bytes),
+				value: match row.field(column) {
+					Ok(field) => format!("{field:?}"),
+					Err(error) => format!("unreadable: {error}"),
+				},
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	Ok((BaseTemplate {
+		title: format!("{} row {}", path.sheet, path.row),
+		content: html! {
+			p {
+				"version " (path.version_key) ", "
+				"page " (page_path) ", "
+				(row_bytes.len()) " bytes"
+			}
+
+			h2 { "columns" }
+			table {
+				thead {
+					tr { th { "#" } th { "offset" } th { "kind" } th { "bytes" } th { "value" } }
+				}
+				tbody {
+					@for column in columns {
+						tr {
+							td { (column.index) }
+							td { code { (format!("{:#06x}", column.offset)) } }
+							td { (format!("{:?}", column.kind)) }
+							td { code { (column.bytes) } }
+							td { code { (column.value) } }
+						}
+					}
+				}
+			}
+
+			h2 { "raw" }
+			pre {
+				@for (index, chunk) in row_bytes.chunks(16).enumerate() {
+					(format!("{:#06x}  {}\n", index * 16, hex(chunk)))
+				}
+			}
+		},
+	})
+	.render())
+}
+
+fn column_size(kind: exh::ColumnKind) -> usize {
+	use exh::ColumnKind as K;
+	match kind {
+		K::Bool
+		| K::Int8
+		| K::UInt8
+		| K::PackedBool0
+		| K::PackedBool1
+		| K::PackedBool2
+		| K::PackedBool3
+		| K::PackedBool4
+		| K::PackedBool5
+		| K::PackedBool6
+		| K::PackedBool7 => 1,
+		K::Int16 | K::UInt16 => 2,
+		// Strings are stored as an offset into the string data following the fixed-size portion of the row.
+		K::String | K::Int32 | K::UInt32 | K::Float32 => 4,
+		K::Int64 | K::UInt64 => 8,
+	}
+}
+
+fn hex(bytes: &[u8]) -> String {
+	bytes
+		.iter()
+		.map(|byte| format!("{byte:02x}"))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
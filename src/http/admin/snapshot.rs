@@ -0,0 +1,43 @@
+use axum::{
+	debug_handler, extract::State, http::header, response::IntoResponse, routing::get, Json, Router,
+};
+
+use serde_json::json;
+
+use crate::{http::service, version::Snapshot};
+
+use super::{auth::Actor, error::Result};
+
+pub fn router() -> Router<service::State> {
+	Router::new().route("/snapshot", get(get_snapshot).post(post_snapshot))
+}
+
+#[debug_handler]
+async fn get_snapshot(State(version): State<service::Version>) -> Result<impl IntoResponse> {
+	let snapshot = version.snapshot()?;
+
+	Ok((
+		[(
+			header::CONTENT_DISPOSITION,
+			"attachment; filename=\"boilmaster-snapshot.json\"",
+		)],
+		Json(snapshot),
+	))
+}
+
+#[debug_handler]
+async fn post_snapshot(
+	State(version): State<service::Version>,
+	State(audit): State<service::Audit>,
+	Actor(actor): Actor,
+	Json(snapshot): Json<Snapshot>,
+) -> Result<impl IntoResponse> {
+	version.restore(snapshot).await?;
+	audit.record(
+		&actor,
+		"snapshot_restore",
+		json!({ "versions": version.keys() }),
+	);
+
+	Ok("snapshot restored")
+}
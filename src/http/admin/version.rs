@@ -8,10 +8,11 @@ use axum::{
 };
 use maud::{html, Render};
 use serde::Deserialize;
+use serde_json::json;
 
 use crate::{http::service, version::VersionKey};
 
-use super::{base::BaseTemplate, error::Result};
+use super::{auth::Actor, base::BaseTemplate, error::Result};
 
 pub fn router() -> Router<service::State> {
 	Router::new().route("/:version_key", get(get_version).post(post_version))
@@ -24,6 +25,7 @@ async fn get_version(
 	State(version): State<service::Version>,
 ) -> Result<impl IntoResponse> {
 	let names = version.names(version_key).context("unknown version")?;
+	let hidden = version.hidden(version_key);
 
 	// Patches are stored in oldest-first order for IW, which is lovely in code
 	// and horrible for reading. Given this is ostensibly the reading bit of the
@@ -52,6 +54,10 @@ async fn get_version(
 						(name)
 					}
 				};
+				label {
+					input type="checkbox" name="hidden" checked[hidden];
+					" hidden from public listing"
+				}
 				button type="submit" { "save" };
 			}
 
@@ -80,6 +86,8 @@ async fn get_version(
 #[derive(Debug, Deserialize)]
 struct VersionPostRequest {
 	names: String,
+	// Checkboxes are only submitted when checked.
+	hidden: Option<String>,
 }
 
 #[debug_handler]
@@ -87,10 +95,33 @@ async fn post_version(
 	OriginalUri(uri): OriginalUri,
 	Path(version_key): Path<VersionKey>,
 	State(version): State<service::Version>,
+	State(audit): State<service::Audit>,
+	Actor(actor): Actor,
 	Form(request): Form<VersionPostRequest>,
 ) -> Result<impl IntoResponse> {
-	let names = request.names.split(',').map(str::trim);
-	version.set_names(version_key, names).await?;
+	let names = request
+		.names
+		.split(',')
+		.map(str::trim)
+		// An empty field clears the version's names.
+		.filter(|name| !name.is_empty())
+		.collect::<Vec<_>>();
+	version.set_names(version_key, &names).await?;
+	audit.record(
+		&actor,
+		"version_names",
+		json!({ "version": version_key, "names": names }),
+	);
+
+	let hidden = request.hidden.is_some();
+	if hidden != version.hidden(version_key) {
+		version.set_hidden(version_key, hidden).await?;
+		audit.record(
+			&actor,
+			"version_visibility",
+			json!({ "version": version_key, "hidden": hidden }),
+		);
+	}
 
 	Ok(Redirect::to(&uri.to_string()))
 }
@@ -20,6 +20,7 @@ struct VersionInfo {
 	key: VersionKey,
 	patches: Vec<(String, String)>,
 	names: Vec<String>,
+	hidden: bool,
 }
 
 #[debug_handler]
@@ -40,6 +41,7 @@ async fn versions(
 			key,
 			patches: latest,
 			names: version.names(key).context("missing version")?,
+			hidden: version.hidden(key),
 		})
 	};
 
@@ -64,6 +66,7 @@ async fn versions(
 						(name)
 					}
 					")"
+					@if version.hidden { " [hidden]" }
 				}
 
 				dl {
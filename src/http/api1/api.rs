@@ -5,43 +5,117 @@ use aide::{
 	openapi::{self, Tag},
 	transform::TransformOpenApi,
 };
-use axum::{debug_handler, response::IntoResponse, routing::get, Extension, Json, Router};
+use axum::{
+	debug_handler,
+	extract::DefaultBodyLimit,
+	middleware,
+	response::IntoResponse,
+	routing::{any, get},
+	Extension, Json, Router,
+};
 use git_version::git_version;
 use maud::{html, DOCTYPE};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
-use crate::http::service;
+use crate::http::{
+	feature::{Feature, Features},
+	service,
+};
 
-use super::{asset, extract::RouterPath, sheet, version};
+use super::{
+	asset, conversion,
+	error::Error,
+	extract::{RouterPath, VersionQueryConfig},
+	limit, search, sheet, timeout, version,
+};
 
 const OPENAPI_JSON_ROUTE: &str = "/openapi.json";
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Config {
+	search: search::Config,
 	sheet: sheet::Config,
+	timeout: timeout::Config,
+	#[serde(default)]
+	version: VersionQueryConfig,
+
+	/// Limits on the size of requests, rejecting pathological inputs before
+	/// they are parsed.
+	#[serde(default)]
+	limit: limit::Config,
+}
+
+impl Config {
+	pub fn reload(&self, config: Config) {
+		self.search.reload(config.search);
+		self.sheet.reload(config.sheet);
+	}
 }
 
-pub fn router(config: Config) -> Router<service::State> {
+pub fn router(config: Config, features: &Features) -> Router<service::State> {
 	let mut openapi = openapi::OpenApi::default();
 
+	// Route groups are named after the feature that toggles them, which is also
+	// the key used for timeout overrides.
+	let group =
+		|feature: Feature, tag: &'static str, router: fn(&Config) -> ApiRouter<service::State>| {
+			if !features.enabled(feature) {
+				return disabled_router(feature);
+			}
+
+			let name = feature.to_string();
+			router(&config)
+				.layer(middleware::from_fn_with_state(
+					config.timeout.duration(&name),
+					timeout::timeout,
+				))
+				.with_path_items(|item| item.tag(tag))
+		};
+
 	ApiRouter::new()
 		.nest(
 			"/asset",
-			asset::router().with_path_items(|item| item.tag("assets")),
+			group(Feature::Asset, "assets", |_| asset::router()),
+		)
+		.nest(
+			"/conversion",
+			group(Feature::Asset, "assets", |_| conversion::router()),
+		)
+		.nest(
+			"/search",
+			group(Feature::Search, "search", |config| {
+				search::router(config.search.clone())
+			}),
 		)
 		.nest(
 			"/sheet",
-			sheet::router(config.sheet).with_path_items(|item| item.tag("sheets")),
+			group(Feature::Sheet, "sheets", |config| {
+				sheet::router(config.sheet.clone())
+			}),
 		)
 		.nest(
 			"/version",
-			version::router().with_path_items(|item| item.tag("versions")),
+			group(Feature::Version, "versions", |_| version::router()),
 		)
 		.finish_api_with(&mut openapi, api_docs)
 		.route(OPENAPI_JSON_ROUTE, get(openapi_json))
 		.route("/docs", get(scalar))
 		.layer(Extension(Arc::new(openapi)))
+		.layer(Extension(config.version.clone()))
+		.layer(DefaultBodyLimit::max(config.limit.body_size()))
+		.layer(middleware::from_fn_with_state(
+			config.limit.clone(),
+			limit::limit,
+		))
+}
+
+/// Placeholder for a disabled route group, rejecting every request to it.
+fn disabled_router(feature: Feature) -> ApiRouter<service::State> {
+	let handler = move || async move { Error::Disabled(feature.to_string()) };
+	ApiRouter::new()
+		.route("/", any(handler))
+		.route("/*path", any(handler))
 }
 
 fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
@@ -53,6 +127,11 @@ fn api_docs(api: TransformOpenApi) -> TransformOpenApi {
 			description: Some("Endpoints for accessing game data on a file-by-file basis. Commonly useful for fetching icons or other textures to display on the web.".into()),
 			..Default::default()
 		})
+		.tag(Tag {
+			name: "search".into(),
+			description: Some("Endpoints for finding rows across sheets.".into()),
+			..Default::default()
+		})
 		.tag(Tag {
 			name: "sheets".into(),
 			description: Some("Endpoints for reading data from the game's static relational data store.".into()),
@@ -1,6 +1,8 @@
 use std::{
 	ffi::OsStr,
 	hash::{Hash, Hasher},
+	net::SocketAddr,
+	sync::Arc,
 };
 
 use aide::{
@@ -9,7 +11,13 @@ use aide::{
 	transform::TransformOperation,
 	NoApi,
 };
-use axum::{debug_handler, extract::State, http::header, response::IntoResponse};
+use axum::{
+	debug_handler,
+	extract::{ConnectInfo, State},
+	http::{header, HeaderMap, HeaderName},
+	response::IntoResponse,
+	Json,
+};
 use axum_extra::{
 	headers::{ContentType, ETag, IfNoneMatch},
 	TypedHeader,
@@ -20,10 +28,15 @@ use seahash::SeaHasher;
 use serde::Deserialize;
 use strum::IntoEnumIterator;
 
-use crate::{asset::Format, http::service, version::VersionKey};
+use crate::{
+	asset::{Format, Queued, Slice, TextureInfo},
+	http::{quota, service},
+	version::VersionKey,
+};
 
 use super::{
-	error::Result,
+	conversion::ConversionResponse,
+	error::{Error, Result},
 	extract::{Path, Query, VersionQuery},
 };
 
@@ -49,6 +62,12 @@ struct AssetQuery {
 	/// Format that the asset should be converted into.
 	#[schemars(example = "example_format")]
 	format: Format,
+
+	/// Mip level to read from texture assets, where 0 is the full-size surface. Defaults to 0.
+	mip: Option<u32>,
+
+	/// Slice to read from texture assets with multiple surfaces per mip level, such as texture arrays, cubemap faces, or volume texture depth slices. Defaults to 0.
+	slice: Option<u32>,
 }
 
 fn example_format() -> Format {
@@ -58,7 +77,7 @@ fn example_format() -> Format {
 fn asset_docs(operation: TransformOperation) -> TransformOperation {
 	operation
 		.summary("read an asset")
-		.description("Read an asset from the game at the specified path, converting it into a usable format. If no valid conversion between the game file type and specified format exists, an error will be returned.")
+		.description("Read an asset from the game at the specified path, converting it into a usable format. If no valid conversion between the game file type and specified format exists, an error will be returned. Conversions of textures include `x-texture-kind`, `x-texture-mip-levels`, and `x-texture-slices` headers describing the surfaces available to select with `mip` and `slice`, with slice counts listed per mip level. Assets that are expensive to convert may instead be converted in the background, responding with `202 Accepted` and the conversion job - once the conversion completes, repeating the request will respond with the converted asset.")
 		.response_with::<200, Vec<u8>, _>(|mut response| {
 			response.inner().content = Format::iter()
 				.map(|format| {
@@ -70,6 +89,9 @@ fn asset_docs(operation: TransformOperation) -> TransformOperation {
 				.collect();
 			response
 		})
+		.response_with::<202, Json<ConversionResponse>, _>(|res| {
+			res.description("conversion started in the background")
+		})
 		.response_with::<304, (), _>(|res| res.description("not modified"))
 }
 
@@ -79,11 +101,20 @@ async fn asset(
 	VersionQuery(version_key): VersionQuery,
 	Query(query): Query<AssetQuery>,
 	NoApi(header_if_none_match): NoApi<Option<TypedHeader<IfNoneMatch>>>,
+	NoApi(headers): NoApi<HeaderMap>,
+	NoApi(connect_info): NoApi<Option<ConnectInfo<SocketAddr>>>,
 	State(asset): State<service::Asset>,
+	State(data): State<service::Data>,
+	State(jobs): State<service::Job>,
+	State(quota_service): State<service::Quota>,
 ) -> Result<impl IntoApiResponse> {
 	let format = query.format;
+	let slice = Slice {
+		mip: query.mip.unwrap_or(0),
+		index: query.slice.unwrap_or(0),
+	};
 
-	let etag = etag(&path, format, version_key);
+	let etag = etag(&path, format, slice, version_key);
 
 	if let Some(TypedHeader(if_none_match)) = header_if_none_match {
 		if !if_none_match.precondition_passes(&etag) {
@@ -91,7 +122,46 @@ async fn asset(
 		}
 	}
 
-	let bytes = asset.convert(version_key, &path, format)?;
+	// Conversions are expensive - only charge for those actually performed, not
+	// cache hits or revalidations. Units are reserved before the conversion
+	// starts, such that concurrent requests can't overshoot the limit.
+	let consumer = quota::consumer(&headers, connect_info.map(|ConnectInfo(address)| address));
+	let conversion_cost = quota_service.conversion_cost();
+
+	let mut charged = None;
+	let queued = asset.convert_queued(version_key, &path, format, slice, || {
+		charged = Some(quota_service.try_consume(consumer, conversion_cost)?);
+		Ok::<_, Error>(())
+	})?;
+
+	let (conversion, quota_status) = match queued {
+		Some(Queued::Ready(conversion)) => (conversion, quota_service.status(consumer)),
+
+		Some(Queued::Pending { job, .. }) => {
+			let quota_status = charged.unwrap_or_else(|| quota_service.status(consumer));
+			let response = jobs
+				.job(job)
+				.map(|job| ConversionResponse::from(&job))
+				.unwrap_or_else(|| ConversionResponse::pending(job));
+			return Ok((
+				StatusCode::ACCEPTED,
+				quota::headers(quota_status),
+				Json(response),
+			)
+				.into_response());
+		}
+
+		None => {
+			let quota_status = quota_service.try_consume(consumer, conversion_cost)?;
+			let conversion = data
+				.blocking()
+				.permit()
+				.await
+				.run(|| asset.convert(version_key, &path, format, slice))
+				.inspect_err(|_| quota_service.refund(consumer, conversion_cost))?;
+			(Arc::new(conversion), quota_status)
+		}
+	};
 
 	let filepath = std::path::Path::new(&path).with_extension(format.extension());
 	let disposition = match filepath.file_name().and_then(OsStr::to_str) {
@@ -103,22 +173,47 @@ async fn asset(
 		TypedHeader(ContentType::from(format_mime(format))),
 		// TypedHeader only has a really naive inline value with no ability to customise :/
 		[(header::CONTENT_DISPOSITION, disposition)],
+		conversion.texture.as_ref().map(texture_headers),
+		quota::headers(quota_status),
 		TypedHeader(etag),
-		bytes,
+		conversion.bytes.clone(),
 	)
 		.into_response())
 }
 
+fn texture_headers(texture: &TextureInfo) -> [(HeaderName, String); 3] {
+	let slices = texture
+		.slices
+		.iter()
+		.map(|count| count.to_string())
+		.collect::<Vec<_>>()
+		.join(",");
+
+	[
+		(
+			HeaderName::from_static("x-texture-kind"),
+			format!("{:?}", texture.kind).to_lowercase(),
+		),
+		(
+			HeaderName::from_static("x-texture-mip-levels"),
+			texture.mip_levels.to_string(),
+		),
+		(HeaderName::from_static("x-texture-slices"), slices),
+	]
+}
+
 fn format_mime(format: Format) -> mime::Mime {
 	match format {
 		Format::Png => mime::IMAGE_PNG,
+		Format::Jpg => mime::IMAGE_JPEG,
 	}
 }
 
-fn etag(path: &str, format: Format, version: VersionKey) -> ETag {
+fn etag(path: &str, format: Format, slice: Slice, version: VersionKey) -> ETag {
 	let mut hasher = SeaHasher::new();
 	path.hash(&mut hasher);
 	format.extension().hash(&mut hasher);
+	slice.hash(&mut hasher);
 	let resource_hash = hasher.finish();
 
 	format!("\"{resource_hash:016x}.{version}\"")
@@ -0,0 +1,189 @@
+use std::convert::Infallible;
+
+use aide::OperationInput;
+use axum::{
+	async_trait,
+	extract::FromRequestParts,
+	http::{header, request::Parts},
+	RequestPartsExt,
+};
+use ironworks::excel;
+use maud::{html, Markup, DOCTYPE};
+
+use crate::read;
+
+use super::{
+	extract::RouterPath,
+	value::{icon_paths, struct_key_name},
+};
+
+/// Browsable HTML rendering of read responses. Present when the client
+/// indicates that it accepts HTML, as browsers do when navigating to a page.
+pub struct Browse(pub Option<Browser>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Browse
+where
+	S: Send + Sync,
+{
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		let accepts_html = parts
+			.headers
+			.get_all(header::ACCEPT)
+			.iter()
+			.filter_map(|value| value.to_str().ok())
+			.flat_map(|value| value.split(','))
+			.any(|media_type| media_type.split(';').next().unwrap_or("").trim() == "text/html");
+
+		if !accepts_html {
+			return Ok(Self(None));
+		}
+
+		// Links are built relative to the root of the API, which is the parent of
+		// the router the browsable endpoints are nested within.
+		let RouterPath(router_path) = parts.extract::<RouterPath>().await?;
+		let api_path = router_path
+			.rsplit_once('/')
+			.map(|(parent, _)| parent)
+			.unwrap_or("");
+
+		Ok(Self(Some(Browser {
+			api_path: api_path.to_string(),
+		})))
+	}
+}
+
+impl OperationInput for Browse {}
+
+pub struct Browser {
+	api_path: String,
+}
+
+impl Browser {
+	pub fn page(&self, title: &str, content: Markup) -> Markup {
+		html! {
+			(DOCTYPE)
+			html {
+				head {
+					title { "boilmaster | " (title) }
+					meta charset="utf-8";
+					style {
+						"table { border-collapse: collapse; } "
+						"td, th { border: 1px solid #ccc; padding: 0.2em 0.4em; text-align: left; vertical-align: top; } "
+						"ol { margin: 0; padding-left: 2em; }"
+					}
+				}
+				body {
+					h1 { (title) }
+					(content)
+				}
+			}
+		}
+	}
+
+	/// Render a link to the specified sheet row.
+	pub fn row_link(&self, sheet: &str, row_id: u32, subrow_id: Option<u16>) -> Markup {
+		let specifier = match subrow_id {
+			Some(subrow_id) => format!("{row_id}:{subrow_id}"),
+			None => row_id.to_string(),
+		};
+
+		html! {
+			a href={ (self.api_path) "/sheet/" (sheet) "/" (specifier) } {
+				(sheet) "#" (specifier)
+			}
+		}
+	}
+
+	/// Render a read value as nested HTML tables and lists.
+	pub fn value(&self, value: &read::Value, language: excel::Language) -> Markup {
+		use read::Value as V;
+		match value {
+			V::Array(values) => html! {
+				ol start="0" {
+					@for value in values {
+						li { (self.value(value, language)) }
+					}
+				}
+			},
+
+			V::Icon(id) => {
+				let (path, _path_hr1) = icon_paths(*id);
+				html! {
+					img src={ (self.api_path) "/asset/" (path) "?format=png" } alt=(id) title=(id);
+				}
+			}
+
+			V::Interpreted(field, interpretation) => html! {
+				(field_string(field)) " (" (interpretation_string(interpretation)) ")"
+			},
+
+			V::Reference(read::Reference::Scalar(value)) => html! { (value) },
+
+			V::Reference(read::Reference::Populated {
+				value,
+				sheet,
+				row_id,
+				fields,
+			}) => html! {
+				details {
+					summary {
+						(value) " → " (self.row_link(sheet, *row_id, None))
+					}
+					(self.value(fields, language))
+				}
+			},
+
+			V::Scalar(field) => html! { (field_string(field)) },
+
+			V::Unsupported(kind) => html! { em { "unsupported " (format!("{kind:?}")) } },
+
+			V::Struct(fields) => {
+				let mut fields = fields
+					.iter()
+					.map(|(key, value)| (struct_key_name(key, language), value))
+					.collect::<Vec<_>>();
+				fields.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+				html! {
+					table {
+						@for (name, value) in fields {
+							tr {
+								th { (name) }
+								td { (self.value(value, language)) }
+							}
+						}
+					}
+				}
+			}
+		}
+	}
+}
+
+fn field_string(field: &excel::Field) -> String {
+	use excel::Field as F;
+	match field {
+		F::String(se_string) => se_string.to_string(),
+		F::Bool(value) => value.to_string(),
+		F::I8(value) => value.to_string(),
+		F::I16(value) => value.to_string(),
+		F::I32(value) => value.to_string(),
+		F::I64(value) => value.to_string(),
+		F::U8(value) => value.to_string(),
+		F::U16(value) => value.to_string(),
+		F::U32(value) => value.to_string(),
+		F::U64(value) => value.to_string(),
+		F::F32(value) => value.to_string(),
+	}
+}
+
+fn interpretation_string(interpretation: &read::Interpretation) -> String {
+	use read::Interpretation as I;
+	match interpretation {
+		I::Flags(names) => names.join(", "),
+		I::DateTime(value) | I::Time(value) | I::Duration(value) => value.clone(),
+		I::Scaled(value) => value.to_string(),
+	}
+}
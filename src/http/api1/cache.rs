@@ -0,0 +1,112 @@
+use std::{hash::Hash, sync::Arc, time::Duration};
+
+use mini_moka::sync as moka;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use super::error::Result;
+
+/// Maximum number of values held by each build cache. Keys are typically
+/// scoped to a version, so this comfortably covers the versions in active use.
+const BUILD_CACHE_CAPACITY: u64 = 64;
+
+/// Values that have not been requested within this duration are dropped, such
+/// that values for versions that are no longer requested - or no longer exist -
+/// do not linger until evicted by capacity.
+const BUILD_CACHE_IDLE: Duration = Duration::from_secs(60 * 60 * 24);
+
+/// Cache of values derived from game data that are expensive to build, such
+/// that they are only built once per key while in use.
+pub struct BuildCache<K, V>(moka::Cache<K, Arc<V>>);
+
+impl<K, V> Clone for BuildCache<K, V> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<K, V> Default for BuildCache<K, V>
+where
+	K: Eq + Hash + Send + Sync + 'static,
+	V: Send + Sync + 'static,
+{
+	fn default() -> Self {
+		Self(
+			moka::Cache::builder()
+				.max_capacity(BUILD_CACHE_CAPACITY)
+				.time_to_idle(BUILD_CACHE_IDLE)
+				.build(),
+		)
+	}
+}
+
+impl<K, V> BuildCache<K, V>
+where
+	K: Eq + Hash + Send + Sync + 'static,
+	V: Send + Sync + 'static,
+{
+	pub fn get_or_try_insert(&self, key: K, build: impl FnOnce() -> Result<V>) -> Result<Arc<V>> {
+		let (value, _status) = self.get_or_try_insert_with_status(key, build)?;
+		Ok(value)
+	}
+
+	/// As `get_or_try_insert`, additionally reporting if the value was built by
+	/// this call.
+	pub fn get_or_try_insert_with_status(
+		&self,
+		key: K,
+		build: impl FnOnce() -> Result<V>,
+	) -> Result<(Arc<V>, CacheStatus)> {
+		if let Some(value) = self.0.get(&key) {
+			return Ok((value, CacheStatus::Hit));
+		}
+
+		let value = Arc::new(build()?);
+		self.0.insert(key, value.clone());
+
+		Ok((value, CacheStatus::Miss))
+	}
+}
+
+/// Whether a value was served from a cache, or built to satisfy a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheStatus {
+	Hit,
+	Miss,
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use crate::http::api1::error::Error;
+
+	use super::*;
+
+	#[test]
+	fn built_once() {
+		let cache = BuildCache::<u32, String>::default();
+
+		let (value, status) = cache
+			.get_or_try_insert_with_status(1, || Ok("first".into()))
+			.unwrap();
+		assert_eq!((value.as_str(), status), ("first", CacheStatus::Miss));
+
+		let (value, status) = cache
+			.get_or_try_insert_with_status(1, || Ok("second".into()))
+			.unwrap();
+		assert_eq!((value.as_str(), status), ("first", CacheStatus::Hit));
+	}
+
+	#[test]
+	fn failures_not_cached() {
+		let cache = BuildCache::<u32, String>::default();
+
+		let result = cache.get_or_try_insert(1, || Err(Error::Invalid("failed".into())));
+		assert!(result.is_err());
+
+		let value = cache.get_or_try_insert(1, || Ok("built".into())).unwrap();
+		assert_eq!(value.as_str(), "built");
+	}
+}
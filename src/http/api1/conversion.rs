@@ -0,0 +1,149 @@
+use aide::{
+	axum::{routing::get_with, ApiRouter, IntoApiResponse},
+	transform::TransformOperation,
+};
+use axum::{
+	debug_handler,
+	extract::State,
+	response::{
+		sse::{Event, KeepAlive, Sse},
+		IntoResponse,
+	},
+	routing::get,
+	Json,
+};
+use futures::{stream, StreamExt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use crate::{
+	http::service,
+	job::{self, JobId},
+};
+
+use super::{
+	error::{Error, Result},
+	extract::Path,
+};
+
+pub fn router() -> ApiRouter<service::State> {
+	ApiRouter::new()
+		.api_route("/:job", get_with(conversion, conversion_docs))
+		.route("/:job/events", get(conversion_events))
+}
+
+/// Path variables accepted by the conversion endpoints.
+#[derive(Deserialize, JsonSchema)]
+struct ConversionPath {
+	/// ID of the job performing the conversion.
+	#[schemars(with = "u64")]
+	job: JobId,
+}
+
+/// Response structure describing the state of a background asset conversion.
+#[derive(Serialize, JsonSchema)]
+pub struct ConversionResponse {
+	/// ID of the job performing the conversion.
+	#[schemars(with = "u64")]
+	job: JobId,
+
+	/// Current state of the conversion, one of `pending`, `running`, `completed`, `failed`, or `cancelled`. Once completed, repeating the original asset request will respond with the converted asset.
+	#[schemars(with = "String")]
+	state: job::State,
+
+	/// Reason the conversion failed, if it did.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error: Option<String>,
+}
+
+impl ConversionResponse {
+	pub fn pending(job: JobId) -> Self {
+		Self {
+			job,
+			state: job::State::Pending,
+			error: None,
+		}
+	}
+}
+
+impl From<&job::Job> for ConversionResponse {
+	fn from(job: &job::Job) -> Self {
+		Self {
+			job: job.id,
+			state: job.state,
+			error: job.error.clone(),
+		}
+	}
+}
+
+fn conversion_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a conversion")
+		.description("Read the state of a background asset conversion, as started by an asset request responding with `202 Accepted`. To wait for the conversion to finish rather than polling, subscribe to the server-sent events at `/conversion/{job}/events` - an event is sent with the current state, followed by a final event once the conversion finishes.")
+		.response_with::<200, Json<ConversionResponse>, _>(|response| {
+			response.example(ConversionResponse::pending(JobId::default()))
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn conversion(
+	Path(path): Path<ConversionPath>,
+	State(jobs): State<service::Job>,
+) -> Result<impl IntoApiResponse> {
+	let job = conversion_job(&jobs, path.job)?;
+	Ok(Json(ConversionResponse::from(&job)))
+}
+
+#[debug_handler(state = service::State)]
+async fn conversion_events(
+	Path(path): Path<ConversionPath>,
+	State(jobs): State<service::Job>,
+) -> Result<impl IntoResponse> {
+	// Subscribe before reading the current state, such that the job can't
+	// finish unobserved between the two.
+	let receiver = jobs.subscribe();
+	let current = conversion_job(&jobs, path.job)?;
+
+	let finished = match current.state.finished() {
+		true => None,
+		false => Some(wait_finished(receiver, jobs, path.job)),
+	};
+
+	let events = stream::iter([current])
+		.chain(stream::iter(finished).filter_map(|finished| finished))
+		.map(|job| {
+			Event::default()
+				.event(job.state.to_string())
+				.json_data(ConversionResponse::from(&job))
+		});
+
+	Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+fn conversion_job(jobs: &job::Manager, id: JobId) -> Result<job::Job> {
+	jobs.job(id)
+		.filter(|job| job.kind == job::Kind::AssetConversion)
+		.ok_or_else(|| Error::NotFound(format!("conversion {id}")))
+}
+
+async fn wait_finished(
+	mut receiver: broadcast::Receiver<job::Job>,
+	jobs: service::Job,
+	id: JobId,
+) -> Option<job::Job> {
+	loop {
+		match receiver.recv().await {
+			Ok(job) if job.id == id => return Some(job),
+			Ok(_) => {}
+			// Skipped messages may have included this job - check its state directly.
+			Err(broadcast::error::RecvError::Lagged(_)) => {
+				let job = jobs.job(id).filter(|job| job.state.finished());
+				if job.is_some() {
+					return job;
+				}
+			}
+			Err(broadcast::error::RecvError::Closed) => return None,
+		}
+	}
+}
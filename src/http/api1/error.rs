@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use aide::{openapi::Response as AideResponse, transform::TransformResponse, OperationOutput};
 use axum::{
 	extract::rejection::{PathRejection, QueryRejection},
@@ -11,6 +13,7 @@ use serde::Serialize;
 use crate::{
 	asset,
 	data,
+	quota,
 	read,
 	schema,
 	// search
@@ -24,9 +27,31 @@ pub enum Error {
 	#[error("invalid request: {0}")]
 	Invalid(String),
 
-	// #[error("unavailable: {0}")]
-	// Unavailable(String),
-	//
+	#[error("language {language} is not available in sheet {sheet}")]
+	LanguageUnavailable {
+		sheet: String,
+		language: String,
+		available: Vec<String>,
+	},
+
+	#[error("request URI too long: {0}")]
+	UriTooLong(String),
+
+	#[error("request too large: {0}")]
+	PayloadTooLarge(String),
+
+	#[error("request timed out after {}s", .0.as_secs())]
+	Timeout(Duration),
+
+	#[error("{0} endpoints are disabled on this instance")]
+	Disabled(String),
+
+	#[error("{0}")]
+	QuotaExceeded(String),
+
+	#[error("unavailable: {0}")]
+	Unavailable(String),
+
 	#[error("internal server error")]
 	Other(#[from] anyhow::Error),
 }
@@ -36,9 +61,11 @@ impl From<asset::Error> for Error {
 		use asset::Error as AE;
 		match error {
 			AE::NotFound(value) => Self::NotFound(value),
-			AE::UnsupportedSource(..) | AE::InvalidConversion(..) | AE::UnknownFormat(..) => {
-				Self::Invalid(error.to_string())
-			}
+			AE::InvalidPath(..)
+			| AE::UnsupportedSource(..)
+			| AE::InvalidConversion(..)
+			| AE::UnknownFormat(..) => Self::Invalid(error.to_string()),
+			AE::QueueFull => Self::Unavailable(error.to_string()),
 			AE::Failure(inner) => Self::Other(inner),
 		}
 	}
@@ -54,6 +81,15 @@ impl From<data::Error> for Error {
 	}
 }
 
+impl From<quota::Error> for Error {
+	fn from(error: quota::Error) -> Self {
+		use quota::Error as QE;
+		match error {
+			QE::Exceeded(..) => Self::QuotaExceeded(error.to_string()),
+		}
+	}
+}
+
 impl From<read::Error> for Error {
 	fn from(error: read::Error) -> Self {
 		use read::Error as RE;
@@ -62,6 +98,8 @@ impl From<read::Error> for Error {
 			RE::FilterSchemaMismatch(..) | RE::SchemaGameMismatch(..) => {
 				Self::Invalid(error.to_string())
 			}
+			// Handlers resolve cancelled reads as timeouts, see `Cancellation`.
+			RE::Cancelled => Self::Other(error.into()),
 			RE::Failure(inner) => Self::Other(inner),
 		}
 	}
@@ -121,6 +159,10 @@ pub struct ErrorResponse {
 
 	/// Description of what went wrong.
 	message: String,
+
+	/// Languages that are available, when a requested language is not.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	available_languages: Option<Vec<String>>,
 }
 
 #[derive(Serialize, JsonSchema)]
@@ -131,15 +173,26 @@ impl From<Error> for ErrorResponse {
 	fn from(value: Error) -> Self {
 		// TODO: INCREDIBLY IMPORTANT: work out how to worm IM_A_TEAPOT into this
 		let status_code = match value {
-			Error::NotFound(..) => StatusCode::NOT_FOUND,
+			Error::NotFound(..) | Error::LanguageUnavailable { .. } => StatusCode::NOT_FOUND,
 			Error::Invalid(..) => StatusCode::BAD_REQUEST,
-			// Error::Unavailable(..) => StatusCode::SERVICE_UNAVAILABLE,
+			Error::UriTooLong(..) => StatusCode::URI_TOO_LONG,
+			Error::PayloadTooLarge(..) => StatusCode::PAYLOAD_TOO_LARGE,
+			Error::Timeout(..) => StatusCode::GATEWAY_TIMEOUT,
+			Error::Disabled(..) => StatusCode::NOT_IMPLEMENTED,
+			Error::QuotaExceeded(..) => StatusCode::TOO_MANY_REQUESTS,
+			Error::Unavailable(..) => StatusCode::SERVICE_UNAVAILABLE,
 			Error::Other(..) => StatusCode::INTERNAL_SERVER_ERROR,
 		};
 
+		let available_languages = match &value {
+			Error::LanguageUnavailable { available, .. } => Some(available.clone()),
+			_ => None,
+		};
+
 		Self {
 			code: status_code,
 			message: value.to_string(),
+			available_languages,
 		}
 	}
 }
@@ -1,24 +1,40 @@
 use std::convert::Infallible;
 
-use aide::OperationIo;
+use aide::{gen::GenContext, openapi::Operation, OperationInput, OperationIo};
 use axum::{
 	async_trait,
 	extract::{FromRef, FromRequestParts, OriginalUri},
 	http::{request::Parts, Uri},
+	response::{IntoResponse, Redirect, Response},
 	RequestPartsExt,
 };
+use ironworks::excel;
 use schemars::JsonSchema;
 use serde::Deserialize;
 
-use crate::{http::service, version::VersionKey};
+use crate::{
+	data::LanguageString,
+	http::{access, service},
+	schema,
+	version::VersionKey,
+};
 
 use super::error::Error;
 
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct VersionQueryConfig {
+	/// Redirect requests specifying a version by name to the equivalent URL
+	/// specifying the version by key, such that links remain stable when the
+	/// name is later pointed at a different version.
+	#[serde(default)]
+	redirect_names: bool,
+}
+
 /// # VersionQuery
 /// Query parameters accepted by endpoints that interact with versioned game data.
 #[derive(Deserialize, JsonSchema)]
 struct VersionQueryParams {
-	/// Game version to utilise for this query.
+	/// Game version to utilise for this query, either by name, such as `latest`, or by key. Names may be re-pointed to newer versions over time - keys will always refer to the same data.
 	version: Option<String>,
 }
 
@@ -32,28 +48,146 @@ where
 	S: Send + Sync,
 	service::Version: FromRef<S>,
 {
-	type Rejection = Error;
+	type Rejection = Response;
 
 	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
 		let Query(params) = parts
 			.extract::<Query<VersionQueryParams>>()
 			.await
-			.map_err(|error| Error::Invalid(error.to_string()))?;
+			.map_err(|error| Error::Invalid(error.to_string()).into_response())?;
 
 		let version = service::Version::from_ref(state);
 
+		// Names take precedence, falling back to treating the value as a key.
 		let version_name = params.version.as_deref();
-		let version_key = version.resolve(version_name).ok_or_else(|| {
-			Error::Invalid(format!(
-				"unknown version \"{}\"",
-				version_name.unwrap_or("(none)")
-			))
-		})?;
+		let (version_key, by_name) = match version.resolve_visible(version_name) {
+			Some(key) => (key, true),
+			None => {
+				let key = version_name
+					.and_then(|name| version.resolve_key(name))
+					.filter(|key| !version.hidden(*key))
+					.ok_or_else(|| {
+						Error::Invalid(format!(
+							"unknown version \"{}\"",
+							version_name.unwrap_or("(none)")
+						))
+						.into_response()
+					})?;
+				(key, false)
+			}
+		};
+
+		// Requests without an explicit version are deliberately tracking the
+		// latest version, and are left as-is.
+		if by_name && version_name.is_some() {
+			let redirect_names = parts
+				.extensions
+				.get::<VersionQueryConfig>()
+				.is_some_and(|config| config.redirect_names);
+
+			if redirect_names {
+				let OriginalUri(uri) = parts
+					.extract::<OriginalUri>()
+					.await
+					.map_err(|error| match error {})?;
+				return Err(Redirect::temporary(&canonical_uri(&uri, version_key)).into_response());
+			}
+		}
+
+		access::record_version(&parts.extensions, version_key);
 
 		Ok(Self(version_key))
 	}
 }
 
+/// # ContextQuery
+/// Query parameters accepted by endpoints that read game data through a schema.
+#[derive(Deserialize, JsonSchema)]
+struct ContextQueryParams {
+	/// Language to use for data with no language otherwise specified.
+	language: Option<LanguageString>,
+
+	/// Schema that data should be read with.
+	schema: Option<schema::Specifier>,
+}
+
+/// The version, language, and schema a request reads game data with, resolved
+/// from the request's query parameters. Endpoints reading game data should use
+/// this rather than resolving the parameters themselves, such that every
+/// endpoint interprets them consistently.
+///
+/// Only plain values are resolved - the data version and schema themselves are
+/// left to the handler, as they can not be held across await points.
+#[derive(Debug, Clone)]
+pub struct ResolvedContext {
+	pub version: VersionKey,
+	pub language: excel::Language,
+	pub schema: schema::CanonicalSpecifier,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for ResolvedContext
+where
+	S: Send + Sync,
+	service::Version: FromRef<S>,
+	service::Data: FromRef<S>,
+	service::Schema: FromRef<S>,
+{
+	type Rejection = Response;
+
+	async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+		let VersionQuery(version) = VersionQuery::from_request_parts(parts, state).await?;
+
+		let Query(params) = parts
+			.extract::<Query<ContextQueryParams>>()
+			.await
+			.map_err(|error| Error::Invalid(error.to_string()).into_response())?;
+
+		let language = params
+			.language
+			.map(excel::Language::from)
+			.unwrap_or_else(|| service::Data::from_ref(state).default_language());
+
+		let schema = service::Schema::from_ref(state)
+			.canonicalize(params.schema, version)
+			.map_err(|error| Error::from(error).into_response())?;
+
+		access::record_schema(&parts.extensions, &schema);
+
+		Ok(Self {
+			version,
+			language,
+			schema,
+		})
+	}
+}
+
+impl OperationInput for ResolvedContext {
+	fn operation_input(ctx: &mut GenContext, operation: &mut Operation) {
+		VersionQuery::operation_input(ctx, operation);
+		Query::<ContextQueryParams>::operation_input(ctx, operation);
+	}
+}
+
+/// Build the URI of a request with its version parameter replaced by the key.
+fn canonical_uri(uri: &Uri, key: VersionKey) -> String {
+	let query = uri
+		.query()
+		.unwrap_or("")
+		.split('&')
+		.filter(|pair| !pair.is_empty())
+		.map(
+			|pair| match pair.split_once('=').map_or(pair, |(name, _)| name) {
+				"version" => format!("version={key}"),
+				_ => pair.to_string(),
+			},
+		)
+		.collect::<Vec<_>>()
+		.join("&");
+
+	format!("{}?{query}", uri.path())
+}
+
 // This cursed garbage courtesy of trying to get the path of the parent router. Fun.
 pub struct RouterPath(pub String);
 
@@ -80,3 +214,22 @@ pub struct Path<T>(pub T);
 #[from_request(via(axum::extract::Query), rejection(Error))]
 #[aide(input_with = "axum::extract::Query<T>", json_schema)]
 pub struct Query<T>(pub T);
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn canonical_uri_replaces_version() {
+		let key = "5f0c4b1f8e1a2d3c".parse::<VersionKey>().unwrap();
+		let uri = "/api/1/sheet/Item?fields=Name&version=7.05&limit=10"
+			.parse::<Uri>()
+			.unwrap();
+		assert_eq!(
+			canonical_uri(&uri, key),
+			"/api/1/sheet/Item?fields=Name&version=5f0c4b1f8e1a2d3c&limit=10"
+		);
+	}
+}
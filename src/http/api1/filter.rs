@@ -6,17 +6,18 @@ use nom::{
 	branch::alt,
 	bytes::complete::{escaped_transform, is_not, tag},
 	character::complete::{alphanumeric1, char},
-	combinator::{all_consuming, map, map_res, opt, value, verify},
+	combinator::{all_consuming, map, map_res, not, opt, value, verify},
+	error::ErrorKind,
 	multi::{many0, separated_list0, separated_list1},
-	sequence::{preceded, tuple},
+	sequence::{delimited, preceded, separated_pair, tuple},
 	Finish, IResult,
 };
 use schemars::JsonSchema;
 use serde::{de, Deserialize};
 
-use crate::{data, read};
+use crate::{data, read, utility::warnings::Warnings};
 
-use super::error;
+use super::{error, limit};
 
 /// A filter string for selecting fields within a row.
 ///
@@ -25,53 +26,149 @@ use super::error;
 ///
 /// A language may be specified on a field by field bases with an `@` suffix, i.e.
 /// `a@ja` will select the field `a`, retrieving the Japanese data associated with it.
+/// Fields without a language use the language of the field containing them,
+/// falling back to the default language of the request, i.e. `a@ja.b` will
+/// select the Japanese data of `b`.
 ///
 /// Nested fields may be selected using dot notation, i.e. `a.b` will select
 /// the field `b` contained in the struct `a`.
 ///
+/// Field names may contain `*` wildcards, matching any sequence of characters,
+/// i.e. `Base*` will select all fields with names starting with `Base`.
+///
 /// Arrays must be targeted if selecting fields within them, i.e. `a[].b` will
 /// select _all_ `b` fields of structs within the array `a`, however `a.b` will
 /// select nothing.
-#[derive(Debug, Clone, JsonSchema)]
-pub struct FilterString(#[schemars(with = "String")] Vec<Path>);
+///
+/// Fields that select incompatible structures, such as `a[]` alongside `a.b`,
+/// cannot be merged - the later field is ignored, and a warning is reported.
+///
+/// Multiple fields sharing a path may be grouped with parentheses, i.e.
+/// `a.(b,c)` is equivalent to `a.b,a.c`. Field names starting with `(`, or
+/// containing `)` within a group, must escape those characters with `\`.
+///
+/// A field may be selected conditionally with an `?if=` suffix, comparing the
+/// value of a top-level field of the row, i.e. `Description?if=IsUntradable=0`
+/// will only select `Description` for rows where `IsUntradable` is `0`.
+/// Conditions apply to every field selected by the preceding path, including
+/// groups. Field names containing `?` must escape it with `\`.
+///
+/// Filters may select a limited number of fields in total, counting each field
+/// selected by a group separately.
+#[derive(Debug, Clone, Hash, JsonSchema)]
+pub struct FilterString(#[schemars(with = "String")] Vec<(Path, Option<read::Condition>)>);
 
 type Path = Vec<Entry>;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Hash)]
 enum Entry {
 	Key(String, Option<excel::Language>),
 	Index,
 }
 
 impl FilterString {
-	pub fn to_filter(self, default_language: excel::Language) -> error::Result<read::Filter> {
-		let mut filters = self
-			.0
-			.into_iter()
-			.map(|entries| build_filter(entries, default_language));
+	pub fn to_filter(self, default_language: excel::Language) -> Warnings<read::Filter> {
+		let mut filters = self.0.into_iter().map(|(entries, _condition)| {
+			(
+				path_string(&entries),
+				build_filter(entries, default_language),
+			)
+		});
 
-		let Some(mut output) = filters.next() else {
-			return Ok(read::Filter::All);
+		let Some((_, mut output)) = filters.next() else {
+			return Warnings::new(read::Filter::All);
 		};
 
-		for filter in filters {
-			output = merge_filters(output, filter)?;
+		let mut warnings = vec![];
+		for (path, filter) in filters {
+			output = match merge_filters(&output, filter, &path) {
+				Ok(merged) => merged,
+				Err(warning) => {
+					warnings.push(warning);
+					output
+				}
+			};
+		}
+
+		Warnings::new(output).with_warnings(warnings)
+	}
+
+	/// Build a filter that only selects conditional fields for rows meeting
+	/// their condition.
+	pub fn to_conditional_filter(
+		self,
+		default_language: excel::Language,
+	) -> Warnings<read::ConditionalFilter> {
+		if self.0.is_empty() {
+			return Warnings::new(read::Filter::All.into());
+		}
+
+		let mut base = read::Filter::Struct(HashMap::new());
+		let mut branches = Vec::<(read::Condition, read::Filter)>::new();
+
+		// Conditional fields may be selected alongside any other field, so each
+		// field is checked against the union of every field accepted so far.
+		let mut union = base.clone();
+		let mut warnings = vec![];
+
+		for (entries, condition) in self.0 {
+			let path = path_string(&entries);
+			let filter = build_filter(entries, default_language);
+			union = match merge_filters(&union, filter.clone(), &path) {
+				Ok(merged) => merged,
+				Err(warning) => {
+					warnings.push(warning);
+					continue;
+				}
+			};
+
+			// Subsets of the union are known to be compatible, so the merges below
+			// cannot fail.
+			let Some(condition) = condition else {
+				base = merge_filters(&base, filter, &path).expect("field is compatible with union");
+				continue;
+			};
+
+			match branches
+				.iter_mut()
+				.find(|(existing, _)| *existing == condition)
+			{
+				Some((_, existing)) => {
+					*existing = merge_filters(existing, filter, &path)
+						.expect("field is compatible with union");
+				}
+				None => branches.push((condition, filter)),
+			}
 		}
 
-		Ok(output)
+		let filter =
+			read::ConditionalFilter::new(base, branches).expect("fields are compatible with union");
+
+		Warnings::new(filter).with_warnings(warnings)
 	}
 }
 
 fn build_filter(path: Path, default_language: excel::Language) -> read::Filter {
+	// Resolve the language of each entry - languages specified on a key are
+	// inherited by the entries following it.
+	let entries = path
+		.into_iter()
+		.scan(default_language, |language, entry| {
+			if let Entry::Key(_, Some(specified_language)) = entry {
+				*language = specified_language;
+			}
+			Some((entry, *language))
+		})
+		.collect::<Vec<_>>();
+
 	let mut output = read::Filter::All;
 
 	// Walk through the path in reverse, building a nested filter structure for it
-	for entry in path.into_iter().rev() {
+	for (entry, language) in entries.into_iter().rev() {
 		output = match entry {
 			Entry::Index => read::Filter::Array(output.into()),
 
-			Entry::Key(key, specified_language) => {
-				let language = specified_language.unwrap_or(default_language);
+			Entry::Key(key, _) => {
 				let mut language_map = IntMap::default();
 				language_map.insert(read::Language(language), output);
 				let key_map = HashMap::from([(key, language_map)]);
@@ -83,44 +180,143 @@ fn build_filter(path: Path, default_language: excel::Language) -> read::Filter {
 	output
 }
 
-fn merge_filters(a: read::Filter, b: read::Filter) -> error::Result<read::Filter> {
-	use read::Filter as F;
-
-	let new_filter = match (a, b) {
-		// If either branch is a catch-all, it propagates.
-		(F::All, _) | (_, F::All) => F::All,
+/// Merge the filter for a field into an existing filter, producing a warning
+/// describing the conflict if the two are incompatible.
+fn merge_filters(
+	existing: &read::Filter,
+	filter: read::Filter,
+	path: &str,
+) -> Result<read::Filter, String> {
+	existing
+		.clone()
+		.try_merge(filter)
+		.map_err(|conflict| format!("ignoring field {path} in filter: {conflict}"))
+}
 
-		// Arrays can directly merge their inner filter.
-		(F::Array(a_inner), F::Array(b_inner)) => {
-			F::Array(merge_filters(*a_inner, *b_inner)?.into())
+/// Format a path in filter string syntax, i.e. `a@ja.b[]`.
+fn path_string(path: &Path) -> String {
+	path.iter().fold(String::new(), |mut output, entry| {
+		match entry {
+			Entry::Key(key, language) => {
+				if !output.is_empty() {
+					output.push('.');
+				}
+				output.push_str(key);
+				if let Some(language) = language {
+					output.push('@');
+					output.push_str(&data::LanguageString::from(*language).to_string());
+				}
+			}
+			Entry::Index => output.push_str("[]"),
 		}
+		output
+	})
+}
 
-		// Structs need to be merged across both the inner maps.
-		(F::Struct(mut a_fields), F::Struct(b_fields)) => {
-			for (field_name, b_languages) in b_fields {
-				let a_languages = a_fields.entry(field_name).or_default();
-				for (language, b_filter) in b_languages {
-					let new_filter = match a_languages.remove(&language) {
-						None => b_filter,
-						Some(a_filter) => merge_filters(a_filter, b_filter)?,
-					};
-					a_languages.insert(language, new_filter);
-				}
+/// A path to a single field within a row, addressed by slash-separated
+/// segments, i.e. `BaseParam/0/Value`.
+///
+/// Numeric segments select an index within an array. As with filter strings,
+/// a language may be specified on a field with an `@` suffix, which is
+/// inherited by the fields nested within it.
+#[derive(Debug, Clone, JsonSchema)]
+pub struct FieldPath(#[schemars(with = "String")] Vec<FieldSegment>);
+
+#[derive(Debug, Clone)]
+enum FieldSegment {
+	Key(String, Option<excel::Language>),
+	Index(usize),
+}
+
+impl FieldPath {
+	/// Build a filter selecting the field, and any data nested within it.
+	pub fn to_filter(&self, default_language: excel::Language) -> read::Filter {
+		let path = self
+			.0
+			.iter()
+			.map(|segment| match segment {
+				FieldSegment::Key(key, language) => Entry::Key(key.clone(), *language),
+				FieldSegment::Index(_) => Entry::Index,
+			})
+			.collect();
+
+		build_filter(path, default_language)
+	}
+
+	/// Select the field from a value read from the root of a row. Populated
+	/// references are stepped through transparently, such that the path may
+	/// continue into the fields of the referenced row.
+	pub fn select(
+		self,
+		value: read::Value,
+		default_language: excel::Language,
+	) -> Option<read::Value> {
+		let mut language = default_language;
+		let mut value = value;
+
+		for segment in self.0 {
+			if let read::Value::Reference(read::Reference::Populated { fields, .. }) = value {
+				value = *fields;
 			}
-			F::Struct(a_fields)
+
+			value = match (segment, value) {
+				(FieldSegment::Key(name, specified_language), read::Value::Struct(mut fields)) => {
+					language = specified_language.unwrap_or(language);
+					fields.remove(&read::StructKey { name, language })?
+				}
+
+				(FieldSegment::Index(index), read::Value::Array(values)) => {
+					values.into_iter().nth(index)?
+				}
+
+				_ => return None,
+			};
 		}
 
-		// Other patterns are invalid. Explicitly checking the first element to
-		// ensure this code path will error if new filter types are added.
-		(F::Array(_), _) | (F::Struct(_), _) => {
-			return Err(error::Error::Invalid(
-				// TODO: improve this error message
-				"invalid filter: tried to merge array and struct".into(),
-			));
+		Some(value)
+	}
+}
+
+impl<'de> Deserialize<'de> for FieldPath {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let raw = String::deserialize(deserializer)?;
+		raw.parse().map_err(de::Error::custom)
+	}
+}
+
+impl FromStr for FieldPath {
+	type Err = error::Error;
+
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		let segments = input
+			.split('/')
+			.filter(|segment| !segment.is_empty())
+			.map(|segment| {
+				if let Ok(index) = segment.parse::<usize>() {
+					return Ok(FieldSegment::Index(index));
+				}
+
+				let (key, language) = match segment.rsplit_once('@') {
+					Some((key, language)) => (key, Some(language.parse::<data::LanguageString>()?)),
+					None => (segment, None),
+				};
+
+				Ok(FieldSegment::Key(
+					key.to_string(),
+					language.map(excel::Language::from),
+				))
+			})
+			.collect::<error::Result<Vec<_>>>()?;
+
+		if segments.is_empty() {
+			return Err(error::Error::Invalid("field path must not be empty".into()));
 		}
-	};
 
-	Ok(new_filter)
+		Ok(Self(segments))
+	}
 }
 
 impl<'de> Deserialize<'de> for FilterString {
@@ -139,35 +335,145 @@ impl FromStr for FilterString {
 
 	fn from_str(input: &str) -> Result<Self, Self::Err> {
 		// TODO: Consider using VerboseError or similar?
-		let (_, filter) = all_consuming(filter)(input)
-			.finish()
-			.map_err(|error| error::Error::Invalid(error.to_string()))?;
+		let (_, filter) =
+			all_consuming(filter)(input)
+				.finish()
+				.map_err(|error| match error.code {
+					ErrorKind::TooLarge => error::Error::Invalid(format!(
+						"filter selects more than {} fields",
+						limit::filter_paths()
+					)),
+					_ => error::Error::Invalid(error.to_string()),
+				})?;
 
 		Ok(filter)
 	}
 }
 
 fn filter(input: &str) -> IResult<&str, FilterString> {
-	map(separated_list0(char(','), path), FilterString)(input)
+	let (rest, paths) = separated_list0(char(','), conditional_path)(input)?;
+	let paths = paths.into_iter().flatten().collect::<Vec<_>>();
+	check_paths(input, paths.len())?;
+	Ok((rest, FilterString(paths)))
 }
 
-fn path(input: &str) -> IResult<&str, Path> {
-	map(separated_list1(char('.'), path_part), |parts| {
-		parts.into_iter().flatten().collect()
-	})(input)
+// Groups multiply the paths they are applied to - bail as soon as a filter
+// exceeds the limit, rather than expanding it in full.
+fn check_paths(input: &str, count: usize) -> Result<(), nom::Err<nom::error::Error<&str>>> {
+	match count > limit::filter_paths() {
+		true => Err(nom::Err::Failure(nom::error::Error::new(
+			input,
+			ErrorKind::TooLarge,
+		))),
+		false => Ok(()),
+	}
 }
 
-fn path_part(input: &str) -> IResult<&str, Vec<Entry>> {
-	map(tuple((key, many0(index))), |(key, mut maybe_index)| {
-		let mut parts = vec![key];
-		parts.append(&mut maybe_index);
-		parts
-	})(input)
+fn conditional_path(input: &str) -> IResult<&str, Vec<(Path, Option<read::Condition>)>> {
+	map(
+		tuple((|input| path(input, false), opt(condition))),
+		|(paths, condition)| {
+			paths
+				.into_iter()
+				.map(|path| (path, condition.clone()))
+				.collect()
+		},
+	)(input)
+}
+
+// Conditions run to the end of the path - values may be empty, matching empty
+// strings, but may not contain `,`.
+fn condition(input: &str) -> IResult<&str, read::Condition> {
+	map(
+		preceded(
+			tag("?if="),
+			separated_pair(is_not("=,"), char('='), opt(is_not(","))),
+		),
+		|(field, value): (&str, Option<&str>)| read::Condition {
+			field: field.into(),
+			value: value.unwrap_or_default().into(),
+		},
+	)(input)
+}
+
+enum Segment {
+	Entries(Vec<Entry>),
+	Group(Vec<Path>),
+}
+
+// Paths are expanded as they are parsed - a path containing groups will
+// produce one path for every field selected by those groups.
+fn path(input: &str, nested: bool) -> IResult<&str, Vec<Path>> {
+	let (rest, segments) = separated_list1(char('.'), |input| segment(input, nested))(input)?;
+
+	let paths =
+		segments
+			.into_iter()
+			.try_fold(vec![Path::new()], |prefixes, segment| match segment {
+				Segment::Entries(entries) => Ok(prefixes
+					.into_iter()
+					.map(|mut prefix| {
+						prefix.extend(entries.iter().cloned());
+						prefix
+					})
+					.collect()),
+
+				Segment::Group(paths) => {
+					check_paths(input, prefixes.len().saturating_mul(paths.len()))?;
+					Ok(prefixes
+						.iter()
+						.flat_map(|prefix| {
+							paths
+								.iter()
+								.map(move |path| prefix.iter().chain(path).cloned().collect())
+						})
+						.collect())
+				}
+			})?;
+
+	Ok((rest, paths))
+}
+
+fn segment(input: &str, nested: bool) -> IResult<&str, Segment> {
+	alt((
+		map(group, Segment::Group),
+		map(|input| path_part(input, nested), Segment::Entries),
+	))(input)
 }
 
-fn key(input: &str) -> IResult<&str, Entry> {
+fn group(input: &str) -> IResult<&str, Vec<Path>> {
+	let (rest, paths) = delimited(
+		char('('),
+		separated_list1(char(','), |input| path(input, true)),
+		char(')'),
+	)(input)?;
+	let paths = paths.into_iter().flatten().collect::<Vec<_>>();
+	check_paths(input, paths.len())?;
+	Ok((rest, paths))
+}
+
+fn path_part(input: &str, nested: bool) -> IResult<&str, Vec<Entry>> {
+	map(
+		tuple((|input| key(input, nested), many0(index))),
+		|(key, mut maybe_index)| {
+			let mut parts = vec![key];
+			parts.append(&mut maybe_index);
+			parts
+		},
+	)(input)
+}
+
+fn key(input: &str, nested: bool) -> IResult<&str, Entry> {
+	// Keys may not start with an unescaped `(`, which always opens a group.
+	// Closing parentheses are only special within a group, permitting their
+	// use elsewhere in top-level keys without escaping.
+	let unescaped = match nested {
+		true => is_not("\\@[.,)?"),
+		false => is_not("\\@[.,?"),
+	};
+
 	let escaped_key = escaped_transform(
-		is_not("\\@[.,"),
+		unescaped,
 		'\\',
 		alt((
 			value("\\", char('\\')),
@@ -177,12 +483,15 @@ fn key(input: &str) -> IResult<&str, Entry> {
 			value("]", char(']')),
 			value(".", char('.')),
 			value(",", char(',')),
+			value("(", char('(')),
+			value(")", char(')')),
+			value("?", char('?')),
 		)),
 	);
 
 	map(
 		tuple((
-			verify(escaped_key, |t: &str| !t.is_empty()),
+			preceded(not(char('(')), verify(escaped_key, |t: &str| !t.is_empty())),
 			opt(preceded(char('@'), language)),
 		)),
 		|(key, language)| Entry::Key(key.into(), language),
@@ -209,12 +518,16 @@ mod test {
 	use super::*;
 
 	fn test_parse(input: &str) -> read::Filter {
+		test_parse_language(input, excel::Language::English)
+	}
+
+	fn test_parse_language(input: &str, default_language: excel::Language) -> read::Filter {
 		let filter_string = input
 			.parse::<FilterString>()
 			.expect("parse should not fail");
-		filter_string
-			.to_filter(excel::Language::English)
-			.expect("conversion should not fail")
+		let (filter, warnings) = filter_string.to_filter(default_language).decompose();
+		assert_eq!(warnings, Vec::<String>::new());
+		filter
 	}
 
 	fn test_struct(
@@ -278,6 +591,94 @@ mod test {
 		assert_eq!(got, expected);
 	}
 
+	#[test]
+	fn parse_default_language() {
+		let expected = test_language_struct([(
+			"a",
+			test_language_map([(excel::Language::German, read::Filter::All)]),
+		)]);
+
+		let got = test_parse_language("a", excel::Language::German);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_default_language_override() {
+		let expected = test_language_struct([
+			(
+				"a",
+				test_language_map([(excel::Language::Japanese, read::Filter::All)]),
+			),
+			(
+				"b",
+				test_language_map([(excel::Language::German, read::Filter::All)]),
+			),
+		]);
+
+		let got = test_parse_language("a@ja,b", excel::Language::German);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_language_inherited() {
+		let expected = test_language_struct([(
+			"a",
+			test_language_map([(
+				excel::Language::Japanese,
+				test_language_struct([(
+					"b",
+					test_language_map([(excel::Language::Japanese, read::Filter::All)]),
+				)]),
+			)]),
+		)]);
+
+		let got = test_parse_language("a@ja.b", excel::Language::German);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_language_inherited_override() {
+		let expected = test_language_struct([(
+			"a",
+			test_language_map([(
+				excel::Language::Japanese,
+				test_array(test_language_struct([(
+					"b",
+					test_language_map([(excel::Language::French, read::Filter::All)]),
+				)])),
+			)]),
+		)]);
+
+		let got = test_parse_language("a@ja[].b@fr", excel::Language::German);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_language_shared_path() {
+		let expected = test_language_struct([(
+			"a",
+			test_language_map([
+				(
+					excel::Language::German,
+					test_language_struct([(
+						"b",
+						test_language_map([(excel::Language::German, read::Filter::All)]),
+					)]),
+				),
+				(
+					excel::Language::Japanese,
+					test_language_struct([(
+						"b",
+						test_language_map([(excel::Language::Japanese, read::Filter::All)]),
+					)]),
+				),
+			]),
+		)]);
+
+		let got = test_parse_language("a.b,a@ja.b", excel::Language::German);
+		assert_eq!(got, expected);
+	}
+
 	#[test]
 	fn parse_struct_nested() {
 		let expected = test_struct([(
@@ -330,6 +731,122 @@ mod test {
 		assert_eq!(got, expected);
 	}
 
+	#[test]
+	fn parse_group_simple() {
+		let expected = test_parse("a.b,a.c");
+
+		let got = test_parse("a.(b,c)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_root() {
+		let expected = test_parse("a,b");
+
+		let got = test_parse("(a,b)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_nested() {
+		let expected = test_struct([(
+			"a",
+			test_struct([
+				(
+					"b",
+					test_struct([("c", read::Filter::All), ("d", read::Filter::All)]),
+				),
+				("e", read::Filter::All),
+			]),
+		)]);
+
+		let got = test_parse("a.(b.(c,d),e)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_array() {
+		let expected = test_parse("a[].b,a[].c");
+
+		let got = test_parse("a[].(b,c)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_continued() {
+		let expected = test_parse("a.b.d,a.c.d");
+
+		let got = test_parse("a.(b,c).d");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_merged() {
+		let expected = test_parse("a.b,a.c,a.d");
+
+		let got = test_parse("a.(b,c),a.(c,d)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_language() {
+		let expected = test_parse("a@ja.b,a@ja.c@fr");
+
+		let got = test_parse("a@ja.(b,c@fr)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_group_escaped_keys() {
+		let expected = test_struct([(
+			"a",
+			test_struct([("b)c", read::Filter::All), ("d(e", read::Filter::All)]),
+		)]);
+
+		let got = test_parse("a.(b\\)c,d(e)");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_merge_conflict() {
+		// Conflicting fields are ignored, keeping the first selected.
+		let (got, warnings) = "a@ja[].b,a@ja.c,d"
+			.parse::<FilterString>()
+			.expect("parse should not fail")
+			.to_filter(excel::Language::English)
+			.decompose();
+		assert_eq!(got, test_parse("a@ja[].b,d"));
+		assert_eq!(
+			warnings,
+			vec!["ignoring field a@ja.c in filter: a is selected as both an array and a struct"]
+		);
+	}
+
+	#[test]
+	fn parse_group_merge_conflict() {
+		// Groups are subject to the same merge rules as separate paths.
+		let (got, warnings) = "a.(b[],b.c)"
+			.parse::<FilterString>()
+			.expect("parse should not fail")
+			.to_filter(excel::Language::English)
+			.decompose();
+		assert_eq!(got, test_parse("a.b[]"));
+		assert_eq!(
+			warnings,
+			vec!["ignoring field a.b.c in filter: a.b is selected as both an array and a struct"]
+		);
+	}
+
+	#[test]
+	fn parse_group_invalid() {
+		for input in ["a.()", "a.(b", "a.(b,)", "(a"] {
+			assert!(
+				input.parse::<FilterString>().is_err(),
+				"{input:?} should not parse"
+			);
+		}
+	}
+
 	#[test]
 	fn parse_complex_struct_keys() {
 		let expected = test_struct([
@@ -348,4 +865,160 @@ mod test {
 		);
 		assert_eq!(got, expected);
 	}
+
+	fn test_parse_conditional(input: &str) -> read::ConditionalFilter {
+		input
+			.parse::<FilterString>()
+			.expect("parse should not fail")
+			.to_conditional_filter(excel::Language::English)
+			.decompose()
+			.0
+	}
+
+	fn test_condition(field: &str, value: &str) -> read::Condition {
+		read::Condition {
+			field: field.into(),
+			value: value.into(),
+		}
+	}
+
+	#[test]
+	fn parse_conditional() {
+		let expected = read::ConditionalFilter::new(
+			test_parse("a"),
+			vec![(test_condition("c", "0"), test_parse("b"))],
+		)
+		.unwrap();
+
+		let got = test_parse_conditional("a,b?if=c=0");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_conditional_shared_condition() {
+		let expected = read::ConditionalFilter::new(
+			read::Filter::Struct(HashMap::new()),
+			vec![(test_condition("c", "1"), test_parse("a.b,d"))],
+		)
+		.unwrap();
+
+		let got = test_parse_conditional("a.(b)?if=c=1,d?if=c=1");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_conditional_empty_value() {
+		let expected = read::ConditionalFilter::new(
+			read::Filter::Struct(HashMap::new()),
+			vec![(test_condition("c", ""), test_parse("a"))],
+		)
+		.unwrap();
+
+		let got = test_parse_conditional("a?if=c=");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_conditional_merge_conflict() {
+		// Conditional fields must be compatible with every other field, as
+		// conditions may be met alongside one another.
+		let (got, warnings) = "a[]?if=c=0,a.b?if=c=1"
+			.parse::<FilterString>()
+			.expect("parse should not fail")
+			.to_conditional_filter(excel::Language::English)
+			.decompose();
+		let expected = read::ConditionalFilter::new(
+			read::Filter::Struct(HashMap::new()),
+			vec![(test_condition("c", "0"), test_parse("a[]"))],
+		)
+		.unwrap();
+		assert_eq!(got, expected);
+		assert_eq!(warnings.len(), 1);
+	}
+
+	#[test]
+	fn parse_conditional_unconditional_filter() {
+		// Filters built without conditions select conditional fields for every row.
+		let expected = test_parse("a,b");
+
+		let got = test_parse("a,b?if=c=0");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_conditional_escaped_key() {
+		let expected = test_struct([("a?if=b=c", read::Filter::All)]);
+
+		let got = test_parse("a\\?if=b=c");
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn parse_path_limit() {
+		// Each group doubles the number of paths selected.
+		let within = vec!["(a,b)"; 10].join(".");
+		assert!(within.parse::<FilterString>().is_ok());
+
+		let exceeding = vec!["(a,b)"; 11].join(".");
+		assert!(matches!(
+			exceeding.parse::<FilterString>(),
+			Err(error::Error::Invalid(message)) if message.contains("1024")
+		));
+	}
+
+	#[test]
+	fn parse_conditional_invalid() {
+		for input in ["a?if=", "a?if=b", "a?b", "a.(b?if=c=0)"] {
+			assert!(
+				input.parse::<FilterString>().is_err(),
+				"{input:?} should not parse"
+			);
+		}
+	}
+
+	fn test_field_path(input: &str) -> FieldPath {
+		input.parse::<FieldPath>().expect("parse should not fail")
+	}
+
+	#[test]
+	fn field_path_filter() {
+		let expected = test_parse("a[].b@ja.c");
+
+		let got = test_field_path("a/0/b@ja/c").to_filter(excel::Language::English);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn field_path_invalid() {
+		for input in ["", "/", "a@unknown"] {
+			assert!(
+				input.parse::<FieldPath>().is_err(),
+				"{input:?} should not parse"
+			);
+		}
+	}
+
+	#[test]
+	fn field_path_select() {
+		let key = |name: &str, language| read::StructKey {
+			name: name.into(),
+			language,
+		};
+		let value = read::Value::Struct(HashMap::from([(
+			key("a", excel::Language::English),
+			read::Value::Array(vec![
+				read::Value::Scalar(excel::Field::U8(1)),
+				read::Value::Struct(HashMap::from([(
+					key("b", excel::Language::Japanese),
+					read::Value::Scalar(excel::Field::U8(2)),
+				)])),
+			]),
+		)]));
+
+		let got = test_field_path("a/1/b@ja").select(value, excel::Language::English);
+		assert!(matches!(
+			got,
+			Some(read::Value::Scalar(excel::Field::U8(2)))
+		));
+	}
 }
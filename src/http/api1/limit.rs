@@ -0,0 +1,89 @@
+use axum::{
+	extract::{Request, State},
+	http::header,
+	middleware::Next,
+	response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+
+use super::error::Error;
+
+/// Number of field paths a filter may expand to when parsed outside of a
+/// request, i.e. in tests.
+const DEFAULT_FILTER_PATHS: usize = 1024;
+
+tokio::task_local! {
+	static FILTER_PATHS: usize;
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Maximum length of a request's query string, in bytes.
+	query_length: usize,
+
+	/// Maximum size of a request body, in bytes.
+	body_size: usize,
+
+	/// Maximum number of field paths a single filter may select, after
+	/// expanding any groups, i.e. `a.(b,c)` selects two paths.
+	filter_paths: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			query_length: 8 * 1024,
+			body_size: 1024 * 1024,
+			filter_paths: DEFAULT_FILTER_PATHS,
+		}
+	}
+}
+
+impl Config {
+	pub fn body_size(&self) -> usize {
+		self.body_size
+	}
+}
+
+/// Reject requests exceeding the configured size limits before their contents
+/// are parsed.
+pub async fn limit(State(config): State<Config>, request: Request, next: Next) -> Response {
+	let query_length = request.uri().query().map(str::len).unwrap_or(0);
+	if query_length > config.query_length {
+		return Error::UriTooLong(format!(
+			"query string of {query_length} bytes exceeds limit of {}",
+			config.query_length
+		))
+		.into_response();
+	}
+
+	// Bodies without a declared length are bounded by the body limit layer as
+	// they are read.
+	let content_length = request
+		.headers()
+		.get(header::CONTENT_LENGTH)
+		.and_then(|value| value.to_str().ok())
+		.and_then(|value| value.parse::<usize>().ok());
+	if let Some(content_length) = content_length {
+		if content_length > config.body_size {
+			return Error::PayloadTooLarge(format!(
+				"body of {content_length} bytes exceeds limit of {}",
+				config.body_size
+			))
+			.into_response();
+		}
+	}
+
+	FILTER_PATHS
+		.scope(config.filter_paths, next.run(request))
+		.await
+}
+
+/// Maximum number of field paths a filter parsed by the current request may
+/// select.
+pub fn filter_paths() -> usize {
+	FILTER_PATHS
+		.try_with(|paths| *paths)
+		.unwrap_or(DEFAULT_FILTER_PATHS)
+}
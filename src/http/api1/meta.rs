@@ -0,0 +1,107 @@
+use ironworks::excel;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::{data::LanguageString, read, utility::anyhow::Anyhow, version::VersionKey};
+
+use super::{cache::CacheStatus, error::Result, extract::ResolvedContext};
+
+/// Metadata describing how a response was resolved. Intended to aid debugging
+/// changes in responses between requests.
+#[derive(Serialize, JsonSchema)]
+pub struct Meta {
+	/// Key of the game version data was read from.
+	#[schemars(with = "String")]
+	pub version: VersionKey,
+
+	/// Language used for data with no language otherwise specified.
+	pub language: String,
+
+	/// Source of the schema used to read data.
+	pub schema_source: String,
+
+	/// Revision of the schema source used to read data.
+	pub schema_revision: String,
+
+	/// Languages requested for the sheet that it does not contain, and the
+	/// language that was read in their place.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	pub language_fallbacks: Vec<LanguageFallback>,
+
+	/// Hash of the content of the sheet read, across all languages. Changes
+	/// whenever any of the sheet's data changes between versions.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub sheet_hash: Option<String>,
+
+	/// Status of the cache of derived data used to build the response, if any.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cache: Option<CacheStatus>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct LanguageFallback {
+	/// Language that was requested.
+	pub requested: String,
+
+	/// Language that was read instead.
+	pub resolved: String,
+}
+
+impl Meta {
+	pub fn new(context: &ResolvedContext) -> Self {
+		Self {
+			version: context.version,
+			language: LanguageString::from(context.language).to_string(),
+			schema_source: context.schema.source.clone(),
+			schema_revision: context.schema.version.clone(),
+			language_fallbacks: vec![],
+			sheet_hash: None,
+			cache: None,
+		}
+	}
+
+	/// Record fallbacks for any of the languages requested by the default
+	/// language and filter that are not available in the specified sheet.
+	pub fn with_language_fallbacks(
+		mut self,
+		excel: &excel::Excel,
+		sheet: &str,
+		language: excel::Language,
+		filter: &read::Filter,
+	) -> Result<Self> {
+		let available = excel.sheet(sheet).anyhow()?.languages().anyhow()?;
+
+		let mut requested = filter.languages();
+		requested.insert(language);
+
+		// Mirror ironworks' behavior of reading languageless data for sheets
+		// that do not contain the requested language.
+		let mut fallbacks = requested
+			.into_iter()
+			.filter(|language| !available.contains(language))
+			.map(|language| LanguageFallback {
+				requested: LanguageString::from(language).to_string(),
+				resolved: LanguageString::from(excel::Language::None).to_string(),
+			})
+			.collect::<Vec<_>>();
+		fallbacks.sort_by(|a, b| a.requested.cmp(&b.requested));
+
+		self.language_fallbacks = fallbacks;
+		Ok(self)
+	}
+
+	pub fn with_sheet_hash(mut self, hash: Option<u64>) -> Self {
+		self.sheet_hash = hash.map(hash_string);
+		self
+	}
+
+	pub fn with_cache(mut self, status: CacheStatus) -> Self {
+		self.cache = Some(status);
+		self
+	}
+}
+
+/// Format a content hash for inclusion in a response.
+pub fn hash_string(hash: u64) -> String {
+	format!("{hash:016x}")
+}
@@ -1,9 +1,18 @@
 mod api;
 mod asset;
+mod browse;
+mod cache;
+mod conversion;
 mod error;
 mod extract;
 mod filter;
+mod limit;
+mod meta;
+mod modified;
+mod search;
 mod sheet;
+mod timeout;
+mod types;
 mod value;
 mod version;
 
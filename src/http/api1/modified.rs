@@ -0,0 +1,77 @@
+use std::time::SystemTime;
+
+use axum::{http::StatusCode, response::IntoResponse};
+use axum_extra::{
+	headers::{IfModifiedSince, LastModified},
+	TypedHeader,
+};
+
+/// Last modification time of responses derived from a version's game data,
+/// being the release time of the version's most recent patch. Data only changes
+/// between patches, so clients may use this to avoid refetching unchanged data.
+pub struct Modified(Option<SystemTime>);
+
+impl Modified {
+	pub fn new(last_modified: Option<SystemTime>) -> Self {
+		Self(last_modified)
+	}
+
+	/// Check a request's `If-Modified-Since` header, returning a `304 Not Modified`
+	/// response if the client's copy of the data is still current.
+	pub fn check(
+		&self,
+		if_modified_since: Option<TypedHeader<IfModifiedSince>>,
+	) -> Option<impl IntoResponse> {
+		let (Some(TypedHeader(if_modified_since)), Some(last_modified)) =
+			(if_modified_since, self.0)
+		else {
+			return None;
+		};
+
+		match if_modified_since.is_modified(last_modified) {
+			true => None,
+			false => Some((StatusCode::NOT_MODIFIED, self.header(), ())),
+		}
+	}
+
+	/// `Last-Modified` header to attach to responses, if the time is known.
+	pub fn header(&self) -> Option<TypedHeader<LastModified>> {
+		self.0.map(|time| TypedHeader(LastModified::from(time)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn status(modified: &Modified, since: SystemTime) -> Option<StatusCode> {
+		let header = TypedHeader(IfModifiedSince::from(since));
+		modified
+			.check(Some(header))
+			.map(|response| response.into_response().status())
+	}
+
+	#[test]
+	fn not_modified() {
+		let released = SystemTime::UNIX_EPOCH + Duration::from_secs(1695859200);
+		let modified = Modified::new(Some(released));
+
+		assert_eq!(status(&modified, released), Some(StatusCode::NOT_MODIFIED));
+		assert_eq!(
+			status(&modified, released + Duration::from_secs(60)),
+			Some(StatusCode::NOT_MODIFIED)
+		);
+		assert_eq!(status(&modified, released - Duration::from_secs(60)), None);
+	}
+
+	#[test]
+	fn unknown_release() {
+		let modified = Modified::new(None);
+		assert_eq!(status(&modified, SystemTime::UNIX_EPOCH), None);
+		assert!(modified.check(None).is_none());
+	}
+}
@@ -0,0 +1,511 @@
+use aide::{
+	axum::{routing::get_with, ApiRouter, IntoApiResponse},
+	transform::TransformOperation,
+};
+use axum::{debug_handler, extract::State, Extension, Json};
+use ironworks::excel;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+	asset,
+	http::{page::Page, service},
+	read, schema,
+	utility::reloadable::Reloadable,
+	version::VersionKey,
+};
+
+use super::{
+	cache::BuildCache,
+	error::Result,
+	extract::{Path, Query, ResolvedContext},
+	meta::Meta,
+	timeout::Cancellation,
+};
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+	limit: Reloadable<LimitConfig>,
+}
+
+impl Config {
+	pub fn reload(&self, config: Config) {
+		if self.limit.set(config.limit.get()) {
+			tracing::info!(limit = ?self.limit, "search limits changed");
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+struct LimitConfig {
+	default: usize,
+	max: usize,
+}
+
+pub fn router(config: Config) -> ApiRouter<service::State> {
+	ApiRouter::new()
+		.api_route("/icon", get_with(icon_manifest, icon_manifest_docs))
+		.api_route("/icon/:icon", get_with(icon, icon_docs))
+		.api_route("/dialogue", get_with(dialogue, dialogue_docs))
+		.layer(Extension(config))
+		.layer(Extension(IconCache::default()))
+		.layer(Extension(IconManifestCache::default()))
+		.layer(Extension(DialogueCache::default()))
+}
+
+/// Rows using each icon, per game and schema version.
+type IconCache = BuildCache<(VersionKey, schema::CanonicalSpecifier), read::IconUses>;
+
+/// Path variables accepted by the icon search endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct IconPath {
+	/// ID of the icon to search for.
+	icon: u32,
+}
+
+/// Query parameters accepted by the icon search endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct IconQuery {
+	/// Maximum number of rows to return.
+	limit: Option<usize>,
+
+	/// Number of rows to skip.
+	offset: Option<usize>,
+
+	/// Cursor returned as `next_cursor` by a previous request. Takes precedence over `offset`.
+	cursor: Option<usize>,
+
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+}
+
+/// Response structure for the icon search endpoint.
+#[derive(Serialize, JsonSchema)]
+struct IconResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Rows using the requested icon.
+	#[serde(flatten)]
+	results: Page<IconResult>,
+
+	/// Details of how the response was resolved. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<Meta>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct IconResult {
+	/// Sheet containing the row.
+	sheet: String,
+
+	/// ID of the row.
+	row_id: u32,
+
+	/// Subrow ID of the row.
+	subrow_id: u16,
+
+	/// Path to the field holding the icon.
+	field: String,
+}
+
+impl From<&read::IconUse> for IconResult {
+	fn from(icon_use: &read::IconUse) -> Self {
+		Self {
+			sheet: icon_use.sheet.clone(),
+			row_id: icon_use.row_id,
+			subrow_id: icon_use.subrow_id,
+			field: icon_use.field.clone(),
+		}
+	}
+}
+
+fn icon_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("search rows by icon")
+		.description(
+			"List all rows, across all sheets, with a field using the specified icon. The first request for any given game and schema version will be slow, as every sheet with an icon field is scanned in full.",
+		)
+		.response_with::<200, Json<IconResponse>, _>(|response| {
+			response.example(IconResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				results: Page::complete(vec![IconResult {
+					sheet: "Item".into(),
+					row_id: 4,
+					subrow_id: 0,
+					field: "Icon".into(),
+				}]),
+				meta: None,
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn icon(
+	Path(path): Path<IconPath>,
+	context: ResolvedContext,
+	Query(query): Query<IconQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+	Extension(cache): Extension<IconCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// Building icon uses scans every sheet with an icon field - run it on the
+	// blocking pool.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let (icon_uses, cache_status) = cancellation.resolve(reader.run(|| {
+		cache.get_or_try_insert_with_status((context.version, context.schema.clone()), || {
+			Ok(read::icon_uses(
+				&excel,
+				schema.as_ref(),
+				data.default_language(),
+				cancellation.token(),
+			)?)
+		})
+	}))?;
+
+	let uses = icon_uses
+		.get(&path.icon)
+		.map(Vec::as_slice)
+		.unwrap_or_default();
+
+	let limits = config.limit.get();
+	let limit = query.limit.unwrap_or(limits.default).min(limits.max);
+
+	let meta = match query.meta.unwrap_or(false) {
+		true => Some(Meta::new(&context).with_cache(cache_status)),
+		false => None,
+	};
+
+	let offset = query.cursor.or(query.offset).unwrap_or(0);
+	let response = IconResponse {
+		schema: context.schema,
+		results: Page::offset(uses.iter().map(IconResult::from), uses.len(), offset, limit),
+		meta,
+	};
+
+	Ok(Json(response))
+}
+
+/// Icons referenced by sheet data that are present in the game, per game and
+/// schema version.
+type IconManifestCache = BuildCache<(VersionKey, schema::CanonicalSpecifier), Vec<asset::IconInfo>>;
+
+/// Query parameters accepted by the icon manifest endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct IconManifestQuery {
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+}
+
+/// Response structure for the icon manifest endpoint.
+#[derive(Serialize, JsonSchema)]
+struct IconManifestResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Icons present in the game data, ordered by ID.
+	#[serde(flatten)]
+	icons: Page<IconManifestResult>,
+
+	/// Details of how the response was resolved. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<Meta>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct IconManifestResult {
+	/// ID of the icon.
+	id: u32,
+
+	/// Game path of the icon's texture, suitable for use with the asset endpoint.
+	path: String,
+
+	/// Width of the icon in pixels, at standard resolution.
+	width: u32,
+
+	/// Height of the icon in pixels, at standard resolution.
+	height: u32,
+
+	/// Whether a high resolution variant of the icon is available. High resolution variants are double the standard dimensions.
+	hires: bool,
+}
+
+impl From<&asset::IconInfo> for IconManifestResult {
+	fn from(icon: &asset::IconInfo) -> Self {
+		Self {
+			id: icon.id,
+			path: asset::icon_paths(icon.id).0,
+			width: icon.width,
+			height: icon.height,
+			hires: icon.hires,
+		}
+	}
+}
+
+fn icon_manifest_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("list icons")
+		.description(
+			"List every icon referenced by a sheet field that is present in the game data, alongside its dimensions and high resolution availability. The first request for any given game and schema version will be very slow, as every sheet with an icon field is scanned in full, and every referenced icon is read.",
+		)
+		.response_with::<200, Json<IconManifestResponse>, _>(|response| {
+			response.example(IconManifestResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				icons: Page::complete(vec![IconManifestResult {
+					id: 51474,
+					path: "ui/icon/051000/051474.tex".into(),
+					width: 40,
+					height: 40,
+					hires: true,
+				}]),
+				meta: None,
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn icon_manifest(
+	context: ResolvedContext,
+	Query(query): Query<IconManifestQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	State(asset): State<service::Asset>,
+	Extension(uses_cache): Extension<IconCache>,
+	Extension(cache): Extension<IconManifestCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// See `icon` - the manifest additionally reads every referenced icon.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let cache_key = (context.version, context.schema.clone());
+	let (icons, cache_status) = cancellation.resolve(reader.run(|| {
+		cache.get_or_try_insert_with_status(cache_key.clone(), || {
+			let icon_uses = uses_cache.get_or_try_insert(cache_key, || {
+				Ok(read::icon_uses(
+					&excel,
+					schema.as_ref(),
+					data.default_language(),
+					cancellation.token(),
+				)?)
+			})?;
+
+			Ok(asset.icon_manifest(context.version, icon_uses.keys().copied())?)
+		})
+	}))?;
+
+	let meta = match query.meta.unwrap_or(false) {
+		true => Some(Meta::new(&context).with_cache(cache_status)),
+		false => None,
+	};
+
+	let response = IconManifestResponse {
+		schema: context.schema,
+		icons: Page::complete(icons.iter().map(IconManifestResult::from).collect()),
+		meta,
+	};
+
+	Ok(Json(response))
+}
+
+/// Dialogue text, per game and schema version, and language.
+type DialogueCache =
+	BuildCache<(VersionKey, schema::CanonicalSpecifier, excel::Language), read::Dialogue>;
+
+/// Query parameters accepted by the dialogue search endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct DialogueQuery {
+	/// Text to search for. Matches are case-insensitive, and must contain every whitespace-separated term.
+	text: Option<String>,
+
+	/// Limit results to the quest or cutscene with the specified ID, i.e. `ClsHrv001_00003`.
+	quest: Option<String>,
+
+	/// Limit results to lines spoken by the specified speaker, as named in the script, i.e. `YSHTOLA`.
+	speaker: Option<String>,
+
+	/// Limit results to quests from the specified expansion, by `ExVersion` row ID.
+	expansion: Option<u32>,
+
+	/// Maximum number of lines to return.
+	limit: Option<usize>,
+
+	/// Number of lines to skip.
+	offset: Option<usize>,
+
+	/// Cursor returned as `next_cursor` by a previous request. Takes precedence over `offset`.
+	cursor: Option<usize>,
+
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+}
+
+/// Response structure for the dialogue search endpoint.
+#[derive(Serialize, JsonSchema)]
+struct DialogueResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Lines of dialogue matching the query.
+	#[serde(flatten)]
+	results: Page<DialogueResult>,
+
+	/// Details of how the response was resolved. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<Meta>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct DialogueResult {
+	/// Dialogue sheet containing the line.
+	sheet: String,
+
+	/// ID of the row holding the line.
+	row_id: u32,
+
+	/// Script key of the line.
+	key: String,
+
+	/// ID of the quest or cutscene the line belongs to.
+	quest: String,
+
+	/// `ExVersion` row ID of the expansion the quest belongs to, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	expansion: Option<u32>,
+
+	/// Speaker of the line, as named in the script, if any.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	speaker: Option<String>,
+
+	/// Text of the line.
+	text: String,
+}
+
+impl From<&read::DialogueLine> for DialogueResult {
+	fn from(line: &read::DialogueLine) -> Self {
+		Self {
+			sheet: line.sheet.clone(),
+			row_id: line.row_id,
+			key: line.key.clone(),
+			quest: line.quest.clone(),
+			expansion: line.expansion,
+			speaker: line.speaker.clone(),
+			text: line.text.clone(),
+		}
+	}
+}
+
+fn dialogue_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("search dialogue")
+		.description(
+			"Search the text of quest and cutscene dialogue, with the speaker and quest of each line. The first request for any given game and schema version and language will be slow, as every dialogue sheet is read in full.",
+		)
+		.response_with::<200, Json<DialogueResponse>, _>(|response| {
+			response.example(DialogueResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				results: Page::complete(vec![DialogueResult {
+					sheet: "quest/000/ClsHrv001_00003".into(),
+					row_id: 10,
+					key: "TEXT_CLSHRV001_00003_YSHTOLA_000_10".into(),
+					quest: "ClsHrv001_00003".into(),
+					expansion: Some(0),
+					speaker: Some("YSHTOLA".into()),
+					text: "Example dialogue.".into(),
+				}]),
+				meta: None,
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn dialogue(
+	context: ResolvedContext,
+	Query(query): Query<DialogueQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+	Extension(cache): Extension<DialogueCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// Building dialogue reads a lot of data - run it on the blocking pool.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let language = context.language;
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let (dialogue, cache_status) = cancellation.resolve(reader.run(|| {
+		cache.get_or_try_insert_with_status(
+			(context.version, context.schema.clone(), language),
+			|| {
+				Ok(read::dialogue(
+					&excel,
+					schema.as_ref(),
+					language,
+					cancellation.token(),
+				)?)
+			},
+		)
+	}))?;
+
+	let search = read::DialogueQuery {
+		text: query.text,
+		quest: query.quest,
+		speaker: query.speaker,
+		expansion: query.expansion,
+	};
+
+	let limits = config.limit.get();
+	let limit = query.limit.unwrap_or(limits.default).min(limits.max);
+
+	let meta = match query.meta.unwrap_or(false) {
+		true => Some(Meta::new(&context).with_cache(cache_status)),
+		false => None,
+	};
+
+	// Matching scans every line of dialogue - do it once, on the blocking pool,
+	// and page over the results.
+	let matches = reader.run(|| dialogue.search(&search).collect::<Vec<_>>());
+	let total = matches.len();
+
+	let offset = query.cursor.or(query.offset).unwrap_or(0);
+	let response = DialogueResponse {
+		schema: context.schema,
+		results: Page::offset(
+			matches.into_iter().map(DialogueResult::from),
+			total,
+			offset,
+			limit,
+		),
+		meta,
+	};
+
+	Ok(Json(response))
+}
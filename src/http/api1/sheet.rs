@@ -1,77 +1,313 @@
-use std::{collections::HashMap, num::ParseIntError, str::FromStr};
+use std::{
+	collections::HashMap,
+	fmt,
+	hash::{Hash, Hasher},
+	num::ParseIntError,
+	str::FromStr,
+	sync::Arc,
+	time::Duration,
+};
 
 use aide::{
 	axum::{routing::get_with, ApiRouter, IntoApiResponse},
 	transform::TransformOperation,
+	NoApi,
+};
+use axum::{
+	debug_handler,
+	extract::State,
+	http::header,
+	response::{IntoResponse, Response},
+	Extension, Json,
+};
+use axum_extra::{
+	headers::{CacheControl, IfModifiedSince},
+	TypedHeader,
 };
-use axum::{debug_handler, extract::State, Extension, Json};
 use either::Either;
 use ironworks::{excel, file::exh};
+use itertools::Itertools;
+use maud::html;
+use mini_moka::sync as moka;
+use rand::{rngs::StdRng, Rng, SeedableRng};
 use schemars::{
 	gen::SchemaGenerator,
 	schema::{InstanceType, Schema, SchemaObject, StringValidation},
 	JsonSchema,
 };
+use seahash::SeaHasher;
 use serde::{de, Deserialize, Deserializer, Serialize};
 
 use crate::{
-	data::LanguageString,
-	http::service,
+	data::{self, LanguageString},
+	http::{page::Page, service},
 	read, schema,
-	utility::{anyhow::Anyhow, jsonschema::impl_jsonschema},
+	utility::{
+		anyhow::Anyhow, jsonschema::impl_jsonschema, reloadable::Reloadable, warnings::Warnings,
+	},
+	version::VersionKey,
 };
 
 use super::{
+	browse::{Browse, Browser},
+	cache::{BuildCache, CacheStatus},
 	error::{Error, Result},
-	extract::{Path, Query, VersionQuery},
-	filter::FilterString,
-	value::ValueString,
+	extract::{Path, Query, ResolvedContext, VersionQuery},
+	filter::{FieldPath, FilterString},
+	meta::{hash_string, Meta},
+	modified::Modified,
+	timeout::Cancellation,
+	types,
+	value::{ValueFormat, ValueString},
 };
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
-	limit: LimitConfig,
+	limit: Reloadable<LimitConfig>,
 
 	filter: HashMap<String, FilterConfig>,
+
+	#[serde(default)]
+	transform: HashMap<String, read::Transforms>,
+
+	#[serde(default)]
+	sentinel: HashMap<String, read::Sentinels>,
+
+	/// Fields of a reference's target included when references are previewed,
+	/// typically those holding its display name. Keyed by schema source.
+	#[serde(default)]
+	preview: HashMap<String, Vec<String>>,
+
+	/// Named response shapes, requested with the `profile` parameter.
+	#[serde(default)]
+	profile: HashMap<String, ProfileConfig>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Config {
+	pub fn reload(&self, config: Config) {
+		if self.limit.set(config.limit.get()) {
+			tracing::info!(limit = ?self.limit, "sheet limits changed");
+		}
+	}
+
+	fn reference_mode(&self, query: Option<ReferenceQuery>, source: &str) -> read::ReferenceMode {
+		match query.unwrap_or_default() {
+			ReferenceQuery::Full => read::ReferenceMode::Full,
+			ReferenceQuery::Preview => read::ReferenceMode::Preview(
+				self.preview
+					.get(source)
+					.map(Vec::as_slice)
+					.unwrap_or_default(),
+			),
+			ReferenceQuery::Id => read::ReferenceMode::Id,
+		}
+	}
+
+	fn profile(&self, name: Option<&str>) -> Result<Option<&ProfileConfig>> {
+		name.map(|name| {
+			self.profile
+				.get(name)
+				.ok_or_else(|| Error::Invalid(format!("unknown profile {name:?}")))
+		})
+		.transpose()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
 struct LimitConfig {
 	default: usize,
 	max: usize,
 	depth: u8,
 }
 
+/// Representation of references that are not selected within by a filter.
+#[derive(Debug, Clone, Copy, Default, Hash, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ReferenceQuery {
+	#[default]
+	Full,
+	Preview,
+	Id,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct FilterConfig {
 	list: Option<FilterString>,
 	entry: Option<FilterString>,
 }
 
+/// Preset shape for responses, replacing the parameters that would otherwise
+/// shape them. As every request for a profile reads the same fields, rows read
+/// with a profile are cached between requests.
+#[derive(Debug, Clone, Deserialize)]
+struct ProfileConfig {
+	/// Fields to read. Defaults to the configured filter for the endpoint.
+	fields: Option<FilterString>,
+
+	/// Language to read. Defaults to the requested language.
+	language: Option<LanguageString>,
+
+	/// Representation of references not selected within by `fields`.
+	reference: Option<ReferenceQuery>,
+
+	/// Whether to include interpretations of fields with configured transforms.
+	#[serde(default)]
+	interpret: bool,
+
+	/// Duration, in seconds, that clients and intermediate caches may reuse
+	/// responses for.
+	max_age: Option<u64>,
+}
+
+impl ProfileConfig {
+	fn cache_control(&self) -> Option<TypedHeader<CacheControl>> {
+		self.max_age.map(|max_age| {
+			TypedHeader(
+				CacheControl::new()
+					.with_public()
+					.with_max_age(Duration::from_secs(max_age)),
+			)
+		})
+	}
+}
+
+/// Parameters shaping the fields of a response, as requested or as set by a
+/// profile.
+struct Shape {
+	fields: Option<FilterString>,
+	language: excel::Language,
+	reference: Option<ReferenceQuery>,
+	interpret: bool,
+}
+
+impl Shape {
+	fn new(
+		profile: Option<&ProfileConfig>,
+		context: &ResolvedContext,
+		fields: Option<FilterString>,
+		reference: Option<ReferenceQuery>,
+		interpret: Option<bool>,
+	) -> Self {
+		match profile {
+			Some(profile) => Self {
+				fields: profile.fields.clone(),
+				language: profile
+					.language
+					.map_or(context.language, excel::Language::from),
+				reference: profile.reference,
+				interpret: profile.interpret,
+			},
+			None => Self {
+				fields,
+				language: context.language,
+				reference,
+				interpret: interpret.unwrap_or(false),
+			},
+		}
+	}
+}
+
 pub fn router(config: Config) -> ApiRouter<service::State> {
 	ApiRouter::new()
 		.api_route("/", get_with(list, list_docs))
+		.api_route("/relations", get_with(relations, relations_docs))
 		.api_route("/:sheet", get_with(sheet, sheet_docs))
+		.api_route("/:sheet/typescript", get_with(typescript, typescript_docs))
+		.api_route("/:sheet/jsonschema", get_with(jsonschema, jsonschema_docs))
+		.api_route("/:sheet/stats", get_with(stats, stats_docs))
+		.api_route("/:sheet/random", get_with(random, random_docs))
 		.api_route("/:sheet/:row", get_with(row, row_docs))
+		.api_route(
+			"/:sheet/:row/references",
+			get_with(references, references_docs),
+		)
+		.api_route(
+			"/:sheet/:row/neighbors",
+			get_with(neighbors, neighbors_docs),
+		)
+		.api_route("/:sheet/:row/history", get_with(history, history_docs))
+		.api_route("/:sheet/:row/*field", get_with(row_field, row_field_docs))
 		// Using Extension so I don't need to worry about nested state destructuring.
 		.layer(Extension(config))
+		.layer(Extension(RelationCache::default()))
+		.layer(Extension(ReferenceCache::default()))
+		.layer(Extension(StatisticsCache::default()))
+		.layer(Extension(RowListCache::default()))
+		.layer(Extension(ProfileCache::new(PROFILE_CACHE_CAPACITY)))
 }
 
 fn list_docs(operation: TransformOperation) -> TransformOperation {
 	operation
 		.summary("list sheets")
-		.description("List known excel sheet names that can be read by the API.")
-		.response_with::<200, Json<Vec<&'static str>>, _>(|response| {
-			response.example(vec!["Action", "Item", "Status"])
+		.description(
+			"List known excel sheet names that can be read by the API. Path-like names containing `/` must be percent-encoded when used in a URL path. The list may be narrowed to sheets matching a name with `search`, in which case each match is listed with its languages and row count.",
+		)
+		.response_with::<200, Json<Page<ListEntry>>, _>(|response| {
+			response.example(Page::complete(vec![
+				ListEntry::Name("Action".into()),
+				ListEntry::Name("Item".into()),
+				ListEntry::Name("Status".into()),
+			]))
 		})
 }
 
+/// Query parameters accepted by the sheet list endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct ListQuery {
+	/// List each sheet as an object including the languages it is available in, rather than as a bare name.
+	languages: Option<bool>,
+
+	/// List each sheet as an object including a hash of its content, rather than as a bare name. Hashes change whenever a sheet's data changes between versions.
+	hashes: Option<bool>,
+
+	/// Limit the list to sheets with names matching this value, case insensitively. Names containing the value match, unless it contains `*` wildcards, in which case the whole name must match, i.e. `quest` matches `Quest` and `QuestReward`, while `Quest*Reward` matches only the latter. Matches are listed as objects including their languages and row count.
+	search: Option<String>,
+}
+
+/// Entry of the sheet list endpoint's response.
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+enum ListEntry {
+	/// Sheet name, when no additional details were requested.
+	Name(String),
+	/// Sheet with its requested details.
+	Sheet(ListSheetResult),
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ListSheetResult {
+	/// Name of the sheet.
+	name: String,
+
+	/// Languages the sheet contains data for. Sheets without language variants
+	/// list only `none`, and can be read with any language.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	languages: Option<Vec<String>>,
+
+	/// Hash of the sheet's content, across all languages.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	hash: Option<String>,
+
+	/// Number of rows in the sheet, excluding subrows.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	rows: Option<usize>,
+}
+
 #[debug_handler(state = service::State)]
 async fn list(
 	VersionQuery(version_key): VersionQuery,
+	Query(query): Query<ListQuery>,
+	NoApi(if_modified_since): NoApi<Option<TypedHeader<IfModifiedSince>>>,
 	State(data): State<service::Data>,
+	State(version_service): State<service::Version>,
 ) -> Result<impl IntoApiResponse> {
-	let excel = data.version(version_key)?.excel();
+	let modified = Modified::new(version_service.released(version_key));
+	if let Some(response) = modified.check(if_modified_since) {
+		return Ok(response.into_response());
+	}
+
+	let version = data.version(version_key)?;
+	let excel = version.excel();
 
 	let list = excel.list().anyhow()?;
 	let mut names = list
@@ -80,17 +316,325 @@ async fn list(
 		.collect::<Vec<_>>();
 	names.sort();
 
-	Ok(Json(names))
+	// Searches list metadata alongside matches, to help tell related sheets apart.
+	let searching = query.search.is_some();
+	if let Some(search) = &query.search {
+		let pattern = search.to_lowercase();
+		names.retain(|name| sheet_name_matches(&pattern, name));
+	}
+
+	let languages = query.languages.unwrap_or(false) || searching;
+	let hashes = query.hashes.unwrap_or(false);
+
+	let entries = match languages || hashes {
+		false => names.into_iter().map(ListEntry::Name).collect(),
+		true => names
+			.into_iter()
+			.map(|name| {
+				ListEntry::Sheet(ListSheetResult {
+					languages: languages.then(|| {
+						version
+							.languages(&name)
+							.unwrap_or_default()
+							.iter()
+							.map(|language| LanguageString::from(*language).to_string())
+							.collect()
+					}),
+					hash: hashes
+						.then(|| version.sheet_hash(&name).map(hash_string))
+						.flatten(),
+					rows: searching
+						.then(|| version.rows(&name).map(|rows| rows.len()))
+						.flatten(),
+					name,
+				})
+			})
+			.collect(),
+	};
+
+	Ok((modified.header(), Json(Page::complete(entries))).into_response())
+}
+
+/// Whether a sheet name matches a lowercased search pattern.
+fn sheet_name_matches(pattern: &str, name: &str) -> bool {
+	let name = name.to_lowercase();
+	match pattern.contains('*') {
+		true => read::wildcard_match(pattern, &name),
+		false => name.contains(pattern),
+	}
+}
+
+/// Relations between sheets, per game and schema version.
+type RelationCache = BuildCache<(VersionKey, schema::CanonicalSpecifier), Vec<read::Relation>>;
+
+/// Rows referencing each sheet, per game and schema version.
+type ReferenceCache =
+	BuildCache<(VersionKey, schema::CanonicalSpecifier, String), read::ReverseReferences>;
+
+/// Rows present in each sheet, per game version.
+type RowListCache = BuildCache<(VersionKey, String), Vec<RowSpecifier>>;
+
+/// Maximum number of rows held by the profile cache.
+const PROFILE_CACHE_CAPACITY: u64 = 10_000;
+
+/// Rows read with a profile, after transforms are applied.
+type ProfileCache = moka::Cache<ProfileKey, Arc<ProfiledRow>>;
+
+/// Key for a row read with a profile. Alongside the profile name, rows are keyed
+/// by a hash of the shape they were read with - rows read with an outdated
+/// definition of the profile, or with an endpoint's differing default fields,
+/// are not reused.
+#[derive(PartialEq, Eq, Hash)]
+struct ProfileKey {
+	profile: String,
+	shape: u64,
+	version: VersionKey,
+	schema: schema::CanonicalSpecifier,
+	language: excel::Language,
+	sheet: String,
+	row_id: u32,
+	subrow_id: u16,
+}
+
+struct ProfiledRow {
+	fields: read::Value,
+	warnings: Vec<String>,
+}
+
+fn shape_hash(
+	fields: Option<&FilterString>,
+	reference: Option<ReferenceQuery>,
+	interpret: bool,
+) -> u64 {
+	let mut hasher = SeaHasher::new();
+	fields.hash(&mut hasher);
+	reference.hash(&mut hasher);
+	interpret.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// Column statistics for each sheet, per game and schema version, and language.
+type StatisticsCache = BuildCache<
+	(
+		VersionKey,
+		schema::CanonicalSpecifier,
+		String,
+		excel::Language,
+	),
+	read::Statistics,
+>;
+
+/// Query parameters accepted by the relations endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct RelationsQuery {
+	/// Limit relations to those involving the specified sheet.
+	sheet: Option<SheetName>,
+
+	/// When a sheet is specified, the direction of relations to include. Defaults to `both`.
+	direction: Option<RelationDirection>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum RelationDirection {
+	/// Relations targeting the sheet.
+	Incoming,
+	/// Relations originating from the sheet.
+	Outgoing,
+	/// Both incoming and outgoing relations.
+	Both,
+}
+
+/// Response structure for the relations endpoint.
+#[derive(Serialize, JsonSchema)]
+struct RelationsResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Directed edges between sheets, one per reference target in the schema.
+	relations: Vec<RelationResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RelationResult {
+	/// Sheet containing the referencing field.
+	source: String,
+
+	/// Path to the referencing field within the source sheet.
+	field: String,
+
+	/// Sheet targeted by the reference.
+	target: String,
+
+	/// Condition that must be met for the reference to target this sheet.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	condition: Option<RelationConditionResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RelationConditionResult {
+	/// Field within the source sheet that the condition is checked against.
+	field: String,
+
+	/// Value the field must hold for the condition to be met.
+	value: u32,
+}
+
+impl From<&read::Relation> for RelationResult {
+	fn from(relation: &read::Relation) -> Self {
+		Self {
+			source: relation.source.clone(),
+			field: relation.field.clone(),
+			target: relation.target.clone(),
+			condition: relation
+				.condition
+				.as_ref()
+				.map(|condition| RelationConditionResult {
+					field: condition.field.clone(),
+					value: condition.value,
+				}),
+		}
+	}
+}
+
+fn relations_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("list sheet relations")
+		.description(
+			"List the directed graph of references between sheets, as declared by the schema. Optionally scoped to the incoming and/or outgoing relations of a single sheet.",
+		)
+		.response_with::<200, Json<RelationsResponse>, _>(|response| {
+			response.example(RelationsResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				relations: vec![RelationResult {
+					source: "Item".into(),
+					field: "ItemUICategory".into(),
+					target: "ItemUICategory".into(),
+					condition: None,
+				}],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn relations(
+	context: ResolvedContext,
+	Query(query): Query<RelationsQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(cache): Extension<RelationCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// See `sheet` - wait for a read slot before building any request state.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let relations = cancellation.resolve(reader.run(|| {
+		cache.get_or_try_insert((context.version, context.schema.clone()), || {
+			let schema = schema_provider.schema(context.schema.clone())?;
+			Ok(read::relations(
+				&excel,
+				schema.as_ref(),
+				cancellation.token(),
+			)?)
+		})
+	}))?;
+
+	let direction = query.direction.unwrap_or(RelationDirection::Both);
+	let relations = relations
+		.iter()
+		.filter(|relation| {
+			let Some(sheet) = &query.sheet else {
+				return true;
+			};
+			let outgoing = relation.source == sheet.as_str();
+			let incoming = relation.target == sheet.as_str();
+			match direction {
+				RelationDirection::Incoming => incoming,
+				RelationDirection::Outgoing => outgoing,
+				RelationDirection::Both => incoming || outgoing,
+			}
+		})
+		.map(RelationResult::from)
+		.collect();
+
+	let response = RelationsResponse {
+		schema: context.schema,
+		relations,
+	};
+
+	Ok(Json(response))
 }
 
 /// Path variables accepted by the sheet endpoint.
 #[derive(Deserialize, JsonSchema)]
 struct SheetPath {
 	/// Name of the sheet to read.
-	sheet: String,
+	sheet: SheetName,
+}
+
+/// Name of an excel sheet. Some sheets, such as quest and cutscene text, have
+/// path-like names (i.e. `quest/000/ClsHrv001_00003`) - these must be
+/// percent-encoded to be used as a single URL path segment.
+#[derive(Debug, PartialEq)]
+struct SheetName(String);
+
+impl SheetName {
+	fn as_str(&self) -> &str {
+		&self.0
+	}
+}
+
+impl FromStr for SheetName {
+	type Err = String;
+
+	fn from_str(string: &str) -> Result<Self, Self::Err> {
+		// Path-like names are only ever relative, forward-slash delimited paths -
+		// reject anything that could be interpreted otherwise.
+		let valid = !string.is_empty()
+			&& string
+				.split('/')
+				.all(|segment| !matches!(segment, "" | "." | "..") && !segment.contains('\\'));
+
+		match valid {
+			true => Ok(Self(string.into())),
+			false => Err(format!("invalid sheet name \"{string}\"")),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for SheetName {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let raw = String::deserialize(deserializer)?;
+		raw.parse().map_err(de::Error::custom)
+	}
+}
+
+impl_jsonschema!(SheetName, sheetname_schema);
+fn sheetname_schema(_generator: &mut SchemaGenerator) -> Schema {
+	Schema::Object(SchemaObject {
+		instance_type: Some(InstanceType::String.into()),
+		string: Some(
+			StringValidation {
+				pattern: Some("^[^/\\\\]+(/[^/\\\\]+)*$".into()),
+				..Default::default()
+			}
+			.into(),
+		),
+		..Default::default()
+	})
 }
 
-#[derive(Debug, PartialEq, PartialOrd)]
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 struct RowSpecifier {
 	row_id: u32,
 	subrow_id: u16,
@@ -115,6 +659,15 @@ impl FromStr for RowSpecifier {
 	}
 }
 
+impl fmt::Display for RowSpecifier {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.subrow_id {
+			0 => write!(formatter, "{}", self.row_id),
+			subrow_id => write!(formatter, "{}:{subrow_id}", self.row_id),
+		}
+	}
+}
+
 impl<'de> Deserialize<'de> for RowSpecifier {
 	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
 	where
@@ -143,27 +696,41 @@ fn rowspecifier_schema(_generator: &mut SchemaGenerator) -> Schema {
 /// Query parameters accepted by the sheet endpoint.
 #[derive(Deserialize, JsonSchema)]
 struct SheetQuery {
-	// Data resolution
-	/// Language to use for data with no language otherwise specified in the fields filter.
-	language: Option<LanguageString>,
-
-	/// Schema that row data should be read with.
-	schema: Option<schema::Specifier>,
-
 	// Data fields to read for selected rows.
 	fields: Option<FilterString>,
 
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Include interpreted representations of fields with known transforms, such as bit flags, alongside their raw values.
+	interpret: Option<bool>,
+
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+
+	/// Include a hash of each row's content, across all languages.
+	hashes: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+
+	/// Named response profile configured by the server. Profiles set the `fields`, `reference`, and `interpret` parameters, and may set `language` - those parameters are ignored when a profile is requested.
+	profile: Option<String>,
+
 	// ID pagination/filtering
 	/// Rows to fetch from the sheet, as a comma-separated list. Behavior is undefined if both `rows` and `after` are provided.
 	#[serde(default, deserialize_with = "deserialize_rows")]
 	#[schemars(schema_with = "rows_schema")]
 	rows: Option<Vec<RowSpecifier>>,
 
-	/// Maximum number of rows to return. To paginate, provide the last returned row to the next request's `after` parameter.
+	/// Maximum number of rows to return. To paginate, provide the returned `next_cursor` to the next request's `cursor` parameter.
 	limit: Option<usize>,
 
 	/// Fetch rows after the specified row. Behavior is undefined if both `rows` and `after` are provided.
 	after: Option<RowSpecifier>,
+
+	/// Cursor returned as `next_cursor` by a previous request. Equivalent to `after`, which it takes precedence over.
+	cursor: Option<RowSpecifier>,
 }
 
 // TODO: this can probably be made as a general purpose "comma seperated" deserializer struct
@@ -208,8 +775,17 @@ struct SheetResponse {
 	#[schemars(with = "String")]
 	schema: schema::CanonicalSpecifier,
 
-	/// Array of rows retrieved by the query.
-	rows: Vec<RowResult>,
+	/// Rows retrieved by the query.
+	#[serde(flatten)]
+	rows: Page<RowResult>,
+
+	/// Non-fatal issues encountered while reading the requested data.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	warnings: Vec<String>,
+
+	/// Details of how the response was resolved. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<Meta>,
 }
 
 // TODO: ideally this structure is equivalent to the relation metadata from read:: - to the point honestly it probably _should_ be that. yet another thing to consider when reworking read::.
@@ -224,136 +800,369 @@ struct RowResult {
 
 	/// Field values for this row, according to the current schema.
 	fields: ValueString,
+
+	/// Hash of the row's content, across all languages. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	hash: Option<String>,
 }
 
 fn sheet_docs(operation: TransformOperation) -> TransformOperation {
 	operation
 		.summary("list rows in a sheet")
-		.description("Read information about one or more rows and their related data.")
+		.description("Read information about one or more rows and their related data. Responses carry a `Last-Modified` header reflecting the release of the game version, and requests with an `If-Modified-Since` header will receive `304 Not Modified` if the version's data has not changed since. Schema updates do not affect this time.")
 		.response_with::<200, Json<SheetResponse>, _>(|response| {
 			response.example(SheetResponse {
 				schema: schema::CanonicalSpecifier {
 					source: "source".into(),
 					version: "version".into(),
 				},
-				rows: vec![row_result_example(1), row_result_example(2)],
+				rows: Page {
+					items: vec![row_result_example(1), row_result_example(2)],
+					next_cursor: Some("2".into()),
+					total_estimate: Some(100),
+				},
+				warnings: vec![],
+				meta: None,
 			})
 		})
 }
 
+/// Reject requests for languages that a sheet does not contain. Sheets without
+/// language variants are readable with any language, falling back to their
+/// languageless data.
+fn check_languages(
+	version: &data::Version,
+	sheet: &str,
+	language: excel::Language,
+	filter: &read::Filter,
+) -> Result<()> {
+	let Some(available) = version.languages(sheet) else {
+		return Ok(());
+	};
+	if available.contains(&excel::Language::None) {
+		return Ok(());
+	}
+
+	let mut requested = filter.languages();
+	requested.insert(language);
+
+	let missing = requested
+		.into_iter()
+		.filter(|language| !available.contains(language))
+		.min_by_key(|language| u8::from(*language));
+
+	match missing {
+		None => Ok(()),
+		Some(language) => Err(Error::LanguageUnavailable {
+			sheet: sheet.into(),
+			language: LanguageString::from(language).to_string(),
+			available: available
+				.iter()
+				.map(|language| LanguageString::from(*language).to_string())
+				.collect(),
+		}),
+	}
+}
+
+/// Fail fast on rows known to be absent from a sheet, without reading its pages.
+fn check_row_exists(version: &data::Version, sheet: &str, row_id: u32) -> Result<()> {
+	match version.rows(sheet) {
+		Some(rows) if !rows.contains(row_id) => Err(Error::NotFound(format!(
+			"row {row_id} not found in sheet {sheet}"
+		))),
+		_ => Ok(()),
+	}
+}
+
 #[debug_handler(state = service::State)]
 async fn sheet(
 	Path(path): Path<SheetPath>,
-	VersionQuery(version_key): VersionQuery,
+	context: ResolvedContext,
 	Query(query): Query<SheetQuery>,
+	NoApi(if_modified_since): NoApi<Option<TypedHeader<IfModifiedSince>>>,
 	State(data): State<service::Data>,
 	State(schema_provider): State<service::Schema>,
+	State(version_service): State<service::Version>,
 	Extension(config): Extension<Config>,
+	Extension(profile_cache): Extension<ProfileCache>,
+	cancellation: Cancellation,
+	Browse(browser): Browse,
 ) -> Result<impl IntoApiResponse> {
-	// Resolve arguments with the services.
-	let excel = data.version(version_key)?.excel();
+	let modified = Modified::new(version_service.released(context.version));
+	if let Some(response) = modified.check(if_modified_since) {
+		return Ok(response.into_response());
+	}
 
-	let language = query
-		.language
-		.map(excel::Language::from)
-		.unwrap_or_else(|| data.default_language());
+	let profile = config.profile(query.profile.as_deref())?;
+	let shape = Shape::new(
+		profile,
+		&context,
+		query.fields,
+		query.reference,
+		query.interpret,
+	);
+	let context = ResolvedContext {
+		language: shape.language,
+		..context
+	};
 
-	// TODO: Consider extractor for this.
-	let schema_specifier = schema_provider.canonicalize(query.schema, version_key)?;
+	// Reads are synchronous, and run on the blocking pool. Wait for a slot up
+	// front, before building any state that can't be held across an await.
+	let reader = data.blocking().permit().await;
 
-	let filter = query
-		.fields
-		.or_else(|| {
-			config
-				.filter
-				.get(&schema_specifier.source)
-				.and_then(|filter_config| filter_config.list.clone())
-		})
-		.map(|filter_string| filter_string.to_filter(language))
-		.unwrap_or(Ok(read::Filter::All))?;
+	// Resolve arguments with the services.
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+	let language = context.language;
+
+	let fields = shape.fields.or_else(|| {
+		config
+			.filter
+			.get(&context.schema.source)
+			.and_then(|filter_config| filter_config.list.clone())
+	});
+	let profile_shape = shape_hash(fields.as_ref(), shape.reference, shape.interpret);
+	let (filter, filter_warnings) = fields
+		.map(|filter_string| filter_string.to_conditional_filter(language))
+		.unwrap_or_else(|| Warnings::new(read::Filter::All.into()))
+		.decompose();
 
-	let schema = schema_provider.schema(schema_specifier.clone())?;
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let transforms = match shape.interpret {
+		true => config.transform.get(&context.schema.source),
+		false => None,
+	};
+
+	let no_sentinels = read::Sentinels::new();
+	let sentinels = config
+		.sentinel
+		.get(&context.schema.source)
+		.unwrap_or(&no_sentinels);
+	let references = config.reference_mode(shape.reference, &context.schema.source);
 
 	// Get a reference to the sheet we'll be reading from.
 	// TODO: should this be in super::error as a default extract? minus the sheet specialised case, that is
-	let sheet = excel.sheet(&path.sheet).map_err(|error| match error {
-		ironworks::Error::NotFound(ironworks::ErrorValue::Sheet(..)) => {
-			Error::NotFound(error.to_string())
-		}
-		other => Error::Other(other.into()),
-	})?;
+	let sheet = excel
+		.sheet(path.sheet.as_str())
+		.map_err(|error| match error {
+			ironworks::Error::NotFound(ironworks::ErrorValue::Sheet(..)) => {
+				Error::NotFound(error.to_string())
+			}
+			other => Error::Other(other.into()),
+		})?;
+
+	check_languages(&version, path.sheet.as_str(), language, filter.filter())?;
+
+	let sheet_kind = sheet.kind().anyhow()?;
+	let row_set = version.rows(path.sheet.as_str());
+	let after = query.cursor.or(query.after);
+
+	// Row sets track row IDs only - for sheets with subrows, this undercounts.
+	let total_estimate = match &query.rows {
+		Some(specifiers) => Some(specifiers.len()),
+		None => row_set.map(|row_set| row_set.len()),
+	};
 
 	// Iterate over the sheet, building row results.
 	// TODO: look into changing the row builder in iw so this assignment isn't required - moving to an owned value would also possibly allow me to move this builder into the None case below.
 	let mut builder = sheet.with();
 	builder.language(language);
 
-	let sheet_iterator = match query.rows {
+	let sheet_iterator = match (query.rows, row_set) {
 		// One or more row specifiers were provided, iterate over those specifically.
-		Some(specifiers) => Either::Left(specifiers.into_iter()),
+		(Some(specifiers), _) => {
+			for specifier in &specifiers {
+				check_row_exists(&version, path.sheet.as_str(), specifier.row_id)?;
+			}
+			Either::Left(specifiers.into_iter())
+		}
+
+		// Sheets without subrows map one-to-one with their row IDs - walk the row
+		// set directly, skipping straight to the requested page.
+		(None, Some(row_set)) if !matches!(sheet_kind, exh::SheetKind::Subrows) => {
+			let start = after.map_or(0, |after| after.row_id);
+			Either::Right(Either::Left(row_set.iter_from(start).map(|row_id| {
+				RowSpecifier {
+					row_id,
+					subrow_id: 0,
+				}
+			})))
+		}
 
 		// None were provided, iterate over the sheet itself.
 		// TODO: Currently, read:: does _all_ the row fetching itself, which means that we're effectively iterating the sheet here _just_ to get the row IDs, then re-fetching in the read:: code. This... probably isn't too problematic, but worth considering how to approach more betterer. If read:: can be modified to take a row, then the Some() case above can be specailised to the read-row logic and this case can be simplified.
-		None => Either::Right(builder.iter().map(|row| RowSpecifier {
+		(None, _) => Either::Right(Either::Right(builder.iter().map(|row| RowSpecifier {
 			row_id: row.row_id(),
 			subrow_id: row.subrow_id(),
-		})),
+		}))),
 	};
 
 	// Paginate the results.
-	let limit = query
-		.limit
-		.unwrap_or(config.limit.default)
-		.min(config.limit.max);
+	let limits = config.limit.get();
+	let limit = query.limit.unwrap_or(limits.default).min(limits.max);
 	let sheet_iterator = sheet_iterator
 		// TODO: Improve this - introducing an explicit "after" method on a sheet iterator would allow skipping a lot of busywork. As-is, this is fetching every single row's data.
-		.skip_while(|specifier| Some(specifier) <= query.after.as_ref())
+		.skip_while(|specifier| Some(specifier) <= after.as_ref())
 		.take(limit);
 
 	// Build Results for the targeted rows.
-	let sheet_kind = sheet.kind().anyhow()?;
+	let format = ValueFormat {
+		flatten: query.flatten.unwrap_or(false),
+	};
+	let mut warnings = filter_warnings;
 	let sheet_iterator = sheet_iterator.map(|specifier| {
+		// Reading rows is synchronous - bail between rows if the request has timed out.
+		cancellation.check()?;
+
 		let row_id = specifier.row_id;
 		let subrow_id = specifier.subrow_id;
 
-		// TODO: This is pretty wasteful to call inside a loop, revisit actual read logic.
-		// TODO: at the moment, an unknown row specifier will cause excel to error with a NotFound (which is fine), however read:: then squashes that with anyhow, meaning the error gets hidden in a 500 ISE. revisit error handling in read:: while i'm at it ref. the above.
-		let fields = read::read(
-			&excel,
-			schema.as_ref(),
-			&path.sheet,
+		// See `row` - rows read with a profile are shared with other requests.
+		let profile_key = query.profile.as_ref().map(|profile| ProfileKey {
+			profile: profile.clone(),
+			shape: profile_shape,
+			version: context.version,
+			schema: context.schema.clone(),
+			language,
+			sheet: path.sheet.as_str().to_string(),
 			row_id,
 			subrow_id,
-			language,
-			&filter,
-			config.limit.depth,
-		)?;
+		});
+
+		let (fields, row_warnings) = match profile_key
+			.as_ref()
+			.and_then(|key| profile_cache.get(key))
+		{
+			Some(row) => (row.fields.clone(), row.warnings.clone()),
+			None => {
+				let row_filter = filter.resolve(
+					&excel,
+					schema.as_ref(),
+					path.sheet.as_str(),
+					row_id,
+					subrow_id,
+					language,
+				)?;
+
+				// TODO: This is pretty wasteful to call inside a loop, revisit actual read logic.
+				// TODO: at the moment, an unknown row specifier will cause excel to error with a NotFound (which is fine), however read:: then squashes that with anyhow, meaning the error gets hidden in a 500 ISE. revisit error handling in read:: while i'm at it ref. the above.
+				let (fields, row_warnings) = read::read(
+					&excel,
+					schema.as_ref(),
+					path.sheet.as_str(),
+					row_id,
+					subrow_id,
+					language,
+					&row_filter,
+					sentinels,
+					limits.depth,
+					references,
+				)?
+				.decompose();
+
+				let fields = match transforms {
+					Some(transforms) => read::transform(fields, path.sheet.as_str(), transforms),
+					None => fields,
+				};
+
+				if let Some(key) = profile_key {
+					let row = ProfiledRow {
+						fields: fields.clone(),
+						warnings: row_warnings.clone(),
+					};
+					profile_cache.insert(key, Arc::new(row));
+				}
+
+				(fields, row_warnings)
+			}
+		};
+		warnings.extend(row_warnings);
+
+		let result_subrow_id = match sheet_kind {
+			exh::SheetKind::Subrows => Some(subrow_id),
+			_ => None,
+		};
+
+		let hash = match query.hashes.unwrap_or(false) {
+			true => Some(hash_string(version.row_hash(
+				path.sheet.as_str(),
+				row_id,
+				result_subrow_id,
+			)?)),
+			false => None,
+		};
 
 		Ok(RowResult {
 			row_id,
-			subrow_id: match sheet_kind {
-				exh::SheetKind::Subrows => Some(subrow_id),
-				_ => None,
-			},
-			fields: ValueString(fields, language),
+			subrow_id: result_subrow_id,
+			fields: ValueString(fields, language, format),
+			hash,
 		})
 	});
 
-	let rows = sheet_iterator.collect::<Result<Vec<_>>>()?;
+	let rows = reader.run(|| sheet_iterator.collect::<Result<Vec<_>>>())?;
 
+	// A full page may be followed by further rows - continue after the last.
+	let next_cursor = match rows.len() == limit {
+		true => rows.last().map(|row| {
+			RowSpecifier {
+				row_id: row.row_id,
+				subrow_id: row.subrow_id.unwrap_or(0),
+			}
+			.to_string()
+		}),
+		false => None,
+	};
+
+	let meta = match query.meta.unwrap_or(false) {
+		true => Some(
+			Meta::new(&context)
+				.with_language_fallbacks(&excel, path.sheet.as_str(), language, filter.filter())?
+				.with_sheet_hash(version.sheet_hash(path.sheet.as_str())),
+		),
+		false => None,
+	};
+
+	let cache_control = profile.and_then(ProfileConfig::cache_control);
 	let response = SheetResponse {
-		schema: schema_specifier,
-		rows,
+		schema: context.schema,
+		rows: Page {
+			items: rows,
+			next_cursor,
+			total_estimate,
+		},
+		// Each row will typically raise the same warnings - only report them once.
+		warnings: warnings.into_iter().unique().collect(),
+		meta,
 	};
 
-	Ok(Json(response))
+	if let Some(browser) = browser {
+		let content = html! {
+			@for warning in &response.warnings {
+				p { "warning: " (warning) }
+			}
+			@for row in &response.rows.items {
+				h2 { (browser.row_link(path.sheet.as_str(), row.row_id, row.subrow_id)) }
+				(browser.value(&row.fields.0, language))
+			}
+		};
+		return Ok((
+			modified.header(),
+			cache_control,
+			browser.page(path.sheet.as_str(), content),
+		)
+			.into_response());
+	}
+
+	Ok((modified.header(), cache_control, Json(response)).into_response())
 }
 
 /// Path variables accepted by the row endpoint.
 #[derive(Deserialize, JsonSchema)]
 struct RowPath {
 	/// Name of the sheet to read.
-	sheet: String,
+	sheet: SheetName,
 	/// Row to read.
 	row: RowSpecifier,
 }
@@ -361,14 +1170,26 @@ struct RowPath {
 /// Query parameters accepted by the row endpoint.
 #[derive(Deserialize, JsonSchema)]
 struct RowQuery {
-	/// Language to use for data with no language otherwise specified in the fields filter.
-	language: Option<LanguageString>,
-
-	/// Schema that row data should be read with.
-	schema: Option<schema::Specifier>,
-
 	/// Data fields to read for selected rows.
 	fields: Option<FilterString>,
+
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Include interpreted representations of fields with known transforms, such as bit flags, alongside their raw values.
+	interpret: Option<bool>,
+
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+
+	/// Include a hash of the row's content, across all languages.
+	hashes: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+
+	/// Named response profile configured by the server. Profiles set the `fields`, `reference`, and `interpret` parameters, and may set `language` - those parameters are ignored when a profile is requested.
+	profile: Option<String>,
 }
 
 /// Response structure for the row endpoint.
@@ -380,13 +1201,21 @@ struct RowResponse {
 
 	#[serde(flatten)]
 	row: RowResult,
+
+	/// Non-fatal issues encountered while reading the requested data.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	warnings: Vec<String>,
+
+	/// Details of how the response was resolved. Only present if requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	meta: Option<Meta>,
 }
 
 fn row_docs(operation: TransformOperation) -> TransformOperation {
 	operation
 		.summary("read a sheet row")
 		.description(
-			"Read detailed, filterable information from a single sheet row and its related data.",
+			"Read detailed, filterable information from a single sheet row and its related data. Supports conditional requests with `If-Modified-Since`, as per the sheet endpoint.",
 		)
 		.response_with::<200, Json<RowResponse>, _>(|response| {
 			response.example(RowResponse {
@@ -395,6 +1224,8 @@ fn row_docs(operation: TransformOperation) -> TransformOperation {
 					version: "version".into(),
 				},
 				row: row_result_example(1),
+				warnings: vec![],
+				meta: None,
 			})
 		})
 }
@@ -412,70 +1243,1623 @@ fn row_result_example(row_id: u32) -> RowResult {
 				read::Value::Scalar(excel::Field::U32(14)),
 			)])),
 			excel::Language::English,
+			ValueFormat::default(),
 		),
+		hash: None,
 	}
 }
 
 #[debug_handler(state = service::State)]
 async fn row(
 	Path(path): Path<RowPath>,
-	VersionQuery(version_key): VersionQuery,
+	context: ResolvedContext,
 	Query(query): Query<RowQuery>,
+	NoApi(if_modified_since): NoApi<Option<TypedHeader<IfModifiedSince>>>,
 	State(data): State<service::Data>,
 	State(schema_provider): State<service::Schema>,
+	State(version_service): State<service::Version>,
 	Extension(config): Extension<Config>,
+	Extension(profile_cache): Extension<ProfileCache>,
+	Browse(browser): Browse,
 ) -> Result<impl IntoApiResponse> {
-	let excel = data.version(version_key)?.excel();
+	let services = RowServices {
+		data,
+		schema_provider,
+		version_service,
+		config,
+		profile_cache,
+	};
 
-	let language = query
-		.language
-		.map(excel::Language::from)
-		.unwrap_or_else(|| data.default_language());
+	read_row(services, path, context, query, if_modified_since, browser).await
+}
 
-	let schema_specifier = schema_provider.canonicalize(query.schema, version_key)?;
+/// Services used to read a single row, shared by every endpoint that responds
+/// with one.
+struct RowServices {
+	data: service::Data,
+	schema_provider: service::Schema,
+	version_service: service::Version,
+	config: Config,
+	profile_cache: ProfileCache,
+}
 
-	let filter = query
-		.fields
-		.or_else(|| {
-			config
-				.filter
-				.get(&schema_specifier.source)
-				.and_then(|filter_config| filter_config.entry.clone())
-		})
-		.map(|filter_string| filter_string.to_filter(language))
-		.unwrap_or(Ok(read::Filter::All))?;
+async fn read_row(
+	services: RowServices,
+	path: RowPath,
+	context: ResolvedContext,
+	query: RowQuery,
+	if_modified_since: Option<TypedHeader<IfModifiedSince>>,
+	browser: Option<Browser>,
+) -> Result<Response> {
+	let RowServices {
+		data,
+		schema_provider,
+		version_service,
+		config,
+		profile_cache,
+	} = services;
+
+	let modified = Modified::new(version_service.released(context.version));
+	if let Some(response) = modified.check(if_modified_since) {
+		return Ok(response.into_response());
+	}
+
+	let profile = config.profile(query.profile.as_deref())?;
+	let shape = Shape::new(
+		profile,
+		&context,
+		query.fields,
+		query.reference,
+		query.interpret,
+	);
+	let context = ResolvedContext {
+		language: shape.language,
+		..context
+	};
+
+	// See `sheet` - wait for a read slot before building any request state.
+	let reader = data.blocking().permit().await;
 
-	let schema = schema_provider.schema(schema_specifier.clone())?;
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+	let language = context.language;
+
+	let fields = shape.fields.or_else(|| {
+		config
+			.filter
+			.get(&context.schema.source)
+			.and_then(|filter_config| filter_config.entry.clone())
+	});
+	let profile_shape = shape_hash(fields.as_ref(), shape.reference, shape.interpret);
+	let (filter, filter_warnings) = fields
+		.map(|filter_string| filter_string.to_conditional_filter(language))
+		.unwrap_or_else(|| Warnings::new(read::Filter::All.into()))
+		.decompose();
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let transforms = match shape.interpret {
+		true => config.transform.get(&context.schema.source),
+		false => None,
+	};
+
+	let no_sentinels = read::Sentinels::new();
+	let sentinels = config
+		.sentinel
+		.get(&context.schema.source)
+		.unwrap_or(&no_sentinels);
+	let references = config.reference_mode(shape.reference, &context.schema.source);
 
 	let row_id = path.row.row_id;
 	let subrow_id = path.row.subrow_id;
 
-	let fields = read::read(
-		&excel,
-		schema.as_ref(),
-		&path.sheet,
+	check_languages(&version, path.sheet.as_str(), language, filter.filter())?;
+	check_row_exists(&version, path.sheet.as_str(), row_id)?;
+
+	// Rows read with a profile share their shape with every other request for
+	// the profile - reuse them where possible.
+	let profile_key = query.profile.map(|profile| ProfileKey {
+		profile,
+		shape: profile_shape,
+		version: context.version,
+		schema: context.schema.clone(),
+		language,
+		sheet: path.sheet.as_str().to_string(),
 		row_id,
 		subrow_id,
-		language,
-		&filter,
-		config.limit.depth,
-	)?;
+	});
+	let cached = profile_key.as_ref().and_then(|key| profile_cache.get(key));
+	let cache_status = profile_key.as_ref().map(|_| match cached.is_some() {
+		true => CacheStatus::Hit,
+		false => CacheStatus::Miss,
+	});
+
+	let (fields, mut warnings) = match cached {
+		Some(row) => (row.fields.clone(), row.warnings.clone()),
+		None => {
+			let depth = config.limit.get().depth;
+			let (fields, warnings) = reader
+				.run(|| {
+					let row_filter = filter.resolve(
+						&excel,
+						schema.as_ref(),
+						path.sheet.as_str(),
+						row_id,
+						subrow_id,
+						language,
+					)?;
+
+					read::read(
+						&excel,
+						schema.as_ref(),
+						path.sheet.as_str(),
+						row_id,
+						subrow_id,
+						language,
+						&row_filter,
+						sentinels,
+						depth,
+						references,
+					)
+				})?
+				.decompose();
+
+			let fields = match transforms {
+				Some(transforms) => read::transform(fields, path.sheet.as_str(), transforms),
+				None => fields,
+			};
+
+			if let Some(key) = profile_key {
+				let row = ProfiledRow {
+					fields: fields.clone(),
+					warnings: warnings.clone(),
+				};
+				profile_cache.insert(key, Arc::new(row));
+			}
+
+			(fields, warnings)
+		}
+	};
+	warnings.extend(filter_warnings);
 
 	// Check the kind of the sheet to determine if we should report a subrow id.
 	// TODO: this is theoretically wasteful, though IW will have cached it anyway.
-	let result_subrow_id = match excel.sheet(&path.sheet).anyhow()?.kind().anyhow()? {
+	let result_subrow_id = match excel.sheet(path.sheet.as_str()).anyhow()?.kind().anyhow()? {
 		exh::SheetKind::Subrows => Some(subrow_id),
 		_ => None,
 	};
 
+	let format = ValueFormat {
+		flatten: query.flatten.unwrap_or(false),
+	};
+
+	let meta = match query.meta.unwrap_or(false) {
+		true => {
+			let meta = Meta::new(&context)
+				.with_language_fallbacks(&excel, path.sheet.as_str(), language, filter.filter())?
+				.with_sheet_hash(version.sheet_hash(path.sheet.as_str()));
+			Some(match cache_status {
+				Some(status) => meta.with_cache(status),
+				None => meta,
+			})
+		}
+		false => None,
+	};
+
+	let hash = match query.hashes.unwrap_or(false) {
+		true => Some(hash_string(reader.run(|| {
+			version.row_hash(path.sheet.as_str(), row_id, result_subrow_id)
+		})?)),
+		false => None,
+	};
+
+	let cache_control = profile.and_then(ProfileConfig::cache_control);
 	let response = RowResponse {
-		schema: schema_specifier,
+		schema: context.schema,
 		row: RowResult {
 			row_id,
 			subrow_id: result_subrow_id,
-			fields: ValueString(fields, language),
+			fields: ValueString(fields, language, format),
+			hash,
+		},
+		warnings,
+		meta,
+	};
+
+	if let Some(browser) = browser {
+		let row = &response.row;
+		let content = html! {
+			@for warning in &response.warnings {
+				p { "warning: " (warning) }
+			}
+			(browser.value(&row.fields.0, language))
+		};
+		let title = match row.subrow_id {
+			Some(subrow_id) => format!("{}#{}:{subrow_id}", path.sheet.as_str(), row.row_id),
+			None => format!("{}#{}", path.sheet.as_str(), row.row_id),
+		};
+		return Ok((
+			modified.header(),
+			cache_control,
+			browser.page(&title, content),
+		)
+			.into_response());
+	}
+
+	Ok((modified.header(), cache_control, Json(response)).into_response())
+}
+
+/// Query parameters accepted by the random row endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct RandomQuery {
+	/// Data fields to read for the selected row.
+	fields: Option<FilterString>,
+
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Include interpreted representations of fields with known transforms, such as bit flags, alongside their raw values.
+	interpret: Option<bool>,
+
+	/// Include a `meta` block describing how the response was resolved, such as the version and schema revision used.
+	meta: Option<bool>,
+
+	/// Include a hash of the row's content, across all languages.
+	hashes: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+
+	/// Named response profile configured by the server. Profiles set the `fields`, `reference`, and `interpret` parameters, and may set `language` - those parameters are ignored when a profile is requested.
+	profile: Option<String>,
+
+	/// Seed for row selection. Requests with the same seed against the same game version will select the same row.
+	seed: Option<u64>,
+}
+
+fn random_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a random sheet row")
+		.description(
+			"Read a uniformly random row from a sheet. Accepts the same parameters as the row endpoint, alongside an optional seed for repeatable selection.",
+		)
+		.response_with::<200, Json<RowResponse>, _>(|response| {
+			response.example(RowResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				row: row_result_example(1),
+				warnings: vec![],
+				meta: None,
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn random(
+	Path(path): Path<SheetPath>,
+	context: ResolvedContext,
+	Query(query): Query<RandomQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	State(version_service): State<service::Version>,
+	Extension(config): Extension<Config>,
+	Extension(row_list_cache): Extension<RowListCache>,
+	Extension(profile_cache): Extension<ProfileCache>,
+	Browse(browser): Browse,
+) -> Result<impl IntoApiResponse> {
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	// Hold the read slot only while listing rows - reading the selected row
+	// acquires its own.
+	let reader = data.blocking().permit().await;
+	let rows = reader.run(|| {
+		row_list(
+			&excel,
+			path.sheet.as_str(),
+			context.version,
+			&row_list_cache,
+		)
+	})?;
+	drop(reader);
+
+	let mut rng = match query.seed {
+		Some(seed) => StdRng::seed_from_u64(seed),
+		None => StdRng::from_entropy(),
+	};
+
+	let row = match rows.len() {
+		0 => {
+			return Err(Error::NotFound(format!(
+				"sheet {} contains no rows",
+				path.sheet.as_str()
+			)))
+		}
+		length => rows[rng.gen_range(0..length)],
+	};
+
+	// Selection is complete - the remainder is identical to reading the row directly.
+	let seeded = query.seed.is_some();
+	let services = RowServices {
+		data,
+		schema_provider,
+		version_service,
+		config,
+		profile_cache,
+	};
+	let mut response = read_row(
+		services,
+		RowPath {
+			sheet: path.sheet,
+			row,
+		},
+		context,
+		RowQuery {
+			fields: query.fields,
+			flatten: query.flatten,
+			interpret: query.interpret,
+			meta: query.meta,
+			hashes: query.hashes,
+			reference: query.reference,
+			profile: query.profile,
 		},
+		// Unseeded selections differ per request, and cannot be revalidated.
+		None,
+		browser,
+	)
+	.await?;
+
+	if !seeded {
+		response.headers_mut().remove(header::LAST_MODIFIED);
+	}
+
+	Ok(response)
+}
+
+/// List every row in a sheet, in order, caching the result.
+fn row_list(
+	excel: &excel::Excel,
+	sheet_name: &str,
+	version_key: VersionKey,
+	cache: &RowListCache,
+) -> Result<Arc<Vec<RowSpecifier>>> {
+	cache.get_or_try_insert((version_key, sheet_name.to_string()), || {
+		let sheet = excel.sheet(sheet_name).map_err(|error| match error {
+			ironworks::Error::NotFound(ironworks::ErrorValue::Sheet(..)) => {
+				Error::NotFound(error.to_string())
+			}
+			other => Error::Other(other.into()),
+		})?;
+
+		let rows = sheet
+			.with()
+			.iter()
+			.map(|row| RowSpecifier {
+				row_id: row.row_id(),
+				subrow_id: row.subrow_id(),
+			})
+			.collect();
+
+		Ok(rows)
+	})
+}
+
+/// Path variables accepted by the row field endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct RowFieldPath {
+	/// Name of the sheet to read.
+	sheet: SheetName,
+	/// Row to read.
+	row: RowSpecifier,
+	/// Path to the field to read, i.e. `BaseParam/0/Value`.
+	field: FieldPath,
+}
+
+/// Query parameters accepted by the row field endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct RowFieldQuery {
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Include interpreted representations of fields with known transforms, such as bit flags, alongside their raw values.
+	interpret: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+}
+
+fn row_field_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a sheet row field")
+		.description(
+			"Read the value of a single field within a sheet row, addressed by the trailing path segments. Numeric segments select an array index, and populated references may be stepped through into the referenced row. Fields named `references` at the root of a row cannot be addressed with this endpoint.",
+		)
+		.response_with::<200, Json<ValueString>, _>(|response| {
+			response.example(ValueString(
+				read::Value::Scalar(excel::Field::U32(14)),
+				excel::Language::English,
+				ValueFormat::default(),
+			))
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn row_field(
+	Path(path): Path<RowFieldPath>,
+	context: ResolvedContext,
+	Query(query): Query<RowFieldQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+) -> Result<impl IntoApiResponse> {
+	// See `sheet` - wait for a read slot before building any request state.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let language = context.language;
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let transforms = match query.interpret.unwrap_or(false) {
+		true => config.transform.get(&context.schema.source),
+		false => None,
+	};
+
+	let no_sentinels = read::Sentinels::new();
+	let sentinels = config
+		.sentinel
+		.get(&context.schema.source)
+		.unwrap_or(&no_sentinels);
+	let references = config.reference_mode(query.reference, &context.schema.source);
+
+	let filter = path.field.to_filter(language);
+
+	check_languages(&version, path.sheet.as_str(), language, &filter)?;
+	check_row_exists(&version, path.sheet.as_str(), path.row.row_id)?;
+
+	let depth = config.limit.get().depth;
+	let (fields, _warnings) = reader
+		.run(|| {
+			read::read(
+				&excel,
+				schema.as_ref(),
+				path.sheet.as_str(),
+				path.row.row_id,
+				path.row.subrow_id,
+				language,
+				&filter,
+				sentinels,
+				depth,
+				references,
+			)
+		})?
+		.decompose();
+
+	let fields = match transforms {
+		Some(transforms) => read::transform(fields, path.sheet.as_str(), transforms),
+		None => fields,
+	};
+
+	let value = path.field.select(fields, language).ok_or_else(|| {
+		Error::NotFound(format!(
+			"field not found in sheet {} row {}",
+			path.sheet.as_str(),
+			path.row.row_id
+		))
+	})?;
+
+	let format = ValueFormat {
+		flatten: query.flatten.unwrap_or(false),
+	};
+
+	Ok(Json(ValueString(value, language, format)))
+}
+
+/// Query parameters accepted by the references endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct ReferencesQuery {
+	/// Maximum number of referencing rows to return.
+	limit: Option<usize>,
+
+	/// Number of referencing rows to skip.
+	offset: Option<usize>,
+
+	/// Cursor returned as `next_cursor` by a previous request. Takes precedence over `offset`.
+	cursor: Option<usize>,
+}
+
+/// Response structure for the references endpoint.
+#[derive(Serialize, JsonSchema)]
+struct ReferencesResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Rows referencing the requested row.
+	#[serde(flatten)]
+	references: Page<ReferrerResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ReferrerResult {
+	/// Sheet containing the referencing row.
+	sheet: String,
+
+	/// ID of the referencing row.
+	row_id: u32,
+
+	/// Subrow ID of the referencing row.
+	subrow_id: u16,
+
+	/// Path to the field holding the reference.
+	field: String,
+}
+
+impl From<&read::Referrer> for ReferrerResult {
+	fn from(referrer: &read::Referrer) -> Self {
+		Self {
+			sheet: referrer.sheet.clone(),
+			row_id: referrer.row_id,
+			subrow_id: referrer.subrow_id,
+			field: referrer.field.clone(),
+		}
+	}
+}
+
+fn references_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("list rows referencing a row")
+		.description(
+			"List all rows, across all sheets, with a field referencing the specified row. The first request for any given sheet will be slow, as referencing sheets are scanned in full.",
+		)
+		.response_with::<200, Json<ReferencesResponse>, _>(|response| {
+			response.example(ReferencesResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				references: Page::complete(vec![ReferrerResult {
+					sheet: "Recipe".into(),
+					row_id: 1,
+					subrow_id: 0,
+					field: "ItemResult".into(),
+				}]),
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn references(
+	Path(path): Path<RowPath>,
+	context: ResolvedContext,
+	Query(query): Query<ReferencesQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+	Extension(relation_cache): Extension<RelationCache>,
+	Extension(reference_cache): Extension<ReferenceCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// Building references scans entire sheets - see `sheet`.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let references = cancellation.resolve(reader.run(|| {
+		// Ensure the sheet exists before doing any heavy lifting.
+		excel
+			.sheet(path.sheet.as_str())
+			.map_err(|error| match error {
+				ironworks::Error::NotFound(ironworks::ErrorValue::Sheet(..)) => {
+					Error::NotFound(error.to_string())
+				}
+				other => Error::Other(other.into()),
+			})?;
+
+		let relations =
+			relation_cache.get_or_try_insert((context.version, context.schema.clone()), || {
+				Ok(read::relations(
+					&excel,
+					schema.as_ref(),
+					cancellation.token(),
+				)?)
+			})?;
+
+		reference_cache.get_or_try_insert(
+			(
+				context.version,
+				context.schema.clone(),
+				path.sheet.as_str().to_string(),
+			),
+			|| {
+				Ok(read::reverse_references(
+					&excel,
+					schema.as_ref(),
+					&relations,
+					path.sheet.as_str(),
+					data.default_language(),
+					cancellation.token(),
+				)?)
+			},
+		)
+	}))?;
+
+	let referrers = references
+		.get(&path.row.row_id)
+		.map(Vec::as_slice)
+		.unwrap_or_default();
+
+	let limits = config.limit.get();
+	let limit = query.limit.unwrap_or(limits.default).min(limits.max);
+
+	let offset = query.cursor.or(query.offset).unwrap_or(0);
+	let response = ReferencesResponse {
+		schema: context.schema,
+		references: Page::offset(
+			referrers.iter().map(ReferrerResult::from),
+			referrers.len(),
+			offset,
+			limit,
+		),
 	};
 
 	Ok(Json(response))
 }
+
+/// Number of neighbouring rows returned in each direction when not specified.
+const DEFAULT_NEIGHBORS: usize = 5;
+
+/// Query parameters accepted by the neighbors endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct NeighborsQuery {
+	/// Number of rows to return in each direction. Defaults to 5.
+	count: Option<usize>,
+
+	/// Data fields to read for each neighbouring row. If omitted, only row IDs are returned.
+	fields: Option<FilterString>,
+
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+}
+
+/// Response structure for the neighbors endpoint.
+#[derive(Serialize, JsonSchema)]
+struct NeighborsResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Rows preceding the requested row, nearest last.
+	previous: Vec<NeighborResult>,
+
+	/// Rows following the requested row, nearest first.
+	next: Vec<NeighborResult>,
+
+	/// Non-fatal issues encountered while reading the requested data.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	warnings: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct NeighborResult {
+	/// ID of this row.
+	row_id: u32,
+
+	/// Subrow ID of this row, when relevant.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	subrow_id: Option<u16>,
+
+	/// Field values for this row. Only present if fields were requested.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	fields: Option<ValueString>,
+}
+
+fn neighbors_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("list neighbouring rows")
+		.description(
+			"List the rows immediately preceding and following a row in a sheet, skipping any gaps in row IDs. The requested row need not exist - its position is determined by ID. The first request for any given sheet will be slower, as the sheet's rows are listed in full.",
+		)
+		.response_with::<200, Json<NeighborsResponse>, _>(|response| {
+			response.example(NeighborsResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				previous: vec![NeighborResult {
+					row_id: 1,
+					subrow_id: None,
+					fields: None,
+				}],
+				next: vec![NeighborResult {
+					row_id: 5,
+					subrow_id: None,
+					fields: None,
+				}],
+				warnings: vec![],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn neighbors(
+	Path(path): Path<RowPath>,
+	context: ResolvedContext,
+	Query(query): Query<NeighborsQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+	Extension(row_list_cache): Extension<RowListCache>,
+) -> Result<impl IntoApiResponse> {
+	// See `sheet` - wait for a read slot before building any request state.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+	let language = context.language;
+
+	let (filter, filter_warnings) = match query.fields {
+		Some(fields) => {
+			let (filter, warnings) = fields.to_conditional_filter(language).decompose();
+			(Some(filter), warnings)
+		}
+		None => (None, vec![]),
+	};
+
+	if let Some(filter) = &filter {
+		check_languages(&version, path.sheet.as_str(), language, filter.filter())?;
+	}
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let no_sentinels = read::Sentinels::new();
+	let sentinels = config
+		.sentinel
+		.get(&context.schema.source)
+		.unwrap_or(&no_sentinels);
+	let references = config.reference_mode(query.reference, &context.schema.source);
+
+	let limits = config.limit.get();
+	let count = query.count.unwrap_or(DEFAULT_NEIGHBORS).min(limits.max);
+	let format = ValueFormat {
+		flatten: query.flatten.unwrap_or(false),
+	};
+
+	let (previous, next, warnings) = reader.run(|| {
+		let rows = row_list(
+			&excel,
+			path.sheet.as_str(),
+			context.version,
+			&row_list_cache,
+		)?;
+		let (previous, next) = adjacent_rows(&rows, path.row, count);
+
+		let subrows = matches!(
+			excel.sheet(path.sheet.as_str()).anyhow()?.kind().anyhow()?,
+			exh::SheetKind::Subrows
+		);
+
+		let mut warnings = filter_warnings;
+		let mut build = |specifier: &RowSpecifier| -> Result<NeighborResult> {
+			let fields = match &filter {
+				None => None,
+				Some(filter) => {
+					let row_filter = filter.resolve(
+						&excel,
+						schema.as_ref(),
+						path.sheet.as_str(),
+						specifier.row_id,
+						specifier.subrow_id,
+						language,
+					)?;
+
+					let (fields, row_warnings) = read::read(
+						&excel,
+						schema.as_ref(),
+						path.sheet.as_str(),
+						specifier.row_id,
+						specifier.subrow_id,
+						language,
+						&row_filter,
+						sentinels,
+						limits.depth,
+						references,
+					)?
+					.decompose();
+					warnings.extend(row_warnings);
+
+					Some(ValueString(fields, language, format))
+				}
+			};
+
+			Ok(NeighborResult {
+				row_id: specifier.row_id,
+				subrow_id: subrows.then_some(specifier.subrow_id),
+				fields,
+			})
+		};
+
+		let previous = previous
+			.iter()
+			.map(&mut build)
+			.collect::<Result<Vec<_>>>()?;
+		let next = next.iter().map(&mut build).collect::<Result<Vec<_>>>()?;
+
+		Ok((previous, next, warnings))
+	})?;
+
+	let response = NeighborsResponse {
+		schema: context.schema,
+		previous,
+		next,
+		// Each row will typically raise the same warnings - only report them once.
+		warnings: warnings.into_iter().unique().collect(),
+	};
+
+	Ok(Json(response))
+}
+
+/// Query parameters accepted by the row history endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct HistoryQuery {
+	/// Data fields to read from the row in each version.
+	fields: Option<FilterString>,
+
+	/// Language to use for data with no language otherwise specified.
+	language: Option<LanguageString>,
+
+	/// Schema that data should be read with. Resolved separately against each version, such that older versions are read with a schema matching their data.
+	schema: Option<schema::Specifier>,
+
+	/// Emit nested field structures as a flat map keyed by their path, i.e. `BaseParam[2].Value`.
+	flatten: Option<bool>,
+
+	/// Include a hash of the row's content in each version, across all languages. Consecutive versions with the same hash hold the same data for the row.
+	hashes: Option<bool>,
+
+	/// Representation of references that `fields` does not select within. `full` resolves them up to the configured depth, `preview` includes only the row ID and display name of their target, and `id` leaves them as raw values. Defaults to `full`.
+	reference: Option<ReferenceQuery>,
+}
+
+/// Response structure for the row history endpoint.
+#[derive(Serialize, JsonSchema)]
+struct HistoryResponse {
+	/// The row as read from each version, ordered from earliest to latest release.
+	versions: Vec<HistoryResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct HistoryResult {
+	/// Key of the version the row was read from.
+	#[schemars(with = "String")]
+	version: VersionKey,
+
+	/// Names that refer to the version.
+	names: Vec<String>,
+
+	availability: RowAvailability,
+
+	/// The canonical specifier for the schema the row was read with in this version.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Field values of the row in this version. Only present if the row is available.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	fields: Option<ValueString>,
+
+	/// Hash of the row's content in this version. Only present if requested, and the row is available.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	hash: Option<String>,
+
+	/// Non-fatal issues encountered while reading the row in this version.
+	#[serde(skip_serializing_if = "Vec::is_empty")]
+	warnings: Vec<String>,
+}
+
+/// Availability of a row within a single version.
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum RowAvailability {
+	/// The row was read successfully.
+	Available,
+	/// The version does not contain the sheet.
+	SheetMissing,
+	/// The sheet does not contain the row in this version.
+	RowMissing,
+	/// The row is present, but could not be read, i.e. as the schema does not match the version's data. Details are listed in the result's warnings.
+	Unreadable,
+}
+
+fn history_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a row's history")
+		.description(
+			"Read a single sheet row from every known version, to trace how its data has changed between game updates. Versions that have not finished preparing are omitted. Reading a row from every version is comparatively slow - prefer narrowing the response with `fields`.",
+		)
+		.response_with::<200, Json<HistoryResponse>, _>(|response| {
+			response.example(HistoryResponse {
+				versions: vec![HistoryResult {
+					version: "5f0c4b1f8e1a2d3c".parse().expect("valid version key"),
+					names: vec!["latest".into()],
+					availability: RowAvailability::Available,
+					schema: schema::CanonicalSpecifier {
+						source: "source".into(),
+						version: "version".into(),
+					},
+					fields: Some(row_result_example(1).fields),
+					hash: None,
+					warnings: vec![],
+				}],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn history(
+	Path(path): Path<RowPath>,
+	Query(query): Query<HistoryQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	State(version_service): State<service::Version>,
+	Extension(config): Extension<Config>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// See `sheet` - wait for a read slot before building any request state.
+	let reader = data.blocking().permit().await;
+
+	let language = query
+		.language
+		.map(excel::Language::from)
+		.unwrap_or_else(|| data.default_language());
+
+	let format = ValueFormat {
+		flatten: query.flatten.unwrap_or(false),
+	};
+	let hashes = query.hashes.unwrap_or(false);
+	let depth = config.limit.get().depth;
+
+	let mut keys = version_service
+		.keys()
+		.into_iter()
+		.filter(|key| !version_service.hidden(*key))
+		.collect::<Vec<_>>();
+	keys.sort_by_key(|key| (version_service.released(*key), *key));
+
+	let sheet = path.sheet.as_str();
+	let row_id = path.row.row_id;
+	let subrow_id = path.row.subrow_id;
+
+	let mut results = vec![];
+	for key in keys {
+		// Each version is a separate synchronous read - bail between them if the
+		// request has timed out.
+		cancellation.check()?;
+
+		// Versions still being prepared have no data to read yet.
+		let Ok(version) = data.version(key) else {
+			continue;
+		};
+		let excel = version.excel();
+
+		let specifier = schema_provider.canonicalize(query.schema.clone(), key)?;
+		let schema = schema_provider.schema(specifier.clone())?;
+
+		let no_sentinels = read::Sentinels::new();
+		let sentinels = config
+			.sentinel
+			.get(&specifier.source)
+			.unwrap_or(&no_sentinels);
+		let references = config.reference_mode(query.reference, &specifier.source);
+
+		let (filter, filter_warnings) = query
+			.fields
+			.clone()
+			.or_else(|| {
+				config
+					.filter
+					.get(&specifier.source)
+					.and_then(|filter_config| filter_config.entry.clone())
+			})
+			.map(|filter_string| filter_string.to_conditional_filter(language))
+			.unwrap_or_else(|| Warnings::new(read::Filter::All.into()))
+			.decompose();
+
+		let mut result = HistoryResult {
+			version: key,
+			names: version_service.names(key).unwrap_or_default(),
+			availability: RowAvailability::Available,
+			schema: specifier,
+			fields: None,
+			hash: None,
+			warnings: filter_warnings,
+		};
+
+		match version.rows(sheet) {
+			None => result.availability = RowAvailability::SheetMissing,
+			Some(rows) if !rows.contains(row_id) => {
+				result.availability = RowAvailability::RowMissing
+			}
+			Some(_) => {
+				if let Err(error) = check_languages(&version, sheet, language, filter.filter()) {
+					result.availability = RowAvailability::Unreadable;
+					result.warnings.push(error.to_string());
+					results.push(result);
+					continue;
+				}
+
+				let read = reader.run(|| {
+					let row_filter = filter.resolve(
+						&excel,
+						schema.as_ref(),
+						sheet,
+						row_id,
+						subrow_id,
+						language,
+					)?;
+
+					read::read(
+						&excel,
+						schema.as_ref(),
+						sheet,
+						row_id,
+						subrow_id,
+						language,
+						&row_filter,
+						sentinels,
+						depth,
+						references,
+					)
+				});
+
+				match read {
+					Ok(value) => {
+						let (fields, warnings) = value.decompose();
+						result.fields = Some(ValueString(fields, language, format));
+						result.warnings.extend(warnings);
+					}
+					// The row may be present while the requested subrow is not.
+					Err(read::Error::NotFound(_)) => {
+						result.availability = RowAvailability::RowMissing
+					}
+					Err(error) => {
+						result.availability = RowAvailability::Unreadable;
+						result.warnings.push(error.to_string());
+					}
+				}
+			}
+		}
+
+		if hashes && result.fields.is_some() {
+			let subrows = matches!(
+				excel.sheet(sheet).anyhow()?.kind().anyhow()?,
+				exh::SheetKind::Subrows
+			);
+			let hash =
+				reader.run(|| version.row_hash(sheet, row_id, subrows.then_some(subrow_id)))?;
+			result.hash = Some(hash_string(hash));
+		}
+
+		results.push(result);
+	}
+
+	Ok(Json(HistoryResponse { versions: results }))
+}
+
+/// Select up to `count` rows either side of the specified row, which need not
+/// itself be present. Rows must be sorted.
+fn adjacent_rows(
+	rows: &[RowSpecifier],
+	row: RowSpecifier,
+	count: usize,
+) -> (&[RowSpecifier], &[RowSpecifier]) {
+	let start = rows.partition_point(|specifier| *specifier < row);
+	let end = rows.partition_point(|specifier| *specifier <= row);
+
+	(
+		&rows[start.saturating_sub(count)..start],
+		&rows[end..rows.len().min(end + count)],
+	)
+}
+
+/// Query parameters accepted by the type definition endpoints.
+#[derive(Deserialize, JsonSchema)]
+struct TypesQuery {
+	/// Data fields to describe. Defaults to the fields returned by the row endpoint.
+	fields: Option<FilterString>,
+}
+
+fn typescript_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("sheet typescript definition")
+		.description(
+			"Generate a TypeScript interface describing rows of a sheet, as returned for the provided fields filter and schema.",
+		)
+		.response::<200, String>()
+}
+
+#[debug_handler(state = service::State)]
+async fn typescript(
+	Path(path): Path<SheetPath>,
+	context: ResolvedContext,
+	Query(query): Query<TypesQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+) -> Result<impl IntoApiResponse> {
+	let (shape, language) = sheet_shape(
+		&path.sheet,
+		&context,
+		query,
+		&data,
+		&schema_provider,
+		&config,
+	)
+	.await?;
+
+	Ok(types::typescript(path.sheet.as_str(), &shape, language))
+}
+
+fn jsonschema_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("sheet json schema")
+		.description(
+			"Generate a JSON Schema describing rows of a sheet, as returned for the provided fields filter and schema.",
+		)
+		.response::<200, Json<serde_json::Value>>()
+}
+
+#[debug_handler(state = service::State)]
+async fn jsonschema(
+	Path(path): Path<SheetPath>,
+	context: ResolvedContext,
+	Query(query): Query<TypesQuery>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(config): Extension<Config>,
+) -> Result<impl IntoApiResponse> {
+	let (shape, language) = sheet_shape(
+		&path.sheet,
+		&context,
+		query,
+		&data,
+		&schema_provider,
+		&config,
+	)
+	.await?;
+
+	Ok(Json(types::json_schema(
+		path.sheet.as_str(),
+		&shape,
+		language,
+	)))
+}
+
+/// Response structure for the sheet statistics endpoint.
+#[derive(Serialize, JsonSchema)]
+struct StatsResponse {
+	/// The canonical specifier for the schema used in this response.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	rows: RowStats,
+
+	/// Statistics for each column of the sheet.
+	columns: Vec<ColumnStats>,
+
+	/// Paths of fields occupying columns of a kind that could not be read.
+	/// Values of these fields are returned as placeholders.
+	unsupported: Vec<String>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RowStats {
+	/// Number of rows in the sheet, including subrows.
+	count: usize,
+
+	/// Lowest row ID in the sheet.
+	min: Option<u32>,
+
+	/// Highest row ID in the sheet.
+	max: Option<u32>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ColumnStats {
+	/// Path to the field occupying the column.
+	field: String,
+
+	kind: ColumnKind,
+
+	/// Lowest value in the column. Only present for numeric columns.
+	min: Option<serde_json::Number>,
+
+	/// Highest value in the column. Only present for numeric columns.
+	max: Option<serde_json::Number>,
+
+	/// Number of distinct values in the column.
+	cardinality: usize,
+
+	/// Whether the column is of a kind that could not be read. Statistics are
+	/// empty for unsupported columns.
+	unsupported: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+enum ColumnKind {
+	Boolean,
+	Number,
+	String,
+}
+
+impl From<exh::ColumnKind> for ColumnKind {
+	fn from(kind: exh::ColumnKind) -> Self {
+		use exh::ColumnKind as K;
+		match kind {
+			K::String => Self::String,
+			K::Bool
+			| K::PackedBool0
+			| K::PackedBool1
+			| K::PackedBool2
+			| K::PackedBool3
+			| K::PackedBool4
+			| K::PackedBool5
+			| K::PackedBool6
+			| K::PackedBool7 => Self::Boolean,
+			_ => Self::Number,
+		}
+	}
+}
+
+// JSON numbers can't represent the full i128 range, though values read from
+// sheets will always fit within either an i64 or u64.
+fn integer_number(value: i128) -> Option<serde_json::Number> {
+	match i64::try_from(value) {
+		Ok(value) => Some(value.into()),
+		Err(_) => u64::try_from(value).ok().map(Into::into),
+	}
+}
+
+fn stats_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("sheet statistics")
+		.description(
+			"Summary statistics for the rows and columns of a sheet, such as the range and number of distinct values in each column. The first request for any given sheet will be slow, as the sheet is scanned in full.",
+		)
+		.response_with::<200, Json<StatsResponse>, _>(|response| {
+			response.example(StatsResponse {
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				rows: RowStats {
+					count: 2,
+					min: Some(1),
+					max: Some(2),
+				},
+				columns: vec![ColumnStats {
+					field: "Level".into(),
+					kind: ColumnKind::Number,
+					min: Some(1.into()),
+					max: Some(90.into()),
+					cardinality: 2,
+					unsupported: false,
+				}],
+				unsupported: vec![],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn stats(
+	Path(path): Path<SheetPath>,
+	context: ResolvedContext,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+	Extension(statistics_cache): Extension<StatisticsCache>,
+	cancellation: Cancellation,
+) -> Result<impl IntoApiResponse> {
+	// Building statistics scans the entire sheet - see `sheet`.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let language = context.language;
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let statistics = cancellation.resolve(reader.run(|| {
+		excel
+			.sheet(path.sheet.as_str())
+			.map_err(|error| match error {
+				ironworks::Error::NotFound(ironworks::ErrorValue::Sheet(..)) => {
+					Error::NotFound(error.to_string())
+				}
+				other => Error::Other(other.into()),
+			})?;
+
+		statistics_cache.get_or_try_insert(
+			(
+				context.version,
+				context.schema.clone(),
+				path.sheet.as_str().to_string(),
+				language,
+			),
+			|| {
+				Ok(read::statistics(
+					&excel,
+					schema.as_ref(),
+					path.sheet.as_str(),
+					language,
+					cancellation.token(),
+				)?)
+			},
+		)
+	}))?;
+
+	let bounds = |range: Option<read::ValueRange>| match range {
+		Some(read::ValueRange::Integer { min, max }) => (integer_number(min), integer_number(max)),
+		Some(read::ValueRange::Float { min, max }) => (
+			serde_json::Number::from_f64(min),
+			serde_json::Number::from_f64(max),
+		),
+		None => (None, None),
+	};
+
+	let response = StatsResponse {
+		schema: context.schema,
+		rows: RowStats {
+			count: statistics.rows.count,
+			min: statistics.rows.min,
+			max: statistics.rows.max,
+		},
+		columns: statistics
+			.columns
+			.iter()
+			.map(|(field, column)| {
+				let (min, max) = bounds(column.range);
+				ColumnStats {
+					field: field.clone(),
+					kind: column.kind.into(),
+					min,
+					max,
+					cardinality: column.cardinality,
+					unsupported: column.unsupported,
+				}
+			})
+			.collect(),
+		unsupported: statistics
+			.columns
+			.iter()
+			.filter(|(_field, column)| column.unsupported)
+			.map(|(field, _column)| field.clone())
+			.collect(),
+	};
+
+	Ok(Json(response))
+}
+
+async fn sheet_shape(
+	sheet: &SheetName,
+	context: &ResolvedContext,
+	query: TypesQuery,
+	data: &service::Data,
+	schema_provider: &service::Schema,
+	config: &Config,
+) -> Result<(read::Shape, excel::Language)> {
+	// Shapes are derived from sheet headers, which may need to be read from disk.
+	let reader = data.blocking().permit().await;
+
+	let version = data.version(context.version)?;
+	let excel = version.excel();
+
+	let language = context.language;
+
+	let filter = query
+		.fields
+		.or_else(|| {
+			config
+				.filter
+				.get(&context.schema.source)
+				.and_then(|filter_config| filter_config.entry.clone())
+		})
+		.map(|filter_string| filter_string.to_filter(language))
+		.unwrap_or_else(|| Warnings::new(read::Filter::All))
+		// Shapes have no means of reporting warnings - conflicting fields are
+		// simply omitted.
+		.decompose()
+		.0;
+
+	let schema = schema_provider.schema(context.schema.clone())?;
+
+	let depth = config.limit.get().depth;
+	let shape = reader.run(|| {
+		read::shape(
+			&excel,
+			schema.as_ref(),
+			sheet.as_str(),
+			language,
+			&filter,
+			depth,
+		)
+	})?;
+
+	Ok((shape, language))
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn simple_sheet_name() {
+		let name = "Item".parse::<SheetName>().expect("parse should not fail");
+		assert_eq!(name.as_str(), "Item");
+	}
+
+	#[test]
+	fn path_sheet_name() {
+		let name = "quest/000/ClsHrv001_00003"
+			.parse::<SheetName>()
+			.expect("parse should not fail");
+		assert_eq!(name.as_str(), "quest/000/ClsHrv001_00003");
+	}
+
+	#[test]
+	fn row_path_sheet_name() {
+		// Path segments are percent-decoded by the router before deserialization.
+		let path = serde_json::from_value::<RowPath>(serde_json::json!({
+			"sheet": "quest/000/ClsHrv001_00003",
+			"row": "1",
+		}))
+		.expect("deserialize should not fail");
+		assert_eq!(path.sheet.as_str(), "quest/000/ClsHrv001_00003");
+		assert_eq!(
+			path.row,
+			RowSpecifier {
+				row_id: 1,
+				subrow_id: 0
+			}
+		);
+	}
+
+	#[test]
+	fn invalid_sheet_names() {
+		for name in [
+			"",
+			"/Item",
+			"Item/",
+			"quest//000",
+			"../Item",
+			"quest/./000",
+			"quest\\000",
+		] {
+			assert!(
+				name.parse::<SheetName>().is_err(),
+				"{name:?} should not parse"
+			);
+		}
+	}
+
+	fn rows(ids: &[u32]) -> Vec<RowSpecifier> {
+		ids.iter()
+			.map(|row_id| RowSpecifier {
+				row_id: *row_id,
+				subrow_id: 0,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn adjacent_rows_skip_gaps() {
+		let rows = rows(&[1, 2, 5, 9, 10, 20]);
+		let (previous, next) = adjacent_rows(&rows, "9".parse().unwrap(), 2);
+		assert_eq!(previous, &rows[1..3]);
+		assert_eq!(next, &rows[4..6]);
+	}
+
+	#[test]
+	fn adjacent_rows_missing_row() {
+		let rows = rows(&[1, 2, 5, 9]);
+		let (previous, next) = adjacent_rows(&rows, "4".parse().unwrap(), 5);
+		assert_eq!(previous, &rows[0..2]);
+		assert_eq!(next, &rows[2..4]);
+	}
+
+	#[test]
+	fn adjacent_rows_subrows() {
+		let rows = ["1:0", "1:1", "1:2", "2:0"]
+			.map(|specifier| specifier.parse::<RowSpecifier>().unwrap());
+		let (previous, next) = adjacent_rows(&rows, "1:1".parse().unwrap(), 1);
+		assert_eq!(previous, &rows[0..1]);
+		assert_eq!(next, &rows[2..3]);
+	}
+
+	#[test]
+	fn sheet_name_search() {
+		let names = ["Quest", "QuestReward", "quest/000/ClsHrv001_00001", "Item"];
+		let search = |pattern: &str| {
+			names
+				.iter()
+				.filter(|name| sheet_name_matches(pattern, name))
+				.copied()
+				.collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			search("quest"),
+			vec!["Quest", "QuestReward", "quest/000/ClsHrv001_00001"]
+		);
+		assert_eq!(search("quest*reward"), vec!["QuestReward"]);
+		assert_eq!(search("quest/*"), vec!["quest/000/ClsHrv001_00001"]);
+		assert_eq!(search("*item"), vec!["Item"]);
+	}
+
+	#[test]
+	fn profile_replaces_shape() {
+		let profile = serde_json::from_value::<ProfileConfig>(serde_json::json!({
+			"fields": "Name",
+			"language": "de",
+			"reference": "id",
+			"max_age": 60,
+		}))
+		.expect("deserialize should not fail");
+		let context = ResolvedContext {
+			version: "5f0c4b1f8e1a2d3c".parse().unwrap(),
+			language: excel::Language::English,
+			schema: schema::CanonicalSpecifier {
+				source: "source".into(),
+				version: "version".into(),
+			},
+		};
+
+		let shape = Shape::new(
+			Some(&profile),
+			&context,
+			None,
+			Some(ReferenceQuery::Full),
+			Some(true),
+		);
+		assert!(shape.fields.is_some());
+		assert_eq!(shape.language, excel::Language::German);
+		assert!(matches!(shape.reference, Some(ReferenceQuery::Id)));
+		assert!(!shape.interpret);
+		assert!(profile.cache_control().is_some());
+
+		let shape = Shape::new(None, &context, None, None, Some(true));
+		assert!(shape.fields.is_none());
+		assert_eq!(shape.language, excel::Language::English);
+		assert!(shape.interpret);
+	}
+
+	#[test]
+	fn shape_hash_tracks_definition() {
+		let fields = |value: &str| {
+			value
+				.parse::<FilterString>()
+				.expect("parse should not fail")
+		};
+
+		let base = shape_hash(Some(&fields("Name")), None, false);
+		assert_eq!(base, shape_hash(Some(&fields("Name")), None, false));
+		assert_ne!(base, shape_hash(Some(&fields("Name,Icon")), None, false));
+		assert_ne!(base, shape_hash(Some(&fields("Name@ja")), None, false));
+		assert_ne!(base, shape_hash(None, None, false));
+		assert_ne!(
+			base,
+			shape_hash(Some(&fields("Name")), Some(ReferenceQuery::Id), false)
+		);
+		assert_ne!(base, shape_hash(Some(&fields("Name")), None, true));
+	}
+}
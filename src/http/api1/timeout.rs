@@ -0,0 +1,119 @@
+use std::{collections::HashMap, convert::Infallible, time::Duration};
+
+use aide::OperationInput;
+use axum::{
+	async_trait,
+	extract::{FromRequestParts, Request, State},
+	http::request::Parts,
+	middleware::Next,
+	response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use tokio::{select, time};
+use tokio_util::sync::CancellationToken;
+
+use super::error::Error;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+	/// Default timeout for requests, in seconds.
+	default: u64,
+
+	/// Timeout overrides for specific route groups, i.e. `sheet`, in seconds.
+	#[serde(flatten)]
+	routes: HashMap<String, u64>,
+}
+
+impl Config {
+	pub fn duration(&self, route: &str) -> Duration {
+		let seconds = self.routes.get(route).copied().unwrap_or(self.default);
+		Duration::from_secs(seconds)
+	}
+}
+
+/// Fail requests that take longer than the provided duration to complete.
+pub async fn timeout(
+	State(duration): State<Duration>,
+	mut request: Request,
+	next: Next,
+) -> Response {
+	let cancel = CancellationToken::new();
+	request.extensions_mut().insert(Cancellation {
+		token: cancel.clone(),
+		duration,
+	});
+
+	// Handlers may perform long-running synchronous work that never yields to
+	// the runtime. Cancelling from a separate task ensures that such work can
+	// still observe the timeout, and bail out early.
+	let timer = tokio::spawn({
+		let cancel = cancel.clone();
+		async move {
+			time::sleep(duration).await;
+			cancel.cancel();
+		}
+	});
+
+	let response = select! {
+		response = next.run(request) => response,
+		_ = cancel.cancelled() => Error::Timeout(duration).into_response(),
+	};
+
+	timer.abort();
+
+	response
+}
+
+/// Cancellation signal for the current request, triggered when its timeout
+/// elapses. Long-running handlers should check this periodically.
+#[derive(Clone)]
+pub struct Cancellation {
+	token: CancellationToken,
+	duration: Duration,
+}
+
+impl Cancellation {
+	pub fn check(&self) -> Result<(), Error> {
+		match self.token.is_cancelled() {
+			true => Err(Error::Timeout(self.duration)),
+			false => Ok(()),
+		}
+	}
+
+	/// Token to pass to work that observes cancellation itself, such as reads
+	/// that scan entire sheets.
+	pub fn token(&self) -> &CancellationToken {
+		&self.token
+	}
+
+	/// Resolve the result of work observing `token`. Work that bailed out due
+	/// to cancellation is reported as the timeout that caused it.
+	pub fn resolve<T>(&self, result: Result<T, Error>) -> Result<T, Error> {
+		self.check()?;
+		result
+	}
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for Cancellation
+where
+	S: Send + Sync,
+{
+	type Rejection = Infallible;
+
+	async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+		// Routes without a timeout are never cancelled.
+		let cancellation = parts
+			.extensions
+			.get::<Self>()
+			.cloned()
+			.unwrap_or_else(|| Self {
+				token: CancellationToken::new(),
+				duration: Duration::MAX,
+			});
+
+		Ok(cancellation)
+	}
+}
+
+impl OperationInput for Cancellation {}
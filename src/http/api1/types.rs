@@ -0,0 +1,176 @@
+use std::fmt::Write;
+
+use ironworks::excel;
+use itertools::Itertools;
+use serde_json::{json, Map, Value};
+
+use crate::read;
+
+use super::value::struct_key_name;
+
+/// Render a TypeScript interface describing rows of the specified shape.
+pub fn typescript(name: &str, shape: &read::Shape, language: excel::Language) -> String {
+	let mut output = String::new();
+	writeln!(output, "export interface {} {{", type_name(name)).expect("infallible");
+	writeln!(output, "\trow_id: number;").expect("infallible");
+	writeln!(output, "\tsubrow_id?: number;").expect("infallible");
+	writeln!(
+		output,
+		"\tfields: {};",
+		typescript_shape(shape, language, 1)
+	)
+	.expect("infallible");
+	writeln!(output, "}}").expect("infallible");
+	output
+}
+
+fn typescript_shape(shape: &read::Shape, language: excel::Language, indent: usize) -> String {
+	use read::Shape as S;
+	match shape {
+		S::Array(element) => format!("Array<{}>", typescript_shape(element, language, indent)),
+		S::Boolean => "boolean".into(),
+		S::Number => "number".into(),
+		S::String => "string".into(),
+
+		S::Icon => typescript_object(
+			[
+				("id".into(), "number".into()),
+				("path".into(), "string".into()),
+				("path_hr1".into(), "string".into()),
+			],
+			indent,
+		),
+
+		S::Reference(targets) => {
+			let scalar = typescript_object([("value".into(), "number".into())], indent);
+			let populated = targets.iter().map(|(sheet, fields)| {
+				typescript_object(
+					[
+						("value".into(), "number".into()),
+						("sheet".into(), json!(sheet).to_string()),
+						("row_id".into(), "number".into()),
+						(
+							"fields".into(),
+							typescript_shape(fields, language, indent + 1),
+						),
+					],
+					indent,
+				)
+			});
+			std::iter::once(scalar).chain(populated).join(" | ")
+		}
+
+		S::Struct(fields) => typescript_object(
+			fields
+				.iter()
+				.map(|(key, shape)| {
+					(
+						struct_key_name(key, language),
+						typescript_shape(shape, language, indent + 1),
+					)
+				})
+				.sorted_by(|a, b| a.0.cmp(&b.0)),
+			indent,
+		),
+	}
+}
+
+fn typescript_object(fields: impl IntoIterator<Item = (String, String)>, indent: usize) -> String {
+	let mut output = String::from("{\n");
+	for (key, value) in fields {
+		// Keys such as `Name@ja` aren't valid identifiers, quote them.
+		let key = match key
+			.chars()
+			.all(|char| char.is_ascii_alphanumeric() || char == '_')
+		{
+			true => key,
+			false => json!(key).to_string(),
+		};
+		writeln!(output, "{}{key}: {value};", "\t".repeat(indent + 1)).expect("infallible");
+	}
+	output.push_str(&"\t".repeat(indent));
+	output.push('}');
+	output
+}
+
+/// Render a JSON Schema describing rows of the specified shape.
+pub fn json_schema(name: &str, shape: &read::Shape, language: excel::Language) -> Value {
+	json!({
+		"$schema": "http://json-schema.org/draft-07/schema#",
+		"title": type_name(name),
+		"type": "object",
+		"properties": {
+			"row_id": { "type": "integer" },
+			"subrow_id": { "type": "integer" },
+			"fields": json_schema_shape(shape, language),
+		},
+		"required": ["row_id", "fields"],
+	})
+}
+
+fn json_schema_shape(shape: &read::Shape, language: excel::Language) -> Value {
+	use read::Shape as S;
+	match shape {
+		S::Array(element) => json!({
+			"type": "array",
+			"items": json_schema_shape(element, language),
+		}),
+		S::Boolean => json!({ "type": "boolean" }),
+		S::Number => json!({ "type": "number" }),
+		S::String => json!({ "type": "string" }),
+
+		S::Icon => json_schema_object([
+			("id".into(), json!({ "type": "integer" })),
+			("path".into(), json!({ "type": "string" })),
+			("path_hr1".into(), json!({ "type": "string" })),
+		]),
+
+		S::Reference(targets) => {
+			let scalar = json_schema_object([("value".into(), json!({ "type": "integer" }))]);
+			let populated = targets.iter().map(|(sheet, fields)| {
+				json_schema_object([
+					("value".into(), json!({ "type": "integer" })),
+					("sheet".into(), json!({ "const": sheet })),
+					("row_id".into(), json!({ "type": "integer" })),
+					("fields".into(), json_schema_shape(fields, language)),
+				])
+			});
+			json!({ "anyOf": std::iter::once(scalar).chain(populated).collect::<Vec<_>>() })
+		}
+
+		S::Struct(fields) => json_schema_object(fields.iter().map(|(key, shape)| {
+			(
+				struct_key_name(key, language),
+				json_schema_shape(shape, language),
+			)
+		})),
+	}
+}
+
+fn json_schema_object(fields: impl IntoIterator<Item = (String, Value)>) -> Value {
+	let properties = fields.into_iter().collect::<Map<_, _>>();
+	let required = properties.keys().cloned().collect::<Vec<_>>();
+	json!({
+		"type": "object",
+		"properties": properties,
+		"required": required,
+		"additionalProperties": false,
+	})
+}
+
+/// Derive a type name from a sheet name, i.e. `quest/000/ClsHrv001_00003`
+/// becomes `Quest000ClsHrv00100003Row`.
+fn type_name(sheet: &str) -> String {
+	let mut name = sheet
+		.split(|char: char| !char.is_ascii_alphanumeric())
+		.map(|segment| {
+			let mut chars = segment.chars();
+			match chars.next() {
+				Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+				None => String::new(),
+			}
+		})
+		.collect::<String>();
+	name.push_str("Row");
+	name
+}
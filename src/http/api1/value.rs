@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use ironworks::excel;
+use ironworks::{excel, file::exh};
 use schemars::{
 	gen::SchemaGenerator,
 	schema::{InstanceType, Schema, SchemaObject},
@@ -9,19 +9,33 @@ use serde::ser::{Serialize, SerializeMap, SerializeSeq, SerializeStruct};
 
 use crate::{data, read, utility::jsonschema::impl_jsonschema};
 
+pub use crate::asset::icon_paths;
+
 #[derive(Debug)]
-pub struct ValueString(pub read::Value, pub excel::Language);
+pub struct ValueString(pub read::Value, pub excel::Language, pub ValueFormat);
+
+/// Presentation options controlling how a value is serialized.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValueFormat {
+	/// Emit nested structures as a single level map keyed by their path, i.e.
+	/// `BaseParam[2].Value`.
+	pub flatten: bool,
+}
 
 impl Serialize for ValueString {
 	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
 	where
 		S: serde::Serializer,
 	{
-		ValueReference {
+		let reference = ValueReference {
 			value: &self.0,
 			language: self.1,
+		};
+
+		match self.2.flatten {
+			true => reference.serialize_flat(serializer),
+			false => reference.serialize(serializer),
 		}
-		.serialize(serializer)
 	}
 }
 
@@ -47,9 +61,13 @@ impl Serialize for ValueReference<'_> {
 		match self.value {
 			V::Array(values) => self.serialize_array(serializer, values),
 			V::Icon(id) => self.serialize_icon(serializer, *id),
+			V::Interpreted(field, interpretation) => {
+				self.serialize_interpreted(serializer, field, interpretation)
+			}
 			V::Reference(reference) => self.serialize_reference(serializer, reference),
-			V::Scalar(field) => self.serialize_scalar(serializer, field),
+			V::Scalar(field) => serialize_field(serializer, field),
 			V::Struct(fields) => self.serialize_struct(serializer, fields),
+			V::Unsupported(kind) => self.serialize_unsupported(serializer, *kind),
 		}
 	}
 }
@@ -73,13 +91,27 @@ impl ValueReference<'_> {
 	where
 		S: serde::Serializer,
 	{
-		let group = (id / 1000) * 1000;
-		let icon_path = format!("ui/icon/{group:0>6}/{id:0>6}");
+		let (path, path_hr1) = icon_paths(id);
 
 		let mut state = serializer.serialize_struct("Icon", 3)?;
 		state.serialize_field("id", &id)?;
-		state.serialize_field("path", &format!("{icon_path}.tex"))?;
-		state.serialize_field("path_hr1", &format!("{icon_path}_hr1.tex"))?;
+		state.serialize_field("path", &path)?;
+		state.serialize_field("path_hr1", &path_hr1)?;
+		state.end()
+	}
+
+	fn serialize_interpreted<S>(
+		&self,
+		serializer: S,
+		field: &excel::Field,
+		interpretation: &read::Interpretation,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		let mut state = serializer.serialize_struct("Interpreted", 2)?;
+		state.serialize_field("value", &FieldReference(field))?;
+		state.serialize_field("interpreted", &InterpretationReference(interpretation))?;
 		state.end()
 	}
 
@@ -121,27 +153,6 @@ impl ValueReference<'_> {
 		}
 	}
 
-	fn serialize_scalar<S>(&self, serializer: S, field: &excel::Field) -> Result<S::Ok, S::Error>
-	where
-		S: serde::Serializer,
-	{
-		use excel::Field as F;
-		match field {
-			// TODO: more comprehensive sestring handling
-			F::String(se_string) => serializer.serialize_str(&se_string.to_string()),
-			F::Bool(value) => serializer.serialize_bool(*value),
-			F::I8(value) => serializer.serialize_i8(*value),
-			F::I16(value) => serializer.serialize_i16(*value),
-			F::I32(value) => serializer.serialize_i32(*value),
-			F::I64(value) => serializer.serialize_i64(*value),
-			F::U8(value) => serializer.serialize_u8(*value),
-			F::U16(value) => serializer.serialize_u16(*value),
-			F::U32(value) => serializer.serialize_u32(*value),
-			F::U64(value) => serializer.serialize_u64(*value),
-			F::F32(value) => serializer.serialize_f32(*value),
-		}
-	}
-
 	fn serialize_struct<S>(
 		&self,
 		serializer: S,
@@ -150,19 +161,7 @@ impl ValueReference<'_> {
 	where
 		S: serde::Serializer,
 	{
-		let mut fields = fields
-			.into_iter()
-			.map(|(read::StructKey { name, language }, value)| {
-				let key = match *language == self.language {
-					true => name.to_owned(),
-					false => format!("{name}@{}", data::LanguageString::from(*language)),
-				};
-
-				(key, value)
-			})
-			.collect::<Vec<_>>();
-
-		fields.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+		let fields = self.sorted_struct_fields(fields);
 
 		let mut map = serializer.serialize_map(Some(fields.len()))?;
 		for (name, value) in fields {
@@ -176,4 +175,198 @@ impl ValueReference<'_> {
 		}
 		map.end()
 	}
+
+	fn serialize_unsupported<S>(
+		&self,
+		serializer: S,
+		kind: exh::ColumnKind,
+	) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		let mut state = serializer.serialize_struct("Unsupported", 1)?;
+		state.serialize_field("unsupported", &format!("{kind:?}"))?;
+		state.end()
+	}
+
+	fn sorted_struct_fields<'v>(
+		&self,
+		fields: &'v HashMap<read::StructKey, read::Value>,
+	) -> Vec<(String, &'v read::Value)> {
+		let mut fields = fields
+			.iter()
+			.map(|(key, value)| (struct_key_name(key, self.language), value))
+			.collect::<Vec<_>>();
+
+		fields.sort_unstable_by(|a, b| a.0.cmp(&b.0));
+
+		fields
+	}
+
+	fn serialize_flat<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		let mut entries = vec![];
+		self.flatten_into(None, self.value, &mut entries);
+
+		let mut map = serializer.serialize_map(Some(entries.len()))?;
+		for (key, value) in entries {
+			map.serialize_entry(&key, &value)?;
+		}
+		map.end()
+	}
+
+	// Keys mirror the path that would be taken through the nested representation,
+	// such that `a.b[1]` in flat output is equivalent to `value["a"]["b"][1]`.
+	fn flatten_into<'v>(
+		&self,
+		prefix: Option<String>,
+		value: &'v read::Value,
+		output: &mut Vec<(String, FlatValue<'v>)>,
+	) {
+		let path = |key: &str| match &prefix {
+			Some(prefix) => format!("{prefix}.{key}"),
+			None => key.to_string(),
+		};
+
+		use read::Value as V;
+		match value {
+			V::Array(values) => {
+				let prefix = prefix.as_deref().unwrap_or("");
+				for (index, value) in values.iter().enumerate() {
+					self.flatten_into(Some(format!("{prefix}[{index}]")), value, output);
+				}
+			}
+
+			V::Icon(id) => {
+				let (icon_path, icon_path_hr1) = icon_paths(*id);
+				output.push((path("id"), FlatValue::U32(*id)));
+				output.push((path("path"), FlatValue::String(icon_path)));
+				output.push((path("path_hr1"), FlatValue::String(icon_path_hr1)));
+			}
+
+			V::Interpreted(field, interpretation) => {
+				output.push((path("value"), FlatValue::Scalar(field)));
+				output.push((
+					path("interpreted"),
+					FlatValue::Interpretation(interpretation),
+				));
+			}
+
+			V::Reference(read::Reference::Scalar(value)) => {
+				output.push((path("value"), FlatValue::I32(*value)));
+			}
+
+			V::Reference(read::Reference::Populated {
+				value,
+				sheet,
+				row_id,
+				fields,
+			}) => {
+				output.push((path("value"), FlatValue::U32(*value)));
+				output.push((path("sheet"), FlatValue::String(sheet.clone())));
+				output.push((path("row_id"), FlatValue::U32(*row_id)));
+				self.flatten_into(Some(path("fields")), fields, output);
+			}
+
+			V::Scalar(field) => {
+				output.push((prefix.unwrap_or_default(), FlatValue::Scalar(field)));
+			}
+
+			V::Struct(fields) => {
+				for (key, value) in self.sorted_struct_fields(fields) {
+					self.flatten_into(Some(path(&key)), value, output);
+				}
+			}
+
+			V::Unsupported(kind) => {
+				output.push((path("unsupported"), FlatValue::String(format!("{kind:?}"))));
+			}
+		}
+	}
+}
+
+enum FlatValue<'a> {
+	Scalar(&'a excel::Field),
+	Interpretation(&'a read::Interpretation),
+	I32(i32),
+	U32(u32),
+	String(String),
+}
+
+impl Serialize for FlatValue<'_> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		match self {
+			Self::Scalar(field) => serialize_field(serializer, field),
+			Self::Interpretation(interpretation) => {
+				InterpretationReference(interpretation).serialize(serializer)
+			}
+			Self::I32(value) => serializer.serialize_i32(*value),
+			Self::U32(value) => serializer.serialize_u32(*value),
+			Self::String(value) => serializer.serialize_str(value),
+		}
+	}
+}
+
+struct FieldReference<'a>(&'a excel::Field);
+
+impl Serialize for FieldReference<'_> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		serialize_field(serializer, self.0)
+	}
+}
+
+struct InterpretationReference<'a>(&'a read::Interpretation);
+
+impl Serialize for InterpretationReference<'_> {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: serde::Serializer,
+	{
+		use read::Interpretation as I;
+		match self.0 {
+			I::Flags(names) => names.serialize(serializer),
+			I::DateTime(value) | I::Time(value) | I::Duration(value) => {
+				serializer.serialize_str(value)
+			}
+			I::Scaled(value) => serializer.serialize_f64(*value),
+		}
+	}
+}
+
+/// Name used for a struct key in output. Fields in languages other than the
+/// output language are suffixed with their language, i.e. `Name@ja`.
+pub fn struct_key_name(key: &read::StructKey, language: excel::Language) -> String {
+	match key.language == language {
+		true => key.name.clone(),
+		false => format!("{}@{}", key.name, data::LanguageString::from(key.language)),
+	}
+}
+
+fn serialize_field<S>(serializer: S, field: &excel::Field) -> Result<S::Ok, S::Error>
+where
+	S: serde::Serializer,
+{
+	use excel::Field as F;
+	match field {
+		// TODO: more comprehensive sestring handling
+		F::String(se_string) => serializer.serialize_str(&se_string.to_string()),
+		F::Bool(value) => serializer.serialize_bool(*value),
+		F::I8(value) => serializer.serialize_i8(*value),
+		F::I16(value) => serializer.serialize_i16(*value),
+		F::I32(value) => serializer.serialize_i32(*value),
+		F::I64(value) => serializer.serialize_i64(*value),
+		F::U8(value) => serializer.serialize_u8(*value),
+		F::U16(value) => serializer.serialize_u16(*value),
+		F::U32(value) => serializer.serialize_u32(*value),
+		F::U64(value) => serializer.serialize_u64(*value),
+		F::F32(value) => serializer.serialize_f32(*value),
+	}
 }
@@ -1,27 +1,651 @@
+use std::{
+	collections::HashSet,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
 use aide::{
-	axum::{routing::get_with, ApiRouter, IntoApiResponse},
+	axum::{
+		routing::{get_with, post_with},
+		ApiRouter, IntoApiResponse,
+	},
 	transform::TransformOperation,
 };
-use axum::{debug_handler, extract::State, Json};
+use axum::{
+	debug_handler, extract::State, http::header, response::IntoResponse, routing::get, Json,
+};
+use maud::{html, PreEscaped};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use crate::http::service;
+use crate::{
+	data::{ChangeKind, Changelog, SheetChange},
+	http::{page::Page, service},
+	read, schema,
+	version::{self, KeyScheme, LiveStatus, VersionKey},
+};
+
+use super::{
+	error::{Error, Result},
+	extract::{Path, Query, RouterPath},
+};
 
 pub fn router() -> ApiRouter<service::State> {
-	ApiRouter::new().api_route("/", get_with(versions, versions_docs))
+	ApiRouter::new()
+		.api_route("/", get_with(versions, versions_docs))
+		.api_route("/key", post_with(derive_key, derive_key_docs))
+		.api_route("/changelog", get_with(changelog_feed, changelog_feed_docs))
+		.route("/changelog.atom", get(changelog_atom))
+		.api_route("/:version", get_with(version, version_docs))
+		.api_route("/:version/changelog", get_with(changelog, changelog_docs))
 }
 
 fn versions_docs(operation: TransformOperation) -> TransformOperation {
 	operation
 		.summary("list versions")
 		.description("List valid version names accepted by the `version` query parameter.")
-		.response_with::<200, Json<Vec<&'static str>>, _>(|response| {
-			response.example(vec!["latest", "6.58", "6.58x1"])
+		.response_with::<200, Json<VersionsResponse>, _>(|response| {
+			response.example(VersionsResponse {
+				names: Page::complete(vec!["latest".into(), "6.58".into(), "6.58x1".into()]),
+				live: None,
+			})
 		})
 }
 
+/// Query parameters accepted by the version list endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct VersionsQuery {
+	/// Include the status of the version served as `latest` relative to the live game servers.
+	status: Option<bool>,
+}
+
+/// Response structure for the version list endpoint.
+#[derive(Serialize, JsonSchema)]
+struct VersionsResponse {
+	/// Valid version names.
+	#[serde(flatten)]
+	names: Page<String>,
+
+	/// Comparison of the live patch lists against the version served as
+	/// `latest`. Only present if requested with `status`, and the live patch
+	/// lists have been checked - replicas, for example, do not check.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	live: Option<LiveStatusResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LiveStatusResult {
+	/// Time the live patch lists were last checked, in seconds since the Unix epoch.
+	checked: u64,
+
+	/// Total number of live patches not yet included in the version served as `latest`.
+	behind: usize,
+
+	/// Number of patches behind for each game repository.
+	repositories: Vec<LiveRepositoryResult>,
+
+	/// Latest patch of the boot repository, if tracked.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	boot: Option<String>,
+
+	/// Whether the boot repository has been patched more recently than the
+	/// served game data, which may indicate a game update not yet reflected in
+	/// game patch lists.
+	boot_ahead: bool,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct LiveRepositoryResult {
+	/// Name of the repository.
+	name: String,
+
+	/// Number of live patches missing from the served version.
+	behind: usize,
+}
+
+impl From<LiveStatus> for LiveStatusResult {
+	fn from(status: LiveStatus) -> Self {
+		Self {
+			checked: status
+				.checked
+				.duration_since(UNIX_EPOCH)
+				.unwrap_or_default()
+				.as_secs(),
+			behind: status.patches_behind(),
+			repositories: status
+				.behind
+				.into_iter()
+				.map(|(name, behind)| LiveRepositoryResult { name, behind })
+				.collect(),
+			boot: status.boot,
+			boot_ahead: status.boot_ahead,
+		}
+	}
+}
+
 #[debug_handler(state = service::State)]
-async fn versions(State(version): State<service::Version>) -> impl IntoApiResponse {
-	let mut names = version.all_names();
+async fn versions(
+	Query(query): Query<VersionsQuery>,
+	State(version): State<service::Version>,
+) -> impl IntoApiResponse {
+	let mut names = version.visible_names();
 	names.sort_unstable();
-	Json(names)
+
+	let live = match query.status.unwrap_or(false) {
+		false => None,
+		true => version.live_status().map(LiveStatusResult::from),
+	};
+
+	let response = VersionsResponse {
+		names: Page::complete(names),
+		live,
+	};
+
+	Json(response)
+}
+
+/// Path variables accepted by the version endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct VersionPath {
+	/// Name of the version to read.
+	version: String,
+}
+
+/// Response structure for the version endpoint.
+#[derive(Serialize, JsonSchema)]
+struct VersionResponse {
+	/// Key uniquely identifying the patches that make up this version.
+	#[schemars(with = "String")]
+	key: VersionKey,
+
+	/// Names that refer to this version.
+	names: Vec<String>,
+
+	/// Repositories that make up this version.
+	repositories: Vec<RepositoryResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct RepositoryResult {
+	/// Name of the repository.
+	name: String,
+
+	/// Patches applied to the repository, oldest first.
+	patches: Vec<PatchResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct PatchResult {
+	/// Name of the patch.
+	name: String,
+
+	/// Size of the patch file in bytes, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	size: Option<u64>,
+
+	/// Hash of the patch file, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	hash: Option<String>,
+}
+
+fn version_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a version")
+		.description(
+			"Read details of a version, including the patches that make up each of its repositories.",
+		)
+		.response_with::<200, Json<VersionResponse>, _>(|response| {
+			response.example(VersionResponse {
+				key: "5f0c4b1f8e1a2d3c".parse().expect("valid version key"),
+				names: vec!["latest".into(), "7.0".into()],
+				repositories: vec![RepositoryResult {
+					name: "ffxivneo/win32/release/game".into(),
+					patches: vec![PatchResult {
+						name: "H2017.06.06.0000.0001a".into(),
+						size: Some(1_024),
+						hash: None,
+					}],
+				}],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn version(
+	Path(path): Path<VersionPath>,
+	State(version): State<service::Version>,
+) -> Result<impl IntoApiResponse> {
+	let not_found = || Error::NotFound(format!("unknown version \"{}\"", path.version));
+
+	let key = version
+		.resolve_visible(Some(path.version.as_str()))
+		.ok_or_else(not_found)?;
+	let details = version.version(key).ok_or_else(not_found)?;
+
+	let mut names = version.names(key).unwrap_or_default();
+	names.sort_unstable();
+
+	let response = VersionResponse {
+		key,
+		names,
+		repositories: details
+			.repositories
+			.into_iter()
+			.map(|repository| RepositoryResult {
+				name: repository.name,
+				patches: repository
+					.patches
+					.into_iter()
+					.map(|patch| PatchResult {
+						name: patch.name,
+						size: patch.size,
+						hash: patch.hash,
+					})
+					.collect(),
+			})
+			.collect(),
+	};
+
+	Ok(Json(response))
+}
+
+/// Request structure for the key derivation endpoint. Matches the structure of
+/// the version endpoint's response, though only repository patch names are
+/// required.
+#[derive(Deserialize, JsonSchema)]
+struct DeriveKeyRequest {
+	/// Repositories that make up the version, in the order they are configured.
+	repositories: Vec<DeriveKeyRepository>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DeriveKeyRepository {
+	/// Patches applied to the repository, oldest first.
+	patches: Vec<DeriveKeyPatch>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+struct DeriveKeyPatch {
+	/// Name of the patch.
+	name: String,
+}
+
+/// Response structure for the key derivation endpoint.
+#[derive(Serialize, JsonSchema)]
+struct DeriveKeyResponse {
+	/// Key derived for the version.
+	#[schemars(with = "String")]
+	key: VersionKey,
+
+	/// Identifier of the scheme used to derive the key. Keys derived by
+	/// differing schemes are not comparable.
+	scheme: u32,
+}
+
+fn derive_key_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("derive a version key")
+		.description(
+			"Derive the key for a version from its patch lists. Keys are currently derived from the name of the latest patch in each repository - the scheme in use is reported alongside the key, and will change if derivation changes in future.",
+		)
+		.response_with::<200, Json<DeriveKeyResponse>, _>(|response| {
+			response.example(DeriveKeyResponse {
+				key: "5f0c4b1f8e1a2d3c".parse().expect("valid version key"),
+				scheme: KeyScheme::CURRENT.into(),
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn derive_key(Json(request): Json<DeriveKeyRequest>) -> Result<impl IntoApiResponse> {
+	let latest = request
+		.repositories
+		.iter()
+		.map(|repository| {
+			repository
+				.patches
+				.last()
+				.map(|patch| patch.name.as_str())
+				.ok_or_else(|| {
+					Error::Invalid("repositories must contain at least one patch".into())
+				})
+		})
+		.collect::<Result<Vec<_>>>()?;
+
+	let scheme = KeyScheme::CURRENT;
+	let response = DeriveKeyResponse {
+		key: scheme.derive_from_latest(latest),
+		scheme: scheme.into(),
+	};
+
+	Ok(Json(response))
+}
+
+/// Response structure for the changelog feed endpoint.
+#[derive(Serialize, JsonSchema)]
+struct ChangelogFeedResponse {
+	/// Summaries of the changes made by each version, most recently released first.
+	entries: Vec<ChangelogSummary>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct ChangelogSummary {
+	/// Key of the version the changes were made in.
+	#[schemars(with = "String")]
+	version: VersionKey,
+
+	/// Names that refer to the version.
+	names: Vec<String>,
+
+	/// Key of the version the changes are relative to. Absent for the earliest
+	/// known version, for which every sheet is considered added.
+	#[schemars(with = "Option<String>")]
+	#[serde(skip_serializing_if = "Option::is_none")]
+	previous: Option<VersionKey>,
+
+	/// Release time of the version, in seconds since the Unix epoch, if known.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	released: Option<u64>,
+
+	/// Time the changelog was generated, in seconds since the Unix epoch.
+	generated: u64,
+
+	/// Number of sheets added.
+	added: usize,
+
+	/// Number of sheets removed.
+	removed: usize,
+
+	/// Number of sheets with modified data.
+	modified: usize,
+
+	/// Net change in the number of rows across all changed sheets.
+	rows_delta: i64,
+}
+
+impl ChangelogSummary {
+	fn new(changelog: &Changelog, version: &version::Manager) -> Self {
+		let count = |kind: ChangeKind| {
+			changelog
+				.sheets
+				.iter()
+				.filter(|change| change.kind == kind)
+				.count()
+		};
+
+		let mut names = version.names(changelog.version).unwrap_or_default();
+		names.sort_unstable();
+
+		Self {
+			version: changelog.version,
+			names,
+			previous: changelog.previous,
+			released: version.released(changelog.version).map(unix_seconds),
+			generated: unix_seconds(changelog.generated),
+			added: count(ChangeKind::Added),
+			removed: count(ChangeKind::Removed),
+			modified: count(ChangeKind::Modified),
+			rows_delta: changelog.sheets.iter().map(SheetChange::rows_delta).sum(),
+		}
+	}
+
+	fn example() -> Self {
+		Self {
+			version: "5f0c4b1f8e1a2d3c".parse().expect("valid version key"),
+			names: vec!["latest".into(), "7.0".into()],
+			previous: Some("0b3d5e7f9a1c2e4f".parse().expect("valid version key")),
+			released: Some(1_719_878_400),
+			generated: 1_719_900_000,
+			added: 12,
+			removed: 1,
+			modified: 340,
+			rows_delta: 5_120,
+		}
+	}
+}
+
+fn changelog_feed_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("list changelogs")
+		.description("List summaries of the changes made to game data by each visible version, relative to the version released before it, most recent first. The same feed is available in Atom format at `/version/changelog.atom`. Details of each version's changes can be read from `/version/{version}/changelog`.")
+		.response_with::<200, Json<ChangelogFeedResponse>, _>(|response| {
+			response.example(ChangelogFeedResponse {
+				entries: vec![ChangelogSummary::example()],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn changelog_feed(
+	State(version): State<service::Version>,
+	State(data): State<service::Data>,
+) -> impl IntoApiResponse {
+	Json(ChangelogFeedResponse {
+		entries: changelog_summaries(&version, &data),
+	})
+}
+
+#[debug_handler(state = service::State)]
+async fn changelog_atom(
+	RouterPath(router_path): RouterPath,
+	State(version): State<service::Version>,
+	State(data): State<service::Data>,
+) -> impl IntoResponse {
+	let entries = changelog_summaries(&version, &data);
+	let updated = entries
+		.iter()
+		.map(|entry| entry.generated)
+		.max()
+		.unwrap_or(0);
+	let timestamp = |seconds: u64| read::format_timestamp(seconds.try_into().unwrap_or(i64::MAX));
+
+	// Elements are written with explicit closing tags, as maud's void element
+	// syntax is not well-formed XML.
+	let feed = html! {
+		(PreEscaped(r#"<?xml version="1.0" encoding="utf-8"?>"#))
+		feed xmlns="http://www.w3.org/2005/Atom" {
+			title { "boilmaster game data changelog" }
+			id { "urn:boilmaster:changelog" }
+			link rel="self" href=(format!("{router_path}/changelog.atom")) {}
+			updated { (timestamp(updated)) }
+			author { name { "boilmaster" } }
+			@for entry in &entries {
+				entry {
+					id { "urn:boilmaster:version:" (entry.version) }
+					title {
+						"version "
+						(entry.names.first().cloned().unwrap_or_else(|| entry.version.to_string()))
+					}
+					link href=(format!("{router_path}/{}/changelog", entry.version)) {}
+					updated { (timestamp(entry.generated)) }
+					@if let Some(released) = entry.released {
+						published { (timestamp(released)) }
+					}
+					summary {
+						(entry.added) " sheets added, "
+						(entry.removed) " removed, "
+						(entry.modified) " modified; "
+						(entry.rows_delta) " rows net"
+					}
+				}
+			}
+		}
+	};
+
+	(
+		[(header::CONTENT_TYPE, "application/atom+xml; charset=utf-8")],
+		feed.into_string(),
+	)
+}
+
+fn changelog_summaries(version: &service::Version, data: &service::Data) -> Vec<ChangelogSummary> {
+	let mut summaries = version
+		.keys()
+		.into_iter()
+		.filter(|key| !version.hidden(*key))
+		.filter_map(|key| data.changelog(key))
+		.map(|changelog| ChangelogSummary::new(&changelog, version))
+		.collect::<Vec<_>>();
+
+	summaries.sort_by(|a, b| (b.released, b.version).cmp(&(a.released, a.version)));
+	summaries
+}
+
+/// Query parameters accepted by the changelog endpoint.
+#[derive(Deserialize, JsonSchema)]
+struct ChangelogQuery {
+	/// Schema used to determine which changed sheets are covered. Resolved
+	/// separately against the version and the version it is compared to.
+	schema: Option<schema::Specifier>,
+}
+
+/// Response structure for the changelog endpoint.
+#[derive(Serialize, JsonSchema)]
+struct ChangelogResponse {
+	#[serde(flatten)]
+	summary: ChangelogSummary,
+
+	/// The canonical specifier for the schema used to determine coverage.
+	#[schemars(with = "String")]
+	schema: schema::CanonicalSpecifier,
+
+	/// Change in the number of changed sheets covered by the schema, relative to
+	/// the schema resolved for the previous version. Positive values indicate
+	/// that more of the changed data can be read with named fields.
+	schema_coverage_delta: i64,
+
+	/// Sheets changed by the version, ordered by name.
+	sheets: Vec<SheetChangeResult>,
+}
+
+#[derive(Serialize, JsonSchema)]
+struct SheetChangeResult {
+	/// Name of the sheet.
+	sheet: String,
+
+	/// Kind of change, one of `added`, `removed`, or `modified`.
+	change: String,
+
+	/// Number of rows in the previous version, if the sheet was present.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	rows_before: Option<usize>,
+
+	/// Number of rows in this version, if the sheet is present.
+	#[serde(skip_serializing_if = "Option::is_none")]
+	rows_after: Option<usize>,
+
+	/// Change in the number of rows.
+	rows_delta: i64,
+
+	/// Whether the schema covers the sheet in this version.
+	covered: bool,
+
+	/// Whether the schema covered the sheet in the previous version.
+	previously_covered: bool,
+}
+
+fn changelog_docs(operation: TransformOperation) -> TransformOperation {
+	operation
+		.summary("read a changelog")
+		.description("Read the changes made to game data by a version, relative to the version released before it. Each changed sheet is listed alongside its change in row count, and whether the schema covers it before and after the change.")
+		.response_with::<200, Json<ChangelogResponse>, _>(|response| {
+			response.example(ChangelogResponse {
+				summary: ChangelogSummary::example(),
+				schema: schema::CanonicalSpecifier {
+					source: "source".into(),
+					version: "version".into(),
+				},
+				schema_coverage_delta: 1,
+				sheets: vec![SheetChangeResult {
+					sheet: "Item".into(),
+					change: ChangeKind::Modified.to_string(),
+					rows_before: Some(45_000),
+					rows_after: Some(45_120),
+					rows_delta: 120,
+					covered: true,
+					previously_covered: true,
+				}],
+			})
+		})
+}
+
+#[debug_handler(state = service::State)]
+async fn changelog(
+	Path(path): Path<VersionPath>,
+	Query(query): Query<ChangelogQuery>,
+	State(version): State<service::Version>,
+	State(data): State<service::Data>,
+	State(schema_provider): State<service::Schema>,
+) -> Result<impl IntoApiResponse> {
+	let key = version
+		.resolve_visible(Some(path.version.as_str()))
+		.ok_or_else(|| Error::NotFound(format!("unknown version \"{}\"", path.version)))?;
+	let changelog = data
+		.changelog(key)
+		.ok_or_else(|| Error::NotFound(format!("changelog for version {key}")))?;
+
+	let specifier = schema_provider.canonicalize(query.schema.clone(), key)?;
+	let covered = schema_coverage(
+		&schema_provider,
+		specifier.clone(),
+		&changelog.sheets,
+		|kind| kind != ChangeKind::Removed,
+	)?;
+
+	let previously_covered = match changelog.previous {
+		None => HashSet::new(),
+		Some(previous) => {
+			let specifier = schema_provider.canonicalize(query.schema, previous)?;
+			schema_coverage(&schema_provider, specifier, &changelog.sheets, |kind| {
+				kind != ChangeKind::Added
+			})?
+		}
+	};
+
+	let schema_coverage_delta = covered.len() as i64 - previously_covered.len() as i64;
+
+	let sheets = changelog
+		.sheets
+		.iter()
+		.map(|change| SheetChangeResult {
+			sheet: change.sheet.clone(),
+			change: change.kind.to_string(),
+			rows_before: change.rows_before,
+			rows_after: change.rows_after,
+			rows_delta: change.rows_delta(),
+			covered: covered.contains(change.sheet.as_str()),
+			previously_covered: previously_covered.contains(change.sheet.as_str()),
+		})
+		.collect();
+
+	let response = ChangelogResponse {
+		summary: ChangelogSummary::new(&changelog, &version),
+		schema: specifier,
+		schema_coverage_delta,
+		sheets,
+	};
+
+	Ok(Json(response))
+}
+
+/// Names of the changed sheets present in a version that the schema covers.
+fn schema_coverage<'a>(
+	schema_provider: &schema::Provider,
+	specifier: schema::CanonicalSpecifier,
+	changes: &'a [SheetChange],
+	present: impl Fn(ChangeKind) -> bool,
+) -> Result<HashSet<&'a str>> {
+	let schema = schema_provider.schema(specifier)?;
+	let covered = changes
+		.iter()
+		.filter(|change| present(change.kind))
+		.filter(|change| schema.sheet(&change.sheet).is_ok())
+		.map(|change| change.sheet.as_str())
+		.collect();
+	Ok(covered)
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.unwrap_or_default()
+		.as_secs()
 }
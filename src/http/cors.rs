@@ -0,0 +1,94 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use axum::http::{HeaderName, HeaderValue, Method};
+use serde::Deserialize;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+
+const WILDCARD: &str = "*";
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+	/// Origins permitted to make cross-origin requests. `*` permits any origin.
+	#[serde(default)]
+	origins: Vec<String>,
+
+	/// Methods permitted in cross-origin requests. `*` permits any method.
+	#[serde(default)]
+	methods: Vec<String>,
+
+	/// Request headers permitted in cross-origin requests. `*` permits any header.
+	#[serde(default)]
+	headers: Vec<String>,
+
+	/// Duration, in seconds, that preflight responses may be cached for.
+	max_age: Option<u64>,
+
+	/// Whether credentials may be included in cross-origin requests.
+	#[serde(default)]
+	credentials: bool,
+}
+
+impl Config {
+	pub fn layer(&self) -> Result<CorsLayer> {
+		let wildcard = |values: &[String]| values.iter().any(|value| value == WILDCARD);
+
+		// tower-http panics at runtime on this combination - catch it early instead.
+		if self.credentials
+			&& (wildcard(&self.origins) || wildcard(&self.methods) || wildcard(&self.headers))
+		{
+			bail!("cors credentials cannot be combined with wildcard origins, methods, or headers");
+		}
+
+		let origins = match wildcard(&self.origins) {
+			true => AllowOrigin::any(),
+			false => AllowOrigin::list(
+				self.origins
+					.iter()
+					.map(|origin| {
+						HeaderValue::from_str(origin)
+							.with_context(|| format!("invalid cors origin \"{origin}\""))
+					})
+					.collect::<Result<Vec<_>>>()?,
+			),
+		};
+
+		let methods = match wildcard(&self.methods) {
+			true => AllowMethods::any(),
+			false => AllowMethods::list(
+				self.methods
+					.iter()
+					.map(|method| {
+						Method::from_bytes(method.as_bytes())
+							.with_context(|| format!("invalid cors method \"{method}\""))
+					})
+					.collect::<Result<Vec<_>>>()?,
+			),
+		};
+
+		let headers = match wildcard(&self.headers) {
+			true => AllowHeaders::any(),
+			false => AllowHeaders::list(
+				self.headers
+					.iter()
+					.map(|header| {
+						HeaderName::from_bytes(header.as_bytes())
+							.with_context(|| format!("invalid cors header \"{header}\""))
+					})
+					.collect::<Result<Vec<_>>>()?,
+			),
+		};
+
+		let mut layer = CorsLayer::new()
+			.allow_origin(origins)
+			.allow_methods(methods)
+			.allow_headers(headers)
+			.allow_credentials(self.credentials);
+
+		if let Some(max_age) = self.max_age {
+			layer = layer.max_age(Duration::from_secs(max_age));
+		}
+
+		Ok(layer)
+	}
+}
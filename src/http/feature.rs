@@ -0,0 +1,40 @@
+use std::{collections::HashSet, fmt};
+
+use serde::Deserialize;
+
+/// An API surface that may be disabled by configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feature {
+	Admin,
+	Asset,
+	Search,
+	Sheet,
+	Version,
+}
+
+impl fmt::Display for Feature {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Self::Admin => "admin",
+			Self::Asset => "asset",
+			Self::Search => "search",
+			Self::Sheet => "sheet",
+			Self::Version => "version",
+		};
+		formatter.write_str(name)
+	}
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Features {
+	/// Surfaces to disable, i.e. `["admin", "asset"]` for a sheet data mirror.
+	#[serde(default)]
+	disabled: HashSet<Feature>,
+}
+
+impl Features {
+	pub fn enabled(&self, feature: Feature) -> bool {
+		!self.disabled.contains(&feature)
+	}
+}
@@ -1,5 +1,10 @@
-use axum::{debug_handler, extract::State, response::IntoResponse, routing::get, Router};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::{debug_handler, extract::State, response::IntoResponse, routing::get, Json, Router};
 use reqwest::StatusCode;
+use serde::Serialize;
+
+use crate::version::CircuitState;
 
 use super::service;
 
@@ -7,6 +12,7 @@ pub fn router() -> Router<service::State> {
 	Router::new()
 		.route("/live", get(live))
 		.route("/ready", get(ready))
+		.route("/update", get(update))
 }
 
 #[debug_handler]
@@ -27,3 +33,39 @@ async fn ready(
 		false => (StatusCode::SERVICE_UNAVAILABLE, "PENDING"),
 	}
 }
+
+#[derive(Serialize)]
+struct UpdateResponse {
+	state: &'static str,
+	consecutive_failures: u32,
+	last_error: Option<String>,
+	/// Unix timestamps, in seconds.
+	last_failure: Option<u64>,
+	next_attempt: Option<u64>,
+}
+
+#[debug_handler(state = service::State)]
+async fn update(State(version): State<service::Version>) -> impl IntoResponse {
+	let health = version.update_health();
+
+	let (status, state) = match health.state {
+		CircuitState::Closed => (StatusCode::OK, "closed"),
+		CircuitState::Open => (StatusCode::SERVICE_UNAVAILABLE, "open"),
+	};
+
+	let response = UpdateResponse {
+		state,
+		consecutive_failures: health.consecutive_failures,
+		last_error: health.last_error,
+		last_failure: health.last_failure.map(unix_seconds),
+		next_attempt: health.next_attempt.map(unix_seconds),
+	};
+
+	(status, Json(response))
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0)
+}
@@ -1,37 +1,84 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	sync::Arc,
+};
 
-use anyhow::Result;
-use axum::Router;
+use anyhow::{ensure, Result};
+use axum::{middleware, Router};
 use serde::Deserialize;
 use tokio::net::TcpListener;
 use tokio_util::sync::CancellationToken;
 use tower_http::trace::TraceLayer;
 
 use super::{
+	access::{self, AccessLog},
 	admin,
 	api1,
+	cors,
+	feature::{Feature, Features},
 	health,
+	maintenance::{self, Maintenance},
+	prime::{self, Primer},
 	// search,
 	service,
 };
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Config {
 	admin: admin::Config,
 	api1: api1::Config,
 
+	/// CORS policy applied to the public API. When omitted, no CORS headers are sent.
+	cors: Option<cors::Config>,
+
+	maintenance: maintenance::Config,
+
+	/// Structured log of requests served, for analysis of usage. When omitted,
+	/// requests are not logged beyond application tracing.
+	access_log: Option<access::Config>,
+
+	/// Replay of popular requests against newly prepared versions.
+	#[serde(default)]
+	prime: prime::Config,
+
+	/// API surfaces to expose. All are enabled unless disabled here.
+	#[serde(default)]
+	features: Features,
+
 	address: Option<IpAddr>,
 	port: u16,
 }
 
+impl Config {
+	/// Apply reload-safe settings from an updated configuration. Settings that
+	/// are baked into the router at startup, such as the bind address, are
+	/// left unchanged.
+	pub fn reload(&self, config: Config) {
+		self.api1.reload(config.api1);
+	}
+}
+
+/// Path segments used by top-level routes, which tenants cannot be named after.
+const RESERVED_TENANTS: &[&str] = &["admin", "api", "health", "search"];
+
+/// Services for a single game region variant, each with its own versions,
+/// game data, and schemas.
+pub struct Tenant {
+	pub asset: service::Asset,
+	pub data: service::Data,
+	pub schema: service::Schema,
+	// pub search: service::Search,
+	pub version: service::Version,
+}
+
 pub async fn serve(
 	cancel: CancellationToken,
 	config: Config,
-	data: service::Data,
-	asset: service::Asset,
-	schema: service::Schema,
-	// search: service::Search,
-	version: service::Version,
+	audit: service::Audit,
+	job: service::Job,
+	quota: service::Quota,
+	tenant: Tenant,
+	tenants: Vec<(String, Tenant)>,
 ) -> Result<()> {
 	let bind_address = SocketAddr::new(
 		config.address.unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
@@ -40,25 +87,95 @@ pub async fn serve(
 
 	tracing::info!("http binding to {bind_address:?}");
 
-	let router = Router::new()
-		.nest("/admin", admin::router(config.admin))
-		.nest("/api/1", api1::router(config.api1))
+	let maintenance = Arc::new(Maintenance::new(config.maintenance));
+	let primer = Arc::new(Primer::new(config.prime)?);
+
+	let api1_router = || -> Result<Router<service::State>> {
+		let mut router = api1::router(config.api1.clone(), &config.features).layer(
+			middleware::from_fn_with_state(maintenance.clone(), maintenance::maintenance),
+		);
+		if let Some(cors) = &config.cors {
+			router = router.layer(cors.layer()?);
+		}
+		Ok(router)
+	};
+
+	let state = |tenant: Tenant| service::State {
+		asset: tenant.asset,
+		audit: audit.clone(),
+		data: tenant.data,
+		job: job.clone(),
+		maintenance: maintenance.clone(),
+		primer: primer.clone(),
+		quota: quota.clone(),
+		schema: tenant.schema,
+		// search: tenant.search,
+		version: tenant.version,
+	};
+
+	let mut router = Router::new();
+	// Disabled admin routes are left out entirely, rather than advertising that
+	// they exist.
+	if config.features.enabled(Feature::Admin) {
+		router = router.nest("/admin", admin::router(config.admin));
+	}
+
+	// Priming replays requests against the default tenant only.
+	let prime_data = tenant.data.clone();
+
+	let mut router = router
+		.nest("/api/1", api1_router()?)
 		.nest("/health", health::router())
 		// .nest("/search", search::router())
-		.layer(TraceLayer::new_for_http())
-		.with_state(service::State {
-			asset,
-			data,
-			schema,
-			// search,
-			version,
-		});
+		.with_state(state(tenant));
+
+	// Additional tenants only expose the public API, under a prefix of their
+	// name. Administration and health are handled by the default tenant.
+	for (name, tenant) in tenants {
+		ensure!(
+			!name.is_empty()
+				&& name
+					.chars()
+					.all(|char| char.is_ascii_alphanumeric() || char == '-'),
+			"tenant name {name:?} must be alphanumeric"
+		);
+		ensure!(
+			!RESERVED_TENANTS.contains(&name.as_str()),
+			"tenant name {name:?} conflicts with a built-in route"
+		);
+		router = router.nest(
+			&format!("/{name}/api/1"),
+			api1_router()?.with_state(state(tenant)),
+		);
+	}
+
+	// Replayed requests are routed internally, bypassing the access log.
+	tokio::spawn(prime::watch(
+		cancel.clone(),
+		primer,
+		prime_data,
+		router.clone(),
+	));
+
+	if let Some(access_config) = config.access_log {
+		let access_log = Arc::new(AccessLog::new(access_config)?);
+		router = router.layer(middleware::from_fn_with_state(
+			access_log,
+			access::access_log,
+		));
+	}
+
+	let router = router.layer(TraceLayer::new_for_http());
 
+	// Connection info is required to record client addresses in the access log.
 	let listener = TcpListener::bind(bind_address).await.unwrap();
-	axum::serve(listener, router)
-		.with_graceful_shutdown(cancel.cancelled_owned())
-		.await
-		.unwrap();
+	axum::serve(
+		listener,
+		router.into_make_service_with_connect_info::<SocketAddr>(),
+	)
+	.with_graceful_shutdown(cancel.cancelled_owned())
+	.await
+	.unwrap();
 
 	Ok(())
 }
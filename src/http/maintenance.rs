@@ -0,0 +1,82 @@
+use std::sync::RwLock;
+
+use axum::{
+	extract::{Request, State},
+	http::{header, StatusCode},
+	middleware::Next,
+	response::{IntoResponse, Response},
+	Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::service;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Config {
+	/// Message returned by public endpoints while maintenance mode is enabled,
+	/// unless overridden when enabling it.
+	message: String,
+
+	/// Suggested delay before clients retry, in seconds.
+	retry_after: u64,
+}
+
+/// Runtime toggle for maintenance mode. While enabled, public endpoints are
+/// unavailable - admin endpoints and background tasks are unaffected.
+pub struct Maintenance {
+	config: Config,
+	message: RwLock<Option<String>>,
+}
+
+impl Maintenance {
+	pub fn new(config: Config) -> Self {
+		Self {
+			config,
+			message: Default::default(),
+		}
+	}
+
+	/// Message currently returned by public endpoints, if maintenance mode is enabled.
+	pub fn message(&self) -> Option<String> {
+		self.message.read().expect("poisoned").clone()
+	}
+
+	/// Enable maintenance mode, optionally overriding the configured message.
+	pub fn enable(&self, message: Option<String>) {
+		let message = message.unwrap_or_else(|| self.config.message.clone());
+		tracing::info!(message, "maintenance mode enabled");
+		*self.message.write().expect("poisoned") = Some(message);
+	}
+
+	pub fn disable(&self) {
+		tracing::info!("maintenance mode disabled");
+		*self.message.write().expect("poisoned") = None;
+	}
+}
+
+/// Reject requests with a 503 while maintenance mode is enabled.
+pub async fn maintenance(
+	State(maintenance): State<service::Maintenance>,
+	request: Request,
+	next: Next,
+) -> Response {
+	let Some(message) = maintenance.message() else {
+		return next.run(request).await;
+	};
+
+	// Body mirrors the API's error response structure.
+	let status = StatusCode::SERVICE_UNAVAILABLE;
+	(
+		status,
+		[(
+			header::RETRY_AFTER,
+			maintenance.config.retry_after.to_string(),
+		)],
+		Json(json!({
+			"code": status.as_u16(),
+			"message": message,
+		})),
+	)
+		.into_response()
+}
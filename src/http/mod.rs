@@ -1,8 +1,15 @@
+mod access;
 mod admin;
 mod api1;
+mod cors;
+mod feature;
 mod http;
+mod maintenance;
+mod page;
+mod prime;
+mod quota;
 // mod search;
 mod health;
 mod service;
 
-pub use http::{serve, Config};
+pub use http::{serve, Config, Tenant};
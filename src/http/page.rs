@@ -0,0 +1,46 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+
+/// Envelope shared by endpoints returning lists, such that clients may page
+/// through any list in the same way. Endpoint-specific fields are flattened
+/// alongside the envelope.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Page<T> {
+	/// Items within this page.
+	pub items: Vec<T>,
+
+	/// Cursor to provide as the `cursor` parameter of a subsequent request to
+	/// fetch the next page. Null if there are no further items.
+	pub next_cursor: Option<String>,
+
+	/// Estimate of the total number of items across all pages, if known.
+	pub total_estimate: Option<usize>,
+}
+
+impl<T> Page<T> {
+	/// Build a page containing every item of a list, with no further pages.
+	pub fn complete(items: Vec<T>) -> Self {
+		let total = items.len();
+		Self {
+			items,
+			next_cursor: None,
+			total_estimate: Some(total),
+		}
+	}
+
+	/// Build a page from a full list of items, using the offset of the page as
+	/// its cursor.
+	pub fn offset(
+		items: impl Iterator<Item = T>,
+		total: usize,
+		offset: usize,
+		limit: usize,
+	) -> Self {
+		let next_offset = offset.saturating_add(limit);
+		Self {
+			items: items.skip(offset).take(limit).collect(),
+			next_cursor: (next_offset < total).then(|| next_offset.to_string()),
+			total_estimate: Some(total),
+		}
+	}
+}
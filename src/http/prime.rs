@@ -0,0 +1,328 @@
+use std::{
+	collections::{HashMap, HashSet},
+	fs,
+	sync::RwLock,
+	time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::Result;
+use axum::{
+	body::{self, Body},
+	extract::Request,
+	Router,
+};
+use figment::value::magic::RelativePathBuf;
+use futures::{stream, StreamExt};
+use serde::Deserialize;
+use tokio::select;
+use tokio_util::sync::CancellationToken;
+use tower::ServiceExt;
+
+use crate::version::VersionKey;
+
+use super::service;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// File the list of requests replayed to prime caches is persisted to. When
+	/// omitted, the list is held in memory, and lost on restart.
+	file: Option<RelativePathBuf>,
+
+	/// Maximum number of distinct requests to keep, most frequent first.
+	limit: usize,
+
+	/// Number of requests replayed concurrently.
+	concurrency: usize,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			file: None,
+			limit: 1000,
+			concurrency: 4,
+		}
+	}
+}
+
+/// Summary of the replay of requests against a single version.
+#[derive(Debug, Clone)]
+pub struct PrimeRun {
+	pub version: VersionKey,
+	pub started: SystemTime,
+	pub duration: Duration,
+	pub succeeded: usize,
+	pub failed: usize,
+}
+
+/// Replays popular requests against newly prepared versions, such that row,
+/// schema, and reader caches are warm before real traffic reaches them.
+pub struct Primer {
+	config: Config,
+	requests: RwLock<Vec<String>>,
+	last_run: RwLock<Option<PrimeRun>>,
+}
+
+impl Primer {
+	pub fn new(config: Config) -> Result<Self> {
+		let requests = match &config.file {
+			Some(file) if file.relative().exists() => fs::read_to_string(file.relative())?
+				.lines()
+				.map(str::to_string)
+				.collect(),
+			_ => vec![],
+		};
+
+		Ok(Self {
+			config,
+			requests: RwLock::new(requests),
+			last_run: Default::default(),
+		})
+	}
+
+	/// Requests that will be replayed, most frequent first.
+	pub fn requests(&self) -> Vec<String> {
+		self.requests.read().expect("poisoned").clone()
+	}
+
+	pub fn last_run(&self) -> Option<PrimeRun> {
+		self.last_run.read().expect("poisoned").clone()
+	}
+
+	/// Replace the requests to replay with those listed in the input, returning
+	/// the number kept. See `parse_requests` for the accepted format.
+	pub fn set_requests(&self, input: &str) -> Result<usize> {
+		let requests = parse_requests(input, self.config.limit);
+
+		if let Some(file) = &self.config.file {
+			let path = file.relative();
+			if let Some(parent) = path.parent() {
+				fs::create_dir_all(parent)?;
+			}
+			fs::write(path, requests.join("\n"))?;
+		}
+
+		let count = requests.len();
+		tracing::info!(count, "prime requests updated");
+		*self.requests.write().expect("poisoned") = requests;
+
+		Ok(count)
+	}
+
+	/// Replay each request against the specified version.
+	async fn prime(&self, router: &Router, version: VersionKey) {
+		let requests = self.requests();
+		if requests.is_empty() {
+			return;
+		}
+
+		tracing::info!(%version, count = requests.len(), "priming caches");
+
+		let started = SystemTime::now();
+		let start = Instant::now();
+		let results = stream::iter(requests)
+			.map(|request| replay(router.clone(), with_version(&request, version)))
+			.buffer_unordered(self.config.concurrency.max(1))
+			.collect::<Vec<_>>()
+			.await;
+
+		let succeeded = results.iter().filter(|success| **success).count();
+		let run = PrimeRun {
+			version,
+			started,
+			duration: start.elapsed(),
+			succeeded,
+			failed: results.len() - succeeded,
+		};
+
+		tracing::info!(
+			%version,
+			succeeded = run.succeeded,
+			failed = run.failed,
+			duration = ?run.duration,
+			"caches primed"
+		);
+		*self.last_run.write().expect("poisoned") = Some(run);
+	}
+}
+
+/// Prime caches for each version as it becomes ready. Versions ready when
+/// first observed are treated as a baseline, and are not primed.
+pub async fn watch(
+	cancel: CancellationToken,
+	primer: service::Primer,
+	data: service::Data,
+	router: Router,
+) {
+	let mut receiver = data.subscribe();
+	let mut known: Option<HashSet<VersionKey>> = None;
+
+	loop {
+		let ready = receiver
+			.borrow_and_update()
+			.iter()
+			.copied()
+			.collect::<HashSet<_>>();
+
+		match &mut known {
+			None => {
+				if !ready.is_empty() {
+					known = Some(ready);
+				}
+			}
+			Some(known) => {
+				for &version in ready.difference(known) {
+					primer.prime(&router, version).await;
+				}
+				*known = ready;
+			}
+		}
+
+		select! {
+			_ = cancel.cancelled() => break,
+			result = receiver.changed() => if result.is_err() { break },
+		}
+	}
+}
+
+async fn replay(router: Router, uri: String) -> bool {
+	let request = match Request::get(&uri).body(Body::empty()) {
+		Ok(request) => request,
+		Err(error) => {
+			tracing::warn!(uri, ?error, "invalid prime request");
+			return false;
+		}
+	};
+
+	let response = router
+		.oneshot(request)
+		.await
+		.unwrap_or_else(|error| match error {});
+	let success = response.status().is_success();
+
+	// Some responses are built as their body is read - drain it so any caching
+	// they perform takes place.
+	if let Err(error) = body::to_bytes(response.into_body(), usize::MAX).await {
+		tracing::warn!(uri, ?error, "failed to read prime response");
+		return false;
+	}
+
+	success
+}
+
+/// A request as recorded in the access log.
+#[derive(Deserialize)]
+struct LoggedRequest {
+	method: String,
+	path: String,
+	query: Option<String>,
+	status: Option<u16>,
+}
+
+/// Parse a list of requests, one per line, ranking them by frequency. Lines
+/// may be either access log entries, or a path and query, i.e.
+/// `/api/1/sheet/Item/1?fields=Name`. Only successful `GET` requests to public
+/// endpoints are kept, and any version parameter is removed, such that each
+/// request may be replayed against any version.
+fn parse_requests(input: &str, limit: usize) -> Vec<String> {
+	let mut counts = HashMap::<String, usize>::new();
+
+	for line in input.lines().map(str::trim) {
+		let request = match line.starts_with('{') {
+			true => match serde_json::from_str::<LoggedRequest>(line) {
+				Ok(entry)
+					if entry.method == "GET"
+						&& entry.status.map_or(true, |status| status < 400) =>
+				{
+					match entry.query {
+						Some(query) => format!("{}?{query}", entry.path),
+						None => entry.path,
+					}
+				}
+				_ => continue,
+			},
+			false => line.to_string(),
+		};
+
+		if !request.starts_with('/') || request.starts_with("/admin") {
+			continue;
+		}
+
+		*counts.entry(without_version(&request)).or_default() += 1;
+	}
+
+	let mut requests = counts.into_iter().collect::<Vec<_>>();
+	requests.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then_with(|| a.cmp(b)));
+	requests
+		.into_iter()
+		.take(limit)
+		.map(|(request, _count)| request)
+		.collect()
+}
+
+fn without_version(request: &str) -> String {
+	let Some((path, query)) = request.split_once('?') else {
+		return request.to_string();
+	};
+
+	let query = query
+		.split('&')
+		.filter(|pair| {
+			!pair.is_empty() && pair.split_once('=').map_or(*pair, |(name, _)| name) != "version"
+		})
+		.collect::<Vec<_>>()
+		.join("&");
+
+	match query.is_empty() {
+		true => path.to_string(),
+		false => format!("{path}?{query}"),
+	}
+}
+
+fn with_version(request: &str, version: VersionKey) -> String {
+	match request.contains('?') {
+		true => format!("{request}&version={version}"),
+		false => format!("{request}?version={version}"),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn parse_ranks_by_frequency() {
+		let input = [
+			r#"{"method":"GET","path":"/api/1/sheet/Item","query":"version=abc","status":200}"#,
+			"/api/1/sheet/Action/1",
+			"/api/1/sheet/Item?version=def",
+			r#"{"method":"GET","path":"/api/1/sheet/Item/1","status":404}"#,
+			r#"{"method":"POST","path":"/api/1/sheet/Item/1","status":200}"#,
+			"/admin/versions",
+			"not a request",
+		]
+		.join("\n");
+
+		assert_eq!(
+			parse_requests(&input, 10),
+			vec!["/api/1/sheet/Item", "/api/1/sheet/Action/1"]
+		);
+		assert_eq!(parse_requests(&input, 1), vec!["/api/1/sheet/Item"]);
+	}
+
+	#[test]
+	fn replace_version() {
+		let key = "5f0c4b1f8e1a2d3c".parse().unwrap();
+		assert_eq!(
+			with_version(&without_version("/a?version=latest&fields=Name"), key),
+			"/a?fields=Name&version=5f0c4b1f8e1a2d3c"
+		);
+		assert_eq!(
+			with_version(&without_version("/a?version=latest"), key),
+			"/a?version=5f0c4b1f8e1a2d3c"
+		);
+	}
+}
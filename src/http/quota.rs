@@ -0,0 +1,38 @@
+use std::net::SocketAddr;
+
+use axum::http::{HeaderMap, HeaderName};
+
+use crate::quota::{Consumer, QuotaStatus};
+
+/// Header used to identify the consumer a request should be charged to.
+const API_KEY_HEADER: &str = "x-api-key";
+
+/// API key provided with a request, if any.
+pub fn api_key(headers: &HeaderMap) -> Option<&str> {
+	headers
+		.get(API_KEY_HEADER)
+		.and_then(|value| value.to_str().ok())
+}
+
+/// Consumer a request should be charged to, identified by its API key and the
+/// address of the client it was received from.
+pub fn consumer(headers: &HeaderMap, address: Option<SocketAddr>) -> Consumer {
+	Consumer {
+		api_key: api_key(headers),
+		address: address.map(|address| address.ip()),
+	}
+}
+
+/// Headers describing the state of a consumer's quota after a request.
+pub fn headers(status: QuotaStatus) -> [(HeaderName, String); 2] {
+	[
+		(
+			HeaderName::from_static("x-quota-limit"),
+			status.limit.to_string(),
+		),
+		(
+			HeaderName::from_static("x-quota-remaining"),
+			status.remaining().to_string(),
+		),
+	]
+}
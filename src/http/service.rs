@@ -4,14 +4,22 @@ use axum::extract::FromRef;
 
 use crate::{
 	asset,
+	audit,
 	data,
+	job,
+	quota,
 	schema,
 	// search,
 	version,
 };
 
 pub type Asset = Arc<asset::Service>;
+pub type Audit = Arc<audit::Log>;
 pub type Data = Arc<data::Data>;
+pub type Job = Arc<job::Manager>;
+pub type Maintenance = Arc<super::maintenance::Maintenance>;
+pub type Primer = Arc<super::prime::Primer>;
+pub type Quota = Arc<quota::Quota>;
 pub type Schema = Arc<schema::Provider>;
 // pub type Search = Arc<search::Search>;
 pub type Version = Arc<version::Manager>;
@@ -19,7 +27,12 @@ pub type Version = Arc<version::Manager>;
 #[derive(Clone, FromRef)]
 pub struct State {
 	pub asset: Asset,
+	pub audit: Audit,
 	pub data: Data,
+	pub job: Job,
+	pub maintenance: Maintenance,
+	pub primer: Primer,
+	pub quota: Quota,
 	pub schema: Schema,
 	// pub search: Search,
 	pub version: Version,
@@ -0,0 +1,109 @@
+use std::{fmt, time::SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JobId(u64);
+
+impl JobId {
+	pub(super) fn next(self) -> Self {
+		Self(self.0 + 1)
+	}
+}
+
+impl Default for JobId {
+	fn default() -> Self {
+		Self(1)
+	}
+}
+
+impl fmt::Display for JobId {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.0.fmt(formatter)
+	}
+}
+
+/// Category of work performed by a job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Kind {
+	PatchDownload,
+	Ingestion,
+	AssetConversion,
+}
+
+impl fmt::Display for Kind {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Self::PatchDownload => "patch download",
+			Self::Ingestion => "ingestion",
+			Self::AssetConversion => "asset conversion",
+		};
+		formatter.write_str(name)
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+	/// Job has been created, but is waiting for resources to begin.
+	Pending,
+	Running,
+	Completed,
+	Failed,
+	Cancelled,
+}
+
+impl State {
+	/// Whether the job has reached a terminal state.
+	pub fn finished(self) -> bool {
+		matches!(self, Self::Completed | Self::Failed | Self::Cancelled)
+	}
+}
+
+impl fmt::Display for State {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			Self::Pending => "pending",
+			Self::Running => "running",
+			Self::Completed => "completed",
+			Self::Failed => "failed",
+			Self::Cancelled => "cancelled",
+		};
+		formatter.write_str(name)
+	}
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Progress {
+	pub current: u64,
+	pub total: Option<u64>,
+}
+
+impl fmt::Display for Progress {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.total {
+			Some(total) if total > 0 => write!(
+				formatter,
+				"{}/{total} ({:.0}%)",
+				self.current,
+				self.current as f64 / total as f64 * 100.0
+			),
+			_ => write!(formatter, "{}", self.current),
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Job {
+	pub id: JobId,
+	pub kind: Kind,
+	pub description: String,
+	pub state: State,
+	pub progress: Option<Progress>,
+	/// Reason for failure, if the job failed.
+	pub error: Option<String>,
+	pub created: SystemTime,
+	pub updated: SystemTime,
+}
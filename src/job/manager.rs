@@ -0,0 +1,328 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	fmt, fs,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex, RwLock},
+	time::SystemTime,
+};
+
+use anyhow::Result;
+use figment::value::magic::RelativePathBuf;
+use serde::Deserialize;
+use tokio::{
+	select,
+	sync::{broadcast, Notify},
+	time,
+};
+use tokio_util::sync::{CancellationToken, WaitForCancellationFuture};
+
+use crate::utility::persist;
+
+use super::job::{Job, JobId, Kind, Progress, State};
+
+// Minimum time between writes of the job list to disk - progress updates can be
+// frequent, there's no need to persist every single one.
+const PERSIST_INTERVAL: time::Duration = time::Duration::from_secs(1);
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	directory: RelativePathBuf,
+
+	/// Number of finished jobs to keep a record of.
+	retain: usize,
+}
+
+pub struct Manager {
+	path: PathBuf,
+	retain: usize,
+
+	next_id: Mutex<JobId>,
+	jobs: RwLock<BTreeMap<JobId, Job>>,
+	tokens: Mutex<HashMap<JobId, CancellationToken>>,
+
+	changed: Notify,
+	finished: broadcast::Sender<Job>,
+}
+
+impl Manager {
+	pub fn new(config: Config) -> Result<Self> {
+		let directory = config.directory.relative();
+		fs::create_dir_all(&directory)?;
+
+		let path = directory.join("jobs.json");
+		let jobs = hydrate(&path)?;
+		let next_id = jobs
+			.last_key_value()
+			.map(|(id, _job)| id.next())
+			.unwrap_or_default();
+
+		Ok(Self {
+			path,
+			retain: config.retain,
+
+			next_id: Mutex::new(next_id),
+			jobs: RwLock::new(jobs),
+			tokens: Default::default(),
+
+			changed: Notify::new(),
+			finished: broadcast::channel(16).0,
+		})
+	}
+
+	/// Create a new pending job. The job is tracked until the returned handle is
+	/// finished or dropped.
+	pub fn create(self: &Arc<Self>, kind: Kind, description: impl Into<String>) -> Handle {
+		self.create_with_token(kind, description.into(), CancellationToken::new())
+	}
+
+	/// Create a new pending job that will additionally be cancelled when the
+	/// parent token is cancelled.
+	pub fn create_child(
+		self: &Arc<Self>,
+		kind: Kind,
+		description: impl Into<String>,
+		parent: &CancellationToken,
+	) -> Handle {
+		self.create_with_token(kind, description.into(), parent.child_token())
+	}
+
+	fn create_with_token(
+		self: &Arc<Self>,
+		kind: Kind,
+		description: String,
+		token: CancellationToken,
+	) -> Handle {
+		let id = {
+			let mut next_id = self.next_id.lock().expect("poisoned");
+			let id = *next_id;
+			*next_id = id.next();
+			id
+		};
+
+		let now = SystemTime::now();
+		let job = Job {
+			id,
+			kind,
+			description,
+			state: State::Pending,
+			progress: None,
+			error: None,
+			created: now,
+			updated: now,
+		};
+
+		tracing::debug!(%id, %kind, description = %job.description, "job created");
+
+		self.jobs.write().expect("poisoned").insert(id, job);
+		self.tokens
+			.lock()
+			.expect("poisoned")
+			.insert(id, token.clone());
+		self.changed.notify_one();
+
+		Handle {
+			id,
+			manager: self.clone(),
+			token,
+			finished: false,
+		}
+	}
+
+	/// List known jobs, most recent first.
+	pub fn jobs(&self) -> Vec<Job> {
+		self.jobs
+			.read()
+			.expect("poisoned")
+			.values()
+			.rev()
+			.cloned()
+			.collect()
+	}
+
+	/// Subscribe to jobs as they finish.
+	pub fn subscribe(&self) -> broadcast::Receiver<Job> {
+		self.finished.subscribe()
+	}
+
+	pub fn job(&self, id: JobId) -> Option<Job> {
+		self.jobs.read().expect("poisoned").get(&id).cloned()
+	}
+
+	/// Request cancellation of a job. Returns `false` if the job is not running.
+	/// Cancellation is cooperative - the job will transition to cancelled once
+	/// the task performing it has stopped.
+	pub fn cancel(&self, id: JobId) -> bool {
+		match self.tokens.lock().expect("poisoned").get(&id) {
+			Some(token) => {
+				tracing::info!(%id, "job cancellation requested");
+				token.cancel();
+				true
+			}
+			None => false,
+		}
+	}
+
+	pub async fn start(&self, cancel: CancellationToken) -> Result<()> {
+		loop {
+			select! {
+				_ = self.changed.notified() => {}
+				_ = cancel.cancelled() => break,
+			}
+
+			if let Err(error) = self.persist().await {
+				tracing::error!(?error, "failed to persist jobs");
+			}
+
+			time::sleep(PERSIST_INTERVAL).await;
+		}
+
+		// Make sure the final state of any jobs is recorded before shutting down.
+		self.persist().await
+	}
+
+	fn update(&self, id: JobId, update: impl FnOnce(&mut Job)) {
+		let mut jobs = self.jobs.write().expect("poisoned");
+		let Some(job) = jobs.get_mut(&id) else {
+			return;
+		};
+
+		update(job);
+		job.updated = SystemTime::now();
+
+		if job.state.finished() {
+			tracing::debug!(%id, state = %job.state, error = ?job.error, "job finished");
+			self.tokens.lock().expect("poisoned").remove(&id);
+			// It's common for nothing to be listening for finished jobs.
+			let _ = self.finished.send(job.clone());
+			prune(&mut jobs, self.retain);
+		}
+
+		drop(jobs);
+		self.changed.notify_one();
+	}
+
+	async fn persist(&self) -> Result<()> {
+		let jobs = self
+			.jobs
+			.read()
+			.expect("poisoned")
+			.values()
+			.cloned()
+			.collect::<Vec<_>>();
+
+		let path = self.path.clone();
+		let join_handle = tokio::task::spawn_blocking(move || -> Result<()> {
+			let contents = serde_json::to_vec_pretty(&jobs)?;
+			persist::write(&path, &contents)
+		});
+
+		join_handle.await?
+	}
+}
+
+fn hydrate(path: &Path) -> Result<BTreeMap<JobId, Job>> {
+	let Some(jobs) = persist::read(path, |bytes| Ok(serde_json::from_slice::<Vec<Job>>(bytes)?))?
+	else {
+		return Ok(BTreeMap::new());
+	};
+
+	// Anything that hadn't finished by the time the previous process stopped was
+	// interrupted - there's no task left to complete it.
+	let jobs = jobs
+		.into_iter()
+		.map(|mut job| {
+			if !job.state.finished() {
+				job.state = State::Failed;
+				job.error = Some("interrupted by shutdown".to_string());
+			}
+			(job.id, job)
+		})
+		.collect();
+
+	Ok(jobs)
+}
+
+fn prune(jobs: &mut BTreeMap<JobId, Job>, retain: usize) {
+	let finished = jobs
+		.values()
+		.filter(|job| job.state.finished())
+		.map(|job| job.id)
+		.collect::<Vec<_>>();
+
+	let excess = finished.len().saturating_sub(retain);
+	for id in &finished[..excess] {
+		jobs.remove(id);
+	}
+}
+
+/// Handle used by the task performing a job to report on its status.
+pub struct Handle {
+	id: JobId,
+	manager: Arc<Manager>,
+	token: CancellationToken,
+	finished: bool,
+}
+
+impl Handle {
+	pub fn id(&self) -> JobId {
+		self.id
+	}
+
+	/// Token that will be cancelled when cancellation of this job is requested.
+	pub fn token(&self) -> &CancellationToken {
+		&self.token
+	}
+
+	pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+		self.token.cancelled()
+	}
+
+	/// Mark the job as running.
+	pub fn start(&self) {
+		self.manager
+			.update(self.id, |job| job.state = State::Running);
+	}
+
+	pub fn progress(&self, current: u64, total: Option<u64>) {
+		self.manager.update(self.id, |job| {
+			job.progress = Some(Progress { current, total });
+		});
+	}
+
+	/// Record the outcome of the job. Any outcome after cancellation has been
+	/// requested is recorded as a cancellation.
+	pub fn finish<T, E>(mut self, result: &Result<T, E>)
+	where
+		E: fmt::Display,
+	{
+		let cancelled = self.token.is_cancelled();
+		self.manager.update(self.id, |job| match result {
+			_ if cancelled => job.state = State::Cancelled,
+			Ok(_) => job.state = State::Completed,
+			Err(error) => {
+				job.state = State::Failed;
+				job.error = Some(format!("{error:#}"));
+			}
+		});
+		self.finished = true;
+	}
+}
+
+impl Drop for Handle {
+	fn drop(&mut self) {
+		if self.finished {
+			return;
+		}
+
+		// The task driving this job went away without reporting an outcome, most
+		// likely due to the future being dropped.
+		let cancelled = self.token.is_cancelled();
+		self.manager.update(self.id, |job| match cancelled {
+			true => job.state = State::Cancelled,
+			false => {
+				job.state = State::Failed;
+				job.error = Some("abandoned before completion".to_string());
+			}
+		});
+	}
+}
@@ -0,0 +1,7 @@
+mod job;
+mod manager;
+
+pub use {
+	job::{Job, JobId, Kind, Progress, State},
+	manager::{Config, Handle, Manager},
+};
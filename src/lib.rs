@@ -1,12 +1,22 @@
+//! Game data services, usable either as the boilmaster server, or embedded
+//! within other projects as a library. See [`embed::Boilmaster`] for the
+//! latter. The HTTP layer is available behind the default `http` feature.
+
 #![allow(clippy::module_inception)]
 
-// TODO: probably take these non-public and expose an explicit interface here? or is it not worth it given this is the entry point
 pub mod asset;
+pub mod audit;
 pub mod data;
+pub mod embed;
+#[cfg(feature = "http")]
 pub mod http;
-mod read;
+pub mod job;
+pub mod notify;
+pub mod quota;
+pub mod read;
 pub mod schema;
 // pub mod search;
+pub mod storage;
 pub mod tracing;
 mod utility;
 pub mod version;
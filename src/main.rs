@@ -1,41 +1,129 @@
-use std::sync::Arc;
+use std::{collections::BTreeMap, env, fs, sync::Arc, time::SystemTime};
 
 use anyhow::Context;
 use boilmaster::{
 	asset,
+	audit,
 	data,
 	http,
+	job,
+	notify,
+	quota,
 	schema,
 	// search,
+	storage,
 	tracing,
 	version,
 };
 use figment::{
 	providers::{Env, Format, Toml},
+	value::Value,
 	Figment,
 };
-use futures::TryFutureExt;
+use futures::{future::try_join_all, TryFutureExt};
 use serde::Deserialize;
-use tokio::signal;
+use tokio::{select, signal, time};
 use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Deserialize)]
 struct Config {
 	// tracing: tracing::Config, - read individually.
 	http: http::Config,
+	audit: audit::Config,
+	job: job::Config,
+	notify: notify::Config,
+	quota: quota::Config,
+	// search: search::Config,
+	storage: Option<storage::Config>,
+
+	#[serde(flatten)]
+	tenant: TenantConfig,
+
+	/// Additional game region variants served from this instance, keyed by the
+	/// path prefix their API is served under, i.e. `/kr/api/1`.
+	#[serde(default)]
+	tenants: BTreeMap<String, TenantConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TenantConfig {
+	#[serde(default)]
+	asset: asset::Config,
 	data: data::Config,
 	version: version::Config,
 	schema: schema::Config,
-	// search: search::Config,
 }
 
+struct Tenant {
+	version: Arc<version::Manager>,
+	data: Arc<data::Data>,
+	asset: Arc<asset::Service>,
+	schema: Arc<schema::Provider>,
+}
+
+impl Tenant {
+	fn new(
+		config: TenantConfig,
+		job: Arc<job::Manager>,
+		storage: Option<Arc<storage::Storage>>,
+	) -> anyhow::Result<Self> {
+		let version = Arc::new(
+			version::Manager::new(config.version, job.clone(), storage)
+				.context("failed to create version manager")?,
+		);
+		let data = Arc::new(data::Data::new(config.data, job.clone()));
+		let asset = Arc::new(asset::Service::new(config.asset, data.clone(), job));
+		let schema = Arc::new(
+			schema::Provider::new(config.schema, data.clone())
+				.context("failed to create schema provider")?,
+		);
+
+		Ok(Self {
+			version,
+			data,
+			asset,
+			schema,
+		})
+	}
+
+	async fn start(&self, cancel: CancellationToken) -> anyhow::Result<()> {
+		tokio::try_join!(
+			self.version.start(cancel.clone()),
+			self.data
+				.start(cancel.clone(), &self.version)
+				.map_err(anyhow::Error::from),
+			self.schema.start(cancel).map_err(anyhow::Error::from),
+		)?;
+
+		Ok(())
+	}
+
+	fn reload(&self, config: TenantConfig) {
+		self.version.reload(config.version);
+		self.schema.reload(config.schema);
+	}
+
+	fn http(&self) -> http::Tenant {
+		http::Tenant {
+			asset: self.asset.clone(),
+			data: self.data.clone(),
+			schema: self.schema.clone(),
+			version: self.version.clone(),
+		}
+	}
+}
+
+const CONFIG_POLL_INTERVAL: time::Duration = time::Duration::from_secs(5);
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-	// Prepare the configuration hierarchy.
 	// TODO: is it worth having a cli flag to specify the config path or is that just immense overkill?
-	let figment = Figment::new()
-		.merge(Toml::file("boilmaster.toml"))
-		.merge(Env::prefixed("BM_").split("_"));
+	let figment = figment();
+
+	// Print the fully resolved configuration and exit, for debugging deployments.
+	if env::args().nth(1).as_deref() == Some("dump-config") {
+		return dump_config(&figment);
+	}
 
 	// Initialise tracing before getting too far into bootstrapping the rest of
 	// the application. We extract only the tracing configuration first, so that
@@ -44,45 +132,77 @@ async fn main() -> anyhow::Result<()> {
 	let tracing_config = figment
 		.extract_inner::<tracing::Config>("tracing")
 		.context("failed to initialize tracing config")?;
-	tracing::init(tracing_config);
+	let tracing_reloader = tracing::init(tracing_config);
 
 	// Load the rest of the configuration.
 	let config = figment
 		.extract::<Config>()
 		.context("failed to extract config")?;
 
-	let version = Arc::new(
-		version::Manager::new(config.version).context("failed to create version manager")?,
-	);
-	let data = Arc::new(data::Data::new(config.data));
-	let asset = Arc::new(asset::Service::new(data.clone()));
-	let schema = Arc::new(
-		schema::Provider::new(config.schema, data.clone())
-			.context("failed to create schema provider")?,
-	);
-	// let search = Arc::new(search::Search::new(config.search, data.clone()).expect("TODO"));
+	let audit = Arc::new(audit::Log::new(config.audit).context("failed to open audit log")?);
+	let job = Arc::new(job::Manager::new(config.job).context("failed to create job manager")?);
+	let quota = Arc::new(quota::Quota::new(config.quota));
+	let storage = config
+		.storage
+		.map(storage::Storage::new)
+		.transpose()
+		.context("failed to create storage backend")?
+		.map(Arc::new);
+	let tenant = Tenant::new(config.tenant, job.clone(), storage.clone())?;
+	// Shared storage is keyed without regard for region, so additional tenants
+	// don't use it to avoid clobbering the default tenant's versions.
+	let tenants = config
+		.tenants
+		.into_iter()
+		.map(|(name, config)| {
+			let tenant = Tenant::new(config, job.clone(), None)
+				.with_context(|| format!("failed to create tenant {name}"))?;
+			Ok((name, tenant))
+		})
+		.collect::<anyhow::Result<Vec<_>>>()?;
+	// let search = Arc::new(search::Search::new(config.search, tenant.data.clone()).expect("TODO"));
+
+	// Keep a handle to the HTTP configuration so reload-safe settings can be
+	// updated after the server has taken ownership of it.
+	let http_config = config.http.clone();
+
+	let notifier = notify::Notifier::new(config.notify);
 
 	// Set up a cancellation token that will fire when a shutdown signal is recieved.
 	let shutdown_token = shutdown_token();
 
 	tokio::try_join!(
-		version.start(shutdown_token.clone()),
-		data.start(shutdown_token.clone(), &version)
-			.map_err(anyhow::Error::from),
-		schema
-			.start(shutdown_token.clone())
-			.map_err(anyhow::Error::from),
+		job.start(shutdown_token.clone()),
+		tenant.start(shutdown_token.clone()),
+		try_join_all(
+			tenants
+				.iter()
+				.map(|(_name, tenant)| tenant.start(shutdown_token.clone()))
+		),
+		notifier.start(shutdown_token.clone(), &tenant.version, &tenant.data, &job),
 		// search
 		// 	.start(shutdown_token.child_token())
 		// 	.map_err(anyhow::Error::from),
+		watch_config(
+			shutdown_token.clone(),
+			&tracing_reloader,
+			&audit,
+			&http_config,
+			&quota,
+			&tenant,
+			&tenants,
+		),
 		http::serve(
 			shutdown_token,
 			config.http,
-			data.clone(),
-			asset,
-			schema.clone(),
-			// search.clone(),
-			version.clone(),
+			audit.clone(),
+			job.clone(),
+			quota.clone(),
+			tenant.http(),
+			tenants
+				.iter()
+				.map(|(name, tenant)| (name.clone(), tenant.http()))
+				.collect(),
 		),
 	)
 	.context("failed to start server")?;
@@ -90,6 +210,208 @@ async fn main() -> anyhow::Result<()> {
 	Ok(())
 }
 
+fn config_files() -> Vec<String> {
+	let mut files = vec!["boilmaster.toml".to_string()];
+
+	// Profiles layer an additional file over the base configuration, i.e.
+	// `BM_PROFILE=dev` will additionally read `boilmaster.dev.toml`.
+	if let Ok(profile) = env::var("BM_PROFILE") {
+		files.push(format!("boilmaster.{profile}.toml"));
+	}
+
+	files
+}
+
+fn figment() -> Figment {
+	let figment = config_files()
+		.into_iter()
+		.fold(Figment::new(), |figment, file| {
+			figment.merge(Toml::file(file))
+		});
+
+	// Splitting on `_` can't address keys that contain an underscore, such as
+	// `version.patch.user_agent` - a `__` separator is also accepted for those.
+	figment
+		.merge(
+			Env::prefixed("BM_")
+				.ignore(&["PROFILE"])
+				.filter(|key| !key.as_str().contains("__"))
+				.split("_"),
+		)
+		.merge(
+			Env::prefixed("BM_")
+				.filter(|key| key.as_str().contains("__"))
+				.split("__"),
+		)
+}
+
+async fn watch_config(
+	cancel: CancellationToken,
+	tracing: &tracing::Reloader,
+	audit: &audit::Log,
+	http: &http::Config,
+	quota: &quota::Quota,
+	tenant: &Tenant,
+	tenants: &[(String, Tenant)],
+) -> anyhow::Result<()> {
+	let mut interval = time::interval(CONFIG_POLL_INTERVAL);
+	interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+	let mut last_modified = config_modified();
+
+	loop {
+		select! {
+			_ = interval.tick() => {}
+			_ = cancel.cancelled() => return Ok(()),
+		}
+
+		let modified = config_modified();
+		if modified == last_modified {
+			continue;
+		}
+		last_modified = modified;
+
+		::tracing::info!("configuration changed, reloading");
+
+		// A broken config file shouldn't take down a running server - keep the
+		// current settings until it's fixed.
+		let result = reload_config(tracing, http, quota, tenant, tenants);
+		audit.record(
+			"config",
+			"config_reload",
+			serde_json::json!({ "success": result.is_ok() }),
+		);
+		if let Err(error) = result {
+			::tracing::error!(?error, "failed to reload configuration");
+		}
+	}
+}
+
+fn config_modified() -> Vec<Option<SystemTime>> {
+	config_files()
+		.iter()
+		.map(|file| {
+			fs::metadata(file)
+				.and_then(|metadata| metadata.modified())
+				.ok()
+		})
+		.collect()
+}
+
+fn reload_config(
+	tracing: &tracing::Reloader,
+	http: &http::Config,
+	quota: &quota::Quota,
+	tenant: &Tenant,
+	tenants: &[(String, Tenant)],
+) -> anyhow::Result<()> {
+	let figment = figment();
+
+	// Extract everything up front, so a partially invalid file doesn't leave
+	// subsystems with a mix of old and new settings.
+	let tracing_config = figment
+		.extract_inner::<tracing::Config>("tracing")
+		.context("failed to extract tracing config")?;
+	let mut config = figment
+		.extract::<Config>()
+		.context("failed to extract config")?;
+
+	tracing
+		.reload(tracing_config)
+		.context("failed to reload tracing filters")?;
+	http.reload(config.http);
+	quota.reload(config.quota);
+	tenant.reload(config.tenant);
+
+	// Tenants are only created at startup - adding or removing them requires a restart.
+	for (name, tenant) in tenants {
+		match config.tenants.remove(name) {
+			Some(tenant_config) => tenant.reload(tenant_config),
+			None => ::tracing::warn!(name, "tenant removed from configuration, restart to apply"),
+		}
+	}
+	for name in config.tenants.keys() {
+		::tracing::warn!(name, "tenant added to configuration, restart to apply");
+	}
+
+	Ok(())
+}
+
+/// Dotted paths to configuration values holding credentials, which are
+/// redacted when configuration is dumped. `*` matches every key of a table, or
+/// every item of an array.
+const SECRET_PATHS: &[&str] = &[
+	"http.admin.auth.password",
+	"notify.webhooks.*.url",
+	"notify.hooks.*.headers.*",
+	"version.bootstrap.password",
+	"tenants.*.version.bootstrap.password",
+];
+
+/// Dotted paths to tables whose keys are themselves credentials, i.e. API keys.
+const SECRET_KEY_PATHS: &[&str] = &["quota.keys"];
+
+const REDACTED: &str = "(redacted)";
+
+fn dump_config(figment: &Figment) -> anyhow::Result<()> {
+	// Parse the configuration first, such that invalid settings are reported
+	// rather than dumped.
+	figment
+		.extract_inner::<tracing::Config>("tracing")
+		.context("failed to extract tracing config")?;
+	figment
+		.extract::<Config>()
+		.context("failed to extract config")?;
+
+	// The merged values are printed, rather than the parsed configuration, such
+	// that the output reflects exactly what was read from files and environment.
+	let mut value = figment
+		.extract::<Value>()
+		.context("failed to extract config values")?;
+
+	for path in SECRET_PATHS {
+		visit_path(&mut value, &path.split('.').collect::<Vec<_>>(), &|value| {
+			*value = Value::from(REDACTED.to_string());
+		});
+	}
+
+	for path in SECRET_KEY_PATHS {
+		visit_path(&mut value, &path.split('.').collect::<Vec<_>>(), &|value| {
+			if let Value::Dict(_, dict) = value {
+				*dict = std::mem::take(dict)
+					.into_values()
+					.enumerate()
+					.map(|(index, value)| (format!("{REDACTED} {}", index + 1), value))
+					.collect();
+			}
+		});
+	}
+
+	println!("{}", serde_json::to_string_pretty(&value)?);
+
+	Ok(())
+}
+
+/// Call the function on every value matching the path. Paths that don't exist
+/// in the value are ignored.
+fn visit_path(value: &mut Value, path: &[&str], function: &impl Fn(&mut Value)) {
+	let Some((segment, rest)) = path.split_first() else {
+		function(value);
+		return;
+	};
+
+	let children: Vec<&mut Value> = match (value, *segment) {
+		(Value::Dict(_, dict), "*") => dict.values_mut().collect(),
+		(Value::Dict(_, dict), key) => dict.get_mut(key).into_iter().collect(),
+		(Value::Array(_, items), "*") => items.iter_mut().collect(),
+		_ => vec![],
+	};
+
+	for child in children {
+		visit_path(child, rest, function);
+	}
+}
+
 fn shutdown_token() -> CancellationToken {
 	// Create a token to represent the shutdown signal.
 	let token = CancellationToken::new();
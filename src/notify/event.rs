@@ -0,0 +1,75 @@
+use std::{fmt, path::PathBuf};
+
+use serde::Deserialize;
+
+use crate::version::VersionKey;
+
+/// Category of event, used to select the events a webhook is interested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+	Version,
+	IngestionCompleted,
+	IngestionFailed,
+	DiskSpace,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+	/// A new or updated version has been detected.
+	Version(VersionKey),
+
+	IngestionCompleted {
+		description: String,
+	},
+
+	IngestionFailed {
+		description: String,
+		error: Option<String>,
+	},
+
+	/// Available space on the filesystem containing a path has dropped below the
+	/// configured minimum.
+	DiskSpace {
+		path: PathBuf,
+		available: u64,
+	},
+}
+
+impl Event {
+	pub fn kind(&self) -> EventKind {
+		match self {
+			Self::Version(..) => EventKind::Version,
+			Self::IngestionCompleted { .. } => EventKind::IngestionCompleted,
+			Self::IngestionFailed { .. } => EventKind::IngestionFailed,
+			Self::DiskSpace { .. } => EventKind::DiskSpace,
+		}
+	}
+}
+
+impl fmt::Display for Event {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Version(key) => write!(formatter, "New version detected: `{key}`"),
+
+			Self::IngestionCompleted { description } => {
+				write!(formatter, "Ingestion completed: {description}")
+			}
+
+			Self::IngestionFailed { description, error } => {
+				write!(formatter, "Ingestion failed: {description}")?;
+				if let Some(error) = error {
+					write!(formatter, " - {error}")?;
+				}
+				Ok(())
+			}
+
+			Self::DiskSpace { path, available } => write!(
+				formatter,
+				"Low disk space: {:.1} GiB available for `{}`",
+				*available as f64 / (1024 * 1024 * 1024) as f64,
+				path.display()
+			),
+		}
+	}
+}
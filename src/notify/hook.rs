@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use reqwest::Method;
+use serde::Deserialize;
+use strum::Display;
+
+use crate::{utility::secret::Secret, version::VersionKey};
+
+/// Transition of version state that fires a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum HookTrigger {
+	/// A version's data has been prepared, and is being served.
+	VersionReady,
+	/// The version served as `latest` has changed to another ready version.
+	LatestChanged,
+}
+
+/// HTTP call made when version state changes, i.e. to purge CDN caches. The
+/// URL and body may contain `{trigger}`, `{version}`, and `{previous}`
+/// placeholders, replaced by the trigger, the version key, and - for
+/// `latest_changed` - the key of the previous latest version.
+#[derive(Debug, Deserialize)]
+pub struct HookConfig {
+	/// Transitions that fire this hook.
+	on: Vec<HookTrigger>,
+	url: String,
+	/// HTTP method to call the URL with, i.e. `PURGE`. Defaults to `POST`.
+	method: Option<String>,
+	#[serde(default)]
+	headers: HashMap<String, Secret<String>>,
+	body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookEvent {
+	pub trigger: HookTrigger,
+	pub version: VersionKey,
+	pub previous: Option<VersionKey>,
+}
+
+impl HookConfig {
+	pub fn handles(&self, event: &HookEvent) -> bool {
+		self.on.contains(&event.trigger)
+	}
+
+	/// Build the request to make for an event.
+	pub fn request(
+		&self,
+		client: &reqwest::Client,
+		event: &HookEvent,
+	) -> anyhow::Result<reqwest::RequestBuilder> {
+		let method = match &self.method {
+			Some(method) => Method::from_bytes(method.as_bytes())?,
+			None => Method::POST,
+		};
+
+		let mut request = client.request(method, render(&self.url, event));
+		for (name, value) in &self.headers {
+			request = request.header(name, render(value.expose(), event));
+		}
+		if let Some(body) = &self.body {
+			request = request.body(render(body, event));
+		}
+
+		Ok(request)
+	}
+}
+
+fn render(template: &str, event: &HookEvent) -> String {
+	template
+		.replace("{trigger}", &event.trigger.to_string())
+		.replace("{version}", &event.version.to_string())
+		.replace(
+			"{previous}",
+			&event
+				.previous
+				.map(|key| key.to_string())
+				.unwrap_or_default(),
+		)
+}
+
+/// Tracks the ready versions and latest version, to detect the transitions
+/// that fire hooks.
+#[derive(Default)]
+pub struct HookState {
+	ready: Option<HashSet<VersionKey>>,
+	latest: Option<VersionKey>,
+}
+
+impl HookState {
+	/// Update the tracked state, returning events for any transitions. The first
+	/// non-empty set of ready versions is treated as a baseline, such that
+	/// versions prepared on startup don't fire hooks.
+	pub fn update(
+		&mut self,
+		ready: impl IntoIterator<Item = VersionKey>,
+		latest: Option<VersionKey>,
+	) -> Vec<HookEvent> {
+		let ready = ready.into_iter().collect::<HashSet<_>>();
+
+		let Some(known) = &mut self.ready else {
+			if !ready.is_empty() {
+				self.latest = latest.filter(|key| ready.contains(key));
+				self.ready = Some(ready);
+			}
+			return vec![];
+		};
+
+		let mut events = ready
+			.iter()
+			.filter(|key| !known.contains(key))
+			.map(|key| HookEvent {
+				trigger: HookTrigger::VersionReady,
+				version: *key,
+				previous: None,
+			})
+			.collect::<Vec<_>>();
+
+		// Latest only moves once the version it moves to is being served.
+		if let Some(latest) = latest.filter(|key| ready.contains(key)) {
+			if self.latest != Some(latest) {
+				events.push(HookEvent {
+					trigger: HookTrigger::LatestChanged,
+					version: latest,
+					previous: self.latest,
+				});
+				self.latest = Some(latest);
+			}
+		}
+
+		*known = ready;
+
+		events
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn key(value: &str) -> VersionKey {
+		value.parse().unwrap()
+	}
+
+	#[test]
+	fn baseline_does_not_fire() {
+		let mut state = HookState::default();
+		assert_eq!(state.update([], None), vec![]);
+		assert_eq!(state.update([key("1")], Some(key("1"))), vec![]);
+		assert_eq!(state.update([key("1")], Some(key("1"))), vec![]);
+	}
+
+	#[test]
+	fn ready_then_latest() {
+		let mut state = HookState::default();
+		state.update([key("1")], Some(key("1")));
+
+		// Latest moves before the version is ready - only the eventual readiness fires.
+		assert_eq!(state.update([key("1")], Some(key("2"))), vec![]);
+		assert_eq!(
+			state.update([key("1"), key("2")], Some(key("2"))),
+			vec![
+				HookEvent {
+					trigger: HookTrigger::VersionReady,
+					version: key("2"),
+					previous: None,
+				},
+				HookEvent {
+					trigger: HookTrigger::LatestChanged,
+					version: key("2"),
+					previous: Some(key("1")),
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn render_placeholders() {
+		let event = HookEvent {
+			trigger: HookTrigger::LatestChanged,
+			version: key("2"),
+			previous: Some(key("1")),
+		};
+		assert_eq!(
+			render("{trigger}:{version}:{previous}", &event),
+			format!("latest_changed:{}:{}", key("2"), key("1"))
+		);
+	}
+}
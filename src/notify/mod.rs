@@ -0,0 +1,5 @@
+mod event;
+mod hook;
+mod notifier;
+
+pub use notifier::{Config, Notifier};
@@ -0,0 +1,267 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use figment::value::magic::RelativePathBuf;
+use futures::future;
+use serde::Deserialize;
+use serde_json::json;
+use tokio::{select, time};
+use tokio_util::sync::CancellationToken;
+
+use crate::{data, job, utility::secret::Secret, version};
+
+use super::{
+	event::{Event, EventKind},
+	hook::{HookConfig, HookEvent, HookState},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	#[serde(default)]
+	webhooks: Vec<WebhookConfig>,
+	disk: Option<DiskConfig>,
+
+	/// HTTP calls made when versions become ready, or `latest` moves, i.e. to
+	/// purge CDN caches.
+	#[serde(default)]
+	hooks: Vec<HookConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WebhookConfig {
+	kind: WebhookKind,
+	url: Secret<String>,
+	/// Events to post to this webhook. When omitted, all events are posted.
+	events: Option<Vec<EventKind>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum WebhookKind {
+	Discord,
+	Slack,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiskConfig {
+	interval: u64,
+	/// Minimum available space, in bytes, before a warning is posted.
+	minimum: u64,
+	paths: Vec<RelativePathBuf>,
+}
+
+/// Posts operational events to chat webhooks, and calls hooks on version
+/// state changes.
+pub struct Notifier {
+	client: reqwest::Client,
+	webhooks: Vec<WebhookConfig>,
+	disk: Option<DiskConfig>,
+	hooks: Vec<HookConfig>,
+}
+
+impl Notifier {
+	pub fn new(config: Config) -> Self {
+		Self {
+			client: reqwest::Client::new(),
+			webhooks: config.webhooks,
+			disk: config.disk,
+			hooks: config.hooks,
+		}
+	}
+
+	pub async fn start(
+		&self,
+		cancel: CancellationToken,
+		version: &version::Manager,
+		data: &data::Data,
+		job: &job::Manager,
+	) -> Result<()> {
+		let watch_webhooks = async {
+			if self.webhooks.is_empty() {
+				return;
+			}
+			future::join(self.watch_events(version, job), self.watch_disk()).await;
+		};
+
+		select! {
+			_ = future::join(watch_webhooks, self.watch_hooks(version, data)) => {}
+			_ = cancel.cancelled() => {}
+		}
+
+		Ok(())
+	}
+
+	async fn watch_events(&self, version: &version::Manager, job: &job::Manager) {
+		let mut version_receiver = version.subscribe();
+		let mut job_receiver = job.subscribe();
+
+		// The first non-empty version list is treated as a baseline, so that
+		// versions hydrated from disk on startup aren't announced as new.
+		let mut known_versions = None::<HashSet<version::VersionKey>>;
+
+		loop {
+			select! {
+				Ok(()) = version_receiver.changed() => {
+					let keys = version_receiver.borrow_and_update().clone();
+					match &mut known_versions {
+						None => {
+							if !keys.is_empty() {
+								known_versions = Some(keys.into_iter().collect());
+							}
+						}
+						Some(known) => {
+							for key in keys {
+								if known.insert(key) {
+									self.notify(Event::Version(key)).await;
+								}
+							}
+						}
+					}
+				}
+
+				Ok(job) = job_receiver.recv() => {
+					if let Some(event) = job_event(job) {
+						self.notify(event).await;
+					}
+				}
+			}
+		}
+	}
+
+	async fn watch_disk(&self) {
+		let Some(disk) = &self.disk else {
+			return;
+		};
+
+		let mut interval = time::interval(time::Duration::from_secs(disk.interval));
+		interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+		// Paths that are currently below the minimum. Notifications are only sent
+		// when a path first drops below the minimum, rather than on every check.
+		let mut low_paths = HashSet::new();
+
+		loop {
+			interval.tick().await;
+
+			for path in disk.paths.iter().map(RelativePathBuf::relative) {
+				let available = match fs4::available_space(&path) {
+					Ok(available) => available,
+					Err(error) => {
+						tracing::warn!(?path, ?error, "could not check available disk space");
+						continue;
+					}
+				};
+
+				if available >= disk.minimum {
+					low_paths.remove(&path);
+					continue;
+				}
+
+				if low_paths.insert(path.clone()) {
+					self.notify(Event::DiskSpace { path, available }).await;
+				}
+			}
+		}
+	}
+
+	async fn watch_hooks(&self, version: &version::Manager, data: &data::Data) {
+		if self.hooks.is_empty() {
+			return;
+		}
+
+		let mut version_receiver = version.subscribe();
+		let mut data_receiver = data.subscribe();
+		let mut state = HookState::default();
+
+		loop {
+			// Latest may move without the prepared versions changing, i.e. when
+			// renamed - check both whenever either changes.
+			let ready = data_receiver.borrow_and_update().clone();
+			version_receiver.borrow_and_update();
+			for event in state.update(ready, version.resolve(None)) {
+				self.call_hooks(event).await;
+			}
+
+			select! {
+				Ok(()) = version_receiver.changed() => {}
+				Ok(()) = data_receiver.changed() => {}
+				else => break,
+			}
+		}
+	}
+
+	async fn call_hooks(&self, event: HookEvent) {
+		tracing::debug!(?event, "calling hooks");
+
+		let hooks = self.hooks.iter().filter(|hook| hook.handles(&event));
+		for hook in hooks {
+			let result = match hook.request(&self.client, &event) {
+				Ok(request) => request
+					.send()
+					.await
+					.and_then(|response| response.error_for_status())
+					.map_err(anyhow::Error::from),
+				Err(error) => Err(error),
+			};
+
+			// As with notifications, a failing hook shouldn't impact anything else.
+			if let Err(error) = result {
+				tracing::warn!(?error, "failed to call hook");
+			}
+		}
+	}
+
+	async fn notify(&self, event: Event) {
+		let kind = event.kind();
+		let message = event.to_string();
+
+		tracing::debug!(?kind, "posting notification: {message}");
+
+		let webhooks = self
+			.webhooks
+			.iter()
+			.filter(|webhook| match &webhook.events {
+				Some(events) => events.contains(&kind),
+				None => true,
+			});
+
+		for webhook in webhooks {
+			let body = match webhook.kind {
+				WebhookKind::Discord => json!({ "content": message }),
+				WebhookKind::Slack => json!({ "text": message }),
+			};
+
+			let result = self
+				.client
+				.post(webhook.url.expose())
+				.json(&body)
+				.send()
+				.await
+				.and_then(|response| response.error_for_status());
+
+			// Failing to post a notification shouldn't impact anything else.
+			if let Err(error) = result {
+				tracing::warn!(?error, "failed to post notification");
+			}
+		}
+	}
+}
+
+fn job_event(job: job::Job) -> Option<Event> {
+	if job.kind != job::Kind::Ingestion {
+		return None;
+	}
+
+	let event = match job.state {
+		job::State::Completed => Event::IngestionCompleted {
+			description: job.description,
+		},
+		job::State::Failed => Event::IngestionFailed {
+			description: job.description,
+			error: job.error,
+		},
+		_ => return None,
+	};
+
+	Some(event)
+}
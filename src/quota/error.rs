@@ -0,0 +1,7 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+	#[error("daily quota of {0} units exceeded")]
+	Exceeded(u32),
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
@@ -0,0 +1,7 @@
+mod error;
+mod quota;
+
+pub use {
+	error::Error,
+	quota::{Config, Consumer, Quota, QuotaStatus},
+};
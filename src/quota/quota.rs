@@ -0,0 +1,262 @@
+use std::{
+	collections::HashMap,
+	net::IpAddr,
+	sync::Mutex,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+
+use crate::utility::{reloadable::Reloadable, secret::Secret};
+
+use super::error::{Error, Result};
+
+const SECONDS_PER_DAY: u64 = 60 * 60 * 24;
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Config {
+	/// Maximum number of units permitted per UTC day for each anonymous client,
+	/// identified by its address.
+	anonymous: u32,
+
+	/// Maximum number of units permitted per UTC day for each recognised API key.
+	keyed: u32,
+
+	/// Number of units charged for each asset conversion.
+	conversion_cost: u32,
+
+	/// Recognised API keys. Requests with an unrecognised key are treated as
+	/// anonymous.
+	#[serde(default)]
+	keys: HashMap<Secret<String>, KeyConfig>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+struct KeyConfig {
+	/// Override of the keyed daily limit for this key.
+	daily: Option<u32>,
+}
+
+/// Identity of the client a request is charged to.
+#[derive(Debug, Clone, Copy)]
+pub struct Consumer<'a> {
+	/// API key provided with the request, if any.
+	pub api_key: Option<&'a str>,
+
+	/// Address of the client. Anonymous usage is tracked per address - requests
+	/// without one share a single budget.
+	pub address: Option<IpAddr>,
+}
+
+/// Consumption of a consumer's quota for the current day.
+#[derive(Debug, Clone, Copy)]
+pub struct QuotaStatus {
+	pub limit: u32,
+	pub used: u32,
+}
+
+impl QuotaStatus {
+	pub fn remaining(&self) -> u32 {
+		self.limit.saturating_sub(self.used)
+	}
+}
+
+/// Tracks daily usage of expensive operations, shared by every endpoint that
+/// performs them. Operations are charged in units reflecting their cost, i.e.
+/// asset conversions are charged a configured fixed cost.
+pub struct Quota {
+	config: Reloadable<Config>,
+	usage: Mutex<Usage>,
+}
+
+#[derive(Default)]
+struct Usage {
+	day: u64,
+	units: HashMap<UsageKey, u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum UsageKey {
+	Keyed(String),
+	Anonymous(Option<IpAddr>),
+}
+
+impl Quota {
+	pub fn new(config: Config) -> Self {
+		Self {
+			config: Reloadable::new(config),
+			usage: Default::default(),
+		}
+	}
+
+	/// Apply updated limits and keys. Usage recorded so far today is retained,
+	/// and counted against the new limits.
+	pub fn reload(&self, config: Config) {
+		if self.config.set(config) {
+			tracing::info!("quota configuration changed");
+		}
+	}
+
+	/// Number of units to charge for an asset conversion.
+	pub fn conversion_cost(&self) -> u32 {
+		self.config.borrow().conversion_cost
+	}
+
+	/// Current usage of the consumer's quota, without charging for anything.
+	pub fn status(&self, consumer: Consumer) -> QuotaStatus {
+		let (key, limit) = self.tier(consumer);
+		self.with_usage(|units| QuotaStatus {
+			limit,
+			used: units.get(&key).copied().unwrap_or(0),
+		})
+	}
+
+	/// Charge units to the consumer, if it has enough quota remaining for the
+	/// current day. Nothing is charged if the units would exceed the limit.
+	pub fn try_consume(&self, consumer: Consumer, count: u32) -> Result<QuotaStatus> {
+		let (key, limit) = self.tier(consumer);
+		self.with_usage(|units| {
+			let used = units.entry(key).or_default();
+			match used.checked_add(count) {
+				Some(total) if total <= limit => {
+					*used = total;
+					Ok(QuotaStatus { limit, used: total })
+				}
+				_ => Err(Error::Exceeded(limit)),
+			}
+		})
+	}
+
+	/// Return units charged for an operation that could not be completed.
+	pub fn refund(&self, consumer: Consumer, count: u32) {
+		let (key, _) = self.tier(consumer);
+		self.with_usage(|units| {
+			if let Some(used) = units.get_mut(&key) {
+				*used = used.saturating_sub(count);
+			}
+		})
+	}
+
+	/// Resolve the usage key and daily limit for a consumer.
+	fn tier(&self, consumer: Consumer) -> (UsageKey, u32) {
+		let config = self.config.borrow();
+		let recognised = consumer
+			.api_key
+			.and_then(|key| Some((key, config.keys.get(key)?)));
+		match recognised {
+			Some((key, key_config)) => (
+				UsageKey::Keyed(key.to_string()),
+				key_config.daily.unwrap_or(config.keyed),
+			),
+			None => (UsageKey::Anonymous(consumer.address), config.anonymous),
+		}
+	}
+
+	fn with_usage<T>(&self, function: impl FnOnce(&mut HashMap<UsageKey, u32>) -> T) -> T {
+		let mut usage = self.usage.lock().expect("poisoned");
+
+		// Quotas reset at the start of each day - drop any prior usage.
+		let today = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+			.unwrap_or(0);
+		if usage.day != today {
+			usage.day = today;
+			usage.units.clear();
+		}
+
+		function(&mut usage.units)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::net::Ipv4Addr;
+
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn quota() -> Quota {
+		Quota::new(Config {
+			anonymous: 10,
+			keyed: 100,
+			conversion_cost: 5,
+			keys: HashMap::from([
+				("known".to_string().into(), KeyConfig::default()),
+				(
+					"override".to_string().into(),
+					KeyConfig { daily: Some(1000) },
+				),
+			]),
+		})
+	}
+
+	fn consumer(api_key: Option<&str>, address: u8) -> Consumer {
+		Consumer {
+			api_key,
+			address: Some(IpAddr::from(Ipv4Addr::new(192, 0, 2, address))),
+		}
+	}
+
+	#[test]
+	fn tiers() {
+		let quota = quota();
+		assert_eq!(quota.status(consumer(None, 1)).limit, 10);
+		assert_eq!(quota.status(consumer(Some("unknown"), 1)).limit, 10);
+		assert_eq!(quota.status(consumer(Some("known"), 1)).limit, 100);
+		assert_eq!(quota.status(consumer(Some("override"), 1)).limit, 1000);
+	}
+
+	#[test]
+	fn unrecognised_keys_share_anonymous_usage() {
+		let quota = quota();
+		quota.try_consume(consumer(Some("unknown"), 1), 4).unwrap();
+		assert_eq!(quota.try_consume(consumer(None, 1), 4).unwrap().used, 8);
+		assert_eq!(quota.status(consumer(Some("known"), 1)).used, 0);
+	}
+
+	#[test]
+	fn anonymous_usage_per_address() {
+		let quota = quota();
+		quota.try_consume(consumer(None, 1), 10).unwrap();
+		assert_eq!(quota.status(consumer(None, 1)).remaining(), 0);
+		assert_eq!(quota.status(consumer(None, 2)).used, 0);
+	}
+
+	#[test]
+	fn exceeded() {
+		let quota = quota();
+		quota.try_consume(consumer(None, 1), 8).unwrap();
+		assert!(matches!(
+			quota.try_consume(consumer(None, 1), 5),
+			Err(Error::Exceeded(10))
+		));
+		// Rejected charges are not recorded.
+		assert_eq!(quota.status(consumer(None, 1)).used, 8);
+		assert!(quota.try_consume(consumer(Some("known"), 1), 5).is_ok());
+	}
+
+	#[test]
+	fn refund() {
+		let quota = quota();
+		quota.try_consume(consumer(None, 1), 5).unwrap();
+		quota.refund(consumer(None, 1), 5);
+		assert_eq!(quota.status(consumer(None, 1)).used, 0);
+	}
+
+	#[test]
+	fn reload_retains_usage() {
+		let quota = quota();
+		quota.try_consume(consumer(None, 1), 5).unwrap();
+		quota.reload(Config {
+			anonymous: 20,
+			keyed: 100,
+			conversion_cost: 5,
+			keys: HashMap::new(),
+		});
+		let status = quota.status(consumer(None, 1));
+		assert_eq!((status.limit, status.used), (20, 5));
+		assert_eq!(quota.status(consumer(Some("known"), 1)).limit, 20);
+	}
+}
@@ -0,0 +1,216 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use ironworks::excel;
+use ironworks_schema as schema;
+use nohash_hasher::IntMap;
+
+use super::{
+	error::Result,
+	filter::{Filter, Language},
+	read::{read, ReferenceMode},
+	sentinel::Sentinels,
+	transform::field_number,
+	value::{Reference, StructKey, Value},
+};
+
+/// A condition on the value of a top-level field of a row, i.e.
+/// `IsUntradable=0`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Condition {
+	pub field: String,
+	pub value: String,
+}
+
+/// A filter with portions that only apply to rows meeting a condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConditionalFilter {
+	/// Every field that may be selected, regardless of conditions.
+	filter: Filter,
+	/// Fields selected for every row.
+	base: Filter,
+	branches: Vec<(Condition, Filter)>,
+}
+
+impl ConditionalFilter {
+	/// Build a conditional filter from the fields selected for every row, and
+	/// those selected only when their condition is met. Returns `None` if the
+	/// filters target incompatible structures.
+	pub fn new(base: Filter, branches: Vec<(Condition, Filter)>) -> Option<Self> {
+		let filter = branches
+			.iter()
+			.try_fold(base.clone(), |filter, (_, branch)| {
+				filter.merge(branch.clone())
+			})?;
+
+		Some(Self {
+			filter,
+			base,
+			branches,
+		})
+	}
+
+	/// Filter selecting every field that may be read, regardless of conditions.
+	pub fn filter(&self) -> &Filter {
+		&self.filter
+	}
+
+	/// Resolve the filter to use for a single row, evaluating each condition
+	/// against that row's data.
+	pub fn resolve(
+		&self,
+		excel: &excel::Excel,
+		schema: &dyn schema::Schema,
+		sheet_name: &str,
+		row_id: u32,
+		subrow_id: u16,
+		language: excel::Language,
+	) -> Result<Cow<Filter>> {
+		if self.branches.is_empty() {
+			return Ok(Cow::Borrowed(&self.filter));
+		}
+
+		let mut results = HashMap::<&Condition, bool>::new();
+		let mut output = self.base.clone();
+
+		for (condition, branch) in &self.branches {
+			let met = match results.get(condition) {
+				Some(met) => *met,
+				None => {
+					let met = condition
+						.evaluate(excel, schema, sheet_name, row_id, subrow_id, language)?;
+					results.insert(condition, met);
+					met
+				}
+			};
+
+			if met {
+				output = output
+					.merge(branch.clone())
+					.expect("branches were merged successfully on construction");
+			}
+		}
+
+		Ok(Cow::Owned(output))
+	}
+}
+
+impl From<Filter> for ConditionalFilter {
+	fn from(filter: Filter) -> Self {
+		Self {
+			base: filter.clone(),
+			filter,
+			branches: vec![],
+		}
+	}
+}
+
+impl Condition {
+	fn evaluate(
+		&self,
+		excel: &excel::Excel,
+		schema: &dyn schema::Schema,
+		sheet_name: &str,
+		row_id: u32,
+		subrow_id: u16,
+		language: excel::Language,
+	) -> Result<bool> {
+		let mut language_map = IntMap::default();
+		language_map.insert(Language(language), Filter::All);
+		let filter = Filter::Struct(HashMap::from([(self.field.clone(), language_map)]));
+
+		// Conditions only consider the value of the field itself - references are
+		// compared by their target, so there's no need to follow them.
+		let (value, _) = read(
+			excel,
+			schema,
+			sheet_name,
+			row_id,
+			subrow_id,
+			language,
+			&filter,
+			&Sentinels::new(),
+			0,
+			ReferenceMode::Full,
+		)?
+		.decompose();
+
+		let field = match value {
+			Value::Struct(mut fields) => fields.remove(&StructKey {
+				name: self.field.clone(),
+				language,
+			}),
+			_ => None,
+		};
+
+		// Fields missing from the row can never meet a condition.
+		Ok(field.map_or(false, |field| self.matches(&field)))
+	}
+
+	fn matches(&self, value: &Value) -> bool {
+		match value {
+			Value::Scalar(field) | Value::Interpreted(field, _) => self.matches_field(field),
+			Value::Icon(id) => self.value == id.to_string(),
+			Value::Reference(Reference::Scalar(target)) => self.value == target.to_string(),
+			Value::Reference(Reference::Populated { value, .. }) => self.value == value.to_string(),
+			Value::Array(_) | Value::Struct(_) | Value::Unsupported(_) => false,
+		}
+	}
+
+	fn matches_field(&self, field: &excel::Field) -> bool {
+		match field {
+			excel::Field::String(string) => self.value == string.to_string(),
+			excel::Field::Bool(bool) => match self.value.as_str() {
+				"true" | "1" => *bool,
+				"false" | "0" => !*bool,
+				_ => false,
+			},
+			field => match (field_number(field), self.value.parse::<f64>()) {
+				(Some(actual), Ok(expected)) => actual == expected,
+				_ => false,
+			},
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn condition(value: &str) -> Condition {
+		Condition {
+			field: "Field".into(),
+			value: value.into(),
+		}
+	}
+
+	#[test]
+	fn matches_numbers() {
+		let value = Value::Scalar(excel::Field::U8(0));
+		assert!(condition("0").matches(&value));
+		assert!(condition("0.0").matches(&value));
+		assert!(!condition("1").matches(&value));
+		assert!(!condition("zero").matches(&value));
+	}
+
+	#[test]
+	fn matches_bools() {
+		let value = Value::Scalar(excel::Field::Bool(true));
+		assert!(condition("true").matches(&value));
+		assert!(condition("1").matches(&value));
+		assert!(!condition("false").matches(&value));
+	}
+
+	#[test]
+	fn matches_references() {
+		let value = Value::Reference(Reference::Scalar(-1));
+		assert!(condition("-1").matches(&value));
+		assert!(!condition("1").matches(&value));
+	}
+
+	#[test]
+	fn unsupported_never_matches() {
+		let value = Value::Unsupported(ironworks::file::exh::ColumnKind::String);
+		assert!(!condition("").matches(&value));
+		assert!(!condition("0").matches(&value));
+	}
+}
@@ -0,0 +1,373 @@
+use std::collections::HashMap;
+
+use ironworks::excel;
+use ironworks_schema as schema;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+	error::{check_cancelled, Result},
+	filter::Filter,
+	read::{read, ReferenceMode},
+	sentinel::Sentinels,
+	value::{Reference, Value},
+};
+
+/// Directories containing dialogue text sheets, i.e. `quest/000/ClsHrv001_00003`.
+const DIALOGUE_DIRECTORIES: &[&str] = &["quest", "cut_scene"];
+
+/// Key segments that describe the structure of a dialogue script rather than
+/// who is speaking, i.e. `SEQ` in `TEXT_CLSHRV001_00003_SEQ_00`.
+const NON_SPEAKER_SEGMENTS: &[&str] = &["SEQ", "TODO", "SYSTEM"];
+
+/// A single line of dialogue text.
+#[derive(Debug, Clone)]
+pub struct DialogueLine {
+	/// Dialogue sheet containing the line.
+	pub sheet: String,
+
+	/// ID of the row holding the line.
+	pub row_id: u32,
+
+	/// Script key of the line, i.e. `TEXT_CLSHRV001_00003_YSHTOLA_000_10`.
+	pub key: String,
+
+	/// Name of the quest or cutscene the line belongs to, taken from the final
+	/// segment of the sheet path.
+	pub quest: String,
+
+	/// Expansion the owning quest belongs to, if it could be determined.
+	pub expansion: Option<u32>,
+
+	/// Speaker of the line, as encoded in the script key, if any.
+	pub speaker: Option<String>,
+
+	/// Text of the line.
+	pub text: String,
+	text_lowercase: String,
+}
+
+/// Filters for a dialogue search. Text matches are case-insensitive, and
+/// require every whitespace-separated term to be present.
+#[derive(Debug, Default)]
+pub struct DialogueQuery {
+	pub text: Option<String>,
+	pub quest: Option<String>,
+	pub speaker: Option<String>,
+	pub expansion: Option<u32>,
+}
+
+/// Lines of dialogue across every dialogue sheet in a version.
+#[derive(Debug, Default)]
+pub struct Dialogue {
+	lines: Vec<DialogueLine>,
+}
+
+impl Dialogue {
+	pub fn search<'a>(
+		&'a self,
+		query: &'a DialogueQuery,
+	) -> impl Iterator<Item = &'a DialogueLine> {
+		let terms = query
+			.text
+			.as_deref()
+			.unwrap_or_default()
+			.to_lowercase()
+			.split_whitespace()
+			.map(str::to_string)
+			.collect::<Vec<_>>();
+
+		self.lines.iter().filter(move |line| {
+			let matches_filter = |filter: &Option<String>, value: Option<&str>| match filter {
+				None => true,
+				Some(filter) => value.map_or(false, |value| value.eq_ignore_ascii_case(filter)),
+			};
+
+			matches_filter(&query.quest, Some(&line.quest))
+				&& matches_filter(&query.speaker, line.speaker.as_deref())
+				&& query
+					.expansion
+					.map_or(true, |expansion| line.expansion == Some(expansion))
+				&& terms
+					.iter()
+					.all(|term| line.text_lowercase.contains(term.as_str()))
+		})
+	}
+}
+
+/// Build the dialogue of a version, reading the text of every row in each
+/// dialogue sheet. Quest expansions are looked up via the `Quest` sheet, as
+/// declared by the schema. This reads a very large amount of data, and is
+/// accordingly expensive.
+pub fn dialogue(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	language: excel::Language,
+	cancel: &CancellationToken,
+) -> Result<Dialogue> {
+	let expansions = quest_expansions(excel, schema, language);
+
+	let mut lines = vec![];
+
+	let list = excel.list()?;
+	for sheet_name in list.iter() {
+		check_cancelled(cancel)?;
+
+		let Some((directory, _)) = sheet_name.split_once('/') else {
+			continue;
+		};
+		if !DIALOGUE_DIRECTORIES.contains(&directory) {
+			continue;
+		}
+
+		let quest = sheet_name
+			.rsplit('/')
+			.next()
+			.unwrap_or_default()
+			.to_string();
+
+		if let Err(error) = read_sheet_lines(
+			excel,
+			&sheet_name,
+			&quest,
+			expansions.get(&quest.to_lowercase()).copied(),
+			language,
+			&mut lines,
+		) {
+			// A single malformed sheet shouldn't prevent searching the rest.
+			tracing::warn!(sheet = %sheet_name, ?error, "could not read dialogue");
+		}
+	}
+
+	Ok(Dialogue { lines })
+}
+
+fn read_sheet_lines(
+	excel: &excel::Excel,
+	sheet_name: &str,
+	quest: &str,
+	expansion: Option<u32>,
+	language: excel::Language,
+	lines: &mut Vec<DialogueLine>,
+) -> Result<()> {
+	let sheet = excel.sheet(sheet_name)?;
+
+	// Dialogue sheets are a pair of string columns - the script key, followed
+	// by the text itself.
+	let columns = sheet.columns()?;
+	let (Some(key_column), Some(text_column)) = (columns.first(), columns.get(1)) else {
+		return Ok(());
+	};
+
+	for row in sheet.with().language(language).iter() {
+		let (excel::Field::String(key), excel::Field::String(text)) =
+			(row.field(key_column)?, row.field(text_column)?)
+		else {
+			continue;
+		};
+
+		let text = text.to_string();
+		if text.is_empty() {
+			continue;
+		}
+
+		let key = key.to_string();
+		lines.push(DialogueLine {
+			sheet: sheet_name.to_string(),
+			row_id: row.row_id(),
+			speaker: speaker(&key, quest),
+			key,
+			quest: quest.to_string(),
+			expansion,
+			text_lowercase: text.to_lowercase(),
+			text,
+		});
+	}
+
+	Ok(())
+}
+
+/// Map of lowercase quest IDs (i.e. `clshrv001_00003`) to the expansion they
+/// belong to. Failure to read quests is not fatal - lines are left without an
+/// expansion instead.
+fn quest_expansions(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	language: excel::Language,
+) -> HashMap<String, u32> {
+	let mut expansions = HashMap::new();
+
+	let sheet = match excel.sheet("Quest") {
+		Ok(sheet) => sheet,
+		Err(error) => {
+			tracing::warn!(?error, "could not read quests for dialogue expansions");
+			return expansions;
+		}
+	};
+
+	let sentinels = Sentinels::new();
+	for row in sheet.with().iter() {
+		let value = match read(
+			excel,
+			schema,
+			"Quest",
+			row.row_id(),
+			0,
+			language,
+			&Filter::All,
+			&sentinels,
+			0,
+			ReferenceMode::Full,
+		) {
+			Ok(value) => value.decompose().0,
+			Err(error) => {
+				tracing::warn!(?error, "could not read quests for dialogue expansions");
+				break;
+			}
+		};
+
+		let Value::Struct(fields) = value else {
+			continue;
+		};
+		let field = |name: &str| {
+			fields
+				.iter()
+				.find(|(key, _)| key.name == name)
+				.map(|(_, value)| value)
+		};
+
+		let id = match field("Id") {
+			Some(Value::Scalar(excel::Field::String(id))) => id.to_string().to_lowercase(),
+			_ => continue,
+		};
+		let expansion = match field("Expansion") {
+			Some(Value::Reference(Reference::Scalar(value))) => u32::try_from(*value).ok(),
+			Some(Value::Reference(Reference::Populated { value, .. })) => Some(*value),
+			_ => None,
+		};
+
+		if let (false, Some(expansion)) = (id.is_empty(), expansion) {
+			expansions.insert(id, expansion);
+		}
+	}
+
+	expansions
+}
+
+/// Derive the speaker of a line from its script key. Keys are prefixed with
+/// the quest ID, followed by a mix of structural segments and, for spoken
+/// lines, the speaker's name, i.e. `TEXT_CLSHRV001_00003_YSHTOLA_000_10`.
+fn speaker(key: &str, quest: &str) -> Option<String> {
+	let prefix = format!("TEXT_{}_", quest.to_uppercase());
+	let remainder = key.strip_prefix(&prefix)?;
+
+	let segments = remainder
+		.split('_')
+		.filter(|segment| !segment.is_empty() && !is_structural_segment(segment))
+		.collect::<Vec<_>>();
+
+	match segments.is_empty() {
+		true => None,
+		false => Some(segments.join("_")),
+	}
+}
+
+fn is_structural_segment(segment: &str) -> bool {
+	if NON_SPEAKER_SEGMENTS.contains(&segment) {
+		return true;
+	}
+
+	// Numeric segments are line indices, while a letter followed by digits
+	// (i.e. `Q1`, `A2`) marks questions and answers of a choice.
+	let mut chars = segment.chars();
+	let first = chars.next();
+	let rest = chars.as_str();
+	segment.chars().all(|char| char.is_ascii_digit())
+		|| (first.map_or(false, |char| char.is_ascii_alphabetic())
+			&& !rest.is_empty()
+			&& rest.chars().all(|char| char.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn speaker_from_key() {
+		let quest = "ClsHrv001_00003";
+		assert_eq!(
+			speaker("TEXT_CLSHRV001_00003_YSHTOLA_000_10", quest),
+			Some("YSHTOLA".to_string())
+		);
+		assert_eq!(speaker("TEXT_CLSHRV001_00003_SEQ_00", quest), None);
+		assert_eq!(speaker("TEXT_CLSHRV001_00003_Q1_000_000", quest), None);
+		assert_eq!(speaker("TEXT_CLSHRV001_00003_TODO_00", quest), None);
+	}
+
+	#[test]
+	fn speaker_from_cutscene_key() {
+		assert_eq!(
+			speaker("TEXT_VOICEMAN_03001_000010_ALISAIE", "VoiceMan_03001"),
+			Some("ALISAIE".to_string())
+		);
+	}
+
+	#[test]
+	fn speaker_from_unrelated_key() {
+		assert_eq!(
+			speaker("TEXT_OTHER_00001_ALPHINAUD", "ClsHrv001_00003"),
+			None
+		);
+	}
+
+	#[test]
+	fn search_filters() {
+		let line = |speaker: Option<&str>, expansion, text: &str| DialogueLine {
+			sheet: "quest/000/ClsHrv001_00003".into(),
+			row_id: 0,
+			key: "key".into(),
+			quest: "ClsHrv001_00003".into(),
+			expansion,
+			speaker: speaker.map(str::to_string),
+			text: text.into(),
+			text_lowercase: text.to_lowercase(),
+		};
+		let dialogue = Dialogue {
+			lines: vec![
+				line(Some("YSHTOLA"), Some(0), "The Echo is a gift."),
+				line(None, Some(1), "Choose a reward."),
+			],
+		};
+
+		let count = |query: DialogueQuery| dialogue.search(&query).count();
+		assert_eq!(
+			count(DialogueQuery {
+				text: Some("echo GIFT".into()),
+				..Default::default()
+			}),
+			1
+		);
+		assert_eq!(
+			count(DialogueQuery {
+				speaker: Some("yshtola".into()),
+				..Default::default()
+			}),
+			1
+		);
+		assert_eq!(
+			count(DialogueQuery {
+				expansion: Some(1),
+				..Default::default()
+			}),
+			1
+		);
+		assert_eq!(
+			count(DialogueQuery {
+				quest: Some("clshrv001_00003".into()),
+				..Default::default()
+			}),
+			2
+		);
+	}
+}
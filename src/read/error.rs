@@ -1,3 +1,5 @@
+use tokio_util::sync::CancellationToken;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
 	/// The requested resource could not be found.
@@ -12,6 +14,10 @@ pub enum Error {
 	#[error("schema <-> game mismatch on {}: {}", .0.field, .0.reason)]
 	SchemaGameMismatch(MismatchError),
 
+	/// The operation was cancelled before it could complete.
+	#[error("operation cancelled")]
+	Cancelled,
+
 	#[error(transparent)]
 	Failure(#[from] anyhow::Error),
 }
@@ -58,3 +64,11 @@ macro_rules! impl_to_failure {
 impl_to_failure!(std::num::TryFromIntError);
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Bail out of a long-running read once it has been cancelled.
+pub(super) fn check_cancelled(cancel: &CancellationToken) -> Result<()> {
+	match cancel.is_cancelled() {
+		true => Err(Error::Cancelled),
+		false => Ok(()),
+	}
+}
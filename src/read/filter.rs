@@ -1,10 +1,17 @@
-use std::collections::HashMap;
+use std::{
+	borrow::Cow,
+	collections::{HashMap, HashSet},
+	fmt,
+};
 
 use ironworks::excel;
 use nohash_hasher::{IntMap, IsEnabled};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Filter {
+	/// Filter fields of a struct, keyed by field name and then language. Keys
+	/// containing `*` are treated as wildcard patterns, matching any field names
+	/// that fit the pattern.
 	Struct(HashMap<String, IntMap<Language, Filter>>),
 	Array(Box<Filter>),
 	All,
@@ -13,3 +20,182 @@ pub enum Filter {
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Language(pub excel::Language);
 impl IsEnabled for Language {}
+
+const WILDCARD: char = '*';
+
+/// Location at which two filters target incompatible structures, i.e. one
+/// selecting an array where the other selects a struct.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+	/// Path to the conflicting field, in filter string syntax, i.e. `a.b[]`.
+	/// Empty if the conflict is at the root of the filters.
+	pub path: String,
+	pub existing: &'static str,
+	pub incoming: &'static str,
+}
+
+impl fmt::Display for MergeConflict {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let path = match self.path.as_str() {
+			"" => "(root)",
+			path => path,
+		};
+		write!(
+			formatter,
+			"{path} is selected as both {} and {}",
+			self.existing, self.incoming
+		)
+	}
+}
+
+impl Filter {
+	/// Merge two filters, such that the result selects any fields selected by
+	/// either. Returns `None` if the filters target incompatible structures.
+	pub fn merge(self, other: Self) -> Option<Self> {
+		self.try_merge(other).ok()
+	}
+
+	/// Merge two filters, as with `merge`, reporting the location of any
+	/// incompatibility between them.
+	pub fn try_merge(self, other: Self) -> Result<Self, MergeConflict> {
+		self.merge_at(other, "")
+	}
+
+	fn merge_at(self, other: Self, path: &str) -> Result<Self, MergeConflict> {
+		let filter = match (self, other) {
+			// If either branch is a catch-all, it propagates.
+			(Self::All, _) | (_, Self::All) => Self::All,
+
+			// Arrays can directly merge their inner filter.
+			(Self::Array(a_inner), Self::Array(b_inner)) => {
+				Self::Array(a_inner.merge_at(*b_inner, &format!("{path}[]"))?.into())
+			}
+
+			// Structs need to be merged across both the inner maps.
+			(Self::Struct(mut a_fields), Self::Struct(b_fields)) => {
+				for (field_name, b_languages) in b_fields {
+					let field_path = match path {
+						"" => field_name.clone(),
+						path => format!("{path}.{field_name}"),
+					};
+					let a_languages = a_fields.entry(field_name).or_default();
+					for (language, b_filter) in b_languages {
+						let new_filter = match a_languages.remove(&language) {
+							None => b_filter,
+							Some(a_filter) => a_filter.merge_at(b_filter, &field_path)?,
+						};
+						a_languages.insert(language, new_filter);
+					}
+				}
+				Self::Struct(a_fields)
+			}
+
+			// Other patterns are invalid. Explicitly checking the first element to
+			// ensure this code path will error if new filter types are added.
+			(existing @ Self::Array(_), incoming) | (existing @ Self::Struct(_), incoming) => {
+				return Err(MergeConflict {
+					path: path.to_string(),
+					existing: existing.kind(),
+					incoming: incoming.kind(),
+				})
+			}
+		};
+
+		Ok(filter)
+	}
+
+	fn kind(&self) -> &'static str {
+		match self {
+			Self::Struct(_) => "a struct",
+			Self::Array(_) => "an array",
+			Self::All => "all fields",
+		}
+	}
+
+	/// Collect every language explicitly requested anywhere within this filter.
+	pub fn languages(&self) -> HashSet<excel::Language> {
+		let mut languages = HashSet::new();
+		self.collect_languages(&mut languages);
+		languages
+	}
+
+	fn collect_languages(&self, output: &mut HashSet<excel::Language>) {
+		match self {
+			Self::Struct(fields) => {
+				for (language, filter) in fields.values().flatten() {
+					output.insert(language.0);
+					filter.collect_languages(output);
+				}
+			}
+			Self::Array(inner) => inner.collect_languages(output),
+			Self::All => {}
+		}
+	}
+}
+
+/// Resolve the filters that apply to the named field of a struct filter,
+/// paired with the language they apply to. Filters from any matching wildcard
+/// patterns are merged with those for the field itself.
+pub(super) fn struct_field_filters<'a>(
+	fields: &'a HashMap<String, IntMap<Language, Filter>>,
+	name: &str,
+) -> Result<Vec<(excel::Language, Cow<'a, Filter>)>, String> {
+	let mut output = Vec::<(excel::Language, Cow<Filter>)>::new();
+
+	let matching = fields
+		.iter()
+		.filter(|(key, _)| match key.contains(WILDCARD) {
+			true => wildcard_match(key, name),
+			false => key.as_str() == name,
+		});
+
+	for (key, languages) in matching {
+		for (language, filter) in languages {
+			match output
+				.iter_mut()
+				.find(|(existing, _)| *existing == language.0)
+			{
+				None => output.push((language.0, Cow::Borrowed(filter))),
+				Some((_, existing)) => {
+					let merged = existing
+						.clone()
+						.into_owned()
+						.try_merge(filter.clone())
+						.map_err(|conflict| {
+							format!("filter for {key:?} conflicts with other filters for {name:?}: {conflict}")
+						})?;
+					*existing = Cow::Owned(merged);
+				}
+			}
+		}
+	}
+
+	Ok(output)
+}
+
+/// Match a name against a pattern, where `*` matches any sequence of characters.
+pub fn wildcard_match(pattern: &str, name: &str) -> bool {
+	let mut segments = pattern.split(WILDCARD);
+
+	// The first segment is anchored to the start of the name.
+	let first = segments.next().unwrap_or_default();
+	let Some(mut remaining) = name.strip_prefix(first) else {
+		return false;
+	};
+
+	let mut segments = segments.peekable();
+	while let Some(segment) = segments.next() {
+		// The last segment is anchored to the end of the name.
+		if segments.peek().is_none() {
+			return remaining.ends_with(segment);
+		}
+
+		match remaining.find(segment) {
+			Some(index) => remaining = &remaining[index + segment.len()..],
+			None => return false,
+		}
+	}
+
+	// No wildcards in the pattern - the prefix must have been an exact match.
+	remaining.is_empty()
+}
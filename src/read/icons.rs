@@ -0,0 +1,212 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use ironworks::excel;
+use ironworks_schema as schema;
+use itertools::Itertools;
+use nohash_hasher::IntMap;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+	error::{check_cancelled, Result},
+	filter::Filter,
+	read::{read, ReferenceMode},
+	references::field_filter,
+	sentinel::Sentinels,
+	value::Value,
+};
+
+/// A row field holding an icon.
+#[derive(Debug, Clone)]
+pub struct IconUse {
+	/// Sheet containing the row.
+	pub sheet: String,
+
+	/// ID of the row.
+	pub row_id: u32,
+
+	/// Subrow ID of the row.
+	pub subrow_id: u16,
+
+	/// Path to the field holding the icon, i.e. `a[].b`.
+	pub field: String,
+}
+
+/// Rows using icons, keyed by icon ID.
+pub type IconUses = IntMap<u32, Vec<IconUse>>;
+
+/// Build a map of every row across all sheets that uses an icon, as declared
+/// by the schema. This requires reading every row of each sheet with an icon
+/// field, and is accordingly expensive.
+pub fn icon_uses(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	language: excel::Language,
+	cancel: &CancellationToken,
+) -> Result<IconUses> {
+	let mut uses = IconUses::default();
+
+	let list = excel.list()?;
+	for sheet_name in list.iter() {
+		let sheet_schema = match schema.sheet(&sheet_name) {
+			Ok(sheet_schema) => sheet_schema,
+			Err(schema::Error::NotFound(_)) => continue,
+			Err(error) => Err(error)?,
+		};
+
+		let mut fields = vec![];
+		collect_icon_fields(&sheet_schema.node, &mut String::new(), &mut fields);
+		if fields.is_empty() {
+			continue;
+		}
+
+		// Every icon field is read at once, such that each sheet is only walked a
+		// single time.
+		let filter = fields
+			.iter()
+			.try_fold(Filter::Struct(HashMap::new()), |filter, field| {
+				filter.try_merge(field_filter(field, language, Filter::All))
+			})
+			.map_err(|conflict| anyhow!("conflicting icon fields in {sheet_name}: {conflict}"))?;
+
+		let sheet = excel.sheet(&sheet_name)?;
+		for row in sheet.with().language(language).iter() {
+			check_cancelled(cancel)?;
+
+			let row_id = row.row_id();
+			let subrow_id = row.subrow_id();
+
+			let (value, _warnings) = read(
+				excel,
+				schema,
+				&sheet_name,
+				row_id,
+				subrow_id,
+				language,
+				&filter,
+				&Sentinels::new(),
+				1,
+				ReferenceMode::Full,
+			)?
+			.decompose();
+
+			let mut icons = vec![];
+			collect_icons(&value, &mut String::new(), &mut icons);
+			icons.sort();
+
+			// Icon 0 is used throughout the game data to represent "no icon".
+			for (field, icon) in icons.into_iter().filter(|(_, icon)| *icon != 0).unique() {
+				uses.entry(icon).or_default().push(IconUse {
+					sheet: sheet_name.to_string(),
+					row_id,
+					subrow_id,
+					field,
+				});
+			}
+		}
+	}
+
+	Ok(uses)
+}
+
+fn collect_icon_fields(node: &schema::Node, path: &mut String, fields: &mut Vec<String>) {
+	use schema::Node as N;
+	match node {
+		N::Array { node, .. } => {
+			let length = path.len();
+			path.push_str("[]");
+			collect_icon_fields(node, path, fields);
+			path.truncate(length);
+		}
+
+		N::Struct(struct_fields) => {
+			for field in struct_fields {
+				let length = path.len();
+				if !path.is_empty() {
+					path.push('.');
+				}
+				path.push_str(&field.name);
+				collect_icon_fields(&field.node, path, fields);
+				path.truncate(length);
+			}
+		}
+
+		N::Scalar(schema::Scalar::Icon) => fields.push(path.clone()),
+
+		N::Scalar(_) => {}
+	}
+}
+
+/// Collect icons held within a value, paired with the path of the field
+/// holding them, in the same form as `collect_icon_fields`.
+fn collect_icons(value: &Value, path: &mut String, output: &mut Vec<(String, u32)>) {
+	match value {
+		Value::Array(values) => {
+			let length = path.len();
+			path.push_str("[]");
+			for value in values {
+				collect_icons(value, path, output);
+			}
+			path.truncate(length);
+		}
+
+		Value::Struct(fields) => {
+			for (key, value) in fields {
+				let length = path.len();
+				if !path.is_empty() {
+					path.push('.');
+				}
+				path.push_str(&key.name);
+				collect_icons(value, path, output);
+				path.truncate(length);
+			}
+		}
+
+		Value::Icon(icon) => output.push((path.clone(), *icon)),
+
+		_ => {}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use crate::read::StructKey;
+
+	use super::*;
+
+	fn key(name: &str) -> StructKey {
+		StructKey {
+			name: name.into(),
+			language: excel::Language::None,
+		}
+	}
+
+	#[test]
+	fn icons_paired_with_fields() {
+		let value = Value::Struct(HashMap::from([
+			(key("Icon"), Value::Icon(1)),
+			(
+				key("Entry"),
+				Value::Array(vec![
+					Value::Struct(HashMap::from([(key("Icon"), Value::Icon(2))])),
+					Value::Struct(HashMap::from([(key("Icon"), Value::Icon(3))])),
+				]),
+			),
+		]));
+
+		let mut icons = vec![];
+		collect_icons(&value, &mut String::new(), &mut icons);
+		icons.sort();
+
+		assert_eq!(
+			icons,
+			vec![
+				("Entry[].Icon".to_string(), 2),
+				("Entry[].Icon".to_string(), 3),
+				("Icon".to_string(), 1),
+			]
+		);
+	}
+}
@@ -1,11 +1,29 @@
+mod condition;
+mod dialogue;
 mod error;
 mod filter;
+mod icons;
 mod read;
+mod references;
+mod relations;
+mod sentinel;
+mod shape;
+mod statistics;
+mod transform;
 mod value;
 
 pub use {
+	condition::{Condition, ConditionalFilter},
+	dialogue::{dialogue, Dialogue, DialogueLine, DialogueQuery},
 	error::Error,
-	filter::{Filter, Language},
-	read::read,
+	filter::{wildcard_match, Filter, Language, MergeConflict},
+	icons::{icon_uses, IconUse, IconUses},
+	read::{read, ReferenceMode},
+	references::{reverse_references, Referrer, ReverseReferences},
+	relations::{relations, Relation},
+	sentinel::Sentinels,
+	shape::{shape, Shape},
+	statistics::{statistics, Statistics, ValueRange},
+	transform::{format_timestamp, transform, Interpretation, Transform, Transforms},
 	value::{Reference, StructKey, Value},
 };
@@ -10,14 +10,28 @@ use ironworks::{excel, file::exh};
 use ironworks_schema as schema;
 use nohash_hasher::IntMap;
 
-use crate::read::Language;
+use crate::{read::Language, utility::warnings::Warnings};
 
 use super::{
 	error::{Error, MismatchError, Result},
-	filter::Filter,
+	filter::{struct_field_filters, Filter},
+	sentinel::Sentinels,
 	value::{Reference, StructKey, Value},
 };
 
+/// Treatment of references that the filter does not select fields within.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ReferenceMode<'a> {
+	/// Resolve references in full, up to the configured depth.
+	#[default]
+	Full,
+	/// Resolve references to only the listed fields of their target, where
+	/// present, identifying the target row without reading it in full.
+	Preview(&'a [String]),
+	/// Leave references unresolved, as their raw value.
+	Id,
+}
+
 pub fn read(
 	excel: &excel::Excel,
 	schema: &dyn schema::Schema,
@@ -29,8 +43,12 @@ pub fn read(
 	default_language: excel::Language,
 
 	filter: &Filter,
+	sentinels: &Sentinels,
 	depth: u8,
-) -> Result<Value> {
+	references: ReferenceMode,
+) -> Result<Warnings<Value>> {
+	let mut warnings = vec![];
+
 	let value = read_sheet(ReaderContext {
 		excel,
 		schema,
@@ -41,18 +59,39 @@ pub fn read(
 		subrow_id,
 
 		filter,
+		sentinels,
+		path: "",
 		rows: &mut HashMap::new(),
 		columns: &[],
 		depth,
+		references,
+		warnings: &mut warnings,
 	})?;
 
-	Ok(value)
+	Ok(Warnings::new(value).with_warnings(warnings))
 }
 
 fn read_sheet(context: ReaderContext) -> Result<Value> {
+	// Field paths are relative to the sheet being read.
+	let context = ReaderContext {
+		path: "",
+		..context
+	};
+
 	let sheet_name = context.sheet;
-	let sheet_schema = context.schema.sheet(sheet_name)?;
 	let sheet_data = context.excel.sheet(sheet_name)?;
+	let sheet_schema = match context.schema.sheet(sheet_name) {
+		Ok(sheet_schema) => sheet_schema,
+		// Schemas frequently lag behind game updates - rather than failing the
+		// read outright, fall back to the raw column layout of the sheet.
+		Err(schema::Error::NotFound(_)) => {
+			context.warnings.push(format!(
+				"sheet {sheet_name} is not covered by the schema, falling back to raw columns"
+			));
+			return read_sheet_raw(&sheet_data, context);
+		}
+		Err(error) => return Err(error.into()),
+	};
 	let columns = get_sorted_columns(&sheet_schema, &sheet_data)?;
 
 	let value = read_node(
@@ -67,7 +106,22 @@ fn read_sheet(context: ReaderContext) -> Result<Value> {
 	Ok(value)
 }
 
-fn get_sorted_columns(
+fn read_sheet_raw(sheet_data: &excel::Sheet<'_, &str>, context: ReaderContext) -> Result<Value> {
+	// Without a schema, there's no meaningful ordering to apply - use the column
+	// definitions as-is, naming each field after its index.
+	let columns = sheet_data.columns()?;
+	let items = (0..columns.len()).map(|index| {
+		(
+			Cow::<str>::Owned(format!("Column{index}")),
+			&schema::Node::Scalar(schema::Scalar::Default),
+			&columns[index..index + 1],
+		)
+	});
+
+	read_struct_items(items, context)
+}
+
+pub(super) fn get_sorted_columns(
 	schema: &schema::Sheet,
 	data: &excel::Sheet<'_, &str>,
 ) -> Result<Vec<exh::ColumnDefinition>> {
@@ -92,7 +146,9 @@ fn read_node(node: &schema::Node, context: ReaderContext) -> Result<Value> {
 }
 
 fn read_node_scalar(scalar: &schema::Scalar, mut context: ReaderContext) -> Result<Value> {
-	let field = context.next_field()?;
+	let Some(field) = context.next_field()? else {
+		return Ok(Value::Unsupported(context.columns[0].kind()));
+	};
 
 	use schema::Scalar as S;
 	let out = match scalar {
@@ -120,10 +176,20 @@ fn read_scalar_reference(
 	let mut reference = Reference::Scalar(target_value);
 
 	// A target less than 0 (typically -1) is usually used to signify that a link
-	// is not present on this row. Also ensure that we've not run out of recursion
-	// depth. We avoid early return if following an active reference chain.
+	// is not present on this row, as are any sentinels configured for the field.
+	// Also ensure that we've not run out of recursion depth. We avoid early
+	// return if following an active reference chain.
 	// TODO: would be neat to halt recursion later, but target checking does have a cost that needs to be considered.
-	if target_value < 0 || (context.depth == 0 && context.filter == &Filter::All) {
+	let unselected = context.filter == &Filter::All;
+	let preview = match context.references {
+		ReferenceMode::Full if unselected && context.depth == 0 => {
+			return Ok(Value::Reference(reference))
+		}
+		ReferenceMode::Id if unselected => return Ok(Value::Reference(reference)),
+		ReferenceMode::Preview(fields) if unselected => Some(fields),
+		_ => None,
+	};
+	if target_value < 0 || context.is_sentinel(target_value.into()) {
 		return Ok(Value::Reference(reference));
 	}
 	let target_value = u32::try_from(target_value)
@@ -141,6 +207,7 @@ fn read_scalar_reference(
 					language_map,
 				)])),
 				rows: &mut *context.rows,
+				warnings: &mut *context.warnings,
 				..context
 			})?;
 
@@ -195,13 +262,34 @@ fn read_scalar_reference(
 		let row_id = row_data.row_id();
 		let subrow_id = row_data.subrow_id();
 
+		// Previews read only the preview fields of the target, without following
+		// any references within them.
+		let preview_filter = preview.map(|fields| {
+			Filter::Struct(
+				fields
+					.iter()
+					.map(|field| {
+						let mut language_map = IntMap::default();
+						language_map.insert(Language(context.language), Filter::All);
+						(field.clone(), language_map)
+					})
+					.collect(),
+			)
+		});
+
 		let child_data = read_sheet(ReaderContext {
 			sheet: &target.sheet,
 			row_id,
 			subrow_id,
 
+			filter: preview_filter.as_ref().unwrap_or(context.filter),
 			rows: &mut HashMap::from([(context.language, row_data)]),
+			warnings: &mut *context.warnings,
 			depth: context.depth.max(1) - 1,
+			references: match preview {
+				Some(_) => ReferenceMode::Id,
+				None => context.references,
+			},
 
 			..context
 		})?;
@@ -272,6 +360,7 @@ fn read_node_array(
 	};
 
 	let size = usize::try_from(element_node.size()).context("schema node too large")?;
+	let path = context.child_path("", "[]");
 	let values = (0..count)
 		.scan(0usize, |index, _| {
 			let Some(columns) = context.columns.get(*index..*index + size) else {
@@ -286,7 +375,9 @@ fn read_node_array(
 				ReaderContext {
 					filter,
 					columns,
+					path: &path,
 					rows: &mut context.rows,
+					warnings: &mut context.warnings,
 
 					..context
 				},
@@ -299,7 +390,15 @@ fn read_node_array(
 	Ok(Value::Array(values))
 }
 
-fn read_node_struct(fields: &[schema::StructField], mut context: ReaderContext) -> Result<Value> {
+fn read_node_struct(fields: &[schema::StructField], context: ReaderContext) -> Result<Value> {
+	let items = iterate_struct_fields(fields, context.columns)?;
+	read_struct_items(items, context)
+}
+
+fn read_struct_items<'s, 'c>(
+	items: impl Iterator<Item = (Cow<'s, str>, &'s schema::Node, &'c [exh::ColumnDefinition])>,
+	mut context: ReaderContext,
+) -> Result<Value> {
 	let filter_fields = match context.filter {
 		Filter::All => None,
 		Filter::Struct(filter_fields) => Some(filter_fields),
@@ -312,32 +411,29 @@ fn read_node_struct(fields: &[schema::StructField], mut context: ReaderContext)
 
 	let mut value_fields = HashMap::new();
 
-	for (name, node, columns) in iterate_struct_fields(fields, context.columns)? {
+	for (name, node, columns) in items {
 		let language_filters = match filter_fields {
-			Some(fields) => either::Left(match fields.get(name.as_ref()) {
-				// Filter exists, but has no entry for this name - no languages to filter to.
-				None => either::Left(iter::empty()),
-
-				// Entry exists for the name, map the language pairs to the expected shape.
-				Some(languages) => either::Right(
-					languages
-						.iter()
-						.map(|(language, filter)| (language.0, filter)),
-				),
-			}),
+			// Filter exists, collect the language pairs that apply to this name. If
+			// none do, there are no languages to filter to.
+			Some(fields) => struct_field_filters(fields, &name)
+				.map_err(|reason| Error::FilterSchemaMismatch(context.mismatch_error(reason)))?,
 
 			// ::All filter, walk with the current context language.
-			None => either::Right(std::iter::once((context.language, &Filter::All))),
+			None => vec![(context.language, Cow::Borrowed(&Filter::All))],
 		};
 
+		let path = context.child_path(".", &name);
+
 		for (language, filter) in language_filters {
 			let value = read_node(
 				node,
 				ReaderContext {
-					filter,
+					filter: &filter,
 					language,
 					columns,
+					path: &path,
 					rows: &mut context.rows,
+					warnings: &mut context.warnings,
 					..context
 				},
 			)?;
@@ -363,7 +459,7 @@ fn read_node_struct(fields: &[schema::StructField], mut context: ReaderContext)
 }
 
 // TODO: this is fairly gnarly - look into a crate for generators, i.e. genawaiter?
-fn iterate_struct_fields<'s, 'c>(
+pub(super) fn iterate_struct_fields<'s, 'c>(
 	fields: &'s [schema::StructField],
 	columns: &'c [exh::ColumnDefinition],
 ) -> Result<impl Iterator<Item = (Cow<'s, str>, &'s schema::Node, &'c [exh::ColumnDefinition])>> {
@@ -429,13 +525,23 @@ struct ReaderContext<'a> {
 	subrow_id: u16,
 
 	filter: &'a Filter,
+	sentinels: &'a Sentinels,
+	/// Path to the field being read, relative to the current sheet. Only
+	/// tracked for sheets with sentinels configured.
+	path: &'a str,
 	columns: &'a [exh::ColumnDefinition],
 	rows: &'a mut HashMap<excel::Language, excel::Row>,
 	depth: u8,
+	references: ReferenceMode<'a>,
+
+	warnings: &'a mut Vec<String>,
 }
 
 impl ReaderContext<'_> {
-	fn next_field(&mut self) -> Result<excel::Field> {
+	/// Read the field of the next column. Fields that can't be read, such as
+	/// those of unsupported column kinds, are reported as a warning rather than
+	/// failing the read.
+	fn next_field(&mut self) -> Result<Option<excel::Field>> {
 		let column = self.columns.get(0).ok_or_else(|| {
 			Error::SchemaGameMismatch(
 				self.mismatch_error("tried to read field but no columns available".to_string()),
@@ -453,7 +559,37 @@ impl ReaderContext<'_> {
 			),
 		};
 
-		Ok(row.field(column)?)
+		match row.field(column) {
+			Ok(field) => Ok(Some(field)),
+			Err(error) => {
+				self.warnings.push(format!(
+					"could not read {:?} column at offset {} of sheet {}: {error}",
+					column.kind(),
+					column.offset(),
+					self.sheet,
+				));
+				Ok(None)
+			}
+		}
+	}
+
+	fn child_path(&self, separator: &str, segment: &str) -> String {
+		// Building paths for every field read is wasteful if nothing will use them.
+		if !self.sentinels.contains_key(self.sheet) {
+			return String::new();
+		}
+
+		match self.path.is_empty() {
+			true => segment.to_string(),
+			false => format!("{}{separator}{segment}", self.path),
+		}
+	}
+
+	fn is_sentinel(&self, value: i64) -> bool {
+		self.sentinels
+			.get(self.sheet)
+			.and_then(|fields| fields.get(self.path))
+			.is_some_and(|values| values.contains(&value))
 	}
 
 	fn mismatch_error(&self, reason: impl ToString) -> MismatchError {
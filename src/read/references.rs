@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use ironworks::excel;
+use ironworks_schema as schema;
+use itertools::Itertools;
+use nohash_hasher::IntMap;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+	error::{check_cancelled, Result},
+	filter::{Filter, Language},
+	read::{read, ReferenceMode},
+	relations::Relation,
+	sentinel::Sentinels,
+	value::{Reference, Value},
+};
+
+/// A row that references another row.
+#[derive(Debug, Clone)]
+pub struct Referrer {
+	/// Sheet containing the referencing row.
+	pub sheet: String,
+
+	/// ID of the referencing row.
+	pub row_id: u32,
+
+	/// Subrow ID of the referencing row.
+	pub subrow_id: u16,
+
+	/// Path to the field holding the reference, i.e. `a[].b`.
+	pub field: String,
+}
+
+/// Rows referencing a sheet, keyed by the ID of the referenced row.
+pub type ReverseReferences = IntMap<u32, Vec<Referrer>>;
+
+/// Build a map of all rows that reference rows in the target sheet. This
+/// requires reading every row of each sheet with a relation to the target, and
+/// is accordingly expensive.
+pub fn reverse_references(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	relations: &[Relation],
+	target: &str,
+	language: excel::Language,
+	cancel: &CancellationToken,
+) -> Result<ReverseReferences> {
+	let mut references = ReverseReferences::default();
+
+	// Multiple targets in a single field may point at the same sheet - each field
+	// only needs to be walked once.
+	let relations = relations
+		.iter()
+		.filter(|relation| relation.target == target)
+		.unique_by(|relation| (&relation.source, &relation.field));
+
+	for relation in relations {
+		// Reference targets are filtered to an empty struct, such that the target
+		// row is resolved but none of its fields are read.
+		let filter = field_filter(&relation.field, language, Filter::Struct(HashMap::new()));
+
+		let sheet = excel.sheet(&relation.source)?;
+		for row in sheet.with().language(language).iter() {
+			check_cancelled(cancel)?;
+
+			let row_id = row.row_id();
+			let subrow_id = row.subrow_id();
+
+			// The filter ensures a depth of 1 is sufficient to resolve the reference
+			// without reading any of the target row's fields.
+			let (value, _warnings) = read(
+				excel,
+				schema,
+				&relation.source,
+				row_id,
+				subrow_id,
+				language,
+				&filter,
+				&Sentinels::new(),
+				1,
+				ReferenceMode::Full,
+			)?
+			.decompose();
+
+			let mut row_ids = vec![];
+			collect_references(&value, target, &mut row_ids);
+
+			for target_row_id in row_ids.into_iter().unique() {
+				references.entry(target_row_id).or_default().push(Referrer {
+					sheet: relation.source.clone(),
+					row_id,
+					subrow_id,
+					field: relation.field.clone(),
+				});
+			}
+		}
+	}
+
+	Ok(references)
+}
+
+/// Build a filter selecting only the field at the given path, i.e. `a[].b`,
+/// with the provided filter applied to the field itself.
+pub(super) fn field_filter(path: &str, language: excel::Language, leaf: Filter) -> Filter {
+	let mut filter = leaf;
+
+	for segment in path.split('.').rev() {
+		let name = segment.trim_end_matches("[]");
+		for _ in 0..(segment.len() - name.len()) / 2 {
+			filter = Filter::Array(filter.into());
+		}
+
+		let mut languages = IntMap::default();
+		languages.insert(Language(language), filter);
+		filter = Filter::Struct(HashMap::from([(name.to_string(), languages)]));
+	}
+
+	filter
+}
+
+fn collect_references(value: &Value, target: &str, output: &mut Vec<u32>) {
+	match value {
+		Value::Array(values) => {
+			for value in values {
+				collect_references(value, target, output);
+			}
+		}
+
+		Value::Struct(fields) => {
+			for value in fields.values() {
+				collect_references(value, target, output);
+			}
+		}
+
+		Value::Reference(Reference::Populated { sheet, row_id, .. }) if sheet == target => {
+			output.push(*row_id);
+		}
+
+		_ => {}
+	}
+}
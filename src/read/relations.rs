@@ -0,0 +1,109 @@
+use ironworks::excel;
+use ironworks_schema as schema;
+use tokio_util::sync::CancellationToken;
+
+use super::error::{check_cancelled, Result};
+
+/// A directed reference from a field in one sheet to another sheet, as
+/// declared by the schema.
+#[derive(Debug, Clone)]
+pub struct Relation {
+	/// Sheet containing the referencing field.
+	pub source: String,
+
+	/// Path to the referencing field within the source sheet, i.e. `a[].b`.
+	pub field: String,
+
+	/// Sheet targeted by the reference.
+	pub target: String,
+
+	/// Condition that must be met for the reference to target this sheet.
+	pub condition: Option<RelationCondition>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelationCondition {
+	/// Field within the source sheet that the condition is checked against.
+	pub field: String,
+
+	/// Value the field must hold for the condition to be met.
+	pub value: u32,
+}
+
+/// Build a list of all relations between sheets in the provided excel data.
+/// Sheets that are not covered by the schema are skipped.
+pub fn relations(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	cancel: &CancellationToken,
+) -> Result<Vec<Relation>> {
+	let list = excel.list()?;
+
+	let mut relations = vec![];
+	for sheet_name in list.iter() {
+		check_cancelled(cancel)?;
+
+		let sheet_schema = match schema.sheet(&sheet_name) {
+			Ok(sheet_schema) => sheet_schema,
+			Err(schema::Error::NotFound(_)) => continue,
+			Err(error) => Err(error)?,
+		};
+
+		collect_node(
+			&sheet_schema.node,
+			&sheet_name,
+			&mut String::new(),
+			&mut relations,
+		);
+	}
+
+	Ok(relations)
+}
+
+fn collect_node(
+	node: &schema::Node,
+	sheet: &str,
+	path: &mut String,
+	relations: &mut Vec<Relation>,
+) {
+	use schema::Node as N;
+	match node {
+		N::Array { node, .. } => {
+			let length = path.len();
+			path.push_str("[]");
+			collect_node(node, sheet, path, relations);
+			path.truncate(length);
+		}
+
+		N::Struct(fields) => {
+			for field in fields {
+				let length = path.len();
+				if !path.is_empty() {
+					path.push('.');
+				}
+				path.push_str(&field.name);
+				collect_node(&field.node, sheet, path, relations);
+				path.truncate(length);
+			}
+		}
+
+		N::Scalar(schema::Scalar::Reference(targets)) => {
+			relations.extend(targets.iter().map(|target| {
+				Relation {
+					source: sheet.into(),
+					field: path.clone(),
+					target: target.sheet.clone(),
+					condition: target
+						.condition
+						.as_ref()
+						.map(|condition| RelationCondition {
+							field: condition.selector.clone(),
+							value: condition.value,
+						}),
+				}
+			}));
+		}
+
+		N::Scalar(_) => {}
+	}
+}
@@ -0,0 +1,6 @@
+use std::collections::HashMap;
+
+/// Values used to signify "no value" in a field, keyed by sheet name, and then
+/// by field path within that sheet (i.e. `a.b`, `a[].b`). References holding a
+/// sentinel value are not resolved.
+pub type Sentinels = HashMap<String, HashMap<String, Vec<i64>>>;
@@ -0,0 +1,259 @@
+use std::{borrow::Cow, collections::HashSet};
+
+use anyhow::Context;
+use ironworks::{excel, file::exh};
+use ironworks_schema as schema;
+
+use super::{
+	error::{Error, MismatchError, Result},
+	filter::{struct_field_filters, Filter},
+	read::{get_sorted_columns, iterate_struct_fields},
+	value::StructKey,
+};
+
+/// The structure of values that would be produced by reading a sheet with a
+/// given filter, independent of any particular row.
+#[derive(Debug)]
+pub enum Shape {
+	Array(Box<Shape>),
+	Boolean,
+	Icon,
+	Number,
+	/// A reference, which may be populated with the fields of any of the listed
+	/// target sheets.
+	Reference(Vec<(String, Shape)>),
+	String,
+	Struct(Vec<(StructKey, Shape)>),
+}
+
+/// Derive the shape of the values that `read` would produce for rows of the
+/// specified sheet.
+pub fn shape(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+
+	sheet_name: &str,
+	default_language: excel::Language,
+
+	filter: &Filter,
+	depth: u8,
+) -> Result<Shape> {
+	shape_sheet(ShapeContext {
+		excel,
+		schema,
+
+		sheet: sheet_name,
+		language: default_language,
+
+		filter,
+		columns: &[],
+		depth,
+	})
+}
+
+fn shape_sheet(context: ShapeContext) -> Result<Shape> {
+	let sheet_data = context.excel.sheet(context.sheet)?;
+	let sheet_schema = match context.schema.sheet(context.sheet) {
+		Ok(sheet_schema) => sheet_schema,
+		// Mirror read's fallback to raw columns for sheets missing from the schema.
+		Err(schema::Error::NotFound(_)) => {
+			let columns = sheet_data.columns()?;
+			let items = (0..columns.len()).map(|index| {
+				(
+					Cow::<str>::Owned(format!("Column{index}")),
+					&schema::Node::Scalar(schema::Scalar::Default),
+					&columns[index..index + 1],
+				)
+			});
+			return shape_struct_items(items, context);
+		}
+		Err(error) => return Err(error.into()),
+	};
+	let columns = get_sorted_columns(&sheet_schema, &sheet_data)?;
+
+	shape_node(
+		&sheet_schema.node,
+		ShapeContext {
+			columns: &columns,
+			..context
+		},
+	)
+}
+
+fn shape_node(node: &schema::Node, context: ShapeContext) -> Result<Shape> {
+	use schema::Node as N;
+	match node {
+		N::Array { node, .. } => shape_node_array(node, context),
+		N::Scalar(scalar) => shape_node_scalar(scalar, context),
+		N::Struct(fields) => {
+			let items = iterate_struct_fields(fields, context.columns)?;
+			shape_struct_items(items, context)
+		}
+	}
+}
+
+fn shape_node_scalar(scalar: &schema::Scalar, context: ShapeContext) -> Result<Shape> {
+	let column = context.columns.first().ok_or_else(|| {
+		Error::SchemaGameMismatch(context.mismatch_error("insufficient columns to satisfy scalar"))
+	})?;
+
+	use schema::Scalar as S;
+	let shape = match scalar {
+		S::Reference(targets) => shape_scalar_reference(targets, context)?,
+		S::Icon => Shape::Icon,
+		_ => column_shape(column.kind()),
+	};
+
+	Ok(shape)
+}
+
+fn shape_scalar_reference(
+	targets: &[schema::ReferenceTarget],
+	context: ShapeContext,
+) -> Result<Shape> {
+	// References are only followed under the same conditions as when reading.
+	if context.depth == 0 && context.filter == &Filter::All {
+		return Ok(Shape::Reference(vec![]));
+	}
+
+	let mut sheets = vec![];
+	for target in targets {
+		// Targets that read doesn't handle halt further target resolution - ref. read_scalar_reference.
+		if target.selector.is_some() {
+			break;
+		}
+
+		let sheet_data = context.excel.sheet(&target.sheet)?;
+		if sheet_data.kind()? == exh::SheetKind::Subrows {
+			break;
+		}
+
+		let fields = shape_sheet(ShapeContext {
+			sheet: &target.sheet,
+			columns: &[],
+			depth: context.depth.max(1) - 1,
+			..context
+		})?;
+
+		sheets.push((target.sheet.clone(), fields));
+	}
+
+	Ok(Shape::Reference(sheets))
+}
+
+fn shape_node_array(element_node: &schema::Node, context: ShapeContext) -> Result<Shape> {
+	let filter = match context.filter {
+		Filter::All => &Filter::All,
+		Filter::Array(inner) => inner.as_ref(),
+		other => {
+			return Err(Error::FilterSchemaMismatch(
+				context.mismatch_error(format!("expected array filter, got {other:?}")),
+			));
+		}
+	};
+
+	let size = usize::try_from(element_node.size()).context("schema node too large")?;
+	let columns = context.columns.get(0..size).ok_or_else(|| {
+		Error::SchemaGameMismatch(context.mismatch_error("insufficient columns to satisfy array"))
+	})?;
+
+	let element = shape_node(
+		element_node,
+		ShapeContext {
+			filter,
+			columns,
+			..context
+		},
+	)?;
+
+	Ok(Shape::Array(element.into()))
+}
+
+fn shape_struct_items<'s, 'c>(
+	items: impl Iterator<Item = (Cow<'s, str>, &'s schema::Node, &'c [exh::ColumnDefinition])>,
+	context: ShapeContext,
+) -> Result<Shape> {
+	let filter_fields = match context.filter {
+		Filter::All => None,
+		Filter::Struct(filter_fields) => Some(filter_fields),
+		other => {
+			return Err(Error::FilterSchemaMismatch(
+				context.mismatch_error(format!("expected struct filter, got {other:?}")),
+			))
+		}
+	};
+
+	let mut seen = HashSet::new();
+	let mut fields = vec![];
+
+	for (name, node, columns) in items {
+		let language_filters = match filter_fields {
+			Some(filter_fields) => struct_field_filters(filter_fields, &name)
+				.map_err(|reason| Error::FilterSchemaMismatch(context.mismatch_error(reason)))?,
+			None => vec![(context.language, Cow::Borrowed(&Filter::All))],
+		};
+
+		for (language, filter) in language_filters {
+			let key = StructKey {
+				name: name.to_string(),
+				language,
+			};
+			if !seen.insert(key.clone()) {
+				continue;
+			}
+
+			let shape = shape_node(
+				node,
+				ShapeContext {
+					filter: &filter,
+					language,
+					columns,
+					..context
+				},
+			)?;
+
+			fields.push((key, shape));
+		}
+	}
+
+	Ok(Shape::Struct(fields))
+}
+
+fn column_shape(kind: exh::ColumnKind) -> Shape {
+	use exh::ColumnKind as K;
+	match kind {
+		K::String => Shape::String,
+		K::Bool
+		| K::PackedBool0
+		| K::PackedBool1
+		| K::PackedBool2
+		| K::PackedBool3
+		| K::PackedBool4
+		| K::PackedBool5
+		| K::PackedBool6
+		| K::PackedBool7 => Shape::Boolean,
+		_ => Shape::Number,
+	}
+}
+
+#[derive(Clone, Copy)]
+struct ShapeContext<'a> {
+	excel: &'a excel::Excel<'a>,
+	schema: &'a dyn schema::Schema,
+
+	sheet: &'a str,
+	language: excel::Language,
+
+	filter: &'a Filter,
+	columns: &'a [exh::ColumnDefinition],
+	depth: u8,
+}
+
+impl ShapeContext<'_> {
+	fn mismatch_error(&self, reason: impl ToString) -> MismatchError {
+		MismatchError {
+			field: format!("sheet {}", self.sheet),
+			reason: reason.to_string(),
+		}
+	}
+}
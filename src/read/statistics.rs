@@ -0,0 +1,232 @@
+use std::{borrow::Cow, collections::HashSet};
+
+use anyhow::Context;
+use ironworks::{excel, file::exh};
+use ironworks_schema as schema;
+use tokio_util::sync::CancellationToken;
+
+use super::{
+	error::{check_cancelled, Error, MismatchError, Result},
+	read::{get_sorted_columns, iterate_struct_fields},
+};
+
+/// Summary statistics of the data held in a sheet.
+#[derive(Debug)]
+pub struct Statistics {
+	pub rows: RowStatistics,
+	/// Statistics for each column, paired with the path of the field occupying
+	/// that column (i.e. `a.b`, `a[0].b`).
+	pub columns: Vec<(String, ColumnStatistics)>,
+}
+
+#[derive(Debug, Default)]
+pub struct RowStatistics {
+	/// Number of rows, including subrows.
+	pub count: usize,
+	pub min: Option<u32>,
+	pub max: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct ColumnStatistics {
+	pub kind: exh::ColumnKind,
+	/// Range of values held by the column. Only tracked for numeric columns.
+	pub range: Option<ValueRange>,
+	/// Number of distinct values held by the column.
+	pub cardinality: usize,
+	/// Whether the column is of a kind that could not be read. Statistics of
+	/// unsupported columns are left empty.
+	pub unsupported: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum ValueRange {
+	Integer { min: i128, max: i128 },
+	Float { min: f64, max: f64 },
+}
+
+/// Calculate statistics for the data in the specified sheet. This reads every
+/// row of the sheet, and is expected to be cached by callers.
+pub fn statistics(
+	excel: &excel::Excel,
+	schema: &dyn schema::Schema,
+	sheet_name: &str,
+	language: excel::Language,
+	cancel: &CancellationToken,
+) -> Result<Statistics> {
+	let sheet_data = excel.sheet(sheet_name)?;
+
+	// Mirror read's handling of languages and sheets missing from the schema.
+	let language = match sheet_data.languages()?.contains(&language) {
+		true => language,
+		false => excel::Language::None,
+	};
+
+	let mut columns = vec![];
+	match schema.sheet(sheet_name) {
+		Ok(sheet_schema) => {
+			let sheet_columns = get_sorted_columns(&sheet_schema, &sheet_data)?;
+			column_paths(&sheet_schema.node, &sheet_columns, "", &mut columns)?;
+		}
+		Err(schema::Error::NotFound(_)) => {
+			for (index, column) in sheet_data.columns()?.into_iter().enumerate() {
+				columns.push((format!("Column{index}"), column));
+			}
+		}
+		Err(error) => return Err(error.into()),
+	}
+
+	let mut rows = RowStatistics::default();
+	let mut accumulators = columns
+		.iter()
+		.map(|_| Accumulator::default())
+		.collect::<Vec<_>>();
+
+	for row in sheet_data.with().language(language).iter() {
+		check_cancelled(cancel)?;
+
+		let row_id = row.row_id();
+		rows.count += 1;
+		rows.min = Some(rows.min.map_or(row_id, |min| min.min(row_id)));
+		rows.max = Some(rows.max.map_or(row_id, |max| max.max(row_id)));
+
+		for ((path, column), accumulator) in columns.iter().zip(accumulators.iter_mut()) {
+			if accumulator.unsupported {
+				continue;
+			}
+
+			match row.field(column) {
+				Ok(field) => accumulator.add(field),
+				Err(error) => {
+					tracing::warn!(sheet_name, %path, ?error, "unsupported column");
+					accumulator.unsupported = true;
+				}
+			}
+		}
+	}
+
+	let columns = columns
+		.into_iter()
+		.zip(accumulators)
+		.map(|((path, column), accumulator)| {
+			let statistics = ColumnStatistics {
+				kind: column.kind(),
+				range: accumulator.range,
+				cardinality: accumulator.distinct.len(),
+				unsupported: accumulator.unsupported,
+			};
+			(path, statistics)
+		})
+		.collect();
+
+	Ok(Statistics { rows, columns })
+}
+
+fn column_paths(
+	node: &schema::Node,
+	columns: &[exh::ColumnDefinition],
+	path: &str,
+	output: &mut Vec<(String, exh::ColumnDefinition)>,
+) -> Result<()> {
+	let mismatch = |reason: &str| {
+		Error::SchemaGameMismatch(MismatchError {
+			field: path.to_string(),
+			reason: reason.to_string(),
+		})
+	};
+
+	use schema::Node as N;
+	match node {
+		N::Array { count, node } => {
+			let size = usize::try_from(node.size()).context("schema node too large")?;
+			for index in 0..usize::try_from(*count).context("schema node too large")? {
+				let element_columns = columns
+					.get(index * size..(index + 1) * size)
+					.ok_or_else(|| mismatch("insufficient columns to satisfy array"))?;
+				column_paths(node, element_columns, &format!("{path}[{index}]"), output)?;
+			}
+		}
+
+		N::Scalar(_) => {
+			let column = columns
+				.first()
+				.ok_or_else(|| mismatch("insufficient columns to satisfy scalar"))?;
+			output.push((path.to_string(), column.clone()));
+		}
+
+		N::Struct(fields) => {
+			for (name, node, columns) in iterate_struct_fields(fields, columns)? {
+				let path = match path.is_empty() {
+					true => Cow::Borrowed(name.as_ref()),
+					false => Cow::Owned(format!("{path}.{name}")),
+				};
+				column_paths(node, columns, &path, output)?;
+			}
+		}
+	}
+
+	Ok(())
+}
+
+#[derive(Default)]
+struct Accumulator {
+	range: Option<ValueRange>,
+	distinct: HashSet<DistinctValue>,
+	unsupported: bool,
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum DistinctValue {
+	Integer(i128),
+	// Floats are compared by their bit representation.
+	Float(u32),
+	String(String),
+	Bool(bool),
+}
+
+impl Accumulator {
+	fn add(&mut self, field: excel::Field) {
+		use excel::Field as F;
+		let distinct = match field {
+			F::String(value) => DistinctValue::String(value.to_string()),
+			F::Bool(value) => DistinctValue::Bool(value),
+			F::F32(value) => {
+				self.range = Some(match self.range {
+					Some(ValueRange::Float { min, max }) => ValueRange::Float {
+						min: min.min(value.into()),
+						max: max.max(value.into()),
+					},
+					_ => ValueRange::Float {
+						min: value.into(),
+						max: value.into(),
+					},
+				});
+				DistinctValue::Float(value.to_bits())
+			}
+			F::I8(value) => self.add_integer(value.into()),
+			F::I16(value) => self.add_integer(value.into()),
+			F::I32(value) => self.add_integer(value.into()),
+			F::I64(value) => self.add_integer(value.into()),
+			F::U8(value) => self.add_integer(value.into()),
+			F::U16(value) => self.add_integer(value.into()),
+			F::U32(value) => self.add_integer(value.into()),
+			F::U64(value) => self.add_integer(value.into()),
+		};
+
+		self.distinct.insert(distinct);
+	}
+
+	fn add_integer(&mut self, value: i128) -> DistinctValue {
+		self.range = Some(match self.range {
+			Some(ValueRange::Integer { min, max }) => ValueRange::Integer {
+				min: min.min(value),
+				max: max.max(value),
+			},
+			_ => ValueRange::Integer {
+				min: value,
+				max: value,
+			},
+		});
+		DistinctValue::Integer(value)
+	}
+}
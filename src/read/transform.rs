@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+
+use ironworks::excel;
+use serde::Deserialize;
+
+use crate::utility::date::civil_from_days;
+
+use super::value::{Reference, Value};
+
+/// Transforms to apply to read values, keyed by sheet name, and then by field
+/// path within that sheet (i.e. `a.b`, `a[].b`).
+pub type Transforms = HashMap<String, HashMap<String, Transform>>;
+
+/// A transformation that can be applied to a scalar field to derive a more
+/// meaningful representation of its value.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Transform {
+	/// Field is a set of bit flags. Names are specified in bit order, starting
+	/// from the least significant bit.
+	Flags { names: Vec<String> },
+
+	/// Field is a Unix timestamp, in seconds.
+	Timestamp,
+
+	/// Field is an Eorzean time of day, packed as `HHMM`.
+	EorzeaTime,
+
+	/// Field is a duration, measured in the specified unit.
+	Duration { unit: DurationUnit },
+
+	/// Field is a scaled integer, i.e. a percentage stored multiplied by 10.
+	/// The interpreted value is the field divided by the divisor.
+	Scale { divisor: f64 },
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DurationUnit {
+	Milliseconds,
+	Deciseconds,
+	Seconds,
+	Minutes,
+	Hours,
+}
+
+impl DurationUnit {
+	fn milliseconds(self) -> i64 {
+		match self {
+			Self::Milliseconds => 1,
+			Self::Deciseconds => 100,
+			Self::Seconds => 1_000,
+			Self::Minutes => 60_000,
+			Self::Hours => 3_600_000,
+		}
+	}
+}
+
+/// Interpreted representation of a field value.
+#[derive(Debug, Clone)]
+pub enum Interpretation {
+	/// Names of flags that are set.
+	Flags(Vec<String>),
+
+	/// ISO-8601 date and time.
+	DateTime(String),
+
+	/// ISO-8601 time of day.
+	Time(String),
+
+	/// ISO-8601 duration.
+	Duration(String),
+
+	/// Value with scaling removed.
+	Scaled(f64),
+}
+
+/// Apply configured transforms to a value read from the specified sheet.
+pub fn transform(value: Value, sheet: &str, transforms: &Transforms) -> Value {
+	transform_value(value, sheet, &mut String::new(), transforms)
+}
+
+fn transform_value(value: Value, sheet: &str, path: &mut String, transforms: &Transforms) -> Value {
+	match value {
+		Value::Array(values) => {
+			let length = path.len();
+			path.push_str("[]");
+			let values = values
+				.into_iter()
+				.map(|value| transform_value(value, sheet, path, transforms))
+				.collect();
+			path.truncate(length);
+			Value::Array(values)
+		}
+
+		Value::Struct(fields) => {
+			let fields = fields
+				.into_iter()
+				.map(|(key, value)| {
+					let length = path.len();
+					if !path.is_empty() {
+						path.push('.');
+					}
+					path.push_str(&key.name);
+					let value = transform_value(value, sheet, path, transforms);
+					path.truncate(length);
+					(key, value)
+				})
+				.collect();
+			Value::Struct(fields)
+		}
+
+		// References step into a new sheet - paths are relative to the target.
+		Value::Reference(Reference::Populated {
+			value,
+			sheet: target,
+			row_id,
+			fields,
+		}) => {
+			let fields = transform_value(*fields, &target, &mut String::new(), transforms);
+			Value::Reference(Reference::Populated {
+				value,
+				sheet: target,
+				row_id,
+				fields: Box::new(fields),
+			})
+		}
+
+		Value::Scalar(field) => match transforms
+			.get(sheet)
+			.and_then(|fields| fields.get(path.as_str()))
+		{
+			Some(transform) => transform.apply(field),
+			None => Value::Scalar(field),
+		},
+
+		other => other,
+	}
+}
+
+impl Transform {
+	fn apply(&self, field: excel::Field) -> Value {
+		let interpretation = match self {
+			Self::Flags { names } => field_bits(&field).map(|bits| {
+				let flags = (0..64)
+					.filter(|index| bits & (1 << index) != 0)
+					.map(|index| match names.get(index) {
+						Some(name) => name.clone(),
+						None => format!("unknown{index}"),
+					})
+					.collect();
+				Interpretation::Flags(flags)
+			}),
+
+			Self::Timestamp => field_integer(&field)
+				.map(|seconds| Interpretation::DateTime(format_timestamp(seconds))),
+
+			Self::EorzeaTime => field_integer(&field)
+				.and_then(format_time_of_day)
+				.map(Interpretation::Time),
+
+			Self::Duration { unit } => field_integer(&field)
+				.and_then(|value| value.checked_mul(unit.milliseconds()))
+				.map(|milliseconds| Interpretation::Duration(format_duration(milliseconds))),
+
+			Self::Scale { divisor } => match *divisor != 0.0 {
+				true => field_number(&field).map(|value| Interpretation::Scaled(value / divisor)),
+				false => None,
+			},
+		};
+
+		// Transforms that can't make sense of the field leave it as-is.
+		match interpretation {
+			Some(interpretation) => Value::Interpreted(field, interpretation),
+			None => {
+				tracing::warn!(?field, ?self, "transform could not be applied to field");
+				Value::Scalar(field)
+			}
+		}
+	}
+}
+
+fn field_bits(field: &excel::Field) -> Option<u64> {
+	// Signed fields are reinterpreted at their own width, such that i.e. -1i8
+	// is treated as all 8 bits set.
+	use excel::Field as F;
+	let bits = match *field {
+		F::I8(value) => (value as u8).into(),
+		F::I16(value) => (value as u16).into(),
+		F::I32(value) => (value as u32).into(),
+		F::I64(value) => value as u64,
+		F::U8(value) => value.into(),
+		F::U16(value) => value.into(),
+		F::U32(value) => value.into(),
+		F::U64(value) => value,
+		_ => return None,
+	};
+	Some(bits)
+}
+
+fn field_integer(field: &excel::Field) -> Option<i64> {
+	use excel::Field as F;
+	let value = match *field {
+		F::I8(value) => value.into(),
+		F::I16(value) => value.into(),
+		F::I32(value) => value.into(),
+		F::I64(value) => value,
+		F::U8(value) => value.into(),
+		F::U16(value) => value.into(),
+		F::U32(value) => value.into(),
+		F::U64(value) => value.try_into().ok()?,
+		_ => return None,
+	};
+	Some(value)
+}
+
+pub(super) fn field_number(field: &excel::Field) -> Option<f64> {
+	match *field {
+		excel::Field::F32(value) => Some(value.into()),
+		// Precision loss is only possible beyond 2^53, well outside values that
+		// are stored scaled.
+		_ => field_integer(field).map(|value| value as f64),
+	}
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 timestamp in UTC.
+pub fn format_timestamp(seconds: i64) -> String {
+	let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+	let time = seconds.rem_euclid(86_400);
+
+	format!(
+		"{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+		time / 3_600,
+		time % 3_600 / 60,
+		time % 60
+	)
+}
+
+fn format_time_of_day(packed: i64) -> Option<String> {
+	let (hours, minutes) = (packed / 100, packed % 100);
+	match (hours, minutes) {
+		// ISO 8601 permits 24:00 to represent the end of a day.
+		(0..=23, 0..=59) | (24, 0) => Some(format!("{hours:02}:{minutes:02}")),
+		_ => None,
+	}
+}
+
+fn format_duration(milliseconds: i64) -> String {
+	let sign = match milliseconds < 0 {
+		true => "-",
+		false => "",
+	};
+	let milliseconds = milliseconds.unsigned_abs();
+
+	let hours = milliseconds / 3_600_000;
+	let minutes = milliseconds % 3_600_000 / 60_000;
+	let seconds = milliseconds % 60_000 / 1_000;
+	let fraction = milliseconds % 1_000;
+
+	let mut output = format!("{sign}PT");
+	if hours > 0 {
+		output.push_str(&format!("{hours}H"));
+	}
+	if minutes > 0 {
+		output.push_str(&format!("{minutes}M"));
+	}
+	match fraction {
+		0 if seconds > 0 || (hours == 0 && minutes == 0) => output.push_str(&format!("{seconds}S")),
+		0 => {}
+		fraction => output.push_str(&format!(
+			"{seconds}.{}S",
+			format!("{fraction:03}").trim_end_matches('0')
+		)),
+	}
+	output
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn timestamp() {
+		assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+		assert_eq!(format_timestamp(-1), "1969-12-31T23:59:59Z");
+		assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+		assert_eq!(format_timestamp(1_709_211_723), "2024-02-29T13:02:03Z");
+		assert_eq!(format_timestamp(253_402_300_799), "9999-12-31T23:59:59Z");
+	}
+
+	#[test]
+	fn time_of_day() {
+		assert_eq!(format_time_of_day(0).as_deref(), Some("00:00"));
+		assert_eq!(format_time_of_day(1230).as_deref(), Some("12:30"));
+		assert_eq!(format_time_of_day(2359).as_deref(), Some("23:59"));
+		assert_eq!(format_time_of_day(2400).as_deref(), Some("24:00"));
+		assert_eq!(format_time_of_day(2459), None);
+		assert_eq!(format_time_of_day(2500), None);
+		assert_eq!(format_time_of_day(1260), None);
+		assert_eq!(format_time_of_day(-1), None);
+	}
+
+	#[test]
+	fn duration() {
+		assert_eq!(format_duration(0), "PT0S");
+		assert_eq!(format_duration(1), "PT0.001S");
+		assert_eq!(format_duration(1_500), "PT1.5S");
+		assert_eq!(format_duration(60_000), "PT1M");
+		assert_eq!(format_duration(3_600_000), "PT1H");
+		assert_eq!(format_duration(3_661_000), "PT1H1M1S");
+		assert_eq!(format_duration(-90_000), "-PT1M30S");
+	}
+
+	fn flags(field: excel::Field) -> Vec<String> {
+		let transform = Transform::Flags {
+			names: vec!["a".into(), "b".into(), "c".into()],
+		};
+		match transform.apply(field) {
+			Value::Interpreted(_, Interpretation::Flags(flags)) => flags,
+			other => panic!("expected flags, got {other:?}"),
+		}
+	}
+
+	#[test]
+	fn flags_named() {
+		assert_eq!(flags(excel::Field::U8(0b101)), vec!["a", "c"]);
+		assert_eq!(flags(excel::Field::U32(0)), Vec::<String>::new());
+	}
+
+	#[test]
+	fn flags_unknown() {
+		assert_eq!(flags(excel::Field::U16(0b1010)), vec!["b", "unknown3"]);
+	}
+
+	#[test]
+	fn flags_signed() {
+		// Signed fields are reinterpreted at their own width.
+		assert_eq!(
+			flags(excel::Field::I8(-1)),
+			vec!["a", "b", "c", "unknown3", "unknown4", "unknown5", "unknown6", "unknown7"]
+		);
+	}
+
+	#[test]
+	fn flags_non_integer() {
+		let transform = Transform::Flags { names: vec![] };
+		assert!(matches!(
+			transform.apply(excel::Field::Bool(true)),
+			Value::Scalar(excel::Field::Bool(true))
+		));
+	}
+}
@@ -1,17 +1,23 @@
 use std::collections::HashMap;
 
-use ironworks::excel;
+use ironworks::{excel, file::exh};
 
-#[derive(Debug)]
+use super::transform::Interpretation;
+
+#[derive(Debug, Clone)]
 pub enum Value {
 	Array(Vec<Value>),
 	Icon(u32),
+	Interpreted(excel::Field, Interpretation),
 	Reference(Reference),
 	Scalar(excel::Field),
 	Struct(HashMap<StructKey, Value>),
+	/// Placeholder for a field held in a column of a kind that could not be
+	/// read.
+	Unsupported(exh::ColumnKind),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum Reference {
 	Scalar(i32),
 	Populated {
@@ -1,12 +1,13 @@
 use std::{collections::HashMap, sync::Arc};
 
+use anyhow::anyhow;
 use futures::future::join_all;
 use ironworks_schema::Schema;
 use serde::Deserialize;
 use tokio::{select, time};
 use tokio_util::sync::CancellationToken;
 
-use crate::{data, version::VersionKey};
+use crate::{data, utility::reloadable::Reloadable, version::VersionKey};
 
 use super::{
 	error::{Error, Result},
@@ -29,7 +30,7 @@ pub trait Source: Send + Sync {
 #[derive(Debug, Deserialize)]
 pub struct Config {
 	default: Specifier,
-	interval: u64,
+	interval: Reloadable<u64>,
 
 	exdschema: exdschema::Config,
 }
@@ -38,12 +39,18 @@ pub struct Config {
 // TODO: look into moving sources into a channel so i'm not leaning on send+sync for other shit
 pub struct Provider {
 	default: Specifier,
-	update_interval: u64,
+	update_interval: Reloadable<u64>,
 	sources: HashMap<&'static str, Arc<dyn Source>>,
 }
 
 impl Provider {
 	pub fn new(config: Config, data: Arc<data::Data>) -> Result<Self> {
+		if config.interval.get() == 0 {
+			return Err(Error::Failure(anyhow!(
+				"schema update interval must be greater than 0"
+			)));
+		}
+
 		// TODO: at the moment this will hard fail if any source fails - should i make sources soft fail?
 		Ok(Self {
 			default: config.default,
@@ -55,6 +62,20 @@ impl Provider {
 		})
 	}
 
+	/// Apply reload-safe settings from an updated configuration.
+	pub fn reload(&self, config: Config) {
+		// A zero interval would panic the update loop - keep the current one.
+		let interval = config.interval.get();
+		if interval == 0 {
+			tracing::warn!("ignoring schema update interval of 0");
+			return;
+		}
+
+		if self.update_interval.set(interval) {
+			tracing::info!(interval = ?self.update_interval, "schema update interval changed");
+		}
+	}
+
 	pub fn ready(&self) -> bool {
 		// Schema is ready if all of its sources are ready.
 		self.sources.values().all(|source| source.ready())
@@ -68,13 +89,23 @@ impl Provider {
 	}
 
 	async fn start_inner(&self) {
-		let mut interval = time::interval(time::Duration::from_secs(self.update_interval));
-		interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+		let mut interval_changes = self.update_interval.subscribe();
+		let mut start = time::Instant::now();
 
+		// The interval is rebuilt whenever the configured period changes.
 		loop {
-			interval.tick().await;
+			let period = time::Duration::from_secs(*interval_changes.borrow_and_update());
+			let mut interval = time::interval_at(start, period);
+			interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+			loop {
+				select! {
+					_ = interval.tick() => self.update().await,
+					Ok(()) = interval_changes.changed() => break,
+				}
+			}
 
-			self.update().await;
+			start = time::Instant::now() + time::Duration::from_secs(*interval_changes.borrow());
 		}
 	}
 
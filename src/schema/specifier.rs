@@ -8,8 +8,7 @@ use serde::{de, Deserialize, Serialize};
 
 use crate::utility::jsonschema::impl_jsonschema;
 
-// TODO: will probably need eq/hash so i can use these as cache keys?
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CanonicalSpecifier {
 	pub source: String,
 	pub version: String,
@@ -0,0 +1,150 @@
+use std::{
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use anyhow::{Context, Result};
+use futures::StreamExt;
+use object_store::{
+	aws::AmazonS3Builder, gcp::GoogleCloudStorageBuilder, local::LocalFileSystem,
+	path::Path as ObjectPath, ObjectStore, WriteMultipart,
+};
+use serde::Deserialize;
+use tokio::{
+	fs,
+	io::{AsyncReadExt, AsyncWriteExt},
+};
+
+// Size of each part sent during multipart uploads, and the number of parts that
+// may be in flight at once.
+const UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+const UPLOAD_CONCURRENCY: usize = 4;
+
+/// Object storage backend. Credentials are read from the environment, using the
+/// variable names conventional to each provider (i.e. `AWS_ACCESS_KEY_ID`,
+/// `GOOGLE_SERVICE_ACCOUNT`).
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Config {
+	/// Amazon S3, or any S3-compatible service when an endpoint is specified.
+	S3 {
+		bucket: String,
+		region: Option<String>,
+		endpoint: Option<String>,
+		#[serde(default)]
+		prefix: String,
+	},
+
+	/// Google Cloud Storage.
+	Gcs {
+		bucket: String,
+		#[serde(default)]
+		prefix: String,
+	},
+
+	/// A directory, typically a network filesystem shared between instances.
+	Local {
+		directory: PathBuf,
+		#[serde(default)]
+		prefix: String,
+	},
+}
+
+/// Shared storage for expensive-to-build artifacts, such as patch files. Keys
+/// are `/`-delimited paths relative to the configured prefix.
+pub struct Storage {
+	store: Arc<dyn ObjectStore>,
+	prefix: ObjectPath,
+}
+
+impl Storage {
+	pub fn new(config: Config) -> Result<Self> {
+		let (store, prefix): (Arc<dyn ObjectStore>, _) = match config {
+			Config::S3 {
+				bucket,
+				region,
+				endpoint,
+				prefix,
+			} => {
+				let mut builder = AmazonS3Builder::from_env().with_bucket_name(bucket);
+				if let Some(region) = region {
+					builder = builder.with_region(region);
+				}
+				if let Some(endpoint) = endpoint {
+					builder = builder.with_endpoint(endpoint);
+				}
+				(Arc::new(builder.build()?), prefix)
+			}
+
+			Config::Gcs { bucket, prefix } => {
+				let builder = GoogleCloudStorageBuilder::from_env().with_bucket_name(bucket);
+				(Arc::new(builder.build()?), prefix)
+			}
+
+			Config::Local { directory, prefix } => {
+				std::fs::create_dir_all(&directory)?;
+				(
+					Arc::new(LocalFileSystem::new_with_prefix(directory)?),
+					prefix,
+				)
+			}
+		};
+
+		Ok(Self {
+			store,
+			prefix: ObjectPath::parse(prefix).context("invalid storage prefix")?,
+		})
+	}
+
+	fn location(&self, key: &str) -> ObjectPath {
+		ObjectPath::from_iter(self.prefix.parts().chain(ObjectPath::from(key).parts()))
+	}
+
+	/// Download the object at `key` to a local file. Returns `false` if the
+	/// object does not exist.
+	pub async fn download_file(&self, key: &str, path: &Path) -> Result<bool> {
+		let result = match self.store.get(&self.location(key)).await {
+			Ok(result) => result,
+			Err(object_store::Error::NotFound { .. }) => return Ok(false),
+			Err(error) => return Err(error.into()),
+		};
+
+		tracing::debug!(key, ?path, "downloading from storage");
+
+		if let Some(parent) = path.parent() {
+			fs::create_dir_all(parent).await?;
+		}
+
+		let mut file = fs::File::create(path).await?;
+		let mut stream = result.into_stream();
+		while let Some(chunk) = stream.next().await {
+			file.write_all(&chunk?).await?;
+		}
+		file.flush().await?;
+
+		Ok(true)
+	}
+
+	/// Upload a local file to the object at `key`, replacing it if it exists.
+	pub async fn upload_file(&self, path: &Path, key: &str) -> Result<()> {
+		tracing::debug!(key, ?path, "uploading to storage");
+
+		let upload = self.store.put_multipart(&self.location(key)).await?;
+		let mut writer = WriteMultipart::new_with_chunk_size(upload, UPLOAD_CHUNK_SIZE);
+
+		let mut file = fs::File::open(path).await?;
+		let mut buffer = vec![0; UPLOAD_CHUNK_SIZE];
+		loop {
+			let read = file.read(&mut buffer).await?;
+			if read == 0 {
+				break;
+			}
+			writer.wait_for_capacity(UPLOAD_CONCURRENCY).await?;
+			writer.write(&buffer[..read]);
+		}
+
+		writer.finish().await?;
+
+		Ok(())
+	}
+}
@@ -2,7 +2,7 @@ use std::{collections::HashMap, fmt, str::FromStr};
 
 use serde::{de, Deserialize};
 use tracing::metadata::LevelFilter;
-use tracing_subscriber::{filter, layer::SubscriberExt, util::SubscriberInitExt, Layer};
+use tracing_subscriber::{filter, layer::SubscriberExt, reload, util::SubscriberInitExt, Layer};
 
 // TODO: tracing should proooobably be it's own file at this point
 #[derive(Debug, Deserialize)]
@@ -45,15 +45,31 @@ impl<'de> Deserialize<'de> for ConfigLevelFilter {
 	}
 }
 
-pub fn init(config: Config) {
+/// Handle to replace the active tracing filters at runtime.
+pub struct Reloader {
+	reload: Box<dyn Fn(filter::Targets) -> Result<(), reload::Error> + Send + Sync>,
+}
+
+impl Reloader {
+	pub fn reload(&self, config: Config) -> Result<(), reload::Error> {
+		(self.reload)(build_filter(config))
+	}
+}
+
+fn build_filter(config: Config) -> filter::Targets {
+	filter::Targets::new()
+		.with_default(config.filters.default)
+		.with_targets(config.filters.targets)
+}
+
+pub fn init(config: Config) -> Reloader {
 	// TODO: consider enabling this with a config flag or something tracing.console?
 	let console_filter = filter::Targets::new()
 		.with_target("tokio", LevelFilter::TRACE)
 		.with_target("runtime", LevelFilter::TRACE);
 
-	let tracing_filter = filter::Targets::new()
-		.with_default(config.filters.default)
-		.with_targets(config.filters.targets);
+	// Wrapped in a reload layer so that filters can be changed without a restart.
+	let (tracing_filter, handle) = reload::Layer::new(build_filter(config));
 
 	// TODO: env filter (will need feature enabled). consider enabling pulling from log! too.
 	// TODO: now that i have config working, is it worth using env filter here or should i handle it via config env?
@@ -61,4 +77,8 @@ pub fn init(config: Config) {
 		.with(console_subscriber::spawn().with_filter(console_filter))
 		.with(tracing_subscriber::fmt::layer().with_filter(tracing_filter))
 		.init();
+
+	Reloader {
+		reload: Box::new(move |filter| handle.reload(filter)),
+	}
 }
@@ -0,0 +1,100 @@
+/// Convert days since the Unix epoch to a `(year, month, day)` date in the
+/// proleptic Gregorian calendar. Months and days are 1-based. Ref. Howard
+/// Hinnant's `civil_from_days`.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+	let shifted = days + 719_468;
+	let era = shifted.div_euclid(146_097);
+	let day_of_era = shifted.rem_euclid(146_097);
+	let year_of_era =
+		(day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let month_index = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+	let month = match month_index < 10 {
+		true => month_index + 3,
+		false => month_index - 9,
+	};
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+	// Day and month are bounded to calendar ranges by the arithmetic above.
+	(year, month as u32, day as u32)
+}
+
+/// Convert a `(year, month, day)` date in the proleptic Gregorian calendar to
+/// days since the Unix epoch, the inverse of `civil_from_days`. Returns `None`
+/// for dates that don't exist, i.e. February 29th outside of a leap year.
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+	if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+		return None;
+	}
+
+	// Years are counted from March, such that leap days fall at the end of each.
+	let year = year - i64::from(month <= 2);
+	let era = year.div_euclid(400);
+	let year_of_era = year.rem_euclid(400);
+	let month_index = i64::from(match month > 2 {
+		true => month - 3,
+		false => month + 9,
+	});
+	let day_of_year = (153 * month_index + 2) / 5 + i64::from(day) - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+	Some(era * 146_097 + day_of_era - 719_468)
+}
+
+fn days_in_month(year: i64, month: u32) -> u32 {
+	let leap_year = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+	match month {
+		2 if leap_year => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn epoch() {
+		assert_eq!(civil_from_days(0), (1970, 1, 1));
+		assert_eq!(civil_from_days(-1), (1969, 12, 31));
+	}
+
+	#[test]
+	fn leap_years() {
+		assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+		assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+		assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+		// Centuries not divisible by 400 are not leap years.
+		assert_eq!(civil_from_days(-25_509), (1900, 2, 28));
+		assert_eq!(civil_from_days(-25_508), (1900, 3, 1));
+	}
+
+	#[test]
+	fn era_boundaries() {
+		assert_eq!(civil_from_days(-719_468), (0, 3, 1));
+		assert_eq!(civil_from_days(10_956), (1999, 12, 31));
+	}
+
+	#[test]
+	fn round_trip() {
+		for days in [-719_468, -25_509, -1, 0, 11_016, 11_017, 19_782] {
+			let (year, month, day) = civil_from_days(days);
+			assert_eq!(days_from_civil(year, month, day), Some(days));
+		}
+	}
+
+	#[test]
+	fn invalid_dates() {
+		assert_eq!(days_from_civil(2023, 2, 29), None);
+		assert_eq!(days_from_civil(1900, 2, 29), None);
+		assert_eq!(days_from_civil(2023, 4, 31), None);
+		assert_eq!(days_from_civil(2023, 1, 0), None);
+		assert_eq!(days_from_civil(2023, 13, 1), None);
+		assert_eq!(days_from_civil(2000, 2, 29), Some(11_016));
+	}
+}
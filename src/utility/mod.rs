@@ -1,4 +1,8 @@
 pub mod anyhow;
+pub mod date;
 pub mod field;
 pub mod jsonschema;
+pub mod persist;
+pub mod reloadable;
+pub mod secret;
 pub mod warnings;
@@ -0,0 +1,213 @@
+use std::{
+	ffi::OsString,
+	fs,
+	hash::Hasher,
+	io::{self, Write},
+	path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use seahash::SeaHasher;
+
+const FOOTER_PREFIX: &[u8] = b"\n#checksum:";
+
+/// Atomically replace the file at `path` with `contents`. The write goes to a
+/// temporary file that is renamed into place, and the previous copy is kept
+/// alongside it as a fallback for `read`. A checksum footer is appended so that
+/// damaged files can be detected.
+pub fn write(path: &Path, contents: &[u8]) -> Result<()> {
+	let temporary_path = sibling_path(path, &format!("{}.tmp", uuid::Uuid::new_v4()));
+
+	let mut file = fs::File::create(&temporary_path)?;
+	file.write_all(contents)?;
+	file.write_all(&footer(contents))?;
+	file.sync_all()?;
+	drop(file);
+
+	// Only keep a backup of a copy that's known to be intact.
+	if fs::read(path)
+		.ok()
+		.and_then(|bytes| verify(&bytes))
+		.is_some()
+	{
+		fs::copy(path, backup_path(path)).with_context(|| format!("failed to back up {path:?}"))?;
+	}
+
+	fs::rename(&temporary_path, path)?;
+
+	// The rename itself is only durable once the directory is synced.
+	sync_directory(path).with_context(|| format!("failed to sync directory of {path:?}"))?;
+
+	Ok(())
+}
+
+/// Read and parse the file at `path`, falling back to the backup kept by
+/// `write` if the file is missing, damaged, or fails to parse. Files written
+/// before checksums were introduced are accepted without verification.
+pub fn read<T>(path: &Path, parse: impl Fn(&[u8]) -> Result<T>) -> Result<Option<T>> {
+	let primary = match read_verified(path, &parse) {
+		Ok(Some(value)) => return Ok(Some(value)),
+		Ok(None) => None,
+		Err(error) => Some(error),
+	};
+
+	let backup = read_verified(&backup_path(path), &parse);
+
+	match (primary, backup) {
+		(None, Ok(None)) => Ok(None),
+		(primary, Ok(Some(value))) => {
+			tracing::warn!(
+				?path,
+				error = ?primary,
+				"persisted file is missing or damaged, falling back to backup"
+			);
+			Ok(Some(value))
+		}
+		(None, Err(error)) => Err(error.context(format!("{path:?} is missing"))),
+		(Some(primary), Ok(None)) => Err(primary),
+		(Some(primary), Err(error)) => {
+			Err(primary.context(format!("backup is also unusable: {error:#}")))
+		}
+	}
+}
+
+fn read_verified<T>(path: &Path, parse: &impl Fn(&[u8]) -> Result<T>) -> Result<Option<T>> {
+	let bytes = match fs::read(path) {
+		Ok(bytes) => bytes,
+		Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(error) => return Err(error.into()),
+	};
+
+	let contents = match footer_position(&bytes) {
+		Some(_) => verify(&bytes).with_context(|| format!("checksum mismatch in {path:?}"))?,
+		None => &bytes,
+	};
+
+	let value = parse(contents).with_context(|| format!("failed to parse {path:?}"))?;
+	Ok(Some(value))
+}
+
+#[cfg(unix)]
+fn sync_directory(path: &Path) -> io::Result<()> {
+	let directory = match path.parent() {
+		Some(parent) if !parent.as_os_str().is_empty() => parent,
+		_ => Path::new("."),
+	};
+	fs::File::open(directory)?.sync_all()
+}
+
+// Directories can't be opened as files on other platforms - renames there are
+// left to the filesystem.
+#[cfg(not(unix))]
+fn sync_directory(_path: &Path) -> io::Result<()> {
+	Ok(())
+}
+
+fn footer(contents: &[u8]) -> Vec<u8> {
+	let mut footer = FOOTER_PREFIX.to_vec();
+	footer.extend(format!("{:016x}\n", checksum(contents)).into_bytes());
+	footer
+}
+
+fn footer_position(bytes: &[u8]) -> Option<usize> {
+	bytes
+		.windows(FOOTER_PREFIX.len())
+		.rposition(|window| window == FOOTER_PREFIX)
+}
+
+/// Strip and check the checksum footer, returning the contents if they match.
+fn verify(bytes: &[u8]) -> Option<&[u8]> {
+	let position = footer_position(bytes)?;
+	let contents = &bytes[..position];
+	(bytes[position..] == footer(contents)[..]).then_some(contents)
+}
+
+fn checksum(contents: &[u8]) -> u64 {
+	let mut hasher = SeaHasher::new();
+	hasher.write(contents);
+	hasher.finish()
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+	sibling_path(path, "bak")
+}
+
+fn sibling_path(path: &Path, extension: &str) -> PathBuf {
+	let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+	name.push(".");
+	name.push(extension);
+	path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn parse_string(bytes: &[u8]) -> Result<String> {
+		Ok(std::str::from_utf8(bytes)?.to_string())
+	}
+
+	fn test_path() -> PathBuf {
+		let directory = std::env::temp_dir().join(format!("persist-{}", uuid::Uuid::new_v4()));
+		fs::create_dir_all(&directory).unwrap();
+		directory.join("file.json")
+	}
+
+	#[test]
+	fn round_trip() {
+		let path = test_path();
+		write(&path, b"first").unwrap();
+		write(&path, b"second").unwrap();
+		assert_eq!(
+			read(&path, parse_string).unwrap(),
+			Some("second".to_string())
+		);
+	}
+
+	#[test]
+	fn damaged_falls_back_to_backup() {
+		let path = test_path();
+		write(&path, b"first").unwrap();
+		write(&path, b"second").unwrap();
+
+		let mut bytes = fs::read(&path).unwrap();
+		bytes[0] = b'x';
+		fs::write(&path, bytes).unwrap();
+
+		assert_eq!(
+			read(&path, parse_string).unwrap(),
+			Some("first".to_string())
+		);
+	}
+
+	#[test]
+	fn missing_falls_back_to_backup() {
+		let path = test_path();
+		write(&path, b"first").unwrap();
+		write(&path, b"second").unwrap();
+		fs::remove_file(&path).unwrap();
+
+		assert_eq!(
+			read(&path, parse_string).unwrap(),
+			Some("first".to_string())
+		);
+	}
+
+	#[test]
+	fn legacy_without_footer() {
+		let path = test_path();
+		fs::write(&path, b"legacy").unwrap();
+		assert_eq!(
+			read(&path, parse_string).unwrap(),
+			Some("legacy".to_string())
+		);
+	}
+
+	#[test]
+	fn missing() {
+		let path = test_path();
+		assert_eq!(read(&path, parse_string).unwrap(), None);
+	}
+}
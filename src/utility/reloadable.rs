@@ -0,0 +1,76 @@
+use std::{fmt, sync::Arc};
+
+use serde::{Deserialize, Deserializer};
+use tokio::sync::watch;
+
+/// Configuration value that may be replaced at runtime. Clones share the same
+/// underlying value, such that updates made via any clone are observed by all.
+pub struct Reloadable<T> {
+	sender: Arc<watch::Sender<T>>,
+}
+
+impl<T> Reloadable<T> {
+	pub fn new(value: T) -> Self {
+		Self {
+			sender: Arc::new(watch::channel(value).0),
+		}
+	}
+
+	/// Borrow the current value. The borrow should be short-lived, as it will
+	/// block updates while held.
+	pub fn borrow(&self) -> watch::Ref<'_, T> {
+		self.sender.borrow()
+	}
+
+	/// Subscribe to changes of this value. The receiver will be notified each
+	/// time the value is updated to a differing value.
+	pub fn subscribe(&self) -> watch::Receiver<T> {
+		self.sender.subscribe()
+	}
+}
+
+impl<T: Clone> Reloadable<T> {
+	pub fn get(&self) -> T {
+		self.sender.borrow().clone()
+	}
+}
+
+impl<T: PartialEq> Reloadable<T> {
+	/// Replace the current value, notifying subscribers if it has changed.
+	/// Returns `true` if the value was changed.
+	pub fn set(&self, value: T) -> bool {
+		self.sender.send_if_modified(|current| {
+			if *current == value {
+				return false;
+			}
+			*current = value;
+			true
+		})
+	}
+}
+
+impl<T> Clone for Reloadable<T> {
+	fn clone(&self) -> Self {
+		Self {
+			sender: self.sender.clone(),
+		}
+	}
+}
+
+impl<T: fmt::Debug> fmt::Debug for Reloadable<T> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.sender.borrow().fmt(formatter)
+	}
+}
+
+impl<'de, T> Deserialize<'de> for Reloadable<T>
+where
+	T: Deserialize<'de>,
+{
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		T::deserialize(deserializer).map(Self::new)
+	}
+}
@@ -0,0 +1,58 @@
+use std::{borrow::Borrow, fmt};
+
+use serde::Deserialize;
+
+/// Configuration value that must not be exposed, such as a password. The value
+/// is redacted when debug formatted, such that configuration holding it can be
+/// printed or logged safely.
+#[derive(Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+	pub fn expose(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> From<T> for Secret<T> {
+	fn from(value: T) -> Self {
+		Self(value)
+	}
+}
+
+impl<T> fmt::Debug for Secret<T> {
+	fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str("(redacted)")
+	}
+}
+
+// Allows secrets to key maps that are queried with plain strings, i.e. API keys.
+impl Borrow<str> for Secret<String> {
+	fn borrow(&self) -> &str {
+		&self.0
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::collections::HashMap;
+
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn redacted() {
+		let secret: Secret<String> = serde_json::from_str(r#""hunter2""#).unwrap();
+		assert_eq!(secret.expose(), "hunter2");
+		assert_eq!(format!("{secret:?}"), "(redacted)");
+	}
+
+	#[test]
+	fn map_key() {
+		let map = HashMap::from([(Secret::from("key".to_string()), 1)]);
+		assert_eq!(map.get("key"), Some(&1));
+		assert_eq!(format!("{map:?}"), "{(redacted): 1}");
+	}
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "http")]
 use serde::{Deserialize, Deserializer};
 
 #[derive(Debug)]
@@ -43,19 +44,19 @@ impl<T> Warnings<T> {
 		function(self.value).with_warnings(self.warnings)
 	}
 
-	// Used primarily for tests at the moment but hey who knows
-	#[allow(dead_code)]
 	pub fn decompose(self) -> (T, Vec<String>) {
 		(self.value, self.warnings)
 	}
 }
 
+#[cfg(feature = "http")]
 pub trait SoftDeserialize<'de>: Sized {
 	fn deserialize<D>(deserializer: D) -> Result<Warnings<Self>, D::Error>
 	where
 		D: Deserializer<'de>;
 }
 
+#[cfg(feature = "http")]
 impl<'de, T> Deserialize<'de> for Warnings<T>
 where
 	T: SoftDeserialize<'de>,
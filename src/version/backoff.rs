@@ -0,0 +1,193 @@
+use std::{
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
+
+use rand::Rng;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+	/// Delay, in seconds, before retrying after a single failed update. The
+	/// delay doubles with each further consecutive failure.
+	initial: u64,
+
+	/// Maximum delay, in seconds, between retries of failed updates.
+	max: u64,
+
+	/// Number of consecutive failures after which the update loop is reported
+	/// as unhealthy. Retries continue regardless.
+	threshold: u32,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			initial: 60,
+			max: 3600,
+			threshold: 5,
+		}
+	}
+}
+
+/// State of the update loop's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+	/// Updates are succeeding, or have failed fewer times than the threshold.
+	Closed,
+	/// Updates have failed consecutively at least as many times as the
+	/// threshold, and are being retried with backoff.
+	Open,
+}
+
+/// Health of the update loop, as reported to monitoring.
+#[derive(Debug, Clone)]
+pub struct UpdateHealth {
+	pub state: CircuitState,
+	pub consecutive_failures: u32,
+	pub last_error: Option<String>,
+	pub last_failure: Option<SystemTime>,
+	/// Time the next update will be attempted, if it is being delayed by backoff.
+	pub next_attempt: Option<SystemTime>,
+}
+
+/// Exponential backoff with jitter for consecutively failing updates.
+pub struct Backoff {
+	config: Config,
+	state: Mutex<BackoffState>,
+}
+
+#[derive(Default)]
+struct BackoffState {
+	failures: u32,
+	last_error: Option<String>,
+	last_failure: Option<SystemTime>,
+	next_attempt: Option<SystemTime>,
+}
+
+impl Backoff {
+	pub fn new(config: Config) -> Self {
+		Self {
+			config,
+			state: Default::default(),
+		}
+	}
+
+	/// Record a successful update, resetting the backoff.
+	pub fn succeed(&self) {
+		let mut state = self.state.lock().expect("poisoned");
+		if state.failures > 0 {
+			tracing::info!(failures = state.failures, "update recovered");
+		}
+		*state = BackoffState::default();
+	}
+
+	/// Record a failed update, returning the delay before it should be retried.
+	pub fn fail(&self, error: &anyhow::Error) -> Duration {
+		let mut state = self.state.lock().expect("poisoned");
+		state.failures = state.failures.saturating_add(1);
+
+		// Spread retries over the latter half of the delay, such that instances
+		// sharing an upstream don't retry in lockstep.
+		let delay = self.delay(state.failures);
+		let delay = delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0));
+
+		let now = SystemTime::now();
+		state.last_error = Some(format!("{error:#}"));
+		state.last_failure = Some(now);
+		state.next_attempt = Some(now + delay);
+
+		if state.failures == self.config.threshold {
+			tracing::warn!(failures = state.failures, "update circuit opened");
+		}
+
+		delay
+	}
+
+	/// Whether an update is being delayed by backoff.
+	pub fn waiting(&self) -> bool {
+		let state = self.state.lock().expect("poisoned");
+		state
+			.next_attempt
+			.is_some_and(|next_attempt| next_attempt > SystemTime::now())
+	}
+
+	pub fn health(&self) -> UpdateHealth {
+		let state = self.state.lock().expect("poisoned");
+		UpdateHealth {
+			state: match state.failures >= self.config.threshold.max(1) {
+				true => CircuitState::Open,
+				false => CircuitState::Closed,
+			},
+			consecutive_failures: state.failures,
+			last_error: state.last_error.clone(),
+			last_failure: state.last_failure,
+			next_attempt: state.next_attempt,
+		}
+	}
+
+	/// Delay before retrying after the given number of consecutive failures,
+	/// prior to jitter.
+	fn delay(&self, failures: u32) -> Duration {
+		let exponent = failures.saturating_sub(1).min(u64::BITS - 1);
+		let seconds = self
+			.config
+			.initial
+			.saturating_mul(1 << exponent)
+			.min(self.config.max);
+		Duration::from_secs(seconds)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn backoff() -> Backoff {
+		Backoff::new(Config {
+			initial: 10,
+			max: 100,
+			threshold: 2,
+		})
+	}
+
+	#[test]
+	fn delay_doubles_to_max() {
+		let backoff = backoff();
+		assert_eq!(backoff.delay(1), Duration::from_secs(10));
+		assert_eq!(backoff.delay(2), Duration::from_secs(20));
+		assert_eq!(backoff.delay(4), Duration::from_secs(80));
+		assert_eq!(backoff.delay(5), Duration::from_secs(100));
+		assert_eq!(backoff.delay(200), Duration::from_secs(100));
+	}
+
+	#[test]
+	fn jittered_delay_within_bounds() {
+		let backoff = backoff();
+		let delay = backoff.fail(&anyhow::anyhow!("failure"));
+		assert!(delay >= Duration::from_secs(5) && delay <= Duration::from_secs(10));
+		assert!(backoff.waiting());
+	}
+
+	#[test]
+	fn circuit_opens_at_threshold() {
+		let backoff = backoff();
+		backoff.fail(&anyhow::anyhow!("first"));
+		assert_eq!(backoff.health().state, CircuitState::Closed);
+
+		backoff.fail(&anyhow::anyhow!("second"));
+		let health = backoff.health();
+		assert_eq!(health.state, CircuitState::Open);
+		assert_eq!(health.consecutive_failures, 2);
+		assert_eq!(health.last_error.as_deref(), Some("second"));
+
+		backoff.succeed();
+		let health = backoff.health();
+		assert_eq!(health.state, CircuitState::Closed);
+		assert_eq!(health.consecutive_failures, 0);
+		assert!(!backoff.waiting());
+	}
+}
@@ -0,0 +1,127 @@
+use anyhow::{Context, Result};
+use axum_extra::headers::{Authorization, HeaderMapExt};
+use futures::future::try_join_all;
+use reqwest::header::HeaderMap;
+use serde::Deserialize;
+
+use crate::utility::secret::Secret;
+
+use super::{
+	patcher::Patcher,
+	provider,
+	snapshot::Snapshot,
+	version::{unix_seconds, Patch, Version},
+};
+
+/// Another boilmaster instance to copy versions from when starting with no
+/// local versions, rather than downloading patches from the patch servers.
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	/// Base URL of the source instance's admin routes, i.e. `http://primary:8080/admin`.
+	url: String,
+	/// Credentials for the source instance's admin routes.
+	username: String,
+	password: Secret<String>,
+}
+
+pub struct Bootstrap {
+	url: String,
+	client: reqwest::Client,
+}
+
+impl Bootstrap {
+	pub fn new(config: Config) -> Result<Self> {
+		// Patch downloads are shared with the patcher, which doesn't know about
+		// credentials - attach them to every request made by the client instead.
+		let mut headers = HeaderMap::new();
+		headers.typed_insert(Authorization::basic(
+			&config.username,
+			config.password.expose(),
+		));
+
+		let client = reqwest::Client::builder()
+			.default_headers(headers)
+			.build()?;
+
+		Ok(Self {
+			url: config.url.trim_end_matches('/').to_string(),
+			client,
+		})
+	}
+
+	/// Fetch a snapshot of the source instance's versions, and download every
+	/// patch it references. The snapshot is returned for restoration once all
+	/// patches are available locally.
+	pub async fn fetch(&self, patcher: &Patcher) -> Result<Snapshot> {
+		tracing::info!(url = self.url, "bootstrapping versions from instance");
+
+		let snapshot = self
+			.client
+			.get(format!("{}/snapshot", self.url))
+			.send()
+			.await?
+			.error_for_status()?
+			.json::<Snapshot>()
+			.await
+			.context("failed to fetch snapshot")?;
+
+		// Versions will typically share the bulk of their patches - only fetch each once.
+		let mut patches = snapshot
+			.versions
+			.values()
+			.map(|value| {
+				Version::deserialize(value, |repository, patch| {
+					patcher.patch_path(repository, patch)
+				})
+			})
+			.collect::<Result<Vec<_>>>()?
+			.into_iter()
+			.flat_map(|version| version.repositories)
+			.flat_map(|repository| {
+				repository
+					.patches
+					.into_iter()
+					.map(move |patch| (repository.name.clone(), patch))
+			})
+			.collect::<Vec<_>>();
+		patches.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+		patches.dedup_by(|a, b| a.0 == b.0 && a.1.name == b.1.name);
+
+		let pending_patches = patches
+			.into_iter()
+			.map(|(repository, patch)| self.fetch_patch(patcher, repository, patch));
+		try_join_all(pending_patches).await?;
+
+		Ok(snapshot)
+	}
+
+	async fn fetch_patch(&self, patcher: &Patcher, repository: String, patch: Patch) -> Result<()> {
+		let url = format!("{}/patches/{repository}/{}", self.url, patch.name);
+
+		// Versions persisted before patch sizes were recorded need to ask the
+		// source for the size, so the download can be validated.
+		let size = match patch.size {
+			Some(size) => size,
+			None => self
+				.client
+				.head(&url)
+				.send()
+				.await?
+				.error_for_status()?
+				.content_length()
+				.with_context(|| format!("no size available for {repository}/{}", patch.name))?,
+		};
+
+		let provider_patch = provider::Patch {
+			name: patch.name,
+			url,
+			size,
+			hash: patch.hash,
+			released: patch.released.map(unix_seconds),
+		};
+
+		patcher
+			.fetch_from(&self.client, &repository, provider_patch)
+			.await
+	}
+}
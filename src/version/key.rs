@@ -15,17 +15,64 @@ pub struct VersionKey(u64);
 
 impl From<&Version> for VersionKey {
 	fn from(version: &Version) -> Self {
-		let mut hasher = SeaHasher::new();
+		KeyScheme::CURRENT.derive(version)
+	}
+}
+
+/// Scheme used to derive a version key from the patches that make up the
+/// version. Keys are persisted, and used to identify derived data such as
+/// search indices - any change to derivation must be made as a new scheme, such
+/// that keys derived under previous schemes can be migrated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "u32", into = "u32")]
+pub enum KeyScheme {
+	/// Hash of the latest patch name in each repository, in repository order.
+	V1,
+}
+
+impl KeyScheme {
+	pub const CURRENT: Self = Self::V1;
+
+	pub fn derive(self, version: &Version) -> VersionKey {
+		self.derive_from_latest(
+			version
+				.repositories
+				.iter()
+				.map(|repository| repository.latest().name.as_str()),
+		)
+	}
 
-		for patch in version
-			.repositories
-			.iter()
-			.map(|repository| repository.latest())
-		{
-			patch.name.hash(&mut hasher);
+	/// Derive a key from the names of the latest patch in each repository of a
+	/// version, in repository order.
+	pub fn derive_from_latest<'a>(self, latest: impl IntoIterator<Item = &'a str>) -> VersionKey {
+		match self {
+			Self::V1 => {
+				let mut hasher = SeaHasher::new();
+				for name in latest {
+					name.hash(&mut hasher);
+				}
+				VersionKey(hasher.finish())
+			}
 		}
+	}
+}
 
-		Self(hasher.finish())
+impl From<KeyScheme> for u32 {
+	fn from(scheme: KeyScheme) -> Self {
+		match scheme {
+			KeyScheme::V1 => 1,
+		}
+	}
+}
+
+impl TryFrom<u32> for KeyScheme {
+	type Error = String;
+
+	fn try_from(value: u32) -> Result<Self, Self::Error> {
+		match value {
+			1 => Ok(Self::V1),
+			other => Err(format!("unknown version key scheme {other}")),
+		}
 	}
 }
 
@@ -61,3 +108,33 @@ impl<'de> Deserialize<'de> for VersionKey {
 		raw.parse().map_err(de::Error::custom)
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn v1_matches_legacy_derivation() {
+		// Keys derived before schemes were introduced hashed the latest patch name
+		// of each repository directly. V1 must remain stable against that.
+		let names = ["H2017.06.06.0000.0001a", "2024.06.18.0000.0000"];
+
+		let mut hasher = SeaHasher::new();
+		for name in names {
+			name.to_string().hash(&mut hasher);
+		}
+		let expected = VersionKey(hasher.finish());
+
+		let got = KeyScheme::V1.derive_from_latest(names);
+		assert_eq!(got, expected);
+	}
+
+	#[test]
+	fn scheme_round_trip() {
+		let scheme = KeyScheme::CURRENT;
+		let got = KeyScheme::try_from(u32::from(scheme));
+		assert_eq!(got, Ok(scheme));
+	}
+}
@@ -0,0 +1,167 @@
+use std::{collections::HashSet, time::SystemTime};
+
+use super::version::Version;
+
+/// Patch names reported by the provider for a repository, oldest-first.
+pub type LivePatchList = (String, Vec<String>);
+
+/// Comparison of the patch lists reported by the version provider against the
+/// version currently served as latest.
+#[derive(Debug, Clone)]
+pub struct LiveStatus {
+	/// Time the live patch lists were checked.
+	pub checked: SystemTime,
+
+	/// Number of live patches missing from the served version, for each game
+	/// repository, in configured order.
+	pub behind: Vec<(String, usize)>,
+
+	/// Latest patch of the boot repository, if tracked.
+	pub boot: Option<String>,
+
+	/// Whether the boot repository has been patched more recently than the
+	/// served game data. The boot repository is typically patched alongside
+	/// game updates, so this can indicate an update the game patch lists have
+	/// not yet caught up with.
+	pub boot_ahead: bool,
+}
+
+impl LiveStatus {
+	pub fn new(served: Option<&Version>, live: &[LivePatchList], boot: Option<String>) -> Self {
+		let behind = live
+			.iter()
+			.map(|(repository, patches)| {
+				let served_patches = served
+					.and_then(|version| {
+						version
+							.repositories
+							.iter()
+							.find(|candidate| &candidate.name == repository)
+					})
+					.map(|repository| {
+						repository
+							.patches
+							.iter()
+							.map(|patch| patch.name.as_str())
+							.collect::<HashSet<_>>()
+					})
+					.unwrap_or_default();
+
+				let missing = patches
+					.iter()
+					.filter(|patch| !served_patches.contains(patch.as_str()))
+					.count();
+
+				(repository.clone(), missing)
+			})
+			.collect();
+
+		// The first repository is the base game, which is patched every update.
+		let served_latest = served
+			.and_then(|version| version.repositories.first())
+			.map(|repository| patch_date(&repository.latest().name));
+		let boot_ahead = match (&boot, served_latest) {
+			(Some(boot), Some(served_latest)) => patch_date(boot) > served_latest,
+			(Some(_), None) => true,
+			(None, _) => false,
+		};
+
+		Self {
+			checked: SystemTime::now(),
+			behind,
+			boot,
+			boot_ahead,
+		}
+	}
+
+	/// Total number of live patches missing from the served version.
+	pub fn patches_behind(&self) -> usize {
+		self.behind.iter().map(|(_, count)| count).sum()
+	}
+}
+
+/// Date portion of a patch name, i.e. `2023.09.28` for `D2023.09.28.0000.0001`.
+/// Dates are zero-padded, and hence compare correctly as strings.
+fn patch_date(name: &str) -> &str {
+	let name = name.trim_start_matches(|char: char| !char.is_ascii_digit());
+	name.get(..10).unwrap_or(name)
+}
+
+#[cfg(test)]
+mod test {
+	use std::path::PathBuf;
+
+	use nonempty::NonEmpty;
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+	use crate::version::{Patch, Repository};
+
+	fn version(repositories: &[(&str, &[&str])]) -> Version {
+		let repositories = repositories
+			.iter()
+			.map(|(name, patches)| Repository {
+				name: name.to_string(),
+				patches: NonEmpty::from_vec(
+					patches
+						.iter()
+						.map(|patch| Patch {
+							name: patch.to_string(),
+							path: PathBuf::new(),
+							size: None,
+							hash: None,
+							released: None,
+						})
+						.collect(),
+				)
+				.unwrap(),
+			})
+			.collect();
+
+		Version { repositories }
+	}
+
+	fn live(repositories: &[(&str, &[&str])]) -> Vec<LivePatchList> {
+		repositories
+			.iter()
+			.map(|(name, patches)| {
+				(
+					name.to_string(),
+					patches.iter().map(|patch| patch.to_string()).collect(),
+				)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn patches_behind() {
+		let served = version(&[("game", &["D2023.01.01.0000.0000"])]);
+		let status = LiveStatus::new(
+			Some(&served),
+			&live(&[
+				("game", &["D2023.01.01.0000.0000", "D2023.02.01.0000.0000"]),
+				("ex1", &["D2023.02.01.0000.0000"]),
+			]),
+			None,
+		);
+
+		assert_eq!(
+			status.behind,
+			vec![("game".to_string(), 1), ("ex1".to_string(), 1)]
+		);
+		assert_eq!(status.patches_behind(), 2);
+		assert!(!status.boot_ahead);
+	}
+
+	#[test]
+	fn boot_ahead() {
+		let served = version(&[("game", &["D2023.01.01.0000.0000"])]);
+		let game = live(&[("game", &["D2023.01.01.0000.0000"])]);
+
+		let ahead = LiveStatus::new(Some(&served), &game, Some("H2023.02.01.0000.0000".into()));
+		assert!(ahead.boot_ahead);
+
+		let current = LiveStatus::new(Some(&served), &game, Some("H2022.12.01.0000.0000".into()));
+		assert!(!current.boot_ahead);
+	}
+}
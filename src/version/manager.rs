@@ -1,80 +1,163 @@
 use std::{
-	collections::{hash_map::Entry, BTreeMap, HashMap},
+	collections::{hash_map::Entry, BTreeMap, BTreeSet, HashMap, HashSet},
 	fs,
-	io::{self, Read},
 	path::{Path, PathBuf},
-	sync::RwLock,
+	sync::{Arc, RwLock},
+	time::SystemTime,
 };
 
-use anyhow::Result;
+use anyhow::{ensure, Context, Result};
 use figment::value::magic::RelativePathBuf;
-use fs4::FileExt;
 use futures::future::{join_all, try_join_all};
 use nonempty::NonEmpty;
-use serde::{Deserialize, Serialize};
+use serde::{de::IgnoredAny, Deserialize, Serialize};
 use tokio::{select, sync::watch, time};
 use tokio_util::sync::CancellationToken;
 
+use crate::{
+	job,
+	storage::Storage,
+	utility::{persist, reloadable::Reloadable},
+};
+
 use super::{
-	key::VersionKey,
-	patcher, thaliak,
+	backoff::{self, Backoff, UpdateHealth},
+	bootstrap::Bootstrap,
+	key::{KeyScheme, VersionKey},
+	live::{LivePatchList, LiveStatus},
+	name::{is_reserved, validate as validate_name, NameError, LATEST},
+	patcher,
+	provider::{self, Provider},
+	snapshot::{Snapshot, SNAPSHOT_FORMAT},
 	version::{Repository, Version},
+	window::Windows,
 };
 
-const TAG_LATEST: &str = "latest";
-
 #[derive(Debug, Deserialize)]
 pub struct Config {
-	thaliak: thaliak::Config,
+	provider: provider::Config,
 	patch: patcher::Config,
 
-	interval: u64,
+	interval: Reloadable<u64>,
 	directory: RelativePathBuf,
 	repositories: Vec<String>,
+
+	/// Boot repository to track alongside the game repositories. The boot
+	/// repository is never downloaded, but its patch list is compared against
+	/// the served game data to detect updates not yet reflected in game patch
+	/// lists.
+	boot_repository: Option<String>,
+
+	/// Run as a read-only replica. Replicas never poll for or download patches,
+	/// instead periodically reading the version metadata persisted by a primary
+	/// instance, either via a shared directory or shared storage.
+	#[serde(default)]
+	replica: bool,
+
+	/// Copy versions and patches from another instance on first start, rather
+	/// than downloading everything from the patch servers.
+	bootstrap: Option<bootstrap::Config>,
+
+	/// Windows during which patch downloads may start. Updates are still
+	/// detected immediately, but downloading them - and ingesting the resulting
+	/// version - is deferred until a window opens.
+	#[serde(default)]
+	download_windows: Windows,
+
+	/// Backoff applied to retries after consecutive failed updates.
+	#[serde(default)]
+	backoff: backoff::Config,
 }
 
+// Key within storage that version metadata is shared under.
+const STORAGE_KEY: &str = "versions";
+
 pub struct Manager {
-	provider: thaliak::Provider,
+	provider: Box<dyn Provider>,
 	patcher: patcher::Patcher,
 
-	update_interval: u64,
+	update_interval: Reloadable<u64>,
 	directory: PathBuf,
 	repositories: Vec<String>,
+	boot_repository: Option<String>,
+	replica: bool,
+	storage: Option<Arc<Storage>>,
+	bootstrap: Option<Bootstrap>,
+	download_windows: Windows,
+	backoff: Backoff,
 
 	versions: RwLock<HashMap<VersionKey, Version>>,
 	names: RwLock<HashMap<String, VersionKey>>,
+	hidden: RwLock<HashSet<VersionKey>>,
+	live: RwLock<Option<LiveStatus>>,
 
 	channel: watch::Sender<Vec<VersionKey>>,
 }
 
 impl Manager {
-	pub fn new(config: Config) -> Result<Self> {
+	pub fn new(
+		config: Config,
+		jobs: Arc<job::Manager>,
+		storage: Option<Arc<Storage>>,
+	) -> Result<Self> {
+		ensure!(
+			config.interval.get() > 0,
+			"version update interval must be greater than 0"
+		);
+
 		let directory = config.directory.relative();
 		fs::create_dir_all(&directory)?;
 
 		let (sender, _receiver) = watch::channel(vec![]);
 
 		Ok(Self {
-			provider: thaliak::Provider::new(config.thaliak),
-			patcher: patcher::Patcher::new(config.patch),
+			provider: provider::provider(config.provider),
+			patcher: patcher::Patcher::new(config.patch, jobs, storage.clone()),
 
 			update_interval: config.interval,
 			directory,
 			repositories: config.repositories,
+			boot_repository: config.boot_repository,
+			replica: config.replica,
+			storage,
+			bootstrap: config.bootstrap.map(Bootstrap::new).transpose()?,
+			download_windows: config.download_windows,
+			backoff: Backoff::new(config.backoff),
 
 			versions: Default::default(),
 			names: Default::default(),
+			hidden: Default::default(),
+			live: Default::default(),
 
 			channel: sender,
 		})
 	}
 
+	/// Apply reload-safe settings from an updated configuration.
+	pub fn reload(&self, config: Config) {
+		// A zero interval would panic the update loop - keep the current one.
+		let interval = config.interval.get();
+		if interval == 0 {
+			tracing::warn!("ignoring version update interval of 0");
+			return;
+		}
+
+		if self.update_interval.set(interval) {
+			tracing::info!(interval = ?self.update_interval, "version update interval changed");
+		}
+	}
+
 	pub fn ready(&self) -> bool {
 		// Mark ready once we've got at least one version - existing systems will
 		// hydrate metadata from disk in one go.
 		self.versions.read().expect("poisoned").len() > 0
 	}
 
+	/// Health of the update loop, reflecting any consecutive failed updates.
+	pub fn update_health(&self) -> UpdateHealth {
+		self.backoff.health()
+	}
+
 	/// Subscribe to changes to the version list.
 	pub fn subscribe(&self) -> watch::Receiver<Vec<VersionKey>> {
 		self.channel.subscribe()
@@ -96,10 +179,25 @@ impl Manager {
 		self.names
 			.read()
 			.expect("poisoned")
-			.get(name.unwrap_or(TAG_LATEST))
+			.get(name.unwrap_or(LATEST))
 			.copied()
 	}
 
+	/// Resolve a version name to its key, as `resolve`, excluding hidden versions.
+	pub fn resolve_visible(&self, name: Option<&str>) -> Option<VersionKey> {
+		self.resolve(name).filter(|key| !self.hidden(*key))
+	}
+
+	/// Resolve a version key in its string form, if the key is known.
+	pub fn resolve_key(&self, key: &str) -> Option<VersionKey> {
+		let key = key.parse::<VersionKey>().ok()?;
+		self.versions
+			.read()
+			.expect("poisoned")
+			.contains_key(&key)
+			.then_some(key)
+	}
+
 	/// Get a list of all known version names.
 	pub fn all_names(&self) -> Vec<String> {
 		self.names
@@ -110,6 +208,46 @@ impl Manager {
 			.collect()
 	}
 
+	/// Get a list of all version names, excluding those of hidden versions.
+	pub fn visible_names(&self) -> Vec<String> {
+		let hidden = self.hidden.read().expect("poisoned").clone();
+		self.names
+			.read()
+			.expect("poisoned")
+			.iter()
+			.filter(|(_name, key)| !hidden.contains(key))
+			.map(|(name, _key)| name.clone())
+			.collect()
+	}
+
+	/// Check if a version is hidden from public listing and name resolution.
+	pub fn hidden(&self, key: VersionKey) -> bool {
+		self.hidden.read().expect("poisoned").contains(&key)
+	}
+
+	/// Hide or reveal a version. Hidden versions retain their data, but are
+	/// excluded from public listing and name resolution.
+	pub async fn set_hidden(&self, key: VersionKey, hidden: bool) -> Result<()> {
+		if self.replica {
+			anyhow::bail!("version visibility is read-only on a replica");
+		}
+
+		let changed = {
+			let mut hidden_keys = self.hidden.write().expect("poisoned");
+			match hidden {
+				true => hidden_keys.insert(key),
+				false => hidden_keys.remove(&key),
+			}
+		};
+
+		if changed {
+			tracing::info!(%key, hidden, "version visibility changed");
+			self.persist_metadata().await?;
+		}
+
+		Ok(())
+	}
+
 	/// Get a list of names for a given version key.
 	pub fn names(&self, key: VersionKey) -> Option<Vec<String>> {
 		// Make sure the version is actually known to exist, to distinguish between an unknown key and a key with no names.
@@ -129,27 +267,184 @@ impl Manager {
 	}
 
 	/// Set the names for the specified version. If a name already exists, it
-	/// will be updated to match.
+	/// will be updated to match. Reserved names, such as `latest`, are managed
+	/// by the version system - they are left in place, and may not be assigned.
 	pub async fn set_names(
 		&self,
 		key: VersionKey,
 		new_names: impl IntoIterator<Item = impl ToString>,
 	) -> Result<()> {
+		if self.replica {
+			anyhow::bail!("version names are read-only on a replica");
+		}
+
+		let new_names = new_names
+			.into_iter()
+			.map(|name| name.to_string())
+			.collect::<Vec<_>>();
+
 		// Funny squigglies because something in the checker(s) doesn't manage to track ownership properly with a drop().
 		{
 			let mut names = self.names.write().expect("poisoned");
-			names.retain(|_, value| *value != key);
-			names.extend(new_names.into_iter().map(|name| (name.to_string(), key)));
+
+			for name in &new_names {
+				match validate_name(name) {
+					// Resubmitting a reserved name the version already holds is a no-op.
+					Err(NameError::Reserved(..)) if names.get(name) == Some(&key) => {}
+					result => result?,
+				}
+			}
+
+			names.retain(|name, value| *value != key || is_reserved(name));
+			names.extend(
+				new_names
+					.into_iter()
+					.filter(|name| !is_reserved(name))
+					.map(|name| (name, key)),
+			);
 		}
 		self.persist_metadata().await?;
 		Ok(())
 	}
 
+	/// Get the most recent comparison of the live patch lists against the
+	/// version served as latest. Replicas do not check live patch lists, and
+	/// will always return `None`.
+	pub fn live_status(&self) -> Option<LiveStatus> {
+		self.live.read().expect("poisoned").clone()
+	}
+
 	/// Get the full version metadata for a given key, if it exists.
 	pub fn version(&self, key: VersionKey) -> Option<Version> {
 		self.versions.read().expect("poisoned").get(&key).cloned()
 	}
 
+	/// Get the time the game data for a given version was last changed, if known.
+	pub fn released(&self, key: VersionKey) -> Option<SystemTime> {
+		self.versions
+			.read()
+			.expect("poisoned")
+			.get(&key)
+			.and_then(Version::released)
+	}
+
+	/// Get the local path of a patch, if it is part of a known version.
+	pub fn patch_path(&self, repository: &str, patch: &str) -> Option<PathBuf> {
+		self.versions
+			.read()
+			.expect("poisoned")
+			.values()
+			.flat_map(|version| version.repositories.iter())
+			.filter(|candidate| candidate.name == repository)
+			.flat_map(|repository| repository.patches.iter())
+			.find(|candidate| candidate.name == patch)
+			.map(|patch| patch.path.clone())
+	}
+
+	/// Create a snapshot of the current version metadata, names, and visibility.
+	pub fn snapshot(&self) -> Result<Snapshot> {
+		// Hold all locks for the duration so the snapshot is consistent.
+		let versions = self.versions.read().expect("poisoned");
+		let names = self.names.read().expect("poisoned");
+		let hidden = self.hidden.read().expect("poisoned");
+
+		let persisted_versions = versions
+			.iter()
+			.map(|(key, version)| -> Result<_> {
+				let value = version.serialize(serde_json::value::Serializer)?;
+				Ok((*key, value))
+			})
+			.collect::<Result<_>>()?;
+
+		Ok(Snapshot {
+			format: SNAPSHOT_FORMAT,
+			created: SystemTime::now(),
+			versions: persisted_versions,
+			names: names
+				.iter()
+				.map(|(name, key)| (name.clone(), *key))
+				.collect(),
+			hidden: hidden.iter().copied().collect(),
+		})
+	}
+
+	/// Replace the current version metadata, names, and visibility with those
+	/// recorded in a snapshot. Patches referenced by the snapshot are expected to already be
+	/// available locally.
+	pub async fn restore(&self, snapshot: Snapshot) -> Result<()> {
+		if self.replica {
+			anyhow::bail!("snapshots cannot be restored on a replica");
+		}
+
+		if snapshot.format != SNAPSHOT_FORMAT {
+			anyhow::bail!(
+				"unsupported snapshot format {}, expected {SNAPSHOT_FORMAT}",
+				snapshot.format
+			);
+		}
+
+		// Validate the full snapshot before touching any state.
+		let mut versions = HashMap::new();
+		for (key, value) in snapshot.versions {
+			let version = Version::deserialize(value, |repository, patch| {
+				self.patcher.patch_path(repository, patch)
+			})?;
+
+			if VersionKey::from(&version) != key {
+				anyhow::bail!("snapshot version {key} does not match its patch list");
+			}
+
+			let missing = version
+				.repositories
+				.iter()
+				.flat_map(|repository| repository.patches.iter())
+				.filter(|patch| !patch.path.is_file())
+				.count();
+			if missing > 0 {
+				tracing::warn!(%key, missing, "restored version is missing local patches");
+			}
+
+			versions.insert(key, version);
+		}
+
+		if let Some((name, key)) = snapshot
+			.names
+			.iter()
+			.find(|(_name, key)| !versions.contains_key(key))
+		{
+			anyhow::bail!("snapshot name {name} refers to unknown version {key}");
+		}
+
+		if let Some(key) = snapshot
+			.hidden
+			.iter()
+			.find(|key| !versions.contains_key(key))
+		{
+			anyhow::bail!("snapshot hides unknown version {key}");
+		}
+
+		tracing::info!(
+			versions = versions.len(),
+			names = snapshot.names.len(),
+			hidden = snapshot.hidden.len(),
+			"restoring snapshot"
+		);
+
+		*self.versions.write().expect("poisoned") = versions.clone();
+		*self.names.write().expect("poisoned") = snapshot.names.into_iter().collect();
+		*self.hidden.write().expect("poisoned") = snapshot.hidden.into_iter().collect();
+
+		let pending_versions = versions
+			.into_iter()
+			.map(|(key, version)| self.persist_version(key, version));
+		try_join_all(pending_versions).await?;
+		self.persist_metadata().await?;
+
+		self.broadcast();
+
+		Ok(())
+	}
+
 	pub async fn start(&self, cancel: CancellationToken) -> Result<()> {
 		select! {
 			result = self.start_inner() => result,
@@ -159,30 +454,131 @@ impl Manager {
 
 	async fn start_inner(&self) -> Result<()> {
 		// Hydrate from disk.
+		if self.replica {
+			self.fetch_shared().await?;
+		}
 		self.hydrate().await?;
 
-		// Set up an interval to check for updates.
-		let mut interval = time::interval(time::Duration::from_secs(self.update_interval));
-		interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+		// A fresh instance has nothing to hydrate - copy versions from the
+		// bootstrap source, if any, before the first update.
+		if let Some(bootstrap) = &self.bootstrap {
+			if !self.replica && !self.ready() {
+				let snapshot = bootstrap.fetch(&self.patcher).await?;
+				self.restore(snapshot).await?;
+			}
+		}
+
+		// Set up an interval to check for updates, rebuilding it whenever the
+		// configured period changes.
+		let mut interval_changes = self.update_interval.subscribe();
+		let mut start = time::Instant::now();
+
+		// Time to retry an update that was deferred until a download window opens,
+		// or backed off after failing.
+		let mut deferred = None::<time::Instant>;
 
 		loop {
-			interval.tick().await;
+			let period = time::Duration::from_secs(*interval_changes.borrow_and_update());
+			let mut interval = time::interval_at(start, period);
+			interval.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+
+			loop {
+				select! {
+					// Interval ticks during backoff are skipped - the deferred retry
+					// will pick the update up.
+					_ = interval.tick(), if !self.backoff.waiting() => {
+						deferred = self.run_update().await
+					}
+					_ = time::sleep_until(deferred.unwrap_or_else(time::Instant::now)), if deferred.is_some() => {
+						deferred = self.run_update().await
+					}
+					Ok(()) = interval_changes.changed() => break,
+				}
+			}
 
-			if let Err(error) = self.update().await {
-				tracing::error!(?error, "update failed");
+			start = time::Instant::now() + time::Duration::from_secs(*interval_changes.borrow());
+		}
+	}
+
+	/// Run a single update pass, returning the time to retry at if the update
+	/// was deferred until a download window opens, or failed.
+	async fn run_update(&self) -> Option<time::Instant> {
+		let result = match self.replica {
+			true => self.refresh().await.map(|_| None),
+			false => self.update().await,
+		};
+
+		match result {
+			Ok(retry) => {
+				self.backoff.succeed();
+				retry.map(|retry| {
+					let delay = retry.duration_since(SystemTime::now()).unwrap_or_default();
+					time::Instant::now() + delay
+				})
+			}
+			Err(error) => {
+				let delay = self.backoff.fail(&error);
+				let consecutive_failures = self.backoff.health().consecutive_failures;
+				tracing::error!(?error, consecutive_failures, ?delay, "update failed");
+				Some(time::Instant::now() + delay)
 			}
 		}
 	}
 
 	// TODO: There should only be one update pass running at a time - two would result in races.
-	async fn update(&self) -> Result<()> {
+	async fn update(&self) -> Result<Option<SystemTime>> {
 		tracing::info!("checking for version updates");
 
-		// Get a fresh view of the repositories.
-		let pending_repositories = self
-			.repositories
+		// Get a fresh view of the repositories' patch lists.
+		let pending_patch_lists = self.repositories.iter().map(|repository| async move {
+			let patch_list = self.provider.patch_list(repository).await?;
+			anyhow::Ok((repository, patch_list))
+		});
+		let patch_lists = try_join_all(pending_patch_lists).await?;
+
+		let live = patch_lists
 			.iter()
-			.map(|repository| self.fetch_repository(repository));
+			.map(|(repository, patch_list)| {
+				let names = patch_list.iter().map(|patch| patch.name.clone()).collect();
+				(repository.to_string(), names)
+			})
+			.collect::<Vec<_>>();
+		let boot = self.fetch_boot().await;
+		self.record_live_status(&live, boot.clone());
+
+		// Downloads are bandwidth-heavy - if any patches are missing, hold off
+		// until a download window is open.
+		let now = SystemTime::now();
+		if !self.download_windows.is_open(now) {
+			let mut missing = 0;
+			for (repository, patch_list) in &patch_lists {
+				for patch in patch_list.iter() {
+					if self.patcher.needs_fetch(repository, patch)? {
+						missing += 1;
+					}
+				}
+			}
+
+			if missing > 0 {
+				let retry = self.download_windows.next_open(now);
+				match retry {
+					Some(retry) => tracing::info!(
+						missing,
+						?retry,
+						"new patches detected, deferring download until the next window"
+					),
+					None => tracing::warn!(
+						missing,
+						"new patches detected, but no configured download window will ever open"
+					),
+				}
+				return Ok(retry);
+			}
+		}
+
+		let pending_repositories = patch_lists
+			.into_iter()
+			.map(|(repository, patch_list)| self.fetch_repository(repository, patch_list));
 		let repositories = try_join_all(pending_repositories).await?;
 
 		// Build a version struct and it's associated key and save it to the versions map.
@@ -212,7 +608,7 @@ impl Manager {
 
 		// If there hasn't been any changes from this update, skip running updates beyond this point.
 		if !changed {
-			return Ok(());
+			return Ok(None);
 		}
 
 		tracing::info!(%key, "new or updated version");
@@ -222,7 +618,7 @@ impl Manager {
 		self.names
 			.write()
 			.expect("poisoned")
-			.insert(TAG_LATEST.to_string(), key);
+			.insert(LATEST.to_string(), key);
 
 		// Persist updated metadata
 		tokio::try_join!(
@@ -231,16 +627,87 @@ impl Manager {
 			self.persist_metadata()
 		)?;
 
+		// The new version is now served as latest - bring the live status up to date.
+		self.record_live_status(&live, boot);
+
 		// There's a change to versions, broadcast as such.
 		self.broadcast();
 
+		Ok(None)
+	}
+
+	/// Fetch the latest patch of the boot repository, if one is tracked. Boot
+	/// status is informational, so failures are logged rather than failing the
+	/// update.
+	async fn fetch_boot(&self) -> Option<String> {
+		let repository = self.boot_repository.as_ref()?;
+		match self.provider.patch_list(repository).await {
+			Ok(patch_list) => Some(patch_list.last().name.clone()),
+			Err(error) => {
+				tracing::warn!(repository, ?error, "failed to fetch boot patch list");
+				None
+			}
+		}
+	}
+
+	fn record_live_status(&self, live: &[LivePatchList], boot: Option<String>) {
+		let served = self.resolve(None).and_then(|key| self.version(key));
+		let status = LiveStatus::new(served.as_ref(), live, boot);
+
+		let behind = status.patches_behind();
+		if behind > 0 || status.boot_ahead {
+			tracing::info!(
+				behind,
+				boot_ahead = status.boot_ahead,
+				"served version lags the live patch lists"
+			);
+		}
+
+		*self.live.write().expect("poisoned") = Some(status);
+	}
+
+	/// Re-read version metadata shared by the primary instance.
+	async fn refresh(&self) -> Result<()> {
+		tracing::info!("refreshing versions from primary");
+		self.fetch_shared().await?;
+		self.hydrate().await
+	}
+
+	/// Copy version metadata from shared storage, if configured. Replicas
+	/// without storage are expected to share the version directory itself.
+	async fn fetch_shared(&self) -> Result<()> {
+		let Some(storage) = &self.storage else {
+			return Ok(());
+		};
+
+		storage
+			.download_directory(STORAGE_KEY, &self.directory)
+			.await?;
+
 		Ok(())
 	}
 
-	async fn fetch_repository(&self, repository: &str) -> Result<Repository> {
-		// a failure to fetch the patch list for a repo is pretty unrecoverable i think?
-		let patch_list = self.provider.patch_list(repository.to_string()).await?;
+	/// Upload a persisted metadata file to shared storage, if configured.
+	async fn share(&self, path: &Path) {
+		let (Some(storage), Some(file_name)) = (&self.storage, path.file_name()) else {
+			return;
+		};
+
+		let key = format!("{STORAGE_KEY}/{}", file_name.to_string_lossy());
+		if let Err(error) = storage.upload_file(path, &key).await {
+			tracing::warn!(
+				?path,
+				?error,
+				"failed to upload version metadata to storage"
+			);
+		}
+	}
 
+	async fn fetch_repository(
+		&self,
+		repository: &str,
+		patch_list: NonEmpty<provider::Patch>,
+	) -> Result<Repository> {
 		// todo: is a failure here meaningful? i imagine retries and so on should be done at the patcher
 		// note: would use nonempty::map but i need asyncnessnessness
 		let pending_patches = patch_list
@@ -268,6 +735,11 @@ impl Manager {
 			return Ok(());
 		};
 
+		// Keys persisted under a previous derivation scheme are re-derived with the
+		// current scheme, carrying names across to the new keys.
+		let migrate = metadata.key_scheme != KeyScheme::CURRENT;
+		let mut migrated = HashMap::new();
+
 		let pending_versions = metadata
 			.versions
 			.iter()
@@ -290,7 +762,17 @@ impl Manager {
 			};
 
 			tracing::debug!(%key, "hydrated version");
-			versions.insert(key, version);
+
+			let current_key = match migrate {
+				true => KeyScheme::CURRENT.derive(&version),
+				false => key,
+			};
+			if current_key != key {
+				tracing::info!(%key, %current_key, "migrated version key");
+				migrated.insert(key, current_key);
+			}
+
+			versions.insert(current_key, version);
 		}
 
 		drop(versions);
@@ -298,7 +780,12 @@ impl Manager {
 		let versions = self.versions.read().expect("poisoned");
 		let mut names = self.names.write().expect("poisoned");
 
+		// Persisted names are authoritative - on a replica, names may have been
+		// moved or removed by the primary since the last hydration.
+		names.clear();
+
 		for (name, key) in metadata.names {
+			let key = migrated.get(&key).copied().unwrap_or(key);
 			if !versions.contains_key(&key) {
 				tracing::warn!(name, %key, "unknown key for name");
 				continue;
@@ -308,20 +795,37 @@ impl Manager {
 			names.insert(name, key);
 		}
 
+		*self.hidden.write().expect("poisoned") = metadata
+			.hidden
+			.into_iter()
+			.map(|key| migrated.get(&key).copied().unwrap_or(key))
+			.filter(|key| versions.contains_key(key))
+			.collect();
+
+		drop(names);
+		drop(versions);
+
 		// Hydration is complete - broadcast the version list.
 		self.broadcast();
 
+		// Persist migrated keys so the migration only occurs once. Replicas leave
+		// this to the primary.
+		if migrate && !self.replica {
+			for key in migrated.into_values() {
+				if let Some(version) = self.version(key) {
+					self.persist_version(key, version).await?;
+				}
+			}
+			self.persist_metadata().await?;
+		}
+
 		Ok(())
 	}
 
 	async fn hydrate_metadata(&self) -> Result<Option<PersistedMetadata>> {
 		let path = self.metadata_path();
-		let join_handle = tokio::task::spawn_blocking(|| -> Result<Option<PersistedMetadata>> {
-			let Some(file) = open_config_read(path)? else {
-				return Ok(None);
-			};
-			let metadata: PersistedMetadata = serde_json::from_reader(file)?;
-			Ok(Some(metadata))
+		let join_handle = tokio::task::spawn_blocking(move || {
+			persist::read(&path, |bytes| Ok(serde_json::from_slice(bytes)?))
 		});
 
 		join_handle.await?
@@ -331,12 +835,13 @@ impl Manager {
 		// NOTE: Parsing outside the task so I don't have to get the self reference into the task for patch paths.
 		let path = self.version_path(key);
 		let join_handle = tokio::task::spawn_blocking(move || -> Result<String> {
-			let Some(mut file) = open_config_read(path)? else {
-				anyhow::bail!("version {key} has no persisted configuration")
-			};
-			let mut buffer = String::new();
-			file.read_to_string(&mut buffer)?;
-			Ok(buffer)
+			// Syntax is checked here so a damaged file falls back to its backup.
+			let buffer = persist::read(&path, |bytes| {
+				let buffer = String::from_utf8(bytes.to_vec())?;
+				serde_json::from_str::<IgnoredAny>(&buffer)?;
+				Ok(buffer)
+			})?;
+			buffer.with_context(|| format!("version {key} has no persisted configuration"))
 		});
 		let string_config = join_handle.await??;
 
@@ -347,11 +852,24 @@ impl Manager {
 
 		// TODO: should probably validate these versions too - will need to store at least the file size, and preferably the hash as well once i have that.
 
+		// Replicas can't download missing patches themselves - make sure they're
+		// available before accepting the version.
+		if self.replica {
+			for repository in &version.repositories {
+				for patch in &repository.patches {
+					self.patcher
+						.ensure_local_patch(&repository.name, &patch.name)
+						.await?;
+				}
+			}
+		}
+
 		Ok(version)
 	}
 
 	async fn persist_metadata(&self) -> Result<()> {
 		let persisted_versions = PersistedMetadata {
+			key_scheme: KeyScheme::CURRENT,
 			versions: self
 				.versions
 				.read()
@@ -367,26 +885,42 @@ impl Manager {
 				.clone()
 				.into_iter()
 				.collect(),
+
+			hidden: self
+				.hidden
+				.read()
+				.expect("poisoned")
+				.iter()
+				.copied()
+				.collect(),
 		};
 
 		let path = self.metadata_path();
+		let write_path = path.clone();
 		let join_handle = tokio::task::spawn_blocking(move || -> Result<()> {
-			let file = open_config_write(path)?;
-			serde_json::to_writer_pretty(file, &persisted_versions)?;
-			Ok(())
+			let contents = serde_json::to_vec_pretty(&persisted_versions)?;
+			persist::write(&write_path, &contents)
 		});
+		join_handle.await??;
 
-		join_handle.await?
+		self.share(&path).await;
+
+		Ok(())
 	}
 
 	async fn persist_version(&self, key: VersionKey, version: Version) -> Result<()> {
-		let path = self.directory.join(format!("version-{key}.json"));
+		let path = self.version_path(key);
+		let write_path = path.clone();
 		let join_handle = tokio::task::spawn_blocking(move || -> Result<()> {
-			let file = open_config_write(path)?;
-			version.serialize(&mut serde_json::Serializer::pretty(file))?;
-			Ok(())
+			let mut contents = vec![];
+			version.serialize(&mut serde_json::Serializer::pretty(&mut contents))?;
+			persist::write(&write_path, &contents)
 		});
-		join_handle.await?
+		join_handle.await??;
+
+		self.share(&path).await;
+
+		Ok(())
 	}
 
 	fn broadcast(&self) {
@@ -413,29 +947,15 @@ impl Manager {
 
 #[derive(Serialize, Deserialize)]
 struct PersistedMetadata {
+	// Metadata persisted before key schemes were introduced used the first scheme.
+	#[serde(default = "legacy_key_scheme")]
+	key_scheme: KeyScheme,
 	versions: Vec<VersionKey>,
 	names: BTreeMap<String, VersionKey>,
+	#[serde(default)]
+	hidden: BTreeSet<VersionKey>,
 }
 
-fn open_config_read(path: impl AsRef<Path>) -> Result<Option<fs::File>> {
-	let file = match fs::File::open(path) {
-		Ok(file) => file,
-		Err(error) => {
-			return match error.kind() {
-				io::ErrorKind::NotFound => Ok(None),
-				_ => Err(error.into()),
-			}
-		}
-	};
-
-	file.lock_shared()?;
-
-	Ok(Some(file))
-}
-
-fn open_config_write(path: impl AsRef<Path>) -> Result<fs::File> {
-	let file = fs::File::options().create(true).write(true).open(path)?;
-	file.lock_exclusive()?;
-	file.set_len(0)?;
-	Ok(file)
+fn legacy_key_scheme() -> KeyScheme {
+	KeyScheme::V1
 }
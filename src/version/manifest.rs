@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use futures::{future::BoxFuture, FutureExt};
+use nonempty::NonEmpty;
+use serde::Deserialize;
+
+use super::provider::{Patch, Provider};
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	/// URL or local file path of the manifest. The manifest is a JSON object
+	/// mapping repository names to their list of patches, oldest-first, i.e.
+	/// `{"ffxivneo/win32/release/game": [{"name": "...", "url": "...", "size": 0}]}`.
+	source: String,
+}
+
+/// Provider reading patch lists from a static JSON manifest. The manifest is
+/// re-read on every request for a patch list, such that it may be updated
+/// without restarting.
+pub struct Manifest {
+	source: String,
+	client: reqwest::Client,
+}
+
+impl Manifest {
+	pub fn new(config: Config) -> Self {
+		Self {
+			source: config.source,
+			client: reqwest::Client::new(),
+		}
+	}
+
+	async fn read(&self) -> Result<HashMap<String, Vec<Patch>>> {
+		let remote = self.source.starts_with("http://") || self.source.starts_with("https://");
+		let manifest = match remote {
+			true => {
+				self.client
+					.get(&self.source)
+					.send()
+					.await?
+					.error_for_status()?
+					.json()
+					.await?
+			}
+			false => {
+				let bytes = tokio::fs::read(&self.source).await?;
+				serde_json::from_slice(&bytes)?
+			}
+		};
+
+		Ok(manifest)
+	}
+
+	#[tracing::instrument(level = "debug", skip(self))]
+	async fn manifest_patch_list(&self, repository: &str) -> Result<NonEmpty<Patch>> {
+		let mut manifest = self.read().await?;
+
+		let patches = manifest
+			.remove(repository)
+			.with_context(|| format!("manifest does not contain repository {repository}"))?;
+
+		NonEmpty::from_vec(patches)
+			.with_context(|| format!("manifest contains no patches for {repository}"))
+	}
+}
+
+impl Provider for Manifest {
+	fn patch_list<'a>(&'a self, repository: &'a str) -> BoxFuture<'a, Result<NonEmpty<Patch>>> {
+		self.manifest_patch_list(repository).boxed()
+	}
+}
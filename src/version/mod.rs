@@ -1,11 +1,24 @@
+mod backoff;
+mod bootstrap;
 mod key;
+mod live;
 mod manager;
+mod manifest;
+mod name;
 mod patcher;
+mod provider;
+mod regional;
+mod snapshot;
 mod thaliak;
 mod version;
+mod window;
 
 pub use {
-	key::VersionKey,
+	backoff::{CircuitState, UpdateHealth},
+	key::{KeyScheme, VersionKey},
+	live::LiveStatus,
 	manager::{Config, Manager},
+	name::NameError,
+	snapshot::Snapshot,
 	version::{Patch, Repository, Version},
 };
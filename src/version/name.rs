@@ -0,0 +1,141 @@
+/// Name tracking the most recent version. Managed by the version system.
+pub const LATEST: &str = "latest";
+
+const RESERVED: &[&str] = &[LATEST];
+
+const MAX_LENGTH: usize = 64;
+
+#[derive(Debug, PartialEq, thiserror::Error)]
+pub enum NameError {
+	#[error("version names must not be empty")]
+	Empty,
+
+	#[error("version name \"{0}\" is longer than {MAX_LENGTH} characters")]
+	TooLong(String),
+
+	#[error(
+		"version name \"{0}\" may only contain ASCII letters, digits, \".\", \"-\", and \"_\""
+	)]
+	InvalidCharacter(String),
+
+	#[error("version name \"{0}\" is reserved")]
+	Reserved(String),
+
+	#[error("version name \"{0}\" could be mistaken for a patch version")]
+	PatchVersion(String),
+}
+
+/// Check if a name is reserved for use by the version system.
+pub fn is_reserved(name: &str) -> bool {
+	RESERVED
+		.iter()
+		.any(|reserved| reserved.eq_ignore_ascii_case(name))
+}
+
+/// Validate a name that is to be assigned to a version.
+pub fn validate(name: &str) -> Result<(), NameError> {
+	if name.is_empty() {
+		return Err(NameError::Empty);
+	}
+
+	if name.len() > MAX_LENGTH {
+		return Err(NameError::TooLong(name.to_string()));
+	}
+
+	let valid_character =
+		|character: char| character.is_ascii_alphanumeric() || matches!(character, '.' | '-' | '_');
+	if !name.chars().all(valid_character) {
+		return Err(NameError::InvalidCharacter(name.to_string()));
+	}
+
+	if is_reserved(name) {
+		return Err(NameError::Reserved(name.to_string()));
+	}
+
+	if is_patch_version(name) {
+		return Err(NameError::PatchVersion(name.to_string()));
+	}
+
+	Ok(())
+}
+
+/// Check if a name matches the format of patch versions, i.e.
+/// `2024.06.18.0000.0000` or `H2017.06.06.0000.0001a`.
+fn is_patch_version(name: &str) -> bool {
+	let name = name.strip_prefix(['H', 'D']).unwrap_or(name);
+	let name = name
+		.strip_suffix(|character: char| character.is_ascii_lowercase())
+		.unwrap_or(name);
+
+	let segments = name.split('.').collect::<Vec<_>>();
+	let lengths = [4, 2, 2, 4, 4];
+
+	segments.len() == lengths.len()
+		&& segments.iter().zip(lengths).all(|(segment, length)| {
+			segment.len() == length && segment.chars().all(|character| character.is_ascii_digit())
+		})
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn valid_names() {
+		for name in ["6.58", "6.58x1", "7.0-hotfix", "beta_2"] {
+			assert_eq!(validate(name), Ok(()), "{name}");
+		}
+	}
+
+	#[test]
+	fn empty() {
+		assert_eq!(validate(""), Err(NameError::Empty));
+	}
+
+	#[test]
+	fn too_long() {
+		let name = "a".repeat(MAX_LENGTH + 1);
+		assert_eq!(validate(&name), Err(NameError::TooLong(name)));
+	}
+
+	#[test]
+	fn invalid_characters() {
+		for name in ["6.58 hotfix", "a/b", "名前", "a,b"] {
+			assert_eq!(
+				validate(name),
+				Err(NameError::InvalidCharacter(name.to_string())),
+				"{name}"
+			);
+		}
+	}
+
+	#[test]
+	fn reserved() {
+		for name in ["latest", "LATEST"] {
+			assert_eq!(
+				validate(name),
+				Err(NameError::Reserved(name.to_string())),
+				"{name}"
+			);
+		}
+	}
+
+	#[test]
+	fn patch_versions() {
+		for name in [
+			"2024.06.18.0000.0000",
+			"H2017.06.06.0000.0001a",
+			"D2023.10.04.0000.0001",
+		] {
+			assert_eq!(
+				validate(name),
+				Err(NameError::PatchVersion(name.to_string())),
+				"{name}"
+			);
+		}
+
+		assert_eq!(validate("2024.06.18"), Ok(()));
+	}
+}
@@ -9,9 +9,14 @@ use std::{
 use anyhow::{Context, Result};
 use figment::value::magic::RelativePathBuf;
 use serde::Deserialize;
-use tokio::sync::{broadcast, Semaphore};
+use tokio::{
+	select,
+	sync::{broadcast, Semaphore},
+};
+
+use crate::{job, storage::Storage};
 
-use super::{thaliak, version};
+use super::{provider, version};
 
 enum State {
 	Pending(broadcast::Receiver<version::Patch>),
@@ -30,10 +35,12 @@ pub struct Patcher {
 	semaphore: Arc<Semaphore>,
 	client: reqwest::Client,
 	patch_states: Arc<Mutex<HashMap<PathBuf, State>>>,
+	jobs: Arc<job::Manager>,
+	storage: Option<Arc<Storage>>,
 }
 
 impl Patcher {
-	pub fn new(config: Config) -> Self {
+	pub fn new(config: Config, jobs: Arc<job::Manager>, storage: Option<Arc<Storage>>) -> Self {
 		Self {
 			directory: config.directory.relative(),
 			semaphore: Arc::new(Semaphore::new(config.concurrency)),
@@ -42,6 +49,8 @@ impl Patcher {
 				.build()
 				.expect("failed to build reqwest client"),
 			patch_states: Default::default(),
+			jobs,
+			storage,
 		}
 	}
 
@@ -49,12 +58,34 @@ impl Patcher {
 		self.directory.join(repository).join(patch)
 	}
 
+	/// Ensure that a known patch is available locally, without consulting the
+	/// patch server. Missing patches will be fetched from storage, if configured.
+	pub async fn ensure_local_patch(&self, repository: &str, patch: &str) -> Result<()> {
+		let patch_path = self.patch_path(repository, patch);
+		if patch_path.is_file() {
+			return Ok(());
+		}
+
+		let Some(storage) = &self.storage else {
+			anyhow::bail!("patch {repository}/{patch} is not available locally");
+		};
+
+		if !storage
+			.download_file(&storage_key(repository, patch), &patch_path)
+			.await?
+		{
+			anyhow::bail!("patch {repository}/{patch} is not available in storage");
+		}
+
+		Ok(())
+	}
+
 	pub async fn to_local_patch(
 		&self,
 		repository: &str,
-		thaliak_patch: thaliak::Patch,
+		provider_patch: provider::Patch,
 	) -> Result<version::Patch> {
-		let patch_path = self.patch_path(repository, &thaliak_patch.name);
+		let patch_path = self.patch_path(repository, &provider_patch.name);
 
 		// TODO: It seems wasteful to call this hundreds of times every update when it'll do something less than 10 times ever.
 		let repository_directory = patch_path
@@ -87,7 +118,7 @@ impl Patcher {
 				drop(patch_states);
 
 				let patch = self
-					.maybe_download_patch(thaliak_patch, patch_path.clone())
+					.maybe_download_patch(repository, provider_patch, patch_path.clone())
 					.await?;
 
 				// Download is complete - relock to insert, and broadcast the value to
@@ -108,20 +139,41 @@ impl Patcher {
 
 	async fn maybe_download_patch(
 		&self,
-		thaliak_patch: thaliak::Patch,
+		repository: &str,
+		provider_patch: provider::Patch,
 		patch_path: PathBuf,
 	) -> Result<version::Patch> {
-		let patch_name = thaliak_patch.name.clone();
+		let patch_name = provider_patch.name.clone();
+		let patch_size = provider_patch.size;
+		let patch_hash = provider_patch.hash.clone();
+		let patch_released = provider_patch.released.map(version::from_unix_seconds);
 
 		// If we need to fetch the patch, wait for a permit then spin off a task to handle the download.
-		if self.should_fetch_patch(&thaliak_patch, &patch_path)? {
+		if self.should_fetch_patch(&provider_patch, &patch_path)? {
+			let job = self.jobs.create(
+				job::Kind::PatchDownload,
+				format!("{repository}/{patch_name}"),
+			);
+
 			let permit = self.semaphore.clone().acquire_owned().await.unwrap();
+			job.start();
 
 			let client = self.client.clone();
+			let storage = self.storage.clone();
+			let storage_key = storage_key(repository, &patch_name);
 			let patch_path = patch_path.clone();
 			let handle = tokio::spawn(async move {
-				let result = fetch_patch(client, &thaliak_patch, &patch_path).await;
+				let result = obtain_patch(
+					client,
+					storage.as_deref(),
+					&storage_key,
+					&provider_patch,
+					&patch_path,
+					&job,
+				)
+				.await;
 				drop(permit);
+				job.finish(&result);
 				result
 			});
 			handle.await??;
@@ -130,12 +182,52 @@ impl Patcher {
 		let patch = version::Patch {
 			name: patch_name,
 			path: patch_path,
+			size: Some(patch_size),
+			hash: patch_hash,
+			released: patch_released,
 		};
 
 		Ok(patch)
 	}
 
-	fn should_fetch_patch(&self, patch: &thaliak::Patch, path: &Path) -> Result<bool> {
+	/// Download a patch from an alternative source using the provided client,
+	/// bypassing shared storage. Patches already available locally are skipped.
+	pub async fn fetch_from(
+		&self,
+		client: &reqwest::Client,
+		repository: &str,
+		patch: provider::Patch,
+	) -> Result<()> {
+		let patch_path = self.patch_path(repository, &patch.name);
+		if !self.should_fetch_patch(&patch, &patch_path)? {
+			return Ok(());
+		}
+
+		let repository_directory = patch_path
+			.parent()
+			.expect("patches should always be within a folder");
+		fs::create_dir_all(repository_directory)
+			.with_context(|| format!("failed to create directory {repository_directory:?}"))?;
+
+		let job = self.jobs.create(
+			job::Kind::PatchDownload,
+			format!("{repository}/{}", patch.name),
+		);
+
+		let _permit = self.semaphore.acquire().await?;
+		job.start();
+		let result = fetch_patch(client.clone(), &patch, &patch_path, &job).await;
+		job.finish(&result);
+
+		result
+	}
+
+	/// Check whether a patch will need to be downloaded to be made available.
+	pub fn needs_fetch(&self, repository: &str, patch: &provider::Patch) -> Result<bool> {
+		self.should_fetch_patch(patch, &self.patch_path(repository, &patch.name))
+	}
+
+	fn should_fetch_patch(&self, patch: &provider::Patch, path: &Path) -> Result<bool> {
 		// If the file doesn't exist, we'll need to download it.
 		let metadata = match path.metadata() {
 			Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(true),
@@ -163,8 +255,57 @@ impl Patcher {
 	}
 }
 
+fn storage_key(repository: &str, patch: &str) -> String {
+	format!("patches/{repository}/{patch}")
+}
+
+async fn obtain_patch(
+	client: reqwest::Client,
+	storage: Option<&Storage>,
+	storage_key: &str,
+	patch: &provider::Patch,
+	path: &Path,
+	job: &job::Handle,
+) -> Result<()> {
+	// Prefer a copy of the patch from shared storage if there is one, it's likely
+	// to be considerably faster than the patch server.
+	if let Some(storage) = storage {
+		match storage.download_file(storage_key, path).await {
+			Ok(true) if file_size(path) == Some(patch.size) => return Ok(()),
+			Ok(true) => {
+				tracing::warn!(patch = %patch.name, "size mismatch in storage, will re-fetch")
+			}
+			Ok(false) => {}
+			Err(error) => {
+				tracing::warn!(patch = %patch.name, ?error, "failed to download patch from storage")
+			}
+		}
+	}
+
+	fetch_patch(client, patch, path, job).await?;
+
+	// Share the freshly fetched patch. A failure here only costs other instances
+	// a trip to the patch server, it's not worth failing the patch over.
+	if let Some(storage) = storage {
+		if let Err(error) = storage.upload_file(path, storage_key).await {
+			tracing::warn!(patch = %patch.name, ?error, "failed to upload patch to storage");
+		}
+	}
+
+	Ok(())
+}
+
+fn file_size(path: &Path) -> Option<u64> {
+	fs::metadata(path).ok().map(|metadata| metadata.len())
+}
+
 #[tracing::instrument(level = "info", skip_all, fields(url = patch.url))]
-async fn fetch_patch(client: reqwest::Client, patch: &thaliak::Patch, path: &Path) -> Result<()> {
+async fn fetch_patch(
+	client: reqwest::Client,
+	patch: &provider::Patch,
+	path: &Path,
+	job: &job::Handle,
+) -> Result<()> {
 	tracing::info!("fetching patch");
 
 	// Create the target file before opening any connections.
@@ -192,7 +333,17 @@ async fn fetch_patch(client: reqwest::Client, patch: &thaliak::Patch, path: &Pat
 	let mut position = 0;
 	let mut last_report = 0.0;
 
-	while let Some(chunk) = response.chunk().await? {
+	loop {
+		// Bail if cancellation of the download is requested. The partial file will
+		// be detected by the size check and re-fetched on the next update.
+		let chunk = select! {
+			chunk = response.chunk() => chunk?,
+			_ = job.cancelled() => anyhow::bail!("download of {} cancelled", patch.name),
+		};
+		let Some(chunk) = chunk else {
+			break;
+		};
+
 		// This is blocking - is it worth trying to use async fs, or is the slowdown from that going to be Problematic:tm:?
 		target_file.write_all(&chunk)?;
 
@@ -200,6 +351,7 @@ async fn fetch_patch(client: reqwest::Client, patch: &thaliak::Patch, path: &Pat
 		let report_pos = f64::round((position as f64 / content_length as f64) * 20.0) * 5.0;
 		if report_pos > last_report {
 			tracing::debug!("{position}/{content_length} ({report_pos}%)");
+			job.progress(position, Some(content_length));
 			last_report = report_pos;
 		}
 	}
@@ -0,0 +1,56 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use nonempty::NonEmpty;
+use serde::Deserialize;
+
+use super::{
+	manifest,
+	regional::{self, Region},
+	thaliak,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct Patch {
+	pub name: String,
+	pub url: String,
+	pub size: u64,
+	/// Hash of the patch file, if known by the provider.
+	#[serde(default)]
+	pub hash: Option<String>,
+	/// Unix timestamp, in seconds, at which the patch was released, if known by
+	/// the provider.
+	#[serde(default)]
+	pub released: Option<u64>,
+}
+
+/// A source of patch lists for game repositories.
+pub trait Provider: Send + Sync {
+	/// Fetch the list of patches for the specified repository, ordered
+	/// oldest-first.
+	fn patch_list<'a>(&'a self, repository: &'a str) -> BoxFuture<'a, Result<NonEmpty<Patch>>>;
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Config {
+	/// A thaliak GraphQL endpoint, or an endpoint implementing a compatible API.
+	Thaliak(thaliak::Config),
+
+	/// A static JSON manifest of patches for each repository.
+	Manifest(manifest::Config),
+
+	/// The Korean client's patch server, operated by Actoz.
+	Actoz(regional::Config),
+
+	/// The Chinese client's patch server, operated by Shanda.
+	Shanda(regional::Config),
+}
+
+pub fn provider(config: Config) -> Box<dyn Provider> {
+	match config {
+		Config::Thaliak(config) => Box::new(thaliak::Thaliak::new(config)),
+		Config::Manifest(config) => Box::new(manifest::Manifest::new(config)),
+		Config::Actoz(config) => Box::new(regional::Regional::new(config, Region::Actoz)),
+		Config::Shanda(config) => Box::new(regional::Regional::new(config, Region::Shanda)),
+	}
+}
@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use futures::{future::BoxFuture, FutureExt};
+use nonempty::NonEmpty;
+use serde::Deserialize;
+
+use super::provider::{Patch, Provider};
+
+/// Version the patch list is requested relative to when none is configured.
+/// Requesting from the initial release yields the full patch chain.
+const DEFAULT_BASE_VERSION: &str = "2012.01.01.0000.0000";
+
+/// Regional publishers operating their own patch servers, outside the global
+/// client tracked by thaliak.
+#[derive(Debug, Clone, Copy)]
+pub enum Region {
+	/// Actoz Soft, publishing the Korean client.
+	Actoz,
+	/// Shanda Games, publishing the Chinese client.
+	Shanda,
+}
+
+impl Region {
+	/// Release channel the region's game patches are published under.
+	fn channel(&self) -> &'static str {
+		match self {
+			Self::Actoz => "actoz_release_ko_game",
+			Self::Shanda => "shanda_release_chs_game",
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+pub struct Config {
+	/// Base URL of the region's patch version server.
+	endpoint: String,
+
+	/// Release channel to request, overriding the region's default.
+	channel: Option<String>,
+
+	/// Version to request the patch list relative to. Patches released before
+	/// this version will not be listed.
+	base_version: Option<String>,
+}
+
+/// Provider requesting patch lists directly from a regional patch server. The
+/// server responds with the patches required to update from the base version,
+/// across every repository - patches are attributed to a repository by the
+/// repository's name appearing as a segment of their URL.
+pub struct Regional {
+	region: Region,
+	endpoint: String,
+	channel: String,
+	base_version: String,
+	client: reqwest::Client,
+}
+
+impl Regional {
+	pub fn new(config: Config, region: Region) -> Self {
+		Self {
+			region,
+			endpoint: config.endpoint.trim_end_matches('/').to_string(),
+			channel: config
+				.channel
+				.unwrap_or_else(|| region.channel().to_string()),
+			base_version: config
+				.base_version
+				.unwrap_or_else(|| DEFAULT_BASE_VERSION.to_string()),
+			client: reqwest::Client::new(),
+		}
+	}
+
+	#[tracing::instrument(level = "debug", skip(self), fields(region = ?self.region))]
+	async fn regional_patch_list(&self, repository: &str) -> Result<NonEmpty<Patch>> {
+		let url = format!(
+			"{}/http/win32/{}/{}/",
+			self.endpoint, self.channel, self.base_version
+		);
+
+		let body = self
+			.client
+			.post(&url)
+			.send()
+			.await?
+			.error_for_status()?
+			.text()
+			.await?;
+
+		NonEmpty::from_vec(parse_patch_list(&body, repository)).with_context(|| {
+			format!(
+				"patch server listed no patches for {repository} since {}",
+				self.base_version
+			)
+		})
+	}
+}
+
+impl Provider for Regional {
+	fn patch_list<'a>(&'a self, repository: &'a str) -> BoxFuture<'a, Result<NonEmpty<Patch>>> {
+		self.regional_patch_list(repository).boxed()
+	}
+}
+
+/// Parse a patch server response into the patches belonging to a repository,
+/// oldest-first. Responses may be wrapped in a multipart body - only lines
+/// describing a patch are considered, being tab-separated fields of the patch
+/// size, total size, part count, part index, version, hash type, hash block
+/// size, hashes, and finally URL.
+fn parse_patch_list(body: &str, repository: &str) -> Vec<Patch> {
+	let segment = format!("/{repository}/");
+
+	body.lines()
+		.filter_map(|line| {
+			let fields = line.trim().split('\t').collect::<Vec<_>>();
+			let (size, version, url) = match fields[..] {
+				[size, _, _, _, version, .., url] if fields.len() >= 9 => (size, version, url),
+				_ => return None,
+			};
+
+			if !url.contains(&segment) {
+				return None;
+			}
+
+			Some(Patch {
+				name: version.to_string(),
+				url: url.to_string(),
+				size: size.parse().ok()?,
+				// Hashes provided by patch servers are per-block, not for the whole file.
+				hash: None,
+				released: None,
+			})
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	const RESPONSE: &str = "--477D80B1_38BC_41d4_8B48_5273ADB89CAC\r
+Content-Type: application/octet-stream\r
+Content-Location: ffxivpatch/4e9a232b/vercheck.dat\r
+X-Repository: actoz_release_ko_game\r
+\r
+44145523\t44145523\t1\t1\tD2023.01.01.0000.0000\tsha1\t50000000\tabc\thttp://patch.example/game/4e9a232b/D2023.01.01.0000.0000.patch\r
+1024\t1024\t1\t1\tD2023.02.01.0000.0000\tsha1\t50000000\tdef\thttp://patch.example/game/ex1/6b936f08/D2023.02.01.0000.0000.patch\r
+2048\t2048\t1\t1\tD2023.03.01.0000.0000\tsha1\t50000000\tghi\thttp://patch.example/game/4e9a232b/D2023.03.01.0000.0000.patch\r
+--477D80B1_38BC_41d4_8B48_5273ADB89CAC--\r
+";
+
+	#[test]
+	fn parse_repository_patches() {
+		let patches = parse_patch_list(RESPONSE, "4e9a232b")
+			.into_iter()
+			.map(|patch| (patch.name, patch.size))
+			.collect::<Vec<_>>();
+
+		assert_eq!(
+			patches,
+			vec![
+				("D2023.01.01.0000.0000".to_string(), 44145523),
+				("D2023.03.01.0000.0000".to_string(), 2048),
+			]
+		);
+	}
+
+	#[test]
+	fn parse_unknown_repository() {
+		assert!(parse_patch_list(RESPONSE, "unknown").is_empty());
+	}
+}
@@ -0,0 +1,25 @@
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::key::VersionKey;
+
+// Incremented on any breaking change to the snapshot structure.
+pub(super) const SNAPSHOT_FORMAT: u32 = 1;
+
+/// Point-in-time copy of the version state of an instance, sufficient to
+/// restore it on another host without re-discovering versions.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+	pub(super) format: u32,
+	pub(super) created: SystemTime,
+	// Stored in the persisted version format, which is private to the version module.
+	pub(super) versions: BTreeMap<VersionKey, serde_json::Value>,
+	pub(super) names: BTreeMap<String, VersionKey>,
+	// Snapshots taken before visibility was recorded have no hidden versions.
+	#[serde(default)]
+	pub(super) hidden: BTreeSet<VersionKey>,
+}
@@ -1,17 +1,16 @@
 use std::collections::HashMap;
 
 use anyhow::Result;
+use futures::{future::BoxFuture, FutureExt};
 use graphql_client::{GraphQLQuery, Response};
 use nonempty::NonEmpty;
 use serde::Deserialize;
+use serde_json::json;
 
-#[derive(Debug)]
-pub struct Patch {
-	pub name: String,
-	pub url: String,
-	pub size: u64,
-	// TODO: hashes (needs fixes @ thaliak)
-}
+use crate::version::{
+	provider::{Patch, Provider},
+	version::{unix_seconds, utc_time},
+};
 
 // TODO: As-is this query can only fetch one repository per request. May be possible to programatically merge multiple into one query with a more struct-driven query system like cynic.
 #[derive(GraphQLQuery)]
@@ -22,34 +21,51 @@ pub struct Patch {
 )]
 struct RepositoryQuery;
 
+// Thaliak represents dates as ISO 8601 strings, i.e. `2023-09-28T08:00:00.000Z`.
+type DateTime = String;
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
 	endpoint: String,
+
+	/// GraphQL document to send in place of the default query, for endpoints
+	/// with a differing schema. The document receives a `$repository` variable,
+	/// and must produce a response of the same shape as the default query -
+	/// field aliases may be used to adapt differing field names.
+	query: Option<String>,
 }
 
-pub struct Provider {
+pub struct Thaliak {
 	endpoint: String,
+	query: Option<String>,
 	client: reqwest::Client,
 }
 
-impl Provider {
+impl Thaliak {
 	pub fn new(config: Config) -> Self {
 		Self {
 			endpoint: config.endpoint,
+			query: config.query,
 			client: reqwest::Client::new(),
 		}
 	}
 
 	#[tracing::instrument(level = "debug", skip(self))]
-	pub async fn patch_list(&self, repository: String) -> Result<NonEmpty<Patch>> {
-		let query = RepositoryQuery::build_query(repository_query::Variables {
-			repository: repository.clone(),
-		});
-
-		let response = self
-			.client
-			.post(&self.endpoint)
-			.json(&query)
+	async fn thaliak_patch_list(&self, repository: &str) -> Result<NonEmpty<Patch>> {
+		let variables = repository_query::Variables {
+			repository: repository.to_string(),
+		};
+
+		let request = self.client.post(&self.endpoint);
+		let request = match &self.query {
+			Some(query) => request.json(&json!({
+				"query": query,
+				"variables": variables,
+			})),
+			None => request.json(&RepositoryQuery::build_query(variables)),
+		};
+
+		let response = request
 			.send()
 			.await?
 			.json::<Response<repository_query::ResponseData>>()
@@ -91,6 +107,9 @@ impl Provider {
 				name: version.version_string.clone(),
 				url: patch.url.clone(),
 				size: patch.size.try_into().unwrap(),
+				// TODO: hashes (needs fixes @ thaliak)
+				hash: None,
+				released: version.first_offered.as_deref().and_then(parse_date_time),
 			});
 
 			// Grab the prerequsite versions, ignoring any that we've seen (to avoid
@@ -125,3 +144,22 @@ impl Provider {
 		})
 	}
 }
+
+/// Parse a UTC ISO 8601 date time into a unix timestamp in seconds. Fractional
+/// seconds are ignored.
+fn parse_date_time(value: &str) -> Option<u64> {
+	let number = |range: std::ops::Range<usize>| value.get(range)?.parse::<u32>().ok();
+	let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+	let seconds = match value.get(10..11) {
+		Some("T") => number(11..13)? * 3600 + number(14..16)? * 60 + number(17..19)?,
+		_ => 0,
+	};
+
+	utc_time(year, month, day, seconds).map(unix_seconds)
+}
+
+impl Provider for Thaliak {
+	fn patch_list<'a>(&'a self, repository: &'a str) -> BoxFuture<'a, Result<NonEmpty<Patch>>> {
+		self.thaliak_patch_list(repository).boxed()
+	}
+}
@@ -1,9 +1,14 @@
-use std::path::PathBuf;
+use std::{
+	path::PathBuf,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
 use anyhow::Result;
 use nonempty::NonEmpty;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::utility::date::days_from_civil;
+
 #[derive(Clone, PartialEq)]
 pub struct Version {
 	pub repositories: Vec<Repository>,
@@ -20,7 +25,15 @@ impl Version {
 				.iter()
 				.map(|repository| PersistedRepository {
 					name: repository.name.clone(),
-					patches: repository.patches.clone().map(|patch| patch.name),
+					patches: repository
+						.patches
+						.clone()
+						.map(|patch| PersistedPatch::Detailed {
+							name: patch.name,
+							size: patch.size,
+							hash: patch.hash,
+							released: patch.released.map(unix_seconds),
+						}),
 				})
 				.collect(),
 		);
@@ -42,10 +55,24 @@ impl Version {
 		let repositories = persisted_repositories
 			.into_iter()
 			.map(|persisted_repository| Repository {
-				patches: persisted_repository.patches.map(|patch_name| Patch {
-					// TODO: I should probably fail out if this doesn't point to a file on disk.
-					path: get_path(&persisted_repository.name, &patch_name),
-					name: patch_name,
+				patches: persisted_repository.patches.map(|persisted_patch| {
+					let (name, size, hash, released) = match persisted_patch {
+						PersistedPatch::Name(name) => (name, None, None, None),
+						PersistedPatch::Detailed {
+							name,
+							size,
+							hash,
+							released,
+						} => (name, size, hash, released),
+					};
+					Patch {
+						// TODO: I should probably fail out if this doesn't point to a file on disk.
+						path: get_path(&persisted_repository.name, &name),
+						name,
+						size,
+						hash,
+						released: released.map(from_unix_seconds),
+					}
 				}),
 				name: persisted_repository.name,
 			})
@@ -64,7 +91,31 @@ pub struct Repository {
 #[derive(Serialize, Deserialize)]
 struct PersistedRepository {
 	name: String,
-	patches: NonEmpty<String>,
+	patches: NonEmpty<PersistedPatch>,
+}
+
+// Versions persisted before patch details were recorded store only the name.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum PersistedPatch {
+	Name(String),
+	Detailed {
+		name: String,
+		size: Option<u64>,
+		hash: Option<String>,
+		released: Option<u64>,
+	},
+}
+
+impl Version {
+	/// Get the time the most recent patch in the version was released. This
+	/// reflects the last time the game data in the version changed.
+	pub fn released(&self) -> Option<SystemTime> {
+		self.repositories
+			.iter()
+			.filter_map(|repository| repository.latest().release_time())
+			.max()
+	}
 }
 
 impl Repository {
@@ -80,4 +131,77 @@ impl Repository {
 pub struct Patch {
 	pub name: String,
 	pub path: PathBuf,
+	/// Size of the patch file in bytes. Not recorded for older versions.
+	pub size: Option<u64>,
+	/// Hash of the patch file, if provided by the version provider.
+	pub hash: Option<String>,
+	/// Time the patch was released, if provided by the version provider.
+	pub released: Option<SystemTime>,
+}
+
+impl Patch {
+	/// Get the time the patch was released. Patches without a recorded release
+	/// time fall back to the date in their name, i.e. `D2023.09.28.0000.0001`.
+	pub fn release_time(&self) -> Option<SystemTime> {
+		self.released.or_else(|| patch_name_date(&self.name))
+	}
+}
+
+fn patch_name_date(name: &str) -> Option<SystemTime> {
+	let name = name.trim_start_matches(|char: char| !char.is_ascii_digit());
+	let mut parts = name.get(..10)?.split('.').map(str::parse::<u32>);
+	match (parts.next(), parts.next(), parts.next()) {
+		(Some(Ok(year)), Some(Ok(month)), Some(Ok(day))) => utc_time(year, month, day, 0),
+		_ => None,
+	}
+}
+
+/// Build a time from a UTC calendar date and the seconds elapsed within it.
+/// Dates that don't exist, or precede the epoch, have no time.
+pub(super) fn utc_time(year: u32, month: u32, day: u32, seconds: u32) -> Option<SystemTime> {
+	let days = u64::try_from(days_from_civil(year.into(), month, day)?).ok()?;
+	Some(from_unix_seconds(days * 86400 + u64::from(seconds)))
+}
+
+pub(super) fn unix_seconds(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH)
+		.map(|duration| duration.as_secs())
+		.unwrap_or(0)
+}
+
+pub(super) fn from_unix_seconds(seconds: u64) -> SystemTime {
+	UNIX_EPOCH + Duration::from_secs(seconds)
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	#[test]
+	fn utc_dates() {
+		assert_eq!(utc_time(1970, 1, 1, 0), Some(UNIX_EPOCH));
+		assert_eq!(utc_time(2000, 3, 1, 0), Some(from_unix_seconds(951868800)));
+		assert_eq!(
+			utc_time(2023, 9, 28, 60),
+			Some(from_unix_seconds(1695859260))
+		);
+		assert_eq!(utc_time(2023, 13, 1, 0), None);
+		assert_eq!(utc_time(2023, 2, 29, 0), None);
+		assert_eq!(utc_time(1969, 12, 31, 0), None);
+	}
+
+	#[test]
+	fn name_dates() {
+		assert_eq!(
+			patch_name_date("D2023.09.28.0000.0001"),
+			utc_time(2023, 9, 28, 0)
+		);
+		assert_eq!(
+			patch_name_date("H2017.06.06.0000.0001a"),
+			utc_time(2017, 6, 6, 0)
+		);
+		assert_eq!(patch_name_date("ffxivgame"), None);
+	}
 }
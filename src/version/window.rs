@@ -0,0 +1,285 @@
+use std::{
+	str::FromStr,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{de, Deserialize};
+
+use crate::utility::date::civil_from_days;
+
+/// Upper bound on how far ahead to search for the next open window. Any valid
+/// expression will match at least once within a (leap) year.
+const SEARCH_MINUTES: u64 = 366 * 24 * 60;
+
+/// Time windows during which bandwidth-heavy work may start. Each window is a
+/// cron-like expression of five space-separated fields - minute, hour, day of
+/// month, month, and day of week (0 or 7 being Sunday) - evaluated in UTC.
+/// Fields accept `*`, values, ranges (`2-5`), steps (`*/15`), and lists
+/// (`1,3-4`). Work may start during any minute matched by any expression, i.e.
+/// `* 2-5 * * *` permits work between 02:00 and 05:59 every day. No windows
+/// permits work at any time.
+#[derive(Debug, Default)]
+pub struct Windows(Vec<Expression>);
+
+impl Windows {
+	/// Check whether work may start at the given time.
+	pub fn is_open(&self, time: SystemTime) -> bool {
+		self.0.is_empty() || {
+			let moment = Moment::from(time);
+			self.0.iter().any(|expression| expression.matches(&moment))
+		}
+	}
+
+	/// Find the earliest time, at or after the given time, that work may
+	/// start. Returns `None` if no expression can ever match.
+	pub fn next_open(&self, time: SystemTime) -> Option<SystemTime> {
+		if self.is_open(time) {
+			return Some(time);
+		}
+
+		let minute = time.duration_since(UNIX_EPOCH).ok()?.as_secs() / 60;
+		(minute + 1..minute + SEARCH_MINUTES)
+			.map(|minute| UNIX_EPOCH + Duration::from_secs(minute * 60))
+			.find(|candidate| self.is_open(*candidate))
+	}
+}
+
+impl<'de> Deserialize<'de> for Windows {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: serde::Deserializer<'de>,
+	{
+		let expressions = Vec::<String>::deserialize(deserializer)?
+			.iter()
+			.map(|raw| raw.parse::<Expression>())
+			.collect::<Result<Vec<_>, _>>()
+			.map_err(de::Error::custom)?;
+
+		Ok(Self(expressions))
+	}
+}
+
+#[derive(Debug)]
+struct Expression {
+	minutes: u64,
+	hours: u64,
+	days: u64,
+	months: u64,
+	weekdays: u64,
+
+	// As with cron, if both the day of month and day of week are restricted,
+	// matching either is sufficient.
+	days_restricted: bool,
+	weekdays_restricted: bool,
+}
+
+impl Expression {
+	fn matches(&self, moment: &Moment) -> bool {
+		let bit = |mask: u64, value: u32| mask & (1 << value) != 0;
+
+		let day = bit(self.days, moment.day);
+		let weekday = bit(self.weekdays, moment.weekday);
+		let day_matches = match (self.days_restricted, self.weekdays_restricted) {
+			(true, true) => day || weekday,
+			_ => day && weekday,
+		};
+
+		bit(self.minutes, moment.minute)
+			&& bit(self.hours, moment.hour)
+			&& bit(self.months, moment.month)
+			&& day_matches
+	}
+}
+
+impl FromStr for Expression {
+	type Err = String;
+
+	fn from_str(input: &str) -> Result<Self, Self::Err> {
+		let fields = input.split_whitespace().collect::<Vec<_>>();
+		let [minutes, hours, days, months, weekdays] = fields[..] else {
+			return Err(format!(
+				"window {input:?} must have 5 fields, found {}",
+				fields.len()
+			));
+		};
+
+		let context = |error: String| format!("invalid window {input:?}: {error}");
+
+		// Sunday may be written as either 0 or 7 - fold the latter onto the former.
+		let weekday_mask = parse_field(weekdays, 0, 7).map_err(context)?;
+		let weekday_mask = (weekday_mask | weekday_mask >> 7) & 0x7f;
+
+		Ok(Self {
+			minutes: parse_field(minutes, 0, 59).map_err(context)?,
+			hours: parse_field(hours, 0, 23).map_err(context)?,
+			days: parse_field(days, 1, 31).map_err(context)?,
+			months: parse_field(months, 1, 12).map_err(context)?,
+			weekdays: weekday_mask,
+
+			days_restricted: days != "*",
+			weekdays_restricted: weekdays != "*",
+		})
+	}
+}
+
+/// Parse a single field of an expression into a bitmask of matching values.
+fn parse_field(input: &str, min: u32, max: u32) -> Result<u64, String> {
+	let parse_value = |value: &str| {
+		value
+			.parse::<u32>()
+			.map_err(|_| format!("invalid value {value:?}"))
+	};
+
+	let mut mask = 0u64;
+
+	for part in input.split(',') {
+		let (range, step) = match part.split_once('/') {
+			Some((range, step)) => (range, parse_value(step)?),
+			None => (part, 1),
+		};
+		if step == 0 {
+			return Err(format!("step in {part:?} must be greater than 0"));
+		}
+
+		let (start, end) = match range {
+			"*" => (min, max),
+			range => match range.split_once('-') {
+				Some((start, end)) => (parse_value(start)?, parse_value(end)?),
+				// A single value with a step runs to the end of the field's range.
+				None => {
+					let value = parse_value(range)?;
+					(value, if step > 1 { max } else { value })
+				}
+			},
+		};
+
+		if start < min || end > max || start > end {
+			return Err(format!("{part:?} is outside the range {min}-{max}"));
+		}
+
+		for value in (start..=end).step_by(step as usize) {
+			mask |= 1 << value;
+		}
+	}
+
+	Ok(mask)
+}
+
+/// A point in time, broken down into the units used by expressions.
+struct Moment {
+	minute: u32,
+	hour: u32,
+	day: u32,
+	month: u32,
+	weekday: u32,
+}
+
+impl From<SystemTime> for Moment {
+	fn from(time: SystemTime) -> Self {
+		let seconds = time
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or_default()
+			.as_secs();
+		let days = seconds / 86_400;
+		let minute_of_day = seconds % 86_400 / 60;
+
+		// Days since the epoch fit comfortably within an i64.
+		let (_year, month, day) = civil_from_days(days as i64);
+
+		// The epoch fell on a Thursday.
+		let weekday = (days + 4) % 7;
+
+		// All values are bounded well within u32 by the arithmetic above.
+		Self {
+			minute: (minute_of_day % 60) as u32,
+			hour: (minute_of_day / 60) as u32,
+			day,
+			month,
+			weekday: weekday as u32,
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use pretty_assertions::assert_eq;
+
+	use super::*;
+
+	fn windows(expressions: &[&str]) -> Windows {
+		Windows(
+			expressions
+				.iter()
+				.map(|expression| expression.parse().expect("parse should not fail"))
+				.collect(),
+		)
+	}
+
+	// 2024-03-02 (a Saturday), at the given time of day.
+	fn time(hour: u64, minute: u64) -> SystemTime {
+		UNIX_EPOCH + Duration::from_secs(1_709_337_600 + hour * 3_600 + minute * 60)
+	}
+
+	#[test]
+	fn empty_always_open() {
+		assert!(windows(&[]).is_open(time(12, 0)));
+	}
+
+	#[test]
+	fn hour_range() {
+		let windows = windows(&["* 2-5 * * *"]);
+		assert!(windows.is_open(time(2, 0)));
+		assert!(windows.is_open(time(5, 59)));
+		assert!(!windows.is_open(time(6, 0)));
+		assert!(!windows.is_open(time(1, 59)));
+	}
+
+	#[test]
+	fn weekday() {
+		assert!(windows(&["* * * * 6"]).is_open(time(12, 0)));
+		assert!(windows(&["* * * * 0,6"]).is_open(time(12, 0)));
+		assert!(!windows(&["* * * * 1-5"]).is_open(time(12, 0)));
+		assert!(!windows(&["* * * * 7"]).is_open(time(12, 0)));
+	}
+
+	#[test]
+	fn day_or_weekday() {
+		// Day of month and day of week match if either does, as with cron.
+		assert!(windows(&["* * 15 * 6"]).is_open(time(12, 0)));
+		assert!(windows(&["* * 2 3 1"]).is_open(time(12, 0)));
+		assert!(!windows(&["* * 15 * 1"]).is_open(time(12, 0)));
+	}
+
+	#[test]
+	fn next_open() {
+		let windows = windows(&["*/30 2 * * *"]);
+		assert_eq!(windows.next_open(time(1, 10)), Some(time(2, 0)));
+		assert_eq!(windows.next_open(time(2, 10)), Some(time(2, 30)));
+		assert_eq!(windows.next_open(time(2, 30)), Some(time(2, 30)));
+		assert_eq!(
+			windows.next_open(time(3, 0)),
+			Some(time(2, 0) + Duration::from_secs(86_400))
+		);
+	}
+
+	#[test]
+	fn never_open() {
+		assert_eq!(windows(&["* * 31 2 *"]).next_open(time(0, 0)), None);
+	}
+
+	#[test]
+	fn invalid() {
+		for input in [
+			"* * * *",
+			"60 * * * *",
+			"* 5-2 * * *",
+			"*/0 * * * *",
+			"a * * * *",
+		] {
+			assert!(
+				input.parse::<Expression>().is_err(),
+				"{input:?} should not parse"
+			);
+		}
+	}
+}